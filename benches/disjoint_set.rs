@@ -0,0 +1,85 @@
+//! Benchmarks comparing the union-find implementations.
+//! Run with `cargo bench --bench disjoint_set`.
+
+#![allow(deprecated)]
+
+#[macro_use]
+extern crate criterion;
+extern crate rand;
+extern crate aisd;
+
+use criterion::{BenchmarkId, Criterion};
+
+use rand::{Rng, SeedableRng};
+use rand::prng::XorShiftRng;
+
+use aisd::disjoint_set::{Auto, DisjointSet};
+use aisd::union_by_rank;
+use aisd::union_by_size;
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+
+/// Random pairs of elements, the same for every implementation.
+fn pairs(size: usize, count: usize) -> Vec<(usize, usize)> {
+    let mut rng = XorShiftRng::from_seed([42; 16]);
+
+    (0 .. count).map(|_| (rng.gen_range(0, size), rng.gen_range(0, size))).collect()
+}
+
+/// Random unions followed by as many random finds.
+fn random<D: DisjointSet>(size: usize, ops: &[(usize, usize)]) -> usize {
+    let mut uf = D::new(size);
+    for &(i, j) in ops {
+        uf.union(i, j);
+    }
+
+    ops.iter().filter(|&&(i, j)| uf.same_set(i, j)).count()
+}
+
+/// A long chain built by unions, then queried from its far end. This is where
+/// path halving and full path compression differ the most.
+fn chain<D: DisjointSet>(size: usize) -> usize {
+    let mut uf = D::new(size);
+    for i in 1 .. size {
+        uf.union(i - 1, i);
+    }
+
+    (0 .. size).rev().filter_map(|i| uf.find(i)).count()
+}
+
+fn bench_random(c: &mut Criterion) {
+    let mut group = c.benchmark_group("random");
+    for &size in SIZES.iter() {
+        let ops = pairs(size, size);
+
+        group.bench_with_input(BenchmarkId::new("Auto", size), &ops, |b, ops| {
+            b.iter(|| random::<Auto>(size, ops))
+        });
+        group.bench_with_input(BenchmarkId::new("union_by_rank", size), &ops, |b, ops| {
+            b.iter(|| random::<union_by_rank::UnionFind>(size, ops))
+        });
+        group.bench_with_input(BenchmarkId::new("union_by_size", size), &ops, |b, ops| {
+            b.iter(|| random::<union_by_size::UnionFind>(size, ops))
+        });
+    }
+    group.finish();
+}
+
+fn bench_chain(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chain");
+    for &size in SIZES.iter() {
+        group.bench_with_input(BenchmarkId::new("Auto", size), &size, |b, &size| {
+            b.iter(|| chain::<Auto>(size))
+        });
+        group.bench_with_input(BenchmarkId::new("union_by_rank", size), &size, |b, &size| {
+            b.iter(|| chain::<union_by_rank::UnionFind>(size))
+        });
+        group.bench_with_input(BenchmarkId::new("union_by_size", size), &size, |b, &size| {
+            b.iter(|| chain::<union_by_size::UnionFind>(size))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_random, bench_chain);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+//! Benchmarks of graph algorithms on random graphs. Run with
+//! `cargo bench --bench graph`.
+
+#[macro_use]
+extern crate criterion;
+extern crate aisd;
+extern crate rand;
+
+use criterion::{BenchmarkId, Criterion};
+
+use aisd::graph::{self, LabeledGraph};
+
+use rand::{Rng, SeedableRng};
+use rand::prng::XorShiftRng;
+
+/// A random graph with `n` nodes and `m` edges with weights up to 1000.
+fn random_graph(directed: bool, n: usize, m: usize) -> LabeledGraph<(), u64> {
+    let mut rng = XorShiftRng::from_seed([7; 16]);
+    let edges: Vec<(usize, usize, u64)> = (0 .. m)
+        .map(|_| (rng.gen_range(0, n), rng.gen_range(0, n), rng.gen_range(1, 1001)))
+        .collect();
+
+    LabeledGraph::from_edges(directed, n, edges)
+}
+
+type ShortestPaths = fn(&LabeledGraph<(), u64>, usize) -> graph::ShortestPaths<u64>;
+
+// Sparse graphs have about 8 edges per node, dense ones about half of all the
+// possible edges. Lazy deletion pushes a node for every improvement, which is
+// more often in dense graphs.
+fn bench_dijkstra(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dijkstra");
+    group.sample_size(20);
+    let graphs = [
+        ("sparse", random_graph(true, 100_000, 800_000)),
+        ("dense", random_graph(true, 2_000, 2_000_000))
+    ];
+    let versions: Vec<(&str, ShortestPaths)> = vec![
+        ("lazy", graph::dijkstra),
+        ("decrease_key", graph::dijkstra_decrease_key)
+    ];
+
+    for (density, g) in graphs.iter() {
+        for &(name, f) in versions.iter() {
+            group.bench_with_input(BenchmarkId::new(name, density), g, |b, g| b.iter(|| f(g, 0)));
+        }
+    }
+    group.finish();
+}
+
+// Kruskal sorts all the edges, while Prim touches every edge once but pays for
+// the heap only once per node.
+fn bench_mst(c: &mut Criterion) {
+    let mut group = c.benchmark_group("minimum spanning tree");
+    group.sample_size(20);
+    let graphs = [
+        ("sparse", random_graph(false, 100_000, 400_000)),
+        ("dense", random_graph(false, 2_000, 1_000_000))
+    ];
+
+    for (density, g) in graphs.iter() {
+        group.bench_with_input(BenchmarkId::new("kruskal", density), g, |b, g| b.iter(|| graph::kruskal(g)));
+        group.bench_with_input(BenchmarkId::new("prim", density), g, |b, g| b.iter(|| graph::prim(g, 0)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_dijkstra, bench_mst);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+//! Benchmarks showing how much cache blocking pays off for big matrices.
+//! Run with `cargo bench --bench matrix`.
+
+#[macro_use]
+extern crate criterion;
+extern crate aisd;
+
+use criterion::{BenchmarkId, Criterion};
+
+use aisd::matrix::Matrix;
+
+const BLOCK: usize = 64;
+
+fn matrix(n: usize) -> Matrix<f64> {
+    Matrix::from_vec(n, n, (0 .. n * n).map(|i| (i % 101) as f64).collect())
+}
+
+fn bench_transpose(c: &mut Criterion) {
+    let mut group = c.benchmark_group("transpose");
+    group.sample_size(10);
+    for &n in [512, 2048, 4096].iter() {
+        let m = matrix(n);
+        group.bench_with_input(BenchmarkId::new("naive", n), &m, |b, m| {
+            b.iter(|| m.transpose())
+        });
+        group.bench_with_input(BenchmarkId::new("blocked", n), &m, |b, m| {
+            b.iter(|| m.transpose_blocked(BLOCK))
+        });
+    }
+    group.finish();
+}
+
+fn bench_multiply(c: &mut Criterion) {
+    let mut group = c.benchmark_group("multiply");
+    group.sample_size(10);
+    for &n in [128, 256, 512].iter() {
+        let m = matrix(n);
+        group.bench_with_input(BenchmarkId::new("naive", n), &m, |b, m| {
+            b.iter(|| m.multiply(m))
+        });
+        group.bench_with_input(BenchmarkId::new("blocked", n), &m, |b, m| {
+            b.iter(|| m.multiply_blocked(m, BLOCK))
+        });
+        group.bench_with_input(BenchmarkId::new("strassen", n), &m, |b, m| {
+            b.iter(|| m.multiply_strassen(m, 128))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_transpose, bench_multiply);
+criterion_main!(benches);
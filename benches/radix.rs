@@ -0,0 +1,79 @@
+//! Benchmarks comparing `RadixTree` with a binary search tree and `HashMap` on
+//! string keys sharing long prefixes, like URLs. Run with `cargo bench --bench radix`.
+
+#[macro_use]
+extern crate criterion;
+extern crate aisd;
+extern crate rand;
+
+use criterion::{BenchmarkId, Criterion};
+
+use aisd::map::Map;
+use aisd::map::bst::Tree;
+use aisd::map::radix::RadixTree;
+
+use rand::{Rng, SeedableRng};
+use rand::prng::XorShiftRng;
+
+use std::collections::HashMap;
+
+const SIZES: [usize; 2] = [10_000, 100_000];
+
+fn keys(n: usize) -> Vec<Vec<u8>> {
+    let mut rng = XorShiftRng::from_seed([7; 16]);
+    let sections = ["docs", "blog", "api/v1", "api/v2", "shop/items"];
+    (0 .. n).map(|_| {
+        let section = sections[rng.gen_range(0, sections.len())];
+        format!("https://example.com/{}/{}", section, rng.gen_range(0, 10 * n)).into_bytes()
+    }).collect()
+}
+
+fn build<M: Map<Key = Vec<u8>, Value = usize>>(keys: &[Vec<u8>]) -> M {
+    let mut m = M::new();
+    for (i, k) in keys.iter().enumerate() {
+        m.ins(k.clone(), i);
+    }
+    m
+}
+
+fn bench_ins(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ins");
+    group.sample_size(10);
+    for &n in SIZES.iter() {
+        let keys = keys(n);
+        group.bench_with_input(BenchmarkId::new("RadixTree", n), &keys, |b, keys| {
+            b.iter(|| build::<RadixTree<usize>>(keys).len())
+        });
+        group.bench_with_input(BenchmarkId::new("bst::Tree", n), &keys, |b, keys| {
+            b.iter(|| build::<Tree<Vec<u8>, usize>>(keys).size())
+        });
+        group.bench_with_input(BenchmarkId::new("HashMap", n), &keys, |b, keys| {
+            b.iter(|| keys.iter().enumerate().map(|(i, k)| (k.clone(), i)).collect::<HashMap<_, _>>().len())
+        });
+    }
+    group.finish();
+}
+
+fn bench_find(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find");
+    for &n in SIZES.iter() {
+        let keys = keys(n);
+        let radix: RadixTree<usize> = build(&keys);
+        let bst: Tree<Vec<u8>, usize> = build(&keys);
+        let hash: HashMap<Vec<u8>, usize> = keys.iter().enumerate().map(|(i, k)| (k.clone(), i)).collect();
+
+        group.bench_with_input(BenchmarkId::new("RadixTree", n), &keys, |b, keys| {
+            b.iter(|| keys.iter().filter(|k| radix.find(k).is_some()).count())
+        });
+        group.bench_with_input(BenchmarkId::new("bst::Tree", n), &keys, |b, keys| {
+            b.iter(|| keys.iter().filter(|k| bst.find(k).is_some()).count())
+        });
+        group.bench_with_input(BenchmarkId::new("HashMap", n), &keys, |b, keys| {
+            b.iter(|| keys.iter().filter(|k| hash.contains_key(*k)).count())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_ins, bench_find);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+//! Benchmarks comparing `SegVec` with `Vec` for very large sizes.
+//! Run with `cargo bench --bench seg_vec`.
+
+#[macro_use]
+extern crate criterion;
+extern crate aisd;
+
+use criterion::{BenchmarkId, Criterion};
+
+use aisd::seq::SegVec;
+
+const SIZES: [usize; 3] = [100_000, 1_000_000, 10_000_000];
+
+fn bench_push(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push");
+    group.sample_size(10);
+    for &size in SIZES.iter() {
+        group.bench_with_input(BenchmarkId::new("Vec", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut v = Vec::new();
+                for i in 0 .. size {
+                    v.push(i);
+                }
+                v.len()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("SegVec", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut v = SegVec::new();
+                for i in 0 .. size {
+                    v.push(i);
+                }
+                v.len()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_iter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iter");
+    group.sample_size(10);
+    for &size in SIZES.iter() {
+        let v: Vec<usize> = (0 .. size).collect();
+        let mut s = SegVec::new();
+        for i in 0 .. size {
+            s.push(i);
+        }
+
+        group.bench_with_input(BenchmarkId::new("Vec", size), &v, |b, v| {
+            b.iter(|| v.iter().sum::<usize>())
+        });
+        group.bench_with_input(BenchmarkId::new("SegVec", size), &s, |b, s| {
+            b.iter(|| s.iter().sum::<usize>())
+        });
+        group.bench_with_input(BenchmarkId::new("SegVec chunks", size), &s, |b, s| {
+            b.iter(|| s.chunks().map(|c| c.iter().sum::<usize>()).sum::<usize>())
+        });
+        group.bench_with_input(BenchmarkId::new("SegVec index", size), &s, |b, s| {
+            b.iter(|| (0 .. s.len()).map(|i| s[i]).sum::<usize>())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_push, bench_iter);
+criterion_main!(benches);
@@ -0,0 +1,148 @@
+//! Benchmarks comparing the sorts of `aisd::sort` with each other and with
+//! `std`, on random and on nearly sorted integers. Run with
+//! `cargo bench --bench sort`, and with `--features parallel` to include
+//! `par_sort`.
+
+#[macro_use]
+extern crate criterion;
+extern crate aisd;
+extern crate rand;
+
+use criterion::{BatchSize, BenchmarkId, Criterion};
+
+use aisd::sort;
+
+use rand::{Rng, SeedableRng};
+use rand::prng::XorShiftRng;
+
+const SIZES: [usize; 2] = [10_000, 1_000_000];
+
+type Sort = fn(&mut [u64]);
+
+fn bench_sorts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sort u64");
+    group.sample_size(10);
+    for &n in SIZES.iter() {
+        let mut rng = XorShiftRng::from_seed([7; 16]);
+        let v: Vec<u64> = (0 .. n).map(|_| rng.gen()).collect();
+
+        #[allow(unused_mut)]
+        let mut sorts: Vec<(&str, Sort)> = vec![
+            ("radix_sort", sort::radix_sort),
+            ("quicksort", sort::quicksort),
+            ("mergesort", sort::mergesort),
+            ("heapsort", sort::heapsort),
+            ("std", <[u64]>::sort_unstable)
+        ];
+        #[cfg(feature = "parallel")]
+        sorts.push(("par_sort", sort::par_sort));
+        for &(name, f) in sorts.iter() {
+            group.bench_with_input(BenchmarkId::new(name, n), &v, |b, v| {
+                b.iter(|| {
+                    let mut v = v.clone();
+                    f(&mut v);
+                    v
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+// Sorted input with 1% of the elements swapped with random others, and sorted
+// input followed by a random tail of 1%.
+fn bench_nearly_sorted(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sort nearly sorted u64");
+    group.sample_size(10);
+    for &n in SIZES.iter() {
+        let mut rng = XorShiftRng::from_seed([7; 16]);
+        let mut swapped: Vec<u64> = (0 .. n as u64).collect();
+        for _ in 0 .. n / 100 {
+            let (i, j) = (rng.gen_range(0, n), rng.gen_range(0, n));
+            swapped.swap(i, j);
+        }
+        let tail: Vec<u64> = (0 .. n as u64 - n as u64 / 100)
+            .chain((0 .. n / 100).map(|_| rng.gen_range(0, n as u64)))
+            .collect();
+
+        let sorts: [(&str, Sort); 4] = [
+            ("natural_mergesort", sort::natural_mergesort),
+            ("mergesort", sort::mergesort),
+            ("quicksort", sort::quicksort),
+            ("std", <[u64]>::sort)
+        ];
+        for &(input, ref v) in [("swapped", &swapped), ("tail", &tail)].iter() {
+            for &(name, f) in sorts.iter() {
+                group.bench_with_input(BenchmarkId::new(format!("{} {}", name, input), n), v, |b, v| {
+                    b.iter(|| {
+                        let mut v = v.to_vec();
+                        f(&mut v);
+                        v
+                    })
+                });
+            }
+        }
+    }
+    group.finish();
+}
+
+// Single and dual pivot quicksort on inputs which stress pivot choice.
+fn bench_quicksorts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("quicksort u64");
+    group.sample_size(10);
+    for &n in SIZES.iter() {
+        let mut rng = XorShiftRng::from_seed([7; 16]);
+        let random: Vec<u64> = (0 .. n).map(|_| rng.gen()).collect();
+        let sorted: Vec<u64> = (0 .. n as u64).collect();
+        let few_unique: Vec<u64> = (0 .. n).map(|_| rng.gen_range(0, 8)).collect();
+
+        let sorts: [(&str, Sort); 2] = [
+            ("quicksort", sort::quicksort),
+            ("dual_pivot_quicksort", sort::dual_pivot_quicksort)
+        ];
+        for &(input, ref v) in [("random", &random), ("sorted", &sorted), ("few unique", &few_unique)].iter() {
+            for &(name, f) in sorts.iter() {
+                group.bench_with_input(BenchmarkId::new(format!("{} {}", name, input), n), v, |b, v| {
+                    b.iter(|| {
+                        let mut v = v.to_vec();
+                        f(&mut v);
+                        v
+                    })
+                });
+            }
+        }
+    }
+    group.finish();
+}
+
+type StringSort = fn(&mut [String]);
+
+// Strings with long common prefixes, like paths or URLs.
+fn bench_strings(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sort strings");
+    group.sample_size(10);
+    for &n in &[10_000, 200_000] {
+        let mut rng = XorShiftRng::from_seed([7; 16]);
+        let v: Vec<String> = (0 .. n).map(|_| {
+            format!("https://example.com/users/{}/posts/{}", rng.gen_range(0, 1000), rng.gen::<u32>())
+        }).collect();
+
+        let sorts: [(&str, StringSort); 5] = [
+            ("msd_radix_sort", sort::msd_radix_sort),
+            ("string_quicksort", sort::string_quicksort),
+            ("quicksort", sort::quicksort),
+            ("mergesort", sort::mergesort),
+            ("std", <[String]>::sort_unstable)
+        ];
+        // Cloning the strings takes about as long as sorting them.
+        for &(name, f) in sorts.iter() {
+            group.bench_with_input(BenchmarkId::new(name, n), &v, |b, v| {
+                b.iter_batched_ref(|| v.clone(), |v| f(v), BatchSize::LargeInput)
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_sorts, bench_nearly_sorted, bench_quicksorts, bench_strings);
+criterion_main!(benches);
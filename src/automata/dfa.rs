@@ -0,0 +1,226 @@
+//! Deterministic finite automata.
+
+use automata::Nfa;
+use automata::nfa::Label;
+use automata::partition::Partition;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+/// A deterministic finite automaton over the states {0, ..., n - 1}. It may be
+/// partial: a missing transition goes to an implicit dead state which accepts
+/// nothing.
+///
+/// Two minimal automata of the same language are equal, because `minimize`
+/// numbers the states in a canonical order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dfa {
+    start: usize,
+    accepting: Vec<bool>,
+    transitions: Vec<BTreeMap<char, usize>>
+}
+
+impl Dfa {
+    /// Builds an automaton accepting the same language as `nfa` with the subset
+    /// construction: every state of the result is a set of states of `nfa`
+    /// closed under ε-transitions. Only the sets reachable from the start are
+    /// built, but there can be exponentially many of them.
+    pub fn from_nfa(nfa: &Nfa) -> Dfa {
+        let mut start = vec![nfa.start()];
+        nfa.close(&mut start);
+
+        let mut dfa = Dfa {start: 0, accepting: vec![], transitions: vec![]};
+        let mut ids = HashMap::new();
+        let mut sets = vec![];
+        ids.insert(start.clone(), 0);
+        sets.push(start);
+
+        let mut i = 0;
+        while i < sets.len() {
+            let mut moves: BTreeMap<char, Vec<usize>> = BTreeMap::new();
+            for &q in &sets[i] {
+                for &(ref label, r) in nfa.transitions(q) {
+                    match label {
+                        Label::Epsilon => {},
+                        Label::Char(c) => moves.entry(*c).or_default().push(r),
+                        // `Nfa::from_regex` doesn't make classes.
+                        Label::Class(_) => unreachable!()
+                    }
+                }
+            }
+
+            let mut transitions = BTreeMap::new();
+            for (c, mut set) in moves {
+                nfa.close(&mut set);
+                set.dedup();
+                let id = *ids.entry(set.clone()).or_insert_with(|| {
+                    sets.push(set);
+                    sets.len() - 1
+                });
+                transitions.insert(c, id);
+            }
+
+            dfa.accepting.push(sets[i].iter().any(|&q| nfa.is_accepting(q)));
+            dfa.transitions.push(transitions);
+            i += 1;
+        }
+
+        dfa
+    }
+
+    /// Returns the number of states, not counting the implicit dead state.
+    pub fn state_count(&self) -> usize {
+        self.transitions.len()
+    }
+
+    /// Checks whether the automaton accepts `s`.
+    /// Time: O(length of `s` * log of the size of the alphabet)
+    pub fn accepts(&self, s: &str) -> bool {
+        let mut q = self.start;
+        for c in s.chars() {
+            match self.transitions[q].get(&c) {
+                Some(&r) => q = r,
+                None => return false
+            }
+        }
+
+        self.accepting[q]
+    }
+
+    /// Builds the automaton with the fewest states accepting the same language,
+    /// with Hopcroft's algorithm. It starts from the partition into accepting
+    /// and other states and refines it until states in the same class go to the
+    /// same classes on every character. A class and a character whose
+    /// predecessors have been used to refine already don't need to be used
+    /// again, and when a class splits only the smaller part is new, so every
+    /// state takes part in O(log n) refinements.
+    /// Time: O(n log n * size of the alphabet)
+    pub fn minimize(&self) -> Dfa {
+        let n = self.state_count();
+        let alphabet: Vec<char> = self.transitions.iter().flat_map(|t| t.keys().cloned())
+            .collect::<BTreeSet<_>>().into_iter().collect();
+
+        // Make the dead state n explicit and find the predecessors of every
+        // state on every character.
+        let dead = n;
+        let mut preds = vec![vec![vec![]; n + 1]; alphabet.len()];
+        for q in 0 ..= n {
+            for (a, c) in alphabet.iter().enumerate() {
+                let r = if q < n {self.transitions[q].get(c).cloned().unwrap_or(dead)} else {dead};
+                preds[a][r].push(q);
+            }
+        }
+
+        let mut p = Partition::new(n + 1);
+        let accepting: Vec<usize> = (0 .. n).filter(|&q| self.accepting[q]).collect();
+        p.refine(&accepting);
+
+        // The pairs (class, character) still to be used to refine. Class 0
+        // alone is enough at the start: refining by one of the two classes
+        // splits the same as refining by the other.
+        let mut waiting = vec![vec![true; alphabet.len()]];
+        let mut pending: Vec<(usize, usize)> = (0 .. alphabet.len()).map(|a| (0, a)).collect();
+        waiting.resize(p.class_count(), vec![false; alphabet.len()]);
+
+        while let Some((class, a)) = pending.pop() {
+            waiting[class][a] = false;
+            let set: Vec<usize> = p.class(class).iter().flat_map(|&r| preds[a][r].iter().cloned()).collect();
+
+            for (old, new) in p.refine(&set) {
+                let smaller = if p.class(new).len() <= p.class(old).len() {new} else {old};
+                let old_waiting = waiting[old].clone();
+                waiting.push(vec![false; alphabet.len()]);
+                for (b, was_waiting) in old_waiting.into_iter().enumerate() {
+                    let add = if was_waiting {new} else {smaller};
+                    waiting[add][b] = true;
+                    pending.push((add, b));
+                }
+            }
+        }
+
+        self.quotient(&p, &alphabet, dead)
+    }
+
+    /// Builds the automaton whose states are the classes of `p`, leaving out
+    /// the class of the dead state, numbered in the order of a breadth-first
+    /// search from the start which tries characters in increasing order.
+    fn quotient(&self, p: &Partition, alphabet: &[char], dead: usize) -> Dfa {
+        let dead_class = p.class_of(dead);
+        let start = p.class_of(self.start);
+        if start == dead_class {
+            return Dfa {start: 0, accepting: vec![false], transitions: vec![BTreeMap::new()]};
+        }
+
+        let mut number = vec![None; p.class_count()];
+        number[start] = Some(0);
+        let mut queue: VecDeque<usize> = vec![start].into();
+        let mut dfa = Dfa {start: 0, accepting: vec![], transitions: vec![]};
+
+        while let Some(class) = queue.pop_front() {
+            // The class of the dead state is left out, so this is a real state.
+            let q = p.class(class)[0];
+            let mut transitions = BTreeMap::new();
+            for c in alphabet {
+                let r = match self.transitions[q].get(c) {
+                    Some(&r) if p.class_of(r) != dead_class => p.class_of(r),
+                    _ => continue
+                };
+                let id = *number[r].get_or_insert_with(|| {
+                    queue.push_back(r);
+                    dfa.transitions.len() + queue.len()
+                });
+                transitions.insert(*c, id);
+            }
+
+            dfa.accepting.push(self.accepting[q]);
+            dfa.transitions.push(transitions);
+        }
+
+        dfa
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use automata::{Dfa, Nfa};
+    use automata::syntax::testing::{naive, word, Re};
+
+    quickcheck! {
+        fn accepts_naive(re: Re, words: Vec<Vec<u8>>) -> bool {
+            let dfa = Dfa::from_nfa(&Nfa::from_regex(&re.0).unwrap());
+            let min = dfa.minimize();
+
+            words.iter().map(|w| word(w)).all(|w| {
+                let expected = naive(&re.0, false, &w);
+                dfa.accepts(&w) == expected && min.accepts(&w) == expected
+            })
+        }
+        fn minimize_minimal(re: Re) -> bool {
+            let dfa = Dfa::from_nfa(&Nfa::from_regex(&re.0).unwrap());
+            let min = dfa.minimize();
+
+            min.state_count() <= dfa.state_count() && min.minimize() == min
+        }
+
+        // Different expressions of the same language give the same minimal
+        // automaton.
+        fn minimize_canonical(re: Re) -> bool {
+            let min = |re: &str| Dfa::from_nfa(&Nfa::from_regex(re).unwrap()).minimize();
+            let r = &re.0;
+
+            min(&format!("({})+", r)) == min(&format!("({0})({0})*", r)) &&
+            min(&format!("({0})|({0})", r)) == min(r) &&
+            min(&format!("(({})*)*", r)) == min(&format!("({})*", r))
+        }
+    }
+
+    #[test]
+    fn textbook() {
+        let min = |re: &str| Dfa::from_nfa(&Nfa::from_regex(re).unwrap()).minimize();
+
+        assert_eq!(min("(a|b)*abb").state_count(), 4);
+        assert_eq!(min("(a|b)*a(a|b)(a|b)").state_count(), 8);
+        assert_eq!(min("(a|b)*"), min("(a*b*)*"));
+        assert_eq!(min("").state_count(), 1);
+        assert!(min("").accepts("") && !min("").accepts("a"));
+    }
+}
@@ -0,0 +1,55 @@
+//! Finite automata: Thompson's construction of an `Nfa` from a regular
+//! expression, the subset construction of a `Dfa` and Hopcroft's minimization,
+//! and a small regular expression engine, `Regex`, built on top of them.
+//!
+//! Regular expressions are made of characters, `|` (alternation), `*` (zero or
+//! more), `+` (one or more), `?` (zero or one) and parentheses for grouping.
+//! A backslash makes the next character stand for itself, like `\*`.
+//! Expressions can be nested at most 250 levels deep, counting groups,
+//! repetitions, alternatives and concatenations; deeper ones are rejected
+//! with a `ParseError`.
+//!
+//! # Example
+//!
+//! ```
+//! extern crate aisd;
+//! use aisd::automata::{Dfa, Nfa};
+//!
+//! let nfa = Nfa::from_regex("(a|b)*abb").unwrap();
+//! let dfa = Dfa::from_nfa(&nfa).minimize();
+//!
+//! assert!(dfa.accepts("babb"));
+//! assert!(!dfa.accepts("abba"));
+//! assert_eq!(dfa.state_count(), 4);
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+mod syntax;
+pub mod regex;
+pub mod partition;
+pub mod nfa;
+pub mod dfa;
+
+pub use self::nfa::Nfa;
+pub use self::dfa::Dfa;
+pub use self::regex::Regex;
+
+/// The reason why a regular expression couldn't be parsed and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The position of the offending character, counted in characters.
+    pub position: usize,
+
+    /// What's wrong.
+    pub message: &'static str
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for ParseError {}
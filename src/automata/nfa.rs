@@ -0,0 +1,211 @@
+//! Nondeterministic finite automata.
+
+use automata::ParseError;
+use automata::syntax::{self, Ast, CharClass};
+
+/// What a transition reads.
+#[derive(Debug, Clone)]
+pub(crate) enum Label {
+    Epsilon,
+    Char(char),
+    Class(CharClass)
+}
+
+impl Label {
+    /// Checks whether the transition can read `c`.
+    pub(crate) fn matches(&self, c: char) -> bool {
+        match self {
+            Label::Epsilon => false,
+            Label::Char(d) => *d == c,
+            Label::Class(class) => class.contains(c)
+        }
+    }
+}
+
+/// A nondeterministic finite automaton with ε-transitions, over the states
+/// {0, ..., n - 1}.
+#[derive(Debug, Clone)]
+pub struct Nfa {
+    start: usize,
+    accepting: Vec<bool>,
+    // The transitions leaving every state.
+    transitions: Vec<Vec<(Label, usize)>>
+}
+
+impl Nfa {
+    /// Builds an automaton accepting the language of the regular expression `re`
+    /// with Thompson's construction. Every part of the expression becomes a
+    /// piece with one entry and one exit, glued to the others with
+    /// ε-transitions, so the automaton has O(length of `re`) states and
+    /// transitions and exactly one accepting state.
+    ///
+    /// See the module documentation for the syntax. Character classes aren't
+    /// allowed, so that the alphabet is finite and the automaton can be made
+    /// deterministic; `[` and `.` stand for themselves.
+    pub fn from_regex(re: &str) -> Result<Nfa, ParseError> {
+        Ok(Nfa::from_ast(&syntax::parse(re, false)?))
+    }
+
+    /// Builds an automaton accepting the language of `ast`.
+    pub(crate) fn from_ast(ast: &Ast) -> Nfa {
+        let mut nfa = Nfa {start: 0, accepting: vec![], transitions: vec![]};
+        let (start, end) = nfa.build(ast);
+        nfa.start = start;
+        nfa.accepting[end] = true;
+
+        nfa
+    }
+
+    /// Returns the number of states.
+    pub fn state_count(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub(crate) fn start(&self) -> usize {
+        self.start
+    }
+
+    pub(crate) fn is_accepting(&self, q: usize) -> bool {
+        self.accepting[q]
+    }
+
+    pub(crate) fn transitions(&self, q: usize) -> &[(Label, usize)] {
+        &self.transitions[q]
+    }
+
+    /// Adds all the states reachable from `states` by ε-transitions to it and
+    /// sorts it.
+    pub(crate) fn close(&self, states: &mut Vec<usize>) {
+        let mut seen = vec![false; self.state_count()];
+        for &q in states.iter() {
+            seen[q] = true;
+        }
+
+        let mut stack = states.clone();
+        while let Some(q) = stack.pop() {
+            for &(ref label, r) in &self.transitions[q] {
+                if matches!(label, Label::Epsilon) && !seen[r] {
+                    seen[r] = true;
+                    states.push(r);
+                    stack.push(r);
+                }
+            }
+        }
+
+        states.sort_unstable();
+    }
+
+    /// The states reached from `states` by reading `c`, closed under
+    /// ε-transitions.
+    pub(crate) fn step(&self, states: &[usize], c: char) -> Vec<usize> {
+        let mut seen = vec![false; self.state_count()];
+        let mut next = vec![];
+        for &q in states {
+            for &(ref label, r) in &self.transitions[q] {
+                if label.matches(c) && !seen[r] {
+                    seen[r] = true;
+                    next.push(r);
+                }
+            }
+        }
+
+        self.close(&mut next);
+        next
+    }
+
+    /// Checks whether the automaton accepts `s`, by following all the states it
+    /// can be in at once (Thompson's simulation). There are at most n of them,
+    /// so unlike backtracking this never blows up.
+    /// Time: O(length of `s` * size of the automaton)
+    pub fn accepts(&self, s: &str) -> bool {
+        let mut states = vec![self.start];
+        self.close(&mut states);
+        for c in s.chars() {
+            if states.is_empty() {
+                return false;
+            }
+            states = self.step(&states, c);
+        }
+
+        states.iter().any(|&q| self.accepting[q])
+    }
+
+    fn add_state(&mut self) -> usize {
+        self.accepting.push(false);
+        self.transitions.push(vec![]);
+        self.transitions.len() - 1
+    }
+
+    fn link(&mut self, from: usize, label: Label, to: usize) {
+        self.transitions[from].push((label, to));
+    }
+
+    /// Adds the piece for `ast` and returns its entry and exit.
+    fn build(&mut self, ast: &Ast) -> (usize, usize) {
+        let (start, end) = (self.add_state(), self.add_state());
+        match ast {
+            Ast::Empty => self.link(start, Label::Epsilon, end),
+            Ast::Char(c) => self.link(start, Label::Char(*c), end),
+            Ast::Class(class) => self.link(start, Label::Class(class.clone()), end),
+            Ast::Concat(parts) => {
+                let mut last = start;
+                for part in parts {
+                    let (s, e) = self.build(part);
+                    self.link(last, Label::Epsilon, s);
+                    last = e;
+                }
+                self.link(last, Label::Epsilon, end);
+            },
+            Ast::Alt(branches) => {
+                for branch in branches {
+                    let (s, e) = self.build(branch);
+                    self.link(start, Label::Epsilon, s);
+                    self.link(e, Label::Epsilon, end);
+                }
+            },
+            Ast::Star(a) | Ast::Plus(a) | Ast::Optional(a) => {
+                let (s, e) = self.build(a);
+                self.link(start, Label::Epsilon, s);
+                self.link(e, Label::Epsilon, end);
+                if let Ast::Star(_) | Ast::Optional(_) = ast {
+                    self.link(start, Label::Epsilon, end);
+                }
+                if let Ast::Star(_) | Ast::Plus(_) = ast {
+                    self.link(e, Label::Epsilon, s);
+                }
+            }
+        }
+
+        (start, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Nfa;
+    use automata::syntax::testing::{naive, word, Re};
+
+    quickcheck! {
+        fn accepts_naive(re: Re, words: Vec<Vec<u8>>) -> bool {
+            let nfa = Nfa::from_regex(&re.0).unwrap();
+            words.iter().map(|w| word(w)).all(|w| nfa.accepts(&w) == naive(&re.0, false, &w))
+        }
+    }
+
+    #[test]
+    fn linear_size() {
+        let nfa = Nfa::from_regex("(a|b)*abb").unwrap();
+        assert!(nfa.state_count() <= 4 * 9);
+        assert_eq!((0 .. nfa.state_count()).filter(|&q| nfa.is_accepting(q)).count(), 1);
+    }
+
+    #[test]
+    fn close() {
+        let nfa = Nfa::from_regex("a*").unwrap();
+        let mut states = vec![nfa.start()];
+        nfa.close(&mut states);
+
+        assert!(states.windows(2).all(|w| w[0] < w[1]));
+        assert!(states.iter().any(|&q| nfa.is_accepting(q)));
+    }
+}
@@ -0,0 +1,145 @@
+//! Partition refinement.
+
+/// A partition of the numbers {0, ..., n - 1} into classes, which can be
+/// refined by a set: every class which the set cuts is split into the part
+/// inside the set and the part outside. Refining costs O(size of the set), no
+/// matter how big the split classes are, which is what makes Hopcroft's
+/// minimization O(n log n).
+///
+/// Elements are kept in an array in which every class is a contiguous segment.
+/// To split a class, the elements of the set are swapped to the front of its
+/// segment, which then gets cut in two.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::automata::partition::Partition;
+///
+/// let mut p = Partition::new(6);
+/// assert_eq!(p.refine(&[0, 2, 4]), vec![(0, 1)]);
+/// assert_eq!(p.refine(&[4, 5]), vec![(1, 2), (0, 3)]);
+///
+/// assert_eq!(p.class_count(), 4);
+/// assert!(p.class_of(0) == p.class_of(2) && p.class_of(0) != p.class_of(4));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Partition {
+    elements: Vec<usize>,
+    // The place of every element in `elements`.
+    index: Vec<usize>,
+    class: Vec<usize>,
+    // The segment of every class in `elements`.
+    bounds: Vec<(usize, usize)>,
+    // The number of elements at the front of every segment marked by the
+    // refinement going on.
+    marked: Vec<usize>
+}
+
+impl Partition {
+    /// Creates the partition with one class, 0, holding all of {0, ..., n - 1},
+    /// or with no classes if n is 0.
+    pub fn new(n: usize) -> Partition {
+        let classes = if n > 0 {1} else {0};
+        Partition {
+            elements: (0 .. n).collect(),
+            index: (0 .. n).collect(),
+            class: vec![0; n],
+            bounds: vec![(0, n); classes],
+            marked: vec![0; classes]
+        }
+    }
+
+    /// Returns the number of classes.
+    pub fn class_count(&self) -> usize {
+        self.bounds.len()
+    }
+
+    /// Returns the class of `x`.
+    pub fn class_of(&self, x: usize) -> usize {
+        self.class[x]
+    }
+
+    /// Returns the elements of class `c`, in no particular order.
+    pub fn class(&self, c: usize) -> &[usize] {
+        let (start, end) = self.bounds[c];
+        &self.elements[start .. end]
+    }
+
+    /// Splits every class which has elements both inside and outside `set`.
+    /// The part inside becomes a new class, numbered after all the existing
+    /// ones, and the part outside keeps the old number. Returns the pairs of
+    /// (old class, new class), in the order of first appearance in `set`.
+    /// Repeated elements of `set` are ignored.
+    /// Time: O(size of `set`)
+    pub fn refine(&mut self, set: &[usize]) -> Vec<(usize, usize)> {
+        let mut touched = vec![];
+        for &x in set {
+            let c = self.class[x];
+            let front = self.bounds[c].0 + self.marked[c];
+            let i = self.index[x];
+            if i < front {
+                continue;
+            }
+            if self.marked[c] == 0 {
+                touched.push(c);
+            }
+
+            let y = self.elements[front];
+            self.elements.swap(i, front);
+            self.index[x] = front;
+            self.index[y] = i;
+            self.marked[c] += 1;
+        }
+
+        let mut splits = vec![];
+        for c in touched {
+            let (start, end) = self.bounds[c];
+            let mid = start + self.marked[c];
+            self.marked[c] = 0;
+            if mid == end {
+                continue;
+            }
+
+            let new = self.bounds.len();
+            self.bounds.push((start, mid));
+            self.marked.push(0);
+            self.bounds[c] = (mid, end);
+            for &x in &self.elements[start .. mid] {
+                self.class[x] = new;
+            }
+            splits.push((c, new));
+        }
+
+        splits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Partition;
+
+    quickcheck! {
+        // Two elements end up in the same class when every set has either both
+        // or neither of them.
+        fn refine_naive(n: u8, sets: Vec<Vec<u8>>) -> bool {
+            let n = n as usize % 32;
+            let sets: Vec<Vec<usize>> =
+                sets.iter().map(|s| s.iter().map(|&x| x as usize).filter(|&x| x < n).collect()).collect();
+
+            let mut p = Partition::new(n);
+            let mut sizes_ok = true;
+            for s in &sets {
+                let before = p.class_count();
+                let splits = p.refine(s);
+                sizes_ok &= p.class_count() == before + splits.len();
+            }
+
+            let classes_ok = (0 .. p.class_count()).all(|c| p.class(c).iter().all(|&x| p.class_of(x) == c));
+            let same = |x: usize, y: usize| sets.iter().all(|s| s.contains(&x) == s.contains(&y));
+
+            sizes_ok && classes_ok &&
+            (0 .. n).all(|x| (0 .. n).all(|y| (p.class_of(x) == p.class_of(y)) == same(x, y)))
+        }
+    }
+}
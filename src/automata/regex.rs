@@ -0,0 +1,174 @@
+//! A small regular expression engine.
+
+use automata::{Nfa, ParseError};
+use automata::nfa::Label;
+use automata::syntax;
+
+use std::ops::Range;
+
+/// A compiled regular expression, matched by simulating its Thompson automaton
+/// (see `Nfa::accepts`). Matching takes O(length of the text * length of the
+/// expression) no matter what, while backtracking engines can take exponential
+/// time on expressions like `(a*)*b`.
+///
+/// Besides the syntax of the module documentation, there are character classes:
+/// `[abc]`, ranges like `[a-z0-9]`, negated classes like `[^0-9]`, `.` for any
+/// character and `\d`, `\w` and `\s` for digits, word characters and whitespace.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::automata::regex::Regex;
+///
+/// let re = Regex::new("[a-z]+@[a-z]+\\.(com|org)").unwrap();
+/// assert!(re.accepts("alice@example.com"));
+/// assert!(!re.accepts("alice@example.net"));
+///
+/// let text = "write to bob@example.org today";
+/// assert_eq!(re.find(text).map(|r| &text[r]), Some("bob@example.org"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Regex {
+    nfa: Nfa
+}
+
+impl Regex {
+    /// Compiles the expression `re`.
+    pub fn new(re: &str) -> Result<Regex, ParseError> {
+        Ok(Regex {nfa: Nfa::from_ast(&syntax::parse(re, true)?)})
+    }
+
+    /// Checks whether all of `s` matches.
+    /// Time: O(length of `s` * length of the expression)
+    pub fn accepts(&self, s: &str) -> bool {
+        self.nfa.accepts(s)
+    }
+
+    /// Finds the leftmost longest match in `s` and returns its range of bytes.
+    ///
+    /// A match may start at every position, so every state of the simulation
+    /// remembers the leftmost start from which it can be reached. Once a match
+    /// is found, states which started later are dropped, and the search goes on
+    /// only to make the match longer.
+    /// Time: O(length of `s` * length of the expression)
+    pub fn find(&self, s: &str) -> Option<Range<usize>> {
+        let n = self.nfa.state_count();
+        let mut best: Option<Range<usize>> = None;
+        // States with the starts they were reached from, in increasing order of
+        // starts.
+        let mut states: Vec<(usize, usize)> = vec![];
+
+        let positions = s.char_indices().map(|(i, c)| (i, Some(c))).chain(Some((s.len(), None)));
+        for (i, c) in positions {
+            if best.is_none() {
+                states.push((self.nfa.start(), i));
+            }
+            states = self.close(states);
+
+            if let Some(&(_, start)) = states.iter().find(|&&(q, _)| self.nfa.is_accepting(q)) {
+                if best.as_ref().is_none_or(|b| start < b.start || (start == b.start && i > b.end)) {
+                    best = Some(start .. i);
+                }
+            }
+            if let Some(b) = &best {
+                states.retain(|&(_, start)| start <= b.start);
+            }
+
+            let c = match c {
+                Some(c) if !states.is_empty() || best.is_none() => c,
+                _ => break
+            };
+
+            let mut seen = vec![false; n];
+            let mut next = vec![];
+            for (q, start) in states {
+                for &(ref label, r) in self.nfa.transitions(q) {
+                    if label.matches(c) && !seen[r] {
+                        seen[r] = true;
+                        next.push((r, start));
+                    }
+                }
+            }
+            states = next;
+        }
+
+        best
+    }
+
+    /// Adds the states reachable by ε-transitions, each with the leftmost start
+    /// it can be reached from. Keeps the order of starts.
+    fn close(&self, states: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+        let mut seen = vec![false; self.nfa.state_count()];
+        let mut closed = vec![];
+        for (q, start) in states {
+            if seen[q] {
+                continue;
+            }
+            seen[q] = true;
+
+            let mut stack = vec![q];
+            while let Some(q) = stack.pop() {
+                closed.push((q, start));
+                for &(ref label, r) in self.nfa.transitions(q) {
+                    if matches!(label, Label::Epsilon) && !seen[r] {
+                        seen[r] = true;
+                        stack.push(r);
+                    }
+                }
+            }
+        }
+
+        closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Regex;
+    use automata::syntax::testing::{naive, word, ClassRe};
+
+    quickcheck! {
+        fn accepts_naive(re: ClassRe, words: Vec<Vec<u8>>) -> bool {
+            let r = Regex::new(&re.0).unwrap();
+            words.iter().map(|w| word(w)).all(|w| r.accepts(&w) == naive(&re.0, true, &w))
+        }
+
+        // The leftmost longest match, found by trying all substrings.
+        fn find_naive(re: ClassRe, w: Vec<u8>) -> bool {
+            let r = Regex::new(&re.0).unwrap();
+            let w = word(&w);
+            let expected = (0 ..= w.len()).flat_map(|i| (i ..= w.len()).rev().map(move |j| i .. j))
+                .find(|range| naive(&re.0, true, &w[range.clone()]));
+
+            r.find(&w) == expected
+        }
+    }
+
+    #[test]
+    fn no_blowup() {
+        let r = Regex::new("(a*)*(a|b)*c").unwrap();
+        let text = "ab".repeat(10_000);
+
+        assert!(!r.accepts(&text));
+        assert_eq!(r.find(&text), None);
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let deep = format!("{}a{}", "(".repeat(20_000), ")".repeat(20_000));
+        assert_eq!(Regex::new(&deep).unwrap_err().message, "nesting too deep");
+
+        let r = Regex::new(&format!("{}a+{}", "(".repeat(200), ")*".repeat(200))).unwrap();
+        assert!(r.accepts("") && r.accepts("aaa") && !r.accepts("b"));
+    }
+
+    #[test]
+    fn unicode() {
+        let r = Regex::new("[α-ω]+").unwrap();
+        let text = "to jest αβγ!";
+
+        assert_eq!(r.find(text).map(|range| &text[range]), Some("αβγ"));
+        assert_eq!(Regex::new("x?").unwrap().find("abc"), Some(0 .. 0));
+    }
+}
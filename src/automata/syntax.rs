@@ -0,0 +1,417 @@
+//! Parsing regular expressions into syntax trees.
+
+use automata::ParseError;
+
+/// A set of characters: the ones in any of the ranges or, if `negated`, the
+/// ones in none of them.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CharClass {
+    pub(crate) ranges: Vec<(char, char)>,
+    pub(crate) negated: bool
+}
+
+impl CharClass {
+    pub(crate) fn contains(&self, c: char) -> bool {
+        self.ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi) != self.negated
+    }
+
+    /// The class of all characters, `.`.
+    fn any() -> CharClass {
+        CharClass {ranges: vec![], negated: true}
+    }
+}
+
+/// The syntax tree of a regular expression.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Ast {
+    /// Matches the empty string.
+    Empty,
+    Char(char),
+    Class(CharClass),
+    Concat(Vec<Ast>),
+    Alt(Vec<Ast>),
+    Star(Box<Ast>),
+    Plus(Box<Ast>),
+    Optional(Box<Ast>)
+}
+
+/// A recursive descent parser with one character of lookahead. The grammar is
+///
+/// ```text
+/// alt    = concat ('|' concat)*
+/// concat = repeat*
+/// repeat = atom ('*' | '+' | '?')*
+/// atom   = '(' alt ')' | '\' char | class | '.' | char
+/// class  = '[' '^'? (char | char '-' char)+ ']'
+/// ```
+///
+/// where classes, `.` and the escapes `\d`, `\w` and `\s` are only there if
+/// `classes` is set. Otherwise `[` and `.` stand for themselves.
+///
+/// Every parsing function returns the height of the tree it built too. Both
+/// the height and the number of open parentheses are at most `MAX_DEPTH`.
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    classes: bool,
+    // The number of open parentheses.
+    depth: usize
+}
+
+/// How deep expressions can be nested. The parser and Thompson's construction
+/// are recursive, so deeper ones would overflow the stack.
+const MAX_DEPTH: usize = 250;
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).cloned()
+    }
+
+    fn error(&self, message: &'static str) -> ParseError {
+        ParseError {position: self.pos, message}
+    }
+
+    /// Checks that a tree of height `height` isn't too deep.
+    fn check(&self, height: usize) -> Result<usize, ParseError> {
+        if height > MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+
+        Ok(height)
+    }
+
+    fn alt(&mut self) -> Result<(Ast, usize), ParseError> {
+        let (first, mut height) = self.concat()?;
+        let mut branches = vec![first];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            let (branch, h) = self.concat()?;
+            branches.push(branch);
+            height = height.max(h);
+        }
+
+        Ok(if branches.len() == 1 {
+            (branches.pop().unwrap(), height)
+        } else {
+            (Ast::Alt(branches), self.check(height + 1)?)
+        })
+    }
+
+    fn concat(&mut self) -> Result<(Ast, usize), ParseError> {
+        let mut parts = vec![];
+        let mut height = 0;
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let (part, h) = self.repeat()?;
+            parts.push(part);
+            height = height.max(h);
+        }
+
+        Ok(match parts.len() {
+            0 => (Ast::Empty, 1),
+            1 => (parts.pop().unwrap(), height),
+            _ => (Ast::Concat(parts), self.check(height + 1)?)
+        })
+    }
+
+    fn repeat(&mut self) -> Result<(Ast, usize), ParseError> {
+        let (mut ast, mut height) = self.atom()?;
+        loop {
+            ast = match self.peek() {
+                Some('*') => Ast::Star(Box::new(ast)),
+                Some('+') => Ast::Plus(Box::new(ast)),
+                Some('?') => Ast::Optional(Box::new(ast)),
+                _ => return Ok((ast, height))
+            };
+            height = self.check(height + 1)?;
+            self.pos += 1;
+        }
+    }
+
+    fn atom(&mut self) -> Result<(Ast, usize), ParseError> {
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return Err(self.error("expected a character"))
+        };
+
+        match c {
+            '(' => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error("nesting too deep"));
+                }
+                let open = self.pos;
+                self.pos += 1;
+                self.depth += 1;
+                let ast = self.alt()?;
+                if self.peek() != Some(')') {
+                    return Err(ParseError {position: open, message: "unmatched '('"});
+                }
+                self.pos += 1;
+                self.depth -= 1;
+                Ok(ast)
+            },
+            '*' | '+' | '?' => Err(self.error("nothing to repeat")),
+            '\\' => self.escape().map(|e| match e {
+                Ok(c) => (Ast::Char(c), 1),
+                Err(class) => (Ast::Class(class), 1)
+            }),
+            '[' if self.classes => self.class().map(|class| (Ast::Class(class), 1)),
+            '.' if self.classes => {
+                self.pos += 1;
+                Ok((Ast::Class(CharClass::any()), 1))
+            },
+            c => {
+                self.pos += 1;
+                Ok((Ast::Char(c), 1))
+            }
+        }
+    }
+
+    /// Parses a backslash and what follows, which is either an escaped
+    /// character or the class of digits (`\d`), word characters (`\w`) or
+    /// whitespace (`\s`).
+    fn escape(&mut self) -> Result<Result<char, CharClass>, ParseError> {
+        self.pos += 1;
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return Err(self.error("nothing to escape"))
+        };
+        self.pos += 1;
+
+        let ranges = match c {
+            'd' if self.classes => vec![('0', '9')],
+            'w' if self.classes => vec![('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')],
+            's' if self.classes => vec![(' ', ' '), ('\t', '\r')],
+            c => return Ok(Ok(c))
+        };
+
+        Ok(Err(CharClass {ranges, negated: false}))
+    }
+
+    fn class(&mut self) -> Result<CharClass, ParseError> {
+        let open = self.pos;
+        self.pos += 1;
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.pos += 1;
+        }
+
+        let mut ranges = vec![];
+        loop {
+            let start = self.pos;
+            let lo = match self.peek() {
+                None => return Err(ParseError {position: open, message: "unmatched '['"}),
+                Some(']') if !ranges.is_empty() => break,
+                Some(']') => return Err(self.error("empty class")),
+                Some('\\') => match self.escape()? {
+                    Ok(c) => c,
+                    Err(class) => {
+                        ranges.extend(class.ranges);
+                        continue;
+                    }
+                },
+                Some(c) => {
+                    self.pos += 1;
+                    c
+                }
+            };
+
+            // A '-' right before the ']' stands for itself.
+            let is_range = self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&c| c != ']');
+            if !is_range {
+                ranges.push((lo, lo));
+                continue;
+            }
+
+            self.pos += 1;
+            let hi = match self.peek() {
+                Some('\\') => match self.escape()? {
+                    Ok(c) => c,
+                    Err(_) => return Err(ParseError {position: start, message: "invalid range"})
+                },
+                Some(c) => {
+                    self.pos += 1;
+                    c
+                },
+                None => unreachable!()
+            };
+            if lo > hi {
+                return Err(ParseError {position: start, message: "invalid range"});
+            }
+            ranges.push((lo, hi));
+        }
+        self.pos += 1;
+
+        Ok(CharClass {ranges, negated})
+    }
+}
+
+/// Parses a regular expression, with character classes if `classes` is set.
+pub(crate) fn parse(re: &str, classes: bool) -> Result<Ast, ParseError> {
+    let mut p = Parser {chars: re.chars().collect(), pos: 0, classes, depth: 0};
+    let (ast, _) = p.alt()?;
+    if p.pos < p.chars.len() {
+        // Only an unmatched ')' stops the parser early.
+        return Err(p.error("unmatched ')'"));
+    }
+
+    Ok(ast)
+}
+
+/// Random expressions and a slow but obviously right matcher, to test the
+/// automata against.
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::{parse, Ast};
+
+    use quickcheck::{Arbitrary, Gen};
+    use rand;
+    use rand::Rng;
+
+    use std::collections::BTreeSet;
+
+    /// A random regular expression over {a, b, c}, with classes if `classes` is
+    /// set.
+    pub fn random_regex<R: Rng>(g: &mut R, depth: usize, classes: bool) -> String {
+        let leaf = depth == 0 || g.gen_range(0usize, 10) < 3;
+        let kinds = if classes {6} else {4};
+        match if leaf {g.gen_range(0usize, kinds)} else {g.gen_range(6, 11)} {
+            0 => String::new(),
+            1 ..= 3 => ['a', 'b', 'c'][g.gen_range(0usize, 3)].to_string(),
+            4 => ["[ab]", "[^a]", "[a-b]", "[^b-c]", "."][g.gen_range(0usize, 5)].to_string(),
+            5 => ["\\w", "\\d", "[c\\d]"][g.gen_range(0usize, 3)].to_string(),
+            6 | 7 => format!("{}{}", random_regex(g, depth - 1, classes), random_regex(g, depth - 1, classes)),
+            8 => format!("({}|{})", random_regex(g, depth - 1, classes), random_regex(g, depth - 1, classes)),
+            9 => format!("({}){}", random_regex(g, depth - 1, classes), ['*', '+', '?'][g.gen_range(0usize, 3)]),
+            _ => format!("({})", random_regex(g, depth - 1, classes))
+        }
+    }
+
+    /// A random regular expression without classes.
+    #[derive(Debug, Clone)]
+    pub struct Re(pub String);
+
+    impl Arbitrary for Re {
+        fn arbitrary<G: Gen>(_: &mut G) -> Re {
+            Re(random_regex(&mut rand::thread_rng(), 5, false))
+        }
+    }
+
+    /// A random regular expression with classes.
+    #[derive(Debug, Clone)]
+    pub struct ClassRe(pub String);
+
+    impl Arbitrary for ClassRe {
+        fn arbitrary<G: Gen>(_: &mut G) -> ClassRe {
+            ClassRe(random_regex(&mut rand::thread_rng(), 5, true))
+        }
+    }
+
+    /// A word over {a, b, c, 1} of length at most 8.
+    pub fn word(w: &[u8]) -> String {
+        w.iter().take(8).map(|&c| ['a', 'b', 'c', '1'][c as usize % 4]).collect()
+    }
+
+    /// The positions in `s` where a match of `ast` starting at `i` can end.
+    pub fn ends(ast: &Ast, s: &[char], i: usize) -> BTreeSet<usize> {
+        let one = |j| vec![j].into_iter().collect();
+        match ast {
+            Ast::Empty => one(i),
+            Ast::Char(c) => if s.get(i) == Some(c) {one(i + 1)} else {BTreeSet::new()},
+            Ast::Class(class) => if s.get(i).is_some_and(|&c| class.contains(c)) {one(i + 1)} else {BTreeSet::new()},
+            Ast::Concat(parts) => parts.iter().fold(one(i), |set, part| {
+                set.into_iter().flat_map(|j| ends(part, s, j)).collect()
+            }),
+            Ast::Alt(branches) => branches.iter().flat_map(|b| ends(b, s, i)).collect(),
+            Ast::Optional(a) => {
+                let mut set = ends(a, s, i);
+                set.insert(i);
+                set
+            },
+            Ast::Star(a) | Ast::Plus(a) => {
+                let mut set = if let Ast::Star(_) = ast {one(i)} else {BTreeSet::new()};
+                let mut todo: Vec<usize> = ends(a, s, i).into_iter().collect();
+                while let Some(j) = todo.pop() {
+                    if set.insert(j) {
+                        todo.extend(ends(a, s, j));
+                    }
+                }
+                set
+            }
+        }
+    }
+
+    /// Checks whether all of `s` matches `re`.
+    pub fn naive(re: &str, classes: bool, s: &str) -> bool {
+        let s: Vec<char> = s.chars().collect();
+        ends(&parse(re, classes).unwrap(), &s, 0).contains(&s.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Ast, CharClass, MAX_DEPTH};
+    use super::Ast::*;
+    use automata::ParseError;
+
+    fn ch(c: char) -> Box<Ast> {
+        Box::new(Char(c))
+    }
+
+    fn class(ranges: &[(char, char)], negated: bool) -> Ast {
+        Class(CharClass {ranges: ranges.to_vec(), negated})
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(parse("ab|c*", false).unwrap(), Alt(vec![Concat(vec![Char('a'), Char('b')]), Star(ch('c'))]));
+        assert_eq!(parse("(a|)+?", false).unwrap(), Optional(Box::new(Plus(Box::new(Alt(vec![Char('a'), Empty]))))));
+        assert_eq!(parse("\\*\\\\", false).unwrap(), Concat(vec![Char('*'), Char('\\')]));
+        assert_eq!(parse("", false).unwrap(), Empty);
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(parse("[a-cx]", true).unwrap(), class(&[('a', 'c'), ('x', 'x')], false));
+        assert_eq!(parse("[^-a-]", true).unwrap(), class(&[('-', '-'), ('a', 'a'), ('-', '-')], true));
+        assert_eq!(parse("[]\\]]", true), Err(ParseError {position: 1, message: "empty class"}));
+        assert_eq!(parse("[\\]\\d]", true).unwrap(), class(&[(']', ']'), ('0', '9')], false));
+        assert_eq!(parse(".", true).unwrap(), class(&[], true));
+        assert_eq!(parse("\\d", true).unwrap(), class(&[('0', '9')], false));
+
+        // Without classes, all of these are plain characters.
+        assert_eq!(parse("[.]", false).unwrap(), Concat(vec![Char('['), Char('.'), Char(']')]));
+        assert_eq!(parse("\\d", false).unwrap(), Char('d'));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |position, message| Err(ParseError {position, message});
+
+        assert_eq!(parse("a(b|c", false), error(1, "unmatched '('"));
+        assert_eq!(parse("ab)c", false), error(2, "unmatched ')'"));
+        assert_eq!(parse("a|*", false), error(2, "nothing to repeat"));
+        assert_eq!(parse("ab\\", false), error(3, "nothing to escape"));
+        assert_eq!(parse("a[bc", true), error(1, "unmatched '['"));
+        assert_eq!(parse("[ac-b]", true), error(2, "invalid range"));
+        assert_eq!(parse("[a-\\d]", true), error(1, "invalid range"));
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let nested = |n| format!("{}a{}", "(".repeat(n), ")".repeat(n));
+        assert_eq!(parse(&nested(20_000), true), Err(ParseError {position: MAX_DEPTH, message: "nesting too deep"}));
+        assert_eq!(parse(&nested(MAX_DEPTH), true), Ok(Char('a')));
+
+        let starred = |n| format!("a{}", "*".repeat(n));
+        assert_eq!(parse(&starred(20_000), true), Err(ParseError {position: MAX_DEPTH, message: "nesting too deep"}));
+        assert!(parse(&starred(MAX_DEPTH - 1), true).is_ok());
+
+        // Every group adds an alternative and a concatenation to the height.
+        let alternated = |n| format!("{}c{}", "(a|b".repeat(n), ")".repeat(n));
+        assert_eq!(parse(&alternated(MAX_DEPTH / 2 + 1), true).unwrap_err().message, "nesting too deep");
+        assert!(parse(&alternated(MAX_DEPTH / 2 - 1), true).is_ok());
+    }
+}
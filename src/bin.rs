@@ -1,21 +1,288 @@
-extern crate aisd;
+//! A command line driver which runs the algorithms of the crate on inputs read
+//! from a file or stdin. Build it with `cargo run --features cli -- <command>`.
 
-//use std::collections::HashSet;
+extern crate aisd;
 
+use aisd::coin_change::{make_change_count, make_change_greedy_unlimited};
+use aisd::graph::{LabeledGraph, MAX_NODE, dijkstra, kruskal, realize_degree_sequence};
 use aisd::pq::Heap;
 
-use aisd::depq::DEPQ;
-use aisd::depq::DoubleHeap;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::process;
+use std::str::FromStr;
+
+/// A subcommand: its name, what arguments it takes, what it does and how to run
+/// it on the arguments and the input.
+struct Command {
+    name: &'static str,
+    args: &'static str,
+    about: &'static str,
+    run: fn(&[String], &str) -> Result<String, String>
+}
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "sort",
+        args: "",
+        about: "heapsorts the integers of the input",
+        run: sort
+    },
+    Command {
+        name: "change",
+        args: "AMOUNT",
+        about: "greedily makes change for AMOUNT using the coin values of the input",
+        run: change
+    },
+    Command {
+        name: "change-count",
+        args: "AMOUNT",
+        about: "computes the least number of coins of the input that sum up to AMOUNT",
+        run: change_count
+    },
+    Command {
+        name: "realize",
+        args: "",
+        about: "prints the edges of a simple graph with the degree sequence of the input",
+        run: realize
+    },
+    Command {
+        name: "mst",
+        args: "",
+        about: "prints a minimum spanning forest of the weighted edges `u v w` of the input",
+        run: mst
+    },
+    Command {
+        name: "dijkstra",
+        args: "SOURCE",
+        about: "prints the shortest distances from SOURCE along the weighted directed edges of the input",
+        run: shortest_paths
+    }
+];
+
+/// Parses whitespace-separated values.
+fn parse<T: FromStr>(input: &str) -> Result<Vec<T>, String> {
+    input.split_whitespace()
+         .map(|w| w.parse().map_err(|_| format!("not a number: {}", w)))
+         .collect()
+}
+
+/// Parses the only argument, which is a number.
+fn amount(args: &[String]) -> Result<usize, String> {
+    match args {
+        [a] => a.parse().map_err(|_| format!("not an amount: {}", a)),
+        _ => Err("expected exactly one argument, the amount".to_string())
+    }
+}
+
+/// Parses a graph from lines `u v w`, each an edge from u to v of weight w. The
+/// graph has the nodes up to the greatest number seen, which can be at most
+/// `MAX_NODE`. Weights fit in `u32`, so that sums of them fit in `u64`.
+fn weighted_edges(input: &str, directed: bool) -> Result<LabeledGraph<(), u64>, String> {
+    let mut n = 0;
+    let mut edges = vec![];
+    for line in input.lines().filter(|l| !l.trim().is_empty()) {
+        let (u, v, w) = match parse::<u64>(line)?[..] {
+            [u, v, w] => (u, v, w),
+            _ => return Err(format!("not an edge `u v w`: {}", line.trim()))
+        };
+        let (u, v) = match (usize::try_from(u), usize::try_from(v)) {
+            (Ok(u), Ok(v)) if u.max(v) <= MAX_NODE => (u, v),
+            _ => return Err(format!("node number too big: {}", u.max(v)))
+        };
+        let w = u32::try_from(w).map_err(|_| format!("weight too big: {}", w))?;
+        n = n.max(u.max(v) + 1);
+        edges.push((u, v, u64::from(w)));
+    }
+
+    Ok(LabeledGraph::from_edges(directed, n, edges))
+}
+
+fn join<T: ToString>(v: &[T]) -> String {
+    v.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+fn sort(_: &[String], input: &str) -> Result<String, String> {
+    let mut v: Vec<i64> = parse(input)?;
+    Heap::sort(&mut v);
+
+    Ok(join(&v))
+}
+
+fn change(args: &[String], input: &str) -> Result<String, String> {
+    let amount = amount(args)?;
+    let amount = u32::try_from(amount).map_err(|_| format!("amount too big: {}", amount))?;
+    let coins: Vec<u32> = parse(input)?;
+
+    match make_change_greedy_unlimited(&coins, amount) {
+        Some(v) => Ok(join(&v)),
+        None => Err(format!("can't make change for {}", amount))
+    }
+}
+
+fn change_count(args: &[String], input: &str) -> Result<String, String> {
+    let amount = amount(args)?;
+    let coins: HashSet<usize> = parse(input)?.into_iter().collect();
+
+    match make_change_count(&coins, amount) {
+        Some(n) => Ok(n.to_string()),
+        None => Err(format!("can't make change for {}", amount))
+    }
+}
+
+fn realize(_: &[String], input: &str) -> Result<String, String> {
+    let degrees: Vec<usize> = parse(input)?;
+
+    match realize_degree_sequence(&degrees) {
+        Some(g) => {
+            let edges: Vec<String> = (0 .. g.node_count())
+                .flat_map(|u| g.neighbors(u).iter().filter(move |&&v| u < v)
+                                              .map(move |v| format!("{} {}", u, v)))
+                .collect();
+            Ok(edges.join("\n"))
+        },
+        None => Err("the sequence isn't graphic".to_string())
+    }
+}
 
-//use aisd::coin_change::*;
+fn mst(_: &[String], input: &str) -> Result<String, String> {
+    let g = weighted_edges(input, false)?;
+    let forest = kruskal(&g);
 
+    let mut lines: Vec<String> = forest.edges.iter().map(|&i| {
+        let (u, v, w) = g.edge(i);
+        format!("{} {} {}", u, v, w)
+    }).collect();
+    lines.push(format!("weight {}", forest.weight));
+
+    Ok(lines.join("\n"))
+}
+
+fn shortest_paths(args: &[String], input: &str) -> Result<String, String> {
+    let g = weighted_edges(input, true)?;
+    let source = match args {
+        [s] => s.parse().map_err(|_| format!("not a node: {}", s))?,
+        _ => return Err("expected exactly one argument, the source".to_string())
+    };
+    if source >= g.node_count() {
+        return Err(format!("no node {} in the graph", source));
+    }
+
+    let paths = dijkstra(&g, source);
+    let lines: Vec<String> = paths.dist.iter().enumerate().map(|(v, d)| match d {
+        Some(d) => format!("{} {}", v, d),
+        None => format!("{} unreachable", v)
+    }).collect();
+
+    Ok(lines.join("\n"))
+}
+
+fn usage() -> String {
+    let mut s = String::from("usage: aisd COMMAND [ARGS] [-f FILE]\n\n");
+    s.push_str("The input is read from FILE, or from stdin if there's none.\n\ncommands:\n");
+    for c in COMMANDS {
+        s.push_str(&format!("  {:14} {:8} {}\n", c.name, c.args, c.about));
+    }
+
+    s
+}
+
+/// Splits off `-f FILE` from the arguments and reads the input.
+fn read_input(args: &mut Vec<String>) -> Result<String, String> {
+    let mut input = String::new();
+
+    match args.iter().position(|a| a == "-f") {
+        Some(i) if i + 1 < args.len() => {
+            let path = args.remove(i + 1);
+            args.remove(i);
+            input = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+        },
+        Some(_) => return Err("-f needs a file name".to_string()),
+        None => {
+            io::stdin().read_to_string(&mut input).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(input)
+}
 
 fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() {
+        eprint!("{}", usage());
+        process::exit(2);
+    }
+
+    let name = args.remove(0);
+    let command = match COMMANDS.iter().find(|c| c.name == name) {
+        Some(c) => c,
+        None => {
+            eprint!("unknown command: {}\n\n{}", name, usage());
+            process::exit(2);
+        }
+    };
+
+    let result = read_input(&mut args).and_then(|input| (command.run)(&args, &input));
+    match result {
+        Ok(out) => println!("{}", out),
+        Err(e) => {
+            eprintln!("aisd {}: {}", name, e);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(a: &[&str]) -> Vec<String> {
+        a.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn mst() {
+        let out = super::mst(&[], "0 1 4\n1 2 1\n0 2 2\n\n3 2 7\n").unwrap();
+        assert_eq!(out, "1 2 1\n0 2 2\n3 2 7\nweight 10");
+        assert!(super::mst(&[], "0 1").is_err());
+    }
+
+    #[test]
+    fn dijkstra() {
+        let out = shortest_paths(&args(&["0"]), "0 1 4\n1 2 1\n0 2 6\n").unwrap();
+        assert_eq!(out, "0 0\n1 4\n2 5");
+        assert_eq!(shortest_paths(&args(&["1"]), "0 1 4\n2 0 1").unwrap(), "0 unreachable\n1 0\n2 unreachable");
+        assert!(shortest_paths(&args(&["3"]), "0 1 4").is_err());
+        assert!(shortest_paths(&[], "0 1 4").is_err());
+    }
+
+    #[test]
+    fn huge_node() {
+        let input = "0 100000000000 1";
+        assert_eq!(super::mst(&[], input), Err("node number too big: 100000000000".to_string()));
+        assert_eq!(shortest_paths(&args(&["0"]), input), Err("node number too big: 100000000000".to_string()));
+        assert!(weighted_edges(&format!("0 {} 1", MAX_NODE + 1), false).is_err());
+        assert!(weighted_edges(&format!("0 {} 1", MAX_NODE), false).is_ok());
+    }
 
-    /*let mut s = HashSet::new();
-    s.insert(4);
-    s.insert(2);
-    make_change_count(&s, 10);*/
+    #[test]
+    fn huge_weight() {
+        let input = "0 1 18446744073709551615\n1 2 5\n";
+        let err = Err("weight too big: 18446744073709551615".to_string());
+        assert_eq!(super::mst(&[], input), err);
+        assert_eq!(shortest_paths(&args(&["0"]), input), err);
 
+        let max = "0 1 4294967295\n1 2 4294967295\n";
+        assert_eq!(shortest_paths(&args(&["0"]), max).unwrap(), "0 0\n1 4294967295\n2 8589934590");
+    }
 
-}
\ No newline at end of file
+    #[test]
+    fn change() {
+        assert_eq!(super::change(&args(&["8"]), "1 2 5"), Ok("5 2 1".to_string()));
+        assert!(super::change(&args(&["4294967296"]), "1").is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//! Bit vectors with rank and select.
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+/// Number of 64-bit words covered by one precomputed rank.
+const WORDS_PER_BLOCK: usize = 8;
+
+/// A growable vector of bits which answers rank queries in O(1) and select
+/// queries in O(log n). The rank index takes one `usize` per 512 bits, so the
+/// whole structure takes little more than n bits.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::bitvec::BitVec;
+///
+/// let mut v = BitVec::new();
+/// v.push(true).push(false).push(true).push(true);
+///
+/// assert_eq!(v.get(1), Some(false));
+///
+/// // `rank1(i)` counts ones before position i...
+/// assert_eq!(v.rank1(3), 2);
+/// assert_eq!(v.rank0(3), 1);
+///
+/// // ...and `select1(k)` finds the position of the k-th one (counting from 0).
+/// assert_eq!(v.select1(2), Some(3));
+/// assert_eq!(v.select0(0), Some(1));
+/// assert_eq!(v.select0(1), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+
+    /// `blocks[b]` is the number of ones in the words before the b-th block.
+    blocks: Vec<usize>
+}
+
+/// Position of the k-th (counting from 0) one in a word.
+fn select_in_word(mut w: u64, k: usize) -> usize {
+    for _ in 0 .. k {
+        w &= w - 1;
+    }
+    w.trailing_zeros() as usize
+}
+
+impl BitVec {
+    /// Creates an empty bit vector.
+    pub fn new() -> BitVec {
+        BitVec {
+            words: vec![],
+            len: 0,
+            blocks: vec![]
+        }
+    }
+
+    /// Returns the number of bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks whether there are no bits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends a bit. Allows chaining calls.
+    /// Time: O(1)
+    pub fn push(&mut self, bit: bool) -> &mut Self {
+        if self.len.is_multiple_of(64) {
+            if self.words.len().is_multiple_of(WORDS_PER_BLOCK) {
+                let ones = match self.blocks.last() {
+                    None => 0,
+                    Some(&b) => {
+                        let start = self.words.len() - WORDS_PER_BLOCK;
+                        b + self.words[start ..].iter()
+                            .map(|w| w.count_ones() as usize).sum::<usize>()
+                    }
+                };
+                self.blocks.push(ones);
+            }
+            self.words.push(0);
+        }
+
+        if bit {
+            self.words[self.len / 64] |= 1 << (self.len % 64);
+        }
+        self.len += 1;
+
+        self
+    }
+
+    /// Returns the i-th bit.
+    pub fn get(&self, i: usize) -> Option<bool> {
+        if i >= self.len {
+            None
+        } else {
+            Some(self.words[i / 64] >> (i % 64) & 1 == 1)
+        }
+    }
+
+    /// Counts ones before position `i` (or in the whole vector, if `i` is past its end).
+    /// Time: O(1)
+    pub fn rank1(&self, i: usize) -> usize {
+        let i = i.min(self.len);
+        let w = i / 64;
+        // If i is the end of a full block, there's no rank stored for the next one.
+        let block = (w / WORDS_PER_BLOCK).min(self.blocks.len().saturating_sub(1));
+
+        let mut ones = if self.blocks.is_empty() {0} else {self.blocks[block]};
+        for word in &self.words[block * WORDS_PER_BLOCK .. w] {
+            ones += word.count_ones() as usize;
+        }
+        if !i.is_multiple_of(64) {
+            ones += (self.words[w] & ((1 << (i % 64)) - 1)).count_ones() as usize;
+        }
+
+        ones
+    }
+
+    /// Counts zeros before position `i` (or in the whole vector, if `i` is past its end).
+    /// Time: O(1)
+    pub fn rank0(&self, i: usize) -> usize {
+        i.min(self.len) - self.rank1(i)
+    }
+
+    /// Finds the position of the k-th one (counting from 0).
+    /// Time: O(log n)
+    pub fn select1(&self, k: usize) -> Option<usize> {
+        self.select(k, |w| w, |b| self.blocks[b])
+    }
+
+    /// Finds the position of the k-th zero (counting from 0).
+    /// Time: O(log n)
+    pub fn select0(&self, k: usize) -> Option<usize> {
+        self.select(k, |w| !w, |b| 64 * WORDS_PER_BLOCK * b - self.blocks[b])
+    }
+
+    /// Select using `word` to turn the bits we're looking for into ones and `before`
+    /// to compute how many of them there are before the given block.
+    fn select<F, G>(&self, k: usize, word: F, before: G) -> Option<usize>
+        where F: Fn(u64) -> u64, G: Fn(usize) -> usize
+    {
+        if self.blocks.is_empty() {
+            return None;
+        }
+
+        // Find the last block with at most k matching bits before it.
+        let (mut lo, mut hi) = (0, self.blocks.len());
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if before(mid) <= k {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+
+        // Scan the words of that block.
+        let mut seen = before(lo);
+        for w in lo * WORDS_PER_BLOCK .. self.words.len().min((lo + 1) * WORDS_PER_BLOCK) {
+            let mut bits = word(self.words[w]);
+            if w == self.words.len() - 1 && !self.len.is_multiple_of(64) {
+                bits &= (1 << (self.len % 64)) - 1;
+            }
+
+            let count = bits.count_ones() as usize;
+            if seen + count > k {
+                return Some(64 * w + select_in_word(bits, k - seen));
+            }
+            seen += count;
+        }
+
+        None
+    }
+}
+
+impl Arbitrary for BitVec {
+    fn arbitrary<G: Gen>(g: &mut G) -> BitVec {
+        let bits: Vec<bool> = Arbitrary::arbitrary(g);
+
+        let mut v = BitVec::new();
+        for b in bits {
+            v.push(b);
+        }
+
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitvec::BitVec;
+
+    fn bits(v: &BitVec) -> Vec<bool> {
+        (0 .. v.len()).map(|i| v.get(i).unwrap()).collect()
+    }
+
+    // Long vectors, so that there is more than one block.
+    fn long(v: Vec<bool>, copies: u8) -> BitVec {
+        let mut bv = BitVec::new();
+        for _ in 0 .. copies % 40 {
+            for &b in &v {
+                bv.push(b);
+            }
+        }
+
+        bv
+    }
+
+    quickcheck! {
+        fn push_get(v: Vec<bool>) -> bool {
+            let mut bv = BitVec::new();
+            for &b in &v {
+                bv.push(b);
+            }
+
+            bits(&bv) == v && bv.get(v.len()) == None
+        }
+
+        fn rank_naive(v: Vec<bool>, copies: u8) -> bool {
+            let bv = long(v, copies);
+            let b = bits(&bv);
+
+            (0 .. bv.len() + 2).all(|i| {
+                let ones = b.iter().take(i).filter(|&&x| x).count();
+                bv.rank1(i) == ones && bv.rank0(i) == i.min(b.len()) - ones
+            })
+        }
+
+        fn select_naive(v: Vec<bool>, copies: u8) -> bool {
+            let bv = long(v, copies);
+            let b = bits(&bv);
+
+            let ones: Vec<usize> = (0 .. b.len()).filter(|&i| b[i]).collect();
+            let zeros: Vec<usize> = (0 .. b.len()).filter(|&i| !b[i]).collect();
+
+            (0 .. ones.len() + 2).all(|k| bv.select1(k) == ones.get(k).cloned()) &&
+            (0 .. zeros.len() + 2).all(|k| bv.select0(k) == zeros.get(k).cloned())
+        }
+
+        fn rank_select(bv: BitVec, k: usize) -> bool {
+            match bv.select1(k) {
+                Some(i) => bv.rank1(i) == k && bv.get(i) == Some(true),
+                None => bv.rank1(bv.len()) <= k
+            }
+        }
+    }
+}
@@ -1,76 +1,144 @@
-//! Greedy algorithm for the change making problem.
+//! Greedy and dynamic programming algorithms for the change making problem.
+//!
+//! The functions work with any integer type of amounts and coins, so that
+//! amounts in the smallest units of a currency (which overflow `u32` fast) can
+//! be `u64` or `u128`. The dynamic programming algorithms have a table entry
+//! for every amount up to the asked one, so for them the amount has to fit in
+//! `usize` anyway.
+
+use knapsack::binary_split;
 
 use std::collections::HashSet;
-use std::u32;
+use std::hash::Hash;
+use std::ops::Sub;
+
+/// Integer types of amounts and coins. Coins which aren't positive are always
+/// ignored.
+pub trait Amount: Copy + Ord + Sub<Output = Self> {
+    const ZERO: Self;
+
+    /// The value as an index of a table, or `None` if it's negative or too big.
+    fn to_index(self) -> Option<usize>;
+
+    /// The value of an index. Only called on indices which came from
+    /// `to_index`, so it doesn't overflow.
+    fn from_index(i: usize) -> Self;
+}
+
+macro_rules! impl_amount {
+    ($($t:ty)*) => {
+        $(impl Amount for $t {
+            const ZERO: $t = 0;
+
+            fn to_index(self) -> Option<usize> {
+                ::std::convert::TryFrom::try_from(self).ok()
+            }
+
+            fn from_index(i: usize) -> $t {
+                i as $t
+            }
+        })*
+    };
+}
+
+impl_amount!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+/// Converts an amount for the dynamic programming algorithms.
+fn index<T: Amount>(amount: T) -> usize {
+    amount.to_index().expect("the amount is negative or too big for a table")
+}
+
+/// The coins which the dynamic programming algorithms can use for amounts up
+/// to `max`, as indices. The others are either not positive or bigger than `max`.
+fn usable<T: Amount>(coins: &[T], max: usize) -> impl Iterator<Item = usize> + '_ {
+    coins.iter().filter_map(|c| c.to_index()).filter(move |&c| c > 0 && c <= max)
+}
 
 /// We have the following problem: we have some coins and we want to select the
 /// smallest subset that sums to the given amount. This function implements a
-/// greedy algorithm that works only for the so-called canonical coin systems.
-/// Note that the coins have to be sorted in ascending order.
-/// 
+/// greedy algorithm that takes the biggest coin which still fits, which gives
+/// optimal solutions only for the so-called canonical coin systems. The coins
+/// may come in any order; they're sorted in a copy. Returns the coins used, in
+/// descending order.
+/// Time: O(n log n)
+///
 /// # Example
-/// 
+///
 /// ```
 /// extern crate aisd;
-/// use aisd::coin_change::make_change;
-/// 
+/// use aisd::coin_change::make_change_greedy;
+///
+/// // Amounts can be of any integer type.
+/// let coins: [u128; 2] = [10_000_000_000_000_000_000, 5_000_000_000_000_000_000];
+/// assert_eq!(make_change_greedy(&coins, 15_000_000_000_000_000_000), Some(coins.to_vec()));
+///
 /// // For this coin system, we will get optimal solutions.
-/// let mut coins = vec![1, 1, 2, 2, 2, 5, 5, 10, 10, 10];
-/// 
-/// assert_eq!(make_change(coins.clone(), 27), Some(vec![10, 10, 5, 2]));
-/// assert_eq!(make_change(coins, 49), None);
-/// 
+/// let coins = [10, 1, 2, 5, 10, 2, 1, 5, 10, 2];
+///
+/// assert_eq!(make_change_greedy(&coins, 27), Some(vec![10, 10, 5, 2]));
+/// assert_eq!(make_change_greedy(&coins, 49), None);
+///
 /// // Note that the coins are considered to be unique.
-/// let mut coins = vec![10];
-/// 
-/// assert_eq!(make_change(coins, 20), None);
-/// 
+/// assert_eq!(make_change_greedy(&[10], 20), None);
+///
 /// // For a non-canonical coin system, we get suboptimal solutions.
-/// let mut coins = vec![3, 5, 9, 9, 10];
-/// 
 /// // The optimal solution is Some(vec![9, 9]).
-/// assert_eq!(make_change(coins, 18), Some(vec![10, 5, 3]));
+/// assert_eq!(make_change_greedy(&[3, 5, 9, 9, 10], 18), Some(vec![10, 5, 3]));
 /// ```
-pub fn make_change(mut coins: Vec<u32>, mut amount: u32) -> Option<Vec<u32>> {
-    let mut v = vec![];
+pub fn make_change_greedy<T: Amount>(coins: &[T], mut amount: T) -> Option<Vec<T>> {
+    let mut coins = coins.to_vec();
+    coins.sort_by(|a, b| b.cmp(a));
 
-    loop {
-        match coins.pop() {
-            Some(c) if c <= amount => {
-                v.push(c);
-                amount -= c;
-            },
-            Some(_) => continue,
-            None => break
+    let mut v = vec![];
+    for c in coins {
+        if c > T::ZERO && c <= amount {
+            v.push(c);
+            amount = amount - c;
         }
     }
 
-    if amount == 0 {Some(v)} else {None}
+    if amount == T::ZERO {Some(v)} else {None}
 }
 
-/// Like `make_change`, but coins are not considered to be unique.
-/// 
+/// Like `make_change_greedy`, but every coin can be used any number of times.
+/// Time: O(n log n + size of the answer)
+///
 /// # Example
-/// 
+///
 /// ```
 /// extern crate aisd;
-/// use aisd::coin_change::make_change2;
-/// 
-/// let mut coins = vec![10];
-/// 
-/// assert_eq!(make_change2(coins, 20), Some(vec![10, 10]));
+/// use aisd::coin_change::make_change_greedy_unlimited;
+///
+/// assert_eq!(make_change_greedy_unlimited(&[10], 20), Some(vec![10, 10]));
+/// assert_eq!(make_change_greedy_unlimited(&[1, 5, 2], 13), Some(vec![5, 5, 2, 1]));
 /// ```
-pub fn make_change2(coins: Vec<u32>, mut amount: u32) -> Option<Vec<u32>> {
-    let mut v = vec![];
+pub fn make_change_greedy_unlimited<T: Amount>(coins: &[T], mut amount: T) -> Option<Vec<T>> {
+    let mut coins = coins.to_vec();
+    coins.sort_by(|a, b| b.cmp(a));
 
-    for mut i in 0 .. coins.len() {
-        while coins[i] <= amount {
-            v.push(coins[i]);
-            amount -= coins[i];
+    let mut v = vec![];
+    for c in coins {
+        while c > T::ZERO && c <= amount {
+            v.push(c);
+            amount = amount - c;
         }
     }
 
-    if amount == 0 {Some(v)} else {None}
+    if amount == T::ZERO {Some(v)} else {None}
+}
+
+/// The old interface of `make_change_greedy`, which took ownership of the
+/// coins and required them to be sorted in ascending order.
+#[deprecated(note = "use `make_change_greedy` instead")]
+pub fn make_change(coins: Vec<u32>, amount: u32) -> Option<Vec<u32>> {
+    make_change_greedy(&coins, amount)
+}
+
+/// The old interface of `make_change_greedy_unlimited`, which took ownership of
+/// the coins and required them to be sorted in descending order.
+#[deprecated(note = "use `make_change_greedy_unlimited` instead")]
+pub fn make_change2(coins: Vec<u32>, amount: u32) -> Option<Vec<u32>> {
+    make_change_greedy_unlimited(&coins, amount)
 }
 
 /// Computes the least number of coins from the given set that sum up to the
@@ -91,7 +159,17 @@ pub fn make_change2(coins: Vec<u32>, mut amount: u32) -> Option<Vec<u32>> {
 /// assert_eq!(make_change_count(&coins, 7), Some(2));
 /// assert_eq!(make_change_count(&coins, 3), None);
 /// ```
-pub fn make_change_count(coins: &HashSet<usize>, amount: usize) -> Option<usize> {
+///
+/// # Panics
+///
+/// Panics if `amount` doesn't fit in `usize`.
+pub fn make_change_count<T: Amount + Hash>(coins: &HashSet<T>, amount: T) -> Option<usize> {
+    if amount < T::ZERO {
+        return None;
+    }
+    let amount = index(amount);
+    let coins: HashSet<usize> = coins.iter().filter_map(|c| c.to_index()).collect();
+
     let mut dp = vec![];
     dp.push(Some(0));
 
@@ -123,4 +201,323 @@ pub fn make_change_count(coins: &HashSet<usize>, amount: usize) -> Option<usize>
     }
 
     dp[amount]
-}
\ No newline at end of file
+}
+/// Like `make_change_greedy_unlimited`, but always finds a solution with the
+/// least number of coins, whatever the coin system. The coins are returned in
+/// descending order.
+/// Time: O(amount * number of coins)
+/// Memory: O(amount)
+///
+/// For every amount `a` up to `amount`, we compute the least number of coins
+/// summing to `a`, and remember the last coin of such a solution. Following
+/// these parent pointers back from `amount` gives the coins themselves. To
+/// answer many amounts, build the table once with `make_change_table`.
+///
+/// # Panics
+///
+/// Panics if `amount` doesn't fit in `usize`.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::coin_change::{make_change_greedy_unlimited, make_change_optimal};
+///
+/// // The greedy algorithm gets this one wrong.
+/// assert_eq!(make_change_greedy_unlimited(&[3, 5, 9, 10], 18), Some(vec![10, 5, 3]));
+/// assert_eq!(make_change_optimal(&[3, 5, 9, 10], 18), Some(vec![9, 9]));
+///
+/// assert_eq!(make_change_optimal(&[4, 6], 7), None);
+/// ```
+pub fn make_change_optimal<T: Amount>(coins: &[T], amount: T) -> Option<Vec<T>> {
+    if amount < T::ZERO {
+        return None;
+    }
+    let mut v = make_change_table(coins, amount).reconstruct(amount)?;
+    v.sort_by(|x, y| y.cmp(x));
+
+    Some(v)
+}
+
+/// The solutions of the change making problem for all the amounts up to some
+/// maximum, computed by `make_change_table`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeTable<T> {
+    // count[a] is the least number of coins summing to a, and last[a] the last
+    // coin of such a solution.
+    count: Vec<Option<usize>>,
+    last: Vec<T>
+}
+
+/// Solves the change making problem, with every coin usable any number of
+/// times, for all the amounts from 0 to `max_amount` at once, so that many
+/// queries about the same coin system can be answered fast. This is the table
+/// built by `make_change_optimal`.
+/// Time: O(max_amount * number of coins)
+/// Memory: O(max_amount)
+///
+/// # Panics
+///
+/// Panics if `max_amount` is negative or doesn't fit in `usize`.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::coin_change::make_change_table;
+///
+/// let t = make_change_table(&[1, 3, 4], 100);
+/// assert_eq!(t.count(6), Some(2));
+/// assert_eq!(t.reconstruct(6).map(|v| v.iter().sum()), Some(6));
+/// assert_eq!(t.count(100), Some(25));
+/// assert_eq!(t.max_amount(), 100);
+///
+/// assert_eq!(make_change_table(&[2], 3).count(3), None);
+/// ```
+pub fn make_change_table<T: Amount>(coins: &[T], max_amount: T) -> ChangeTable<T> {
+    let max_amount = index(max_amount);
+    let coins: Vec<usize> = usable(coins, max_amount).collect();
+
+    let mut count: Vec<Option<usize>> = vec![None; max_amount + 1];
+    let mut last = vec![T::ZERO; max_amount + 1];
+    count[0] = Some(0);
+
+    for a in 1 ..= max_amount {
+        for &c in coins.iter().filter(|&&c| c <= a) {
+            if let Some(n) = count[a - c] {
+                if count[a].is_none_or(|m| n + 1 < m) {
+                    count[a] = Some(n + 1);
+                    last[a] = T::from_index(c);
+                }
+            }
+        }
+    }
+
+    ChangeTable {count, last}
+}
+
+impl<T: Amount> ChangeTable<T> {
+    /// The greatest amount in the table.
+    pub fn max_amount(&self) -> T {
+        T::from_index(self.count.len() - 1)
+    }
+
+    /// The least number of coins summing to `amount`, or `None` if there's no
+    /// way to make it.
+    /// Time: O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `amount` is negative or greater than `max_amount()`.
+    pub fn count(&self, amount: T) -> Option<usize> {
+        self.count[index(amount)]
+    }
+
+    /// The coins of a solution for `amount` with the least number of them, in
+    /// no particular order, or `None` if there's no way to make it. It follows
+    /// the last coins of the solutions back from `amount`.
+    /// Time: O(size of the answer)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `amount` is negative or greater than `max_amount()`.
+    pub fn reconstruct(&self, amount: T) -> Option<Vec<T>> {
+        let n = self.count(amount)?;
+
+        let mut v = Vec::with_capacity(n);
+        let mut a = index(amount);
+        while a > 0 {
+            v.push(self.last[a]);
+            a -= index(self.last[a]);
+        }
+
+        Some(v)
+    }
+}
+
+/// Like `make_change_optimal`, but every denomination comes with the number of
+/// coins available, given as pairs `(denomination, count)`. Returns the coins
+/// of a solution with the least number of them, in descending order.
+/// Time: O(amount * sum of log(count + 1))
+/// Memory: the same
+///
+/// This is a bounded knapsack problem. Binary splitting turns `count` coins
+/// of value `d` into bundles of 1, 2, 4, ... coins, plus one for the rest, so
+/// that every number of coins up to `count` is a sum of distinct bundles.
+/// Then every bundle is used at most once, which is a 0/1 knapsack: `best[a]`
+/// is the least number of coins summing to `a` using the bundles so far. A
+/// table of which bundle improved which amount lets us walk back from `amount`.
+///
+/// # Panics
+///
+/// Panics if `amount` doesn't fit in `usize`.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::coin_change::{make_change_bounded, make_change_optimal};
+///
+/// // Only one 9 is there, so the best is 10 + 5 + 3.
+/// assert_eq!(make_change_optimal(&[3, 5, 9, 10], 18), Some(vec![9, 9]));
+/// assert_eq!(make_change_bounded(&[(3, 5), (5, 1), (9, 1), (10, 2)], 18), Some(vec![10, 5, 3]));
+///
+/// assert_eq!(make_change_bounded(&[(2, 3)], 8), None);
+/// ```
+pub fn make_change_bounded<T: Amount>(coins: &[(T, usize)], amount: T) -> Option<Vec<T>> {
+    if amount < T::ZERO {
+        return None;
+    }
+    let amount = index(amount);
+
+    // The bundles as (denomination, number of coins).
+    let mut bundles = vec![];
+    for &(d, count) in coins {
+        let d = match usable(&[d], amount).next() {
+            Some(d) => d,
+            None => continue
+        };
+        bundles.extend(binary_split(count).map(|k| (d, k)));
+    }
+
+    let mut best: Vec<Option<usize>> = vec![None; amount + 1];
+    best[0] = Some(0);
+    // used[i][a] says that bundle i is in the best solution for a among the
+    // bundles up to i.
+    let mut used = vec![];
+    for &(d, k) in &bundles {
+        let mut row = vec![false; amount + 1];
+        let value = d.saturating_mul(k);
+        // Downwards, so that best[a - value] doesn't use this bundle yet.
+        for a in (value ..= amount).rev() {
+            if let Some(n) = best[a - value] {
+                if best[a].is_none_or(|m| n + k < m) {
+                    best[a] = Some(n + k);
+                    row[a] = true;
+                }
+            }
+        }
+        used.push(row);
+    }
+
+    best[amount]?;
+
+    let mut v = vec![];
+    let mut a = amount;
+    for (i, &(d, k)) in bundles.iter().enumerate().rev() {
+        if used[i][a] {
+            v.extend(::std::iter::repeat_n(T::from_index(d), k));
+            a -= d * k;
+        }
+    }
+    v.sort_by(|x, y| y.cmp(x));
+
+    Some(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{make_change_bounded, make_change_count, make_change_greedy_unlimited,
+                make_change_optimal, make_change_table};
+
+    use std::collections::HashSet;
+
+    #[test]
+    fn wide_amounts() {
+        let cent = 1u64;
+        let big = 1u64 << 40;
+        assert_eq!(make_change_greedy_unlimited(&[big, cent], 3 * big + 2),
+                   Some(vec![big, big, big, cent, cent]));
+
+        // Negative amounts and coins can't be used.
+        assert_eq!(make_change_greedy_unlimited(&[-5, 5], 10), Some(vec![5, 5]));
+        assert_eq!(make_change_optimal(&[-5, 5], -10), None);
+        assert_eq!(make_change_bounded(&[(-1, 3), (2, 3)], 4i64), Some(vec![2, 2]));
+
+        // Coins too big to be indices don't fit in a table anyway.
+        assert_eq!(make_change_optimal(&[u128::MAX, 3], 9), Some(vec![3, 3, 3]));
+    }
+
+    quickcheck! {
+        fn optimal_is_least(coins: Vec<u8>, amount: u8) -> bool {
+            let coins: Vec<u32> = coins.into_iter().map(|c| c as u32).collect();
+            let set: HashSet<usize> = coins.iter().filter(|&&c| c > 0).map(|&c| c as usize).collect();
+
+            let v = make_change_optimal(&coins, amount as u32);
+            v.as_ref().map(|v| v.len()) == make_change_count(&set, amount as usize) &&
+            v.iter().all(|v| v.iter().sum::<u32>() == amount as u32 && v.iter().all(|c| coins.contains(c)))
+        }
+    
+        // The table answers like separate runs for every amount.
+        fn table_every_amount(coins: Vec<u8>, max: u8) -> bool {
+            let coins: Vec<u32> = coins.into_iter().map(|c| c as u32).collect();
+            let t = make_change_table(&coins, max as u32);
+
+            (0 ..= max as u32).all(|a| {
+                let v = t.reconstruct(a);
+                t.count(a) == make_change_optimal(&coins, a).map(|w| w.len()) &&
+                v.as_ref().map(|v| v.len()) == t.count(a) &&
+                v.iter().all(|v| v.iter().sum::<u32>() == a && v.iter().all(|c| coins.contains(c)))
+            })
+        }
+
+        // The greedy solution is a solution, though maybe not the best one.
+        fn greedy_order_agnostic(coins: Vec<u8>, amount: u8) -> bool {
+            let coins: Vec<u32> = coins.into_iter().map(|c| c as u32).collect();
+            let mut sorted = coins.clone();
+            sorted.sort();
+
+            let v = make_change_greedy_unlimited(&coins, amount as u32);
+            v == make_change_greedy_unlimited(&sorted, amount as u32) &&
+            v.iter().all(|v| {
+                let best = make_change_optimal(&coins, amount as u32).map(|w| w.len());
+                v.iter().sum::<u32>() == amount as u32 && best.is_some_and(|b| v.len() >= b)
+            })
+        }
+
+        // With enough coins of every kind, the bound doesn't matter.
+        fn bounded_unlimited(coins: Vec<u8>, amount: u8) -> bool {
+            let coins: Vec<u32> = coins.into_iter().map(|c| c as u32).collect();
+            let bounded: Vec<(u32, usize)> = coins.iter().map(|&c| (c, amount as usize)).collect();
+
+            make_change_bounded(&bounded, amount as u32).map(|v| v.len()) ==
+            make_change_optimal(&coins, amount as u32).map(|v| v.len())
+        }
+
+        // All the subsets of a few coins, by brute force.
+        fn bounded_brute_force(coins: Vec<(u8, u8)>, amount: u8) -> bool {
+            let coins: Vec<(usize, usize)> = coins.into_iter().take(3)
+                .map(|(d, c)| (d as usize % 20, c as usize % 4)).collect();
+            let amount = amount as usize % 60;
+
+            let mut brute: Option<usize> = None;
+            let mut counts = vec![0; coins.len()];
+            loop {
+                let sum: usize = counts.iter().zip(&coins).map(|(k, &(d, _))| k * d).sum();
+                let n: usize = counts.iter().sum();
+                if sum == amount && brute.is_none_or(|b| n < b) {
+                    brute = Some(n);
+                }
+
+                match (0 .. coins.len()).find(|&i| counts[i] < coins[i].1) {
+                    Some(i) => {
+                        counts[i] += 1;
+                        for c in &mut counts[.. i] {
+                            *c = 0;
+                        }
+                    },
+                    None => break
+                }
+            }
+
+            let v = make_change_bounded(&coins, amount);
+            let fits = v.iter().all(|v| {
+                let available = |d| coins.iter().filter(|e| e.0 == d).map(|e| e.1).sum::<usize>();
+                v.iter().sum::<usize>() == amount &&
+                v.iter().all(|&d| v.iter().filter(|&&x| x == d).count() <= available(d))
+            });
+
+            v.map(|v| v.len()) == brute && fits
+        }
+    }
+}
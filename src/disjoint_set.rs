@@ -0,0 +1,525 @@
+//! The disjoint-set problem, also known as union-find, behind a common trait.
+//!
+//! # Which implementation should I pick?
+//!
+//! Numbers below come from `cargo bench --bench disjoint_set` (n unions followed by
+//! n queries on n elements, and a chain of n unions queried from its far end).
+//!
+//! - `Auto` links by rank (ties broken by size) and compresses paths fully. It is
+//!   the only one that knows the size of every set and the number of sets and
+//!   counts its work in `Stats`. Pick it by default, and always when studying costs.
+//! - `union_by_size::UnionFind` is the fastest on random workloads (about 1.5x
+//!   faster than `Auto` at n = 100 000), because it keeps one array less and
+//!   doesn't count anything. Pick it when every nanosecond counts.
+//! - `union_by_rank::UnionFind` is about as fast as `Auto` on random workloads,
+//!   but twice as slow on long chains. There's no reason to pick it.
+//! - `MergingDsu` works on keys of any hashable type instead of numbers and keeps
+//!   a value for every set, merged on union. It doesn't implement `DisjointSet`.
+//!
+//! Both of the older modules are deprecated in favour of the `DisjointSet` trait,
+//! which all three implement, so switching between them is a one-line change.
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use rand;
+use rand::Rng;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The interface of a union-find structure over the numbers {0, ..., size - 1}.
+pub trait DisjointSet {
+    /// Creates a new structure in which every number is in its own singleton set.
+    fn new(size: usize) -> Self;
+
+    /// Returns the number of elements of the structure (not the number of distinct sets!).
+    fn size(&self) -> usize;
+
+    /// Joins together the sets to which `i` and `j` belong.
+    fn union(&mut self, i: usize, j: usize);
+
+    /// Finds the representative of the set to which `i` belongs.
+    fn find(&mut self, i: usize) -> Option<usize>;
+
+    /// Checks whether `i` and `j` belong to the same set.
+    fn same_set(&mut self, i: usize, j: usize) -> bool {
+        match (self.find(i), self.find(j)) {
+            (Some(pi), Some(pj)) => pi == pj,
+            _ => false
+        }
+    }
+}
+
+/// Counters of the work done by `Auto`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    /// Number of calls to `find` (including the ones made by `union`).
+    pub finds: usize,
+
+    /// Number of calls to `union`.
+    pub unions: usize,
+
+    /// Number of calls to `union` that actually joined two different sets.
+    pub links: usize,
+
+    /// Number of parent pointers followed by all calls to `find`.
+    pub steps: usize,
+
+    /// Number of parent pointers rewritten by path compression.
+    pub compressions: usize
+}
+
+/// Union-find with union by rank (ties broken by size) and full path compression.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::disjoint_set::*;
+///
+/// let mut uf = Auto::new(10);
+/// assert_eq!(uf.set_count(), 10);
+///
+/// uf.union(1, 2);
+/// uf.union(2, 3);
+///
+/// assert!(uf.same_set(1, 3));
+/// assert!(!uf.same_set(1, 4));
+/// assert_eq!(uf.set_size(3), Some(3));
+/// assert_eq!(uf.set_count(), 8);
+///
+/// // Every operation is counted.
+/// assert_eq!(uf.stats().unions, 2);
+/// assert_eq!(uf.stats().links, 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Auto {
+    parents: Vec<usize>,
+    ranks: Vec<usize>,
+    sizes: Vec<usize>,
+    sets: usize,
+    stats: Stats
+}
+
+impl Auto {
+    /// Returns the number of distinct sets.
+    pub fn set_count(&self) -> usize {
+        self.sets
+    }
+
+    /// Returns the size of the set to which `i` belongs.
+    pub fn set_size(&mut self, i: usize) -> Option<usize> {
+        self.find(i).map(|p| self.sizes[p])
+    }
+
+    /// Returns the counters of the work done so far.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Resets all counters to zero.
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats::default();
+    }
+}
+
+impl DisjointSet for Auto {
+    fn new(size: usize) -> Auto {
+        Auto {
+            parents: (0 .. size).collect(),
+            ranks: vec![0; size],
+            sizes: vec![1; size],
+            sets: size,
+            stats: Stats::default()
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.parents.len()
+    }
+
+    fn union(&mut self, i: usize, j: usize) {
+        self.stats.unions += 1;
+
+        if let (Some(pi), Some(pj)) = (self.find(i), self.find(j)) {
+            if pi != pj {
+                // The root of higher rank wins. If ranks are equal, the bigger set wins.
+                let (child, root) =
+                    if (self.ranks[pi], self.sizes[pi]) < (self.ranks[pj], self.sizes[pj]) {
+                        (pi, pj)
+                    } else {
+                        (pj, pi)
+                    };
+
+                self.parents[child] = root;
+                self.sizes[root] += self.sizes[child];
+                self.sizes[child] = 0;
+                if self.ranks[child] == self.ranks[root] {
+                    self.ranks[root] += 1;
+                }
+
+                self.sets -= 1;
+                self.stats.links += 1;
+            }
+        }
+    }
+
+    fn find(&mut self, i: usize) -> Option<usize> {
+        if i >= self.size() {
+            return None;
+        }
+
+        self.stats.finds += 1;
+
+        // First pass: walk up to the root.
+        let mut root = i;
+        while self.parents[root] != root {
+            root = self.parents[root];
+            self.stats.steps += 1;
+        }
+
+        // Second pass: make every node on the path point directly to the root.
+        let mut current = i;
+        while self.parents[current] != root && current != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            self.stats.compressions += 1;
+            current = next;
+        }
+
+        Some(root)
+    }
+}
+
+impl Arbitrary for Auto {
+    fn arbitrary<G: Gen>(g: &mut G) -> Auto {
+        let size: usize = Arbitrary::arbitrary(g);
+        let mut uf = Auto::new(size);
+
+        let mut rng = rand::thread_rng();
+
+        if size != 0 {
+            for _ in 0 .. rng.gen_range(0, size) {
+                let i = rng.gen_range(0, size);
+                let j = rng.gen_range(0, size);
+
+                uf.union(i, j);
+            }
+        }
+
+        uf
+    }
+}
+
+/// Union-find over keys of any hashable type, where every set carries a value.
+/// Joining two sets merges their values with a function given to `union`, for
+/// example summing them or merging collections.
+///
+/// Links by size with full path compression. The merge function gets the value
+/// of the bigger set first, so merging collections "small into large" (adding the
+/// elements of the second argument to the first) moves every element O(log n)
+/// times in total.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::disjoint_set::MergingDsu;
+/// use std::collections::HashSet;
+///
+/// let mut d = MergingDsu::new();
+/// for &name in &["ann", "bob", "cid"] {
+///     d.insert(name, vec![name].into_iter().collect::<HashSet<_>>());
+/// }
+///
+/// let merge = |mut big: HashSet<_>, small: HashSet<_>| {big.extend(small); big};
+/// d.union(&"ann", &"bob", merge);
+///
+/// assert!(d.same_set(&"ann", &"bob"));
+/// assert_eq!(d.value(&"bob").unwrap().len(), 2);
+/// assert_eq!(d.set_count(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MergingDsu<K, V> {
+    indices: HashMap<K, usize>,
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    // Only roots have values.
+    values: Vec<Option<V>>,
+    sets: usize
+}
+
+impl<K: Hash + Eq, V> Default for MergingDsu<K, V> {
+    fn default() -> MergingDsu<K, V> {
+        MergingDsu::new()
+    }
+}
+
+impl<K: Hash + Eq, V> MergingDsu<K, V> {
+    /// Creates a structure with no keys.
+    pub fn new() -> MergingDsu<K, V> {
+        MergingDsu {
+            indices: HashMap::new(),
+            parents: vec![],
+            sizes: vec![],
+            values: vec![],
+            sets: 0
+        }
+    }
+
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    /// Checks whether there are no keys.
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Returns the number of distinct sets.
+    pub fn set_count(&self) -> usize {
+        self.sets
+    }
+
+    /// Adds `key` in a singleton set with the given value. Returns false and
+    /// changes nothing if `key` is already there.
+    pub fn insert(&mut self, key: K, value: V) -> bool {
+        if self.indices.contains_key(&key) {
+            return false;
+        }
+
+        self.indices.insert(key, self.parents.len());
+        self.parents.push(self.parents.len());
+        self.sizes.push(1);
+        self.values.push(Some(value));
+        self.sets += 1;
+        true
+    }
+
+    /// Finds the root of the set of element `i`, compressing the path.
+    fn root(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        let mut current = i;
+        while current != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+
+        root
+    }
+
+    fn find(&mut self, key: &K) -> Option<usize> {
+        let i = *self.indices.get(key)?;
+        Some(self.root(i))
+    }
+
+    /// Checks whether `a` and `b` belong to the same set.
+    pub fn same_set(&mut self, a: &K, b: &K) -> bool {
+        match (self.find(a), self.find(b)) {
+            (Some(ra), Some(rb)) => ra == rb,
+            _ => false
+        }
+    }
+
+    /// Returns the size of the set to which `key` belongs.
+    pub fn set_size(&mut self, key: &K) -> Option<usize> {
+        self.find(key).map(|r| self.sizes[r])
+    }
+
+    /// Returns the value of the set to which `key` belongs.
+    pub fn value(&mut self, key: &K) -> Option<&V> {
+        let r = self.find(key)?;
+        self.values[r].as_ref()
+    }
+
+    /// Returns the value of the set to which `key` belongs, for modification.
+    pub fn value_mut(&mut self, key: &K) -> Option<&mut V> {
+        let r = self.find(key)?;
+        self.values[r].as_mut()
+    }
+
+    /// Joins the sets to which `a` and `b` belong, giving the new set the value
+    /// `merge(value of the bigger set, value of the smaller set)`. Returns whether
+    /// two different sets got joined; if not, `merge` isn't called.
+    pub fn union<F: FnOnce(V, V) -> V>(&mut self, a: &K, b: &K, merge: F) -> bool {
+        let (ra, rb) = match (self.find(a), self.find(b)) {
+            (Some(ra), Some(rb)) if ra != rb => (ra, rb),
+            _ => return false
+        };
+
+        let (child, root) = if self.sizes[ra] < self.sizes[rb] {(ra, rb)} else {(rb, ra)};
+        self.parents[child] = root;
+        self.sizes[root] += self.sizes[child];
+
+        let big = self.values[root].take().unwrap();
+        let small = self.values[child].take().unwrap();
+        self.values[root] = Some(merge(big, small));
+
+        self.sets -= 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use disjoint_set::*;
+
+    // Interface tests.
+    quickcheck! {
+        fn union_find(uf: Auto, i: usize, j: usize) -> bool {
+            let mut uf = uf.clone();
+
+            if uf.size() == 0 {
+                true
+            } else {
+                let i = i % uf.size();
+                let j = j % uf.size();
+
+                uf.union(i, j);
+                uf.find(i) == uf.find(j)
+            }
+        }
+
+        fn same_set_union(uf: Auto, i: usize, j: usize) -> bool {
+            let mut uf = uf.clone();
+
+            if uf.size() == 0 {
+                true
+            } else {
+                let i = i % uf.size();
+                let j = j % uf.size();
+
+                uf.union(i, j);
+                uf.same_set(i, j)
+            }
+        }
+
+        fn find_out_of_range(uf: Auto, i: usize) -> bool {
+            let mut uf = uf.clone();
+            let size = uf.size();
+
+            uf.find(size + i % 100) == None
+        }
+    }
+
+    // Implementation tests.
+    quickcheck! {
+        // The size of a new structure is given by the argument.
+        fn size_new(size: usize) -> bool {
+            Auto::new(size).size() == size
+        }
+
+        // Every element of a new structure is its own representative.
+        fn find_new(size: usize) -> bool {
+            let mut uf = Auto::new(size);
+
+            (0 .. size).all(|i| uf.find(i) == Some(i))
+        }
+
+        // Sizes of all the sets sum up to the number of elements.
+        fn sum_of_sizes(uf: Auto) -> bool {
+            uf.sizes.iter().sum::<usize>() == uf.size()
+        }
+
+        // The number of sets is the number of roots.
+        fn set_count_roots(uf: Auto) -> bool {
+            let roots = (0 .. uf.size()).filter(|&i| uf.parents[i] == i).count();
+
+            uf.set_count() == roots
+        }
+
+        // Each successful link reduces the number of sets by one.
+        fn set_count_links(uf: Auto) -> bool {
+            uf.set_count() + uf.stats().links == uf.size()
+        }
+
+        // After `find`, the element points directly to its representative.
+        fn find_compresses(uf: Auto, i: usize) -> bool {
+            let mut uf = uf.clone();
+
+            if uf.size() == 0 {
+                true
+            } else {
+                let i = i % uf.size();
+                let p = uf.find(i).unwrap();
+
+                uf.parents[i] == p
+            }
+        }
+
+        // Ranks bound the height of the trees: a root of rank r has at least 2^r elements.
+        fn rank_bounds_size(uf: Auto) -> bool {
+            (0 .. uf.size())
+                .filter(|&i| uf.parents[i] == i)
+                .all(|i| uf.sizes[i] >= 1 << uf.ranks[i])
+        }
+
+        fn reset_stats(uf: Auto) -> bool {
+            let mut uf = uf.clone();
+            uf.reset_stats();
+
+            *uf.stats() == Stats::default()
+        }
+    }
+
+    // `MergingDsu` tests: values are sums, checked against a naive partition.
+    quickcheck! {
+        fn merging_dsu_naive(n: u8, unions: Vec<(u8, u8)>) -> bool {
+            let n = n as u32 % 30 + 1;
+            let mut d = MergingDsu::new();
+            for k in 0 .. n {
+                d.insert(k, k as u64);
+            }
+            // The set of every key.
+            let mut set: Vec<u32> = (0 .. n).collect();
+
+            unions.iter().all(|&(a, b)| {
+                let (a, b) = (a as u32 % n, b as u32 % n);
+                let joined = d.union(&a, &b, |x, y| x + y);
+                let (sa, sb) = (set[a as usize], set[b as usize]);
+                for s in set.iter_mut() {
+                    if *s == sb {
+                        *s = sa;
+                    }
+                }
+
+                joined == (sa != sb) &&
+                d.set_count() == (0 .. n).filter(|&k| set[k as usize] == k).count() &&
+                (0 .. n).all(|k| {
+                    let members = (0 .. n).filter(|&j| set[j as usize] == set[k as usize]);
+                    d.value(&k) == Some(&members.clone().map(|j| j as u64).sum()) &&
+                    d.set_size(&k) == Some(members.count()) &&
+                    d.same_set(&k, &a) == (set[k as usize] == set[a as usize])
+                })
+            })
+        }
+
+        fn merging_dsu_insert_twice(k: u8, v: u8, w: u8) -> bool {
+            let mut d = MergingDsu::new();
+
+            d.insert(k, v) && !d.insert(k, w) && d.value(&k) == Some(&v) && d.len() == 1
+        }
+
+        // The bigger set's value comes first.
+        fn merging_dsu_bigger_first(n: u8) -> bool {
+            let n = n as usize % 10 + 2;
+            let mut d = MergingDsu::new();
+            for k in 0 .. n {
+                d.insert(k, vec![k]);
+            }
+            for k in 2 .. n {
+                d.union(&1, &k, |mut x, y| {x.extend(y); x});
+            }
+            d.union(&0, &1, |x, y| if x.len() >= y.len() {x} else {y});
+
+            d.value(&0).map(|v| v.len()) == Some(n - 1)
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! The Bellman-Ford algorithm: shortest paths with negative weights, and
+//! negative cycles.
+
+use graph::{LabeledGraph, ShortestPaths, Weight};
+
+/// A cycle whose edges weigh less than zero in total, as the list of its nodes:
+/// there's an edge from every node to the next one, and from the last one to
+/// the first one. With a negative cycle, there are no shortest paths, since
+/// going around it once more makes a path shorter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeCycle(pub Vec<usize>);
+
+/// Relaxes every edge in rounds, starting from `dist`, until a round changes
+/// nothing or a negative cycle shows up.
+fn relax<N, W: Weight>(g: &LabeledGraph<N, W>, mut dist: Vec<Option<W>>)
+    -> Result<ShortestPaths<W>, NegativeCycle>
+{
+    let n = g.node_count();
+    let mut pred = vec![None; n];
+
+    // Without negative cycles, shortest paths have at most n - 1 edges, so
+    // the n-th round changes nothing.
+    let mut last = None;
+    for _ in 0 .. n {
+        last = None;
+        for u in 0 .. n {
+            if let Some(du) = dist[u] {
+                for (v, &w) in g.neighbors(u) {
+                    if dist[v].is_none_or(|dv| du + w < dv) {
+                        dist[v] = Some(du + w);
+                        pred[v] = Some(u);
+                        last = Some(v);
+                    }
+                }
+            }
+        }
+        if last.is_none() {
+            break;
+        }
+    }
+
+    let mut v = match last {
+        None => return Ok(ShortestPaths {dist, pred}),
+        Some(v) => v
+    };
+
+    // A node changed in the n-th round, so the predecessors lead back from it
+    // to a negative cycle, which they reach within n steps.
+    let back = |u: usize| pred[u].expect("bellman_ford: the predecessors lead to a cycle");
+    for _ in 0 .. n {
+        v = back(v);
+    }
+    let mut cycle = vec![v];
+    let mut u = back(v);
+    while u != v {
+        cycle.push(u);
+        u = back(u);
+    }
+    cycle.reverse();
+
+    Err(NegativeCycle(cycle))
+}
+
+/// The Bellman-Ford algorithm: shortest paths from `source` when weights may be
+/// negative, or a negative cycle reachable from it. The values of the edges are
+/// their weights. In an undirected graph, an edge with a negative weight is a
+/// negative cycle on its own, going there and back.
+///
+/// It stops early once a round of relaxing all the edges changes nothing.
+/// Time: O(number of nodes * number of edges)
+///
+/// # Panics
+///
+/// Panics if `source` isn't a node.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{LabeledGraph, NegativeCycle, bellman_ford};
+///
+/// let mut g: LabeledGraph<(), i32> = LabeledGraph::from_edges(true, 4, vec![
+///     (0, 1, 4), (0, 2, 5), (2, 1, -3), (1, 3, 2)
+/// ]);
+///
+/// let p = bellman_ford(&g, 0).unwrap();
+/// assert_eq!(p.dist, vec![Some(0), Some(2), Some(5), Some(4)]);
+/// assert_eq!(p.path_to(3), Some(vec![0, 2, 1, 3]));
+///
+/// g.add_edge(3, 2, -5);
+/// assert_eq!(bellman_ford(&g, 0), Err(NegativeCycle(vec![2, 1, 3])));
+/// ```
+pub fn bellman_ford<N, W: Weight>(g: &LabeledGraph<N, W>, source: usize)
+    -> Result<ShortestPaths<W>, NegativeCycle>
+{
+    let mut dist = vec![None; g.node_count()];
+    dist[source] = Some(W::ZERO);
+
+    relax(g, dist)
+}
+
+/// Finds a negative cycle anywhere in the graph, if there is one. It's
+/// Bellman-Ford from all the nodes at once, as if from an extra node with
+/// edges of weight zero to all of them.
+///
+/// For example, with exchange rates as the edges, weighted by minus their
+/// logarithms (scaled and rounded to integers), a negative cycle is an
+/// arbitrage. With a system of constraints
+/// `x[v] - x[u] <= w` as the edges `(u, v, w)`, a negative cycle means there's
+/// no solution, and otherwise the distances of `bellman_ford` from the extra
+/// node are one.
+/// Time: O(number of nodes * number of edges)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{LabeledGraph, NegativeCycle, negative_cycle};
+///
+/// let g: LabeledGraph<(), i64> = LabeledGraph::from_edges(true, 5, vec![
+///     (0, 1, 1), (1, 2, 1), (3, 4, 2), (4, 3, -3)
+/// ]);
+/// assert_eq!(negative_cycle(&g), Some(NegativeCycle(vec![3, 4])));
+/// ```
+pub fn negative_cycle<N, W: Weight>(g: &LabeledGraph<N, W>) -> Option<NegativeCycle> {
+    relax(g, vec![Some(W::ZERO); g.node_count()]).err()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Shortest distances by Floyd-Warshall, where negative cycles show up as
+    // negative distances from a node to itself. Around negative cycles, the
+    // distances keep falling, so they saturate.
+    fn floyd_warshall(g: &LabeledGraph<(), i64>) -> Vec<Vec<Option<i64>>> {
+        let n = g.node_count();
+        let mut d = vec![vec![None; n]; n];
+        for (u, row) in d.iter_mut().enumerate() {
+            row[u] = Some(0);
+            for (v, &w) in g.neighbors(u) {
+                if row[v].is_none_or(|x| w < x) {
+                    row[v] = Some(w);
+                }
+            }
+        }
+        for k in 0 .. n {
+            for i in 0 .. n {
+                for j in 0 .. n {
+                    if let (Some(a), Some(b)) = (d[i][k], d[k][j]) {
+                        if d[i][j].is_none_or(|x| a.saturating_add(b) < x) {
+                            d[i][j] = Some(a.saturating_add(b));
+                        }
+                    }
+                }
+            }
+        }
+
+        d
+    }
+
+    fn is_negative_cycle(g: &LabeledGraph<(), i64>, cycle: &[usize]) -> bool {
+        let edge = |u: usize, v: usize| g.neighbors(u).filter(|&(x, _)| x == v).map(|(_, &w)| w).min();
+        let weight = (0 .. cycle.len()).map(|i| edge(cycle[i], cycle[(i + 1) % cycle.len()])).sum::<Option<i64>>();
+
+        !cycle.is_empty() && weight.is_some_and(|w| w < 0)
+    }
+
+    fn widen(g: &LabeledGraph<(), i8>) -> LabeledGraph<(), i64> {
+        LabeledGraph::from_edges(g.is_directed(), g.node_count(), g.edges().map(|(u, v, &w)| (u, v, w as i64)))
+    }
+
+    quickcheck! {
+        fn bellman_ford_floyd_warshall(g: LabeledGraph<(), i8>, s: usize) -> bool {
+            let n = g.node_count();
+            if n == 0 {
+                return true;
+            }
+            let g = widen(&g);
+            let s = s % n;
+            let d = floyd_warshall(&g);
+            let cycle_reachable = (0 .. n).any(|v| d[s][v].is_some() && d[v][v].is_some_and(|x| x < 0));
+
+            match bellman_ford(&g, s) {
+                Ok(p) => !cycle_reachable && p.dist == d[s],
+                Err(NegativeCycle(cycle)) => {
+                    is_negative_cycle(&g, &cycle) && d[s][cycle[0]].is_some()
+                }
+            }
+        }
+
+        // Only the non-negative weights of an arbitrary graph, so that there are
+        // graphs without negative cycles too.
+        fn negative_cycle_floyd_warshall(g: LabeledGraph<(), i8>, shift: i8) -> bool {
+            let n = g.node_count();
+            let edges = g.edges().map(|(u, v, &w)| (u, v, w.max(0) as i64 - (shift as i64 % 4).abs()));
+            let g = LabeledGraph::from_edges(g.is_directed(), n, edges);
+            let d = floyd_warshall(&g);
+            let any_cycle = (0 .. n).any(|v| d[v][v].is_some_and(|x| x < 0));
+
+            match negative_cycle(&g) {
+                None => !any_cycle,
+                Some(NegativeCycle(cycle)) => is_negative_cycle(&g, &cycle)
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Breadth-first search.
+
+use graph::Adjacency;
+
+use std::collections::VecDeque;
+
+/// Distances and shortest paths found by breadth-first search, as computed by
+/// `bfs` and `bfs_multi`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bfs {
+    /// For every node, the number of edges on a shortest path to it from the
+    /// nearest source, or `None` if it isn't reachable.
+    pub dist: Vec<Option<usize>>,
+
+    /// For every node, the node before it on a shortest path from the nearest
+    /// source, or `None` for the sources and the nodes which aren't reachable.
+    /// Together they form a forest of shortest paths, rooted at the sources.
+    pub pred: Vec<Option<usize>>
+}
+
+impl Bfs {
+    /// Returns a shortest path from the nearest source to `target`, both
+    /// included, or `None` if `target` isn't reachable.
+    /// Time: O(length of the path)
+    pub fn path_to(&self, target: usize) -> Option<Vec<usize>> {
+        self.dist[target]?;
+
+        let mut path = vec![target];
+        let mut current = target;
+        while let Some(u) = self.pred[current] {
+            path.push(u);
+            current = u;
+        }
+        path.reverse();
+
+        Some(path)
+    }
+}
+
+/// Breadth-first search from `source`: visits the nodes in the order of their
+/// distance from it, which gives shortest paths counting edges.
+/// Time: O(number of nodes + number of edges)
+///
+/// # Panics
+///
+/// Panics if `source` isn't a node.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{Graph, bfs};
+///
+/// // A square 0 - 1 - 2 - 3 - 0 with a tail 2 - 4, and a lone node 5.
+/// let mut g = Graph::new(6);
+/// g.add_edge(0, 1).add_edge(1, 2).add_edge(2, 3).add_edge(3, 0).add_edge(2, 4);
+///
+/// let b = bfs(&g, 0);
+/// assert_eq!(b.dist, vec![Some(0), Some(1), Some(2), Some(1), Some(3), None]);
+/// assert_eq!(b.path_to(4), Some(vec![0, 1, 2, 4]));
+/// assert_eq!(b.path_to(5), None);
+/// ```
+pub fn bfs<G: Adjacency>(g: &G, source: usize) -> Bfs {
+    bfs_multi(g, Some(source))
+}
+
+/// Breadth-first search from many sources at once, as if from a single node
+/// with edges to all of them. Every node gets its distance from the nearest
+/// source, like the distance to the nearest exit in a maze.
+/// Time: O(number of nodes + number of edges + number of sources)
+///
+/// # Panics
+///
+/// Panics if a source isn't a node.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{Graph, bfs_multi};
+///
+/// // A 3 x 4 grid, with the node of the cell (r, c) at 4 * r + c.
+/// let mut g = Graph::new(12);
+/// for r in 0 .. 3 {
+///     for c in 0 .. 4 {
+///         if c + 1 < 4 { g.add_edge(4 * r + c, 4 * r + c + 1); }
+///         if r + 1 < 3 { g.add_edge(4 * r + c, 4 * (r + 1) + c); }
+///     }
+/// }
+///
+/// // Exits at the opposite corners.
+/// let b = bfs_multi(&g, vec![0, 11]);
+/// let dist: Vec<usize> = b.dist.iter().map(|d| d.unwrap()).collect();
+/// assert_eq!(dist, vec![0, 1, 2, 2,
+///                       1, 2, 2, 1,
+///                       2, 2, 1, 0]);
+/// assert_eq!(b.path_to(6), Some(vec![11, 7, 6]));
+/// ```
+pub fn bfs_multi<G, I>(g: &G, sources: I) -> Bfs
+    where G: Adjacency, I: IntoIterator<Item = usize>
+{
+    let n = g.node_count();
+    let mut dist = vec![None; n];
+    let mut pred = vec![None; n];
+    let mut queue = VecDeque::new();
+
+    for s in sources {
+        assert!(s < n, "bfs: source {} out of bounds (nodes {})", s, n);
+        if dist[s].is_none() {
+            dist[s] = Some(0);
+            queue.push_back(s);
+        }
+    }
+
+    while let Some(u) = queue.pop_front() {
+        let d = dist[u].map(|d| d + 1);
+        for &v in g.successors(u) {
+            if dist[v].is_none() {
+                dist[v] = d;
+                pred[v] = Some(u);
+                queue.push_back(v);
+            }
+        }
+    }
+
+    Bfs {dist, pred}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::{DirectedGraph, Graph};
+
+    // Distances by relaxing every edge until nothing changes.
+    fn distances<G: Adjacency>(g: &G, sources: &[usize]) -> Vec<Option<usize>> {
+        let n = g.node_count();
+        let mut dist = vec![None; n];
+        for &s in sources {
+            dist[s] = Some(0);
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for u in 0 .. n {
+                for &v in g.successors(u) {
+                    if let Some(d) = dist[u] {
+                        if dist[v].is_none_or(|dv| d + 1 < dv) {
+                            dist[v] = Some(d + 1);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        dist
+    }
+
+    // Every path leads from a source along edges and is as long as the distance.
+    fn paths_valid<G: Adjacency>(g: &G, b: &Bfs, sources: &[usize]) -> bool {
+        (0 .. g.node_count()).all(|v| match (b.path_to(v), b.dist[v]) {
+            (Some(path), Some(d)) => {
+                path.len() == d + 1 && sources.contains(&path[0]) && path[d] == v &&
+                path.windows(2).all(|w| g.successors(w[0]).contains(&w[1]))
+            },
+            (None, None) => b.pred[v].is_none(),
+            _ => false
+        })
+    }
+
+    quickcheck! {
+        fn bfs_undirected(g: Graph, s: usize) -> bool {
+            if g.node_count() == 0 {
+                return true;
+            }
+            let s = s % g.node_count();
+            let b = bfs(&g, s);
+
+            b.dist == distances(&g, &[s]) && paths_valid(&g, &b, &[s])
+        }
+
+        fn bfs_directed(g: DirectedGraph, s: usize) -> bool {
+            if g.node_count() == 0 {
+                return true;
+            }
+            let s = s % g.node_count();
+            let b = bfs(&g, s);
+
+            b.dist == distances(&g, &[s]) && paths_valid(&g, &b, &[s])
+        }
+
+        fn bfs_multi_nearest(g: DirectedGraph, sources: Vec<usize>) -> bool {
+            if g.node_count() == 0 {
+                return true;
+            }
+            let sources: Vec<usize> = sources.into_iter().map(|s| s % g.node_count()).collect();
+            let b = bfs_multi(&g, sources.iter().cloned());
+
+            b.dist == distances(&g, &sources) && paths_valid(&g, &b, &sources)
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn source_out_of_bounds() {
+        bfs(&Graph::new(3), 3);
+    }
+}
@@ -0,0 +1,151 @@
+//! Checking whether a graph is bipartite.
+
+use graph::Graph;
+
+use std::collections::VecDeque;
+
+/// The two sides of a bipartite graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Left,
+    Right
+}
+
+impl Side {
+    /// Returns the other side.
+    pub fn other(self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left
+        }
+    }
+}
+
+/// A cycle of odd length, which proves that a graph isn't bipartite, as the
+/// list of its nodes: there's an edge between every node and the next one,
+/// and between the last one and the first one. A loop is a cycle of length 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OddCycle(pub Vec<usize>);
+
+/// Splits the nodes into two sides so that every edge goes between them, or
+/// finds an odd cycle, which makes that impossible. Every connected component
+/// is colored by breadth-first search, starting from its least node, which
+/// goes to the left side.
+///
+/// When an edge joins two nodes on the same side, they're at the same depth
+/// of the search tree, and the paths from them up to their lowest common
+/// ancestor, with the edge, make an odd cycle.
+/// Time: O(number of nodes + number of edges)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{Graph, OddCycle, Side, bipartition};
+///
+/// // A square 0 - 1 - 2 - 3 - 0.
+/// let mut g = Graph::new(4);
+/// g.add_edge(0, 1).add_edge(1, 2).add_edge(2, 3).add_edge(3, 0);
+/// assert_eq!(bipartition(&g), Ok(vec![Side::Left, Side::Right, Side::Left, Side::Right]));
+///
+/// // A diagonal makes two triangles.
+/// g.add_edge(1, 3);
+/// assert_eq!(bipartition(&g), Err(OddCycle(vec![1, 0, 3])));
+/// ```
+pub fn bipartition(g: &Graph) -> Result<Vec<Side>, OddCycle> {
+    let n = g.node_count();
+    let mut side: Vec<Option<Side>> = vec![None; n];
+    let mut parent = vec![None; n];
+    let mut queue = VecDeque::new();
+
+    for s in 0 .. n {
+        if side[s].is_some() {
+            continue;
+        }
+        side[s] = Some(Side::Left);
+        queue.push_back(s);
+
+        while let Some(u) = queue.pop_front() {
+            let su = side[u];
+            for &v in g.neighbors(u) {
+                if side[v].is_none() {
+                    side[v] = su.map(Side::other);
+                    parent[v] = Some(u);
+                    queue.push_back(v);
+                } else if side[v] == su {
+                    return Err(odd_cycle(&parent, u, v));
+                }
+            }
+        }
+    }
+
+    Ok(side.into_iter().map(Option::unwrap).collect())
+}
+
+/// The cycle made by the edge between `u` and `v`, which are at the same depth
+/// of the search tree, and the tree paths from them to their lowest common
+/// ancestor.
+fn odd_cycle(parent: &[Option<usize>], u: usize, v: usize) -> OddCycle {
+    let (mut a, mut b) = (u, v);
+    let mut from_u = vec![];
+    let mut from_v = vec![];
+    while a != b {
+        from_u.push(a);
+        from_v.push(b);
+        a = parent[a].unwrap();
+        b = parent[b].unwrap();
+    }
+
+    // u, ..., the ancestor, ..., v
+    from_u.push(a);
+    from_u.extend(from_v.into_iter().rev());
+
+    OddCycle(from_u)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    quickcheck! {
+        fn bipartition_witness(g: Graph) -> bool {
+            match bipartition(&g) {
+                Ok(side) => (0 .. g.node_count()).all(|u| g.neighbors(u).iter().all(|&v| side[u] != side[v])),
+                Err(OddCycle(cycle)) => {
+                    let k = cycle.len();
+                    let mut distinct = cycle.clone();
+                    distinct.sort();
+                    distinct.dedup();
+
+                    k % 2 == 1 && distinct.len() == k &&
+                    (0 .. k).all(|i| g.has_edge(cycle[i], cycle[(i + 1) % k]))
+                }
+            }
+        }
+
+        // Only the edges between even and odd nodes.
+        fn bipartite_by_parity(g: Graph) -> bool {
+            let mut h = Graph::new(g.node_count());
+            for u in 0 .. g.node_count() {
+                for &v in g.neighbors(u) {
+                    if u < v && (u + v) % 2 == 1 {
+                        h.add_edge(u, v);
+                    }
+                }
+            }
+
+            bipartition(&h).is_ok()
+        }
+    }
+
+    #[test]
+    fn odd_cycles() {
+        let mut g = Graph::new(6);
+        g.add_edge(0, 1).add_edge(1, 2).add_edge(2, 3).add_edge(3, 4).add_edge(4, 0).add_edge(4, 5);
+        assert_eq!(bipartition(&g), Err(OddCycle(vec![2, 1, 0, 4, 3])));
+
+        let mut g = Graph::new(2);
+        g.add_edge(0, 1).add_edge(1, 1);
+        assert_eq!(bipartition(&g), Err(OddCycle(vec![1])));
+    }
+}
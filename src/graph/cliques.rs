@@ -0,0 +1,219 @@
+//! Maximal cliques by the Bron-Kerbosch algorithm.
+
+use graph::Graph;
+
+/// The common elements of two increasing lists.
+fn intersect(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let (mut i, mut j) = (0, 0);
+    let mut common = vec![];
+    while i < a.len() && j < b.len() {
+        if a[i] < b[j] {
+            i += 1;
+        } else if a[i] > b[j] {
+            j += 1;
+        } else {
+            common.push(a[i]);
+            i += 1;
+            j += 1;
+        }
+    }
+
+    common
+}
+
+/// Orders the nodes by repeatedly taking a node of the least degree among the
+/// nodes left. Every node then has at most d neighbours after it, where d is
+/// the degeneracy of the graph.
+fn degeneracy_order(adj: &[Vec<usize>]) -> Vec<usize> {
+    let n = adj.len();
+    let mut degree: Vec<usize> = adj.iter().map(|a| a.len()).collect();
+    // Buckets of nodes by degree, with stale entries skipped on the way out.
+    let mut buckets = vec![vec![]; n];
+    for u in 0 .. n {
+        buckets[degree[u]].push(u);
+    }
+    let mut taken = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+    let mut d = 0;
+    while order.len() < n {
+        match buckets[d].pop() {
+            Some(u) if !taken[u] && degree[u] == d => {
+                taken[u] = true;
+                order.push(u);
+                for &v in &adj[u] {
+                    if !taken[v] {
+                        degree[v] -= 1;
+                        buckets[degree[v]].push(v);
+                        d = d.min(degree[v]);
+                    }
+                }
+            },
+            Some(_) => {},
+            None => d += 1
+        }
+    }
+
+    order
+}
+
+/// Extends the clique `r` by nodes of `p` in every maximal way. The nodes of
+/// `x` are adjacent to all of `r` too, but the cliques with them were already
+/// reported.
+fn extend<F: FnMut(&[usize])>(adj: &[Vec<usize>], r: &mut Vec<usize>, mut p: Vec<usize>, mut x: Vec<usize>, visit: &mut F) {
+    if p.is_empty() {
+        if x.is_empty() {
+            visit(r);
+        }
+        return;
+    }
+
+    // Every maximal clique has a node outside the pivot's neighbourhood, so
+    // only those nodes need to be tried first.
+    let pivot = p.iter().chain(&x).cloned()
+        .max_by_key(|&u| intersect(&p, &adj[u]).len())
+        .unwrap();
+    let candidates: Vec<usize> = p.iter().cloned().filter(|v| adj[pivot].binary_search(v).is_err()).collect();
+    for v in candidates {
+        r.push(v);
+        extend(adj, r, intersect(&p, &adj[v]), intersect(&x, &adj[v]), visit);
+        r.pop();
+
+        p.retain(|&u| u != v);
+        let i = x.binary_search(&v).unwrap_err();
+        x.insert(i, v);
+    }
+}
+
+/// Finds all maximal cliques of a graph, sets of pairwise adjacent nodes which
+/// no other node is adjacent to all of, and calls `visit` with the nodes of
+/// every one, once, in no particular order. There may be exponentially many,
+/// so they're reported as they're found instead of being collected. Loops are
+/// ignored, so a node without other neighbours is a clique on its own.
+///
+/// Bron-Kerbosch with pivoting, started from every node in a degeneracy
+/// order with the candidates restricted to its neighbours later in the order
+/// (Eppstein, Löffler and Strash). The recursion is no deeper than the
+/// greatest clique.
+/// Time: O(d n 3^(d / 3)) for n nodes and degeneracy d
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{Graph, maximal_cliques};
+///
+/// // Two triangles sharing the edge 1 - 2, and an edge 3 - 4.
+/// let mut g = Graph::new(5);
+/// g.add_edge(0, 1).add_edge(0, 2).add_edge(1, 2).add_edge(1, 3).add_edge(2, 3).add_edge(3, 4);
+///
+/// let mut cliques = vec![];
+/// maximal_cliques(&g, |c| {
+///     let mut c = c.to_vec();
+///     c.sort();
+///     cliques.push(c);
+/// });
+/// cliques.sort();
+/// assert_eq!(cliques, vec![vec![0, 1, 2], vec![1, 2, 3], vec![3, 4]]);
+/// ```
+pub fn maximal_cliques<F: FnMut(&[usize])>(g: &Graph, mut visit: F) {
+    let n = g.node_count();
+    let adj: Vec<Vec<usize>> = (0 .. n).map(|u| {
+        let mut a: Vec<usize> = g.neighbors(u).iter().cloned().filter(|&v| v != u).collect();
+        a.sort();
+        a.dedup();
+        a
+    }).collect();
+
+    let order = degeneracy_order(&adj);
+    let mut position = vec![0; n];
+    for (i, &u) in order.iter().enumerate() {
+        position[u] = i;
+    }
+
+    let mut r = vec![];
+    for &v in &order {
+        let (p, x) = adj[v].iter().partition(|&&u| position[u] > position[v]);
+        r.push(v);
+        extend(&adj, &mut r, p, x, &mut visit);
+        r.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // All the maximal cliques, found by trying all sets of nodes.
+    fn maximal_cliques_naive(g: &Graph) -> Vec<Vec<usize>> {
+        let n = g.node_count();
+        let adjacent = |u: usize, v: usize| u == v || g.has_edge(u, v);
+        let is_clique = |s: u32| {
+            (0 .. n).all(|u| s >> u & 1 == 0 || (0 .. n).all(|v| s >> v & 1 == 0 || adjacent(u, v)))
+        };
+
+        (0 .. 1u32 << n)
+            .filter(|&s| s != 0 && is_clique(s) && (0 .. n).all(|v| s >> v & 1 == 1 || !is_clique(s | 1 << v)))
+            .map(|s| (0 .. n).filter(|&u| s >> u & 1 == 1).collect())
+            .collect()
+    }
+
+    quickcheck! {
+        fn maximal_cliques_small(g: Graph) -> bool {
+            // At most 12 nodes, so that all the sets can be tried.
+            let n = g.node_count().min(12);
+            let mut small = Graph::new(n);
+            for u in 0 .. n {
+                for &v in g.neighbors(u) {
+                    if u < v && v < n {
+                        small.add_edge(u, v);
+                    }
+                }
+            }
+
+            let mut cliques = vec![];
+            maximal_cliques(&small, |c| {
+                let mut c = c.to_vec();
+                c.sort();
+                cliques.push(c);
+            });
+            cliques.sort();
+            let mut expected = maximal_cliques_naive(&small);
+            expected.sort();
+
+            cliques == expected
+        }
+    }
+
+    // The Moon-Moser graph: all edges between 10 groups of 3 nodes. Its
+    // maximal cliques take a node from every group, so there are 3^10.
+    #[test]
+    fn moon_moser() {
+        let n = 30;
+        let mut g = Graph::new(n);
+        for u in 0 .. n {
+            for v in u + 1 .. n {
+                if u / 3 != v / 3 {
+                    g.add_edge(u, v);
+                }
+            }
+        }
+
+        let mut count = 0;
+        maximal_cliques(&g, |c| {
+            assert_eq!(c.len(), 10);
+            count += 1;
+        });
+        assert_eq!(count, 59049);
+    }
+
+    #[test]
+    fn loops_and_multiple_edges() {
+        let mut g = Graph::new(3);
+        g.add_edge(0, 0).add_edge(0, 1).add_edge(1, 0);
+
+        let mut cliques = vec![];
+        maximal_cliques(&g, |c| cliques.push(c.len()));
+        cliques.sort();
+        assert_eq!(cliques, vec![1, 2]);
+    }
+}
@@ -0,0 +1,191 @@
+//! Coloring the nodes of a graph so that the ends of every edge differ, with
+//! few colors: greedily and by DSATUR.
+
+use graph::Graph;
+
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashSet};
+
+/// A coloring of the nodes, as computed by `color_greedy` and `color_dsatur`.
+/// The colors are 0 .. count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coloring {
+    /// The color of every node.
+    pub color: Vec<usize>,
+
+    /// The number of colors used.
+    pub count: usize
+}
+
+impl Coloring {
+    /// Checks whether the coloring is proper for `g`: it gives a color less
+    /// than `count` to every node, and different colors to the ends of every
+    /// edge which isn't a loop.
+    /// Time: O(number of nodes + number of edges)
+    pub fn is_proper(&self, g: &Graph) -> bool {
+        let n = g.node_count();
+        self.color.len() == n &&
+        self.color.iter().all(|&c| c < self.count) &&
+        (0 .. n).all(|u| g.neighbors(u).iter().all(|&v| u == v || self.color[u] != self.color[v]))
+    }
+}
+
+/// The least color which none of the neighbours of `u` has.
+fn least_free(g: &Graph, color: &[Option<usize>], u: usize) -> usize {
+    let mut used = vec![false; g.degree(u) + 1];
+    for &v in g.neighbors(u) {
+        if let Some(c) = color[v] {
+            if c < used.len() {
+                used[c] = true;
+            }
+        }
+    }
+
+    used.iter().position(|&b| !b).unwrap()
+}
+
+/// Colors the nodes in increasing order, each with the least color which none
+/// of its neighbours has so far. Uses at most one color more than the greatest
+/// degree, but may use many more than needed. Loops are ignored.
+/// Time: O(number of nodes + number of edges)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{Graph, color_greedy};
+///
+/// // A path 0 - 2 - 3 - 1, which greedy colors with 3 colors instead of 2.
+/// let mut g = Graph::new(4);
+/// g.add_edge(0, 2).add_edge(2, 3).add_edge(3, 1);
+///
+/// let c = color_greedy(&g);
+/// assert_eq!(c.color, vec![0, 0, 1, 2]);
+/// assert_eq!(c.count, 3);
+/// assert!(c.is_proper(&g));
+/// ```
+pub fn color_greedy(g: &Graph) -> Coloring {
+    let n = g.node_count();
+    let mut color = vec![None; n];
+    for u in 0 .. n {
+        color[u] = Some(least_free(g, &color, u));
+    }
+
+    let color: Vec<usize> = color.into_iter().map(Option::unwrap).collect();
+    let count = color.iter().max().map_or(0, |&c| c + 1);
+    Coloring {color, count}
+}
+
+/// DSATUR (Brélaz): colors next the node whose neighbours have the most
+/// different colors, which is the most constrained one, with the least color
+/// which none of its neighbours has. Ties go to the node with the most
+/// uncolored neighbours, and then to the least node. It colors bipartite
+/// graphs, and so trees and even cycles, with at most 2 colors, and usually
+/// uses fewer colors than `color_greedy`. Loops are ignored.
+/// Time: O((n + m) log n) for n nodes and m edges
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{Graph, color_dsatur};
+///
+/// // The path from the example of `color_greedy`, now with 2 colors.
+/// let mut g = Graph::new(4);
+/// g.add_edge(0, 2).add_edge(2, 3).add_edge(3, 1);
+///
+/// let c = color_dsatur(&g);
+/// assert_eq!(c.color, vec![1, 0, 0, 1]);
+/// assert_eq!(c.count, 2);
+/// ```
+pub fn color_dsatur(g: &Graph) -> Coloring {
+    let n = g.node_count();
+    let mut color = vec![None; n];
+    // The colors of the neighbours of every node and its number of uncolored
+    // neighbours, and the uncolored nodes by these, the greatest last.
+    let mut seen: Vec<HashSet<usize>> = vec![HashSet::new(); n];
+    let mut uncolored: Vec<usize> = (0 .. n).map(|u| g.neighbors(u).iter().filter(|&&v| v != u).count()).collect();
+    let mut queue: BTreeSet<(usize, usize, Reverse<usize>)> = (0 .. n).map(|u| (0, uncolored[u], Reverse(u))).collect();
+
+    while let Some(&key) = queue.iter().next_back() {
+        queue.remove(&key);
+        let Reverse(u) = key.2;
+        let c = least_free(g, &color, u);
+        color[u] = Some(c);
+
+        for &v in g.neighbors(u) {
+            if v == u || color[v].is_some() {
+                continue;
+            }
+            queue.remove(&(seen[v].len(), uncolored[v], Reverse(v)));
+            seen[v].insert(c);
+            uncolored[v] -= 1;
+            queue.insert((seen[v].len(), uncolored[v], Reverse(v)));
+        }
+    }
+
+    let color: Vec<usize> = color.into_iter().map(Option::unwrap).collect();
+    let count = color.iter().max().map_or(0, |&c| c + 1);
+    Coloring {color, count}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::bipartition;
+
+    fn max_degree(g: &Graph) -> usize {
+        (0 .. g.node_count()).map(|u| g.degree(u)).max().unwrap_or(0)
+    }
+
+    quickcheck! {
+        fn color_greedy_proper(g: Graph) -> bool {
+            let c = color_greedy(&g);
+            c.is_proper(&g) && c.count <= max_degree(&g) + 1
+        }
+
+        fn color_dsatur_proper(g: Graph) -> bool {
+            let c = color_dsatur(&g);
+            c.is_proper(&g) && c.count <= max_degree(&g) + 1 &&
+            (bipartition(&g).is_err() || c.count <= 2)
+        }
+
+        // A tree from a list of parents is bipartite.
+        fn color_dsatur_tree(parents: Vec<usize>) -> bool {
+            let mut g = Graph::new(parents.len() + 1);
+            for (i, &p) in parents.iter().enumerate() {
+                g.add_edge(p % (i + 1), i + 1);
+            }
+            let c = color_dsatur(&g);
+
+            c.is_proper(&g) && c.count == if parents.is_empty() {1} else {2}
+        }
+
+        fn is_proper_conflict(g: Graph) -> bool {
+            let mut c = color_greedy(&g);
+            match (0 .. g.node_count()).find(|&u| g.degree(u) > 0) {
+                Some(u) => {
+                    let v = g.neighbors(u)[0];
+                    c.color[u] = c.color[v];
+                    !c.is_proper(&g)
+                },
+                None => c.is_proper(&g)
+            }
+        }
+    }
+
+    #[test]
+    fn complete_and_loops() {
+        let mut g = Graph::new(6);
+        for u in 0 .. 6 {
+            g.add_edge(u, u);
+            for v in u + 1 .. 6 {
+                g.add_edge(u, v);
+            }
+        }
+        assert_eq!(color_greedy(&g).count, 6);
+        assert_eq!(color_dsatur(&g).count, 6);
+        assert!(color_dsatur(&g).is_proper(&g));
+        assert_eq!(color_greedy(&Graph::new(0)).count, 0);
+    }
+}
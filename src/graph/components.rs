@@ -0,0 +1,236 @@
+//! Connected components of undirected graphs, computed at once or kept up to
+//! date while edges are added.
+
+use disjoint_set::{Auto, DisjointSet};
+use graph::Graph;
+
+use std::collections::VecDeque;
+
+/// The connected components of a graph, as computed by `connected_components`.
+/// Components are numbered from 0 in the order of their least nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Components {
+    /// The component of every node.
+    pub component: Vec<usize>,
+
+    /// The number of nodes of every component.
+    pub sizes: Vec<usize>
+}
+
+impl Components {
+    /// Returns the number of components.
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Returns the biggest component, the first one of the biggest if there
+    /// are many, or `None` if the graph has no nodes.
+    pub fn largest(&self) -> Option<usize> {
+        (0 .. self.count()).rev().max_by_key(|&c| self.sizes[c])
+    }
+
+    /// Returns the nodes of the component `c`, in increasing order.
+    /// Time: O(number of nodes)
+    pub fn members(&self, c: usize) -> Vec<usize> {
+        (0 .. self.component.len()).filter(|&v| self.component[v] == c).collect()
+    }
+}
+
+/// Finds the connected components with breadth-first search.
+/// Time: O(number of nodes + number of edges)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{Graph, connected_components};
+///
+/// let mut g = Graph::new(6);
+/// g.add_edge(0, 3).add_edge(1, 2).add_edge(2, 4).add_edge(4, 1);
+///
+/// let c = connected_components(&g);
+/// assert_eq!(c.component, vec![0, 1, 1, 0, 1, 2]);
+/// assert_eq!(c.sizes, vec![2, 3, 1]);
+/// assert_eq!(c.largest(), Some(1));
+/// assert_eq!(c.members(1), vec![1, 2, 4]);
+/// ```
+pub fn connected_components(g: &Graph) -> Components {
+    let n = g.node_count();
+    let mut component = vec![None; n];
+    let mut sizes = vec![];
+    let mut queue = VecDeque::new();
+
+    for s in 0 .. n {
+        if component[s].is_some() {
+            continue;
+        }
+        let c = sizes.len();
+        let mut size = 0;
+        component[s] = Some(c);
+        queue.push_back(s);
+        while let Some(u) = queue.pop_front() {
+            size += 1;
+            for &v in g.neighbors(u) {
+                if component[v].is_none() {
+                    component[v] = Some(c);
+                    queue.push_back(v);
+                }
+            }
+        }
+        sizes.push(size);
+    }
+
+    Components {component: component.into_iter().map(Option::unwrap).collect(), sizes}
+}
+
+/// Connected components of a graph which grows by edges, kept in union-find.
+/// Unlike `connected_components`, there are no stable numbers of components:
+/// a component is named by any of its nodes.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::IncrementalComponents;
+///
+/// let mut c = IncrementalComponents::new(5);
+/// assert_eq!(c.count(), 5);
+/// assert!(c.add_edge(0, 1));
+/// assert!(c.add_edge(3, 4));
+/// assert!(!c.add_edge(1, 0));
+/// assert!(c.add_edge(1, 4));
+///
+/// assert_eq!((c.count(), c.largest_size()), (2, 4));
+/// assert!(c.connected(0, 3) && !c.connected(0, 2));
+/// ```
+#[derive(Debug, Clone)]
+pub struct IncrementalComponents {
+    sets: Auto,
+    largest: usize
+}
+
+impl IncrementalComponents {
+    /// Creates the components of `n` nodes without edges.
+    pub fn new(n: usize) -> IncrementalComponents {
+        IncrementalComponents {sets: Auto::new(n), largest: n.min(1)}
+    }
+
+    /// Creates the components of `g`, to add more edges to later.
+    /// Time: O(number of nodes + number of edges * α(number of nodes))
+    pub fn from_graph(g: &Graph) -> IncrementalComponents {
+        let mut c = IncrementalComponents::new(g.node_count());
+        for u in 0 .. g.node_count() {
+            for &v in g.neighbors(u) {
+                c.add_edge(u, v);
+            }
+        }
+
+        c
+    }
+
+    /// Returns the number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.sets.size()
+    }
+
+    /// Adds an edge between `u` and `v`. Returns whether it joined two
+    /// components.
+    /// Time: O(α(number of nodes)) amortized
+    ///
+    /// # Panics
+    ///
+    /// Panics if `u` or `v` isn't a node.
+    pub fn add_edge(&mut self, u: usize, v: usize) -> bool {
+        let n = self.node_count();
+        assert!(u < n && v < n, "IncrementalComponents: edge ({}, {}) out of bounds (nodes {})", u, v, n);
+        if self.sets.same_set(u, v) {
+            return false;
+        }
+
+        self.sets.union(u, v);
+        self.largest = self.largest.max(self.size(u));
+        true
+    }
+
+    /// Returns the number of components.
+    pub fn count(&self) -> usize {
+        self.sets.set_count()
+    }
+
+    /// Checks whether `u` and `v` are in the same component.
+    /// Time: O(α(number of nodes)) amortized
+    pub fn connected(&mut self, u: usize, v: usize) -> bool {
+        self.sets.same_set(u, v)
+    }
+
+    /// Returns the number of nodes in the component of `u`.
+    /// Time: O(α(number of nodes)) amortized
+    ///
+    /// # Panics
+    ///
+    /// Panics if `u` isn't a node.
+    pub fn size(&mut self, u: usize) -> usize {
+        self.sets.set_size(u).expect("IncrementalComponents: node out of bounds")
+    }
+
+    /// Returns the number of nodes in the biggest component, or 0 if there
+    /// are no nodes.
+    pub fn largest_size(&self) -> usize {
+        self.largest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::bfs;
+
+    quickcheck! {
+        fn components_bfs(g: Graph) -> bool {
+            let c = connected_components(&g);
+            let n = g.node_count();
+
+            c.sizes.iter().sum::<usize>() == n &&
+            (0 .. c.count()).all(|i| c.members(i).len() == c.sizes[i]) &&
+            (0 .. n).all(|u| {
+                let reach = bfs(&g, u);
+                (0 .. n).all(|v| (c.component[u] == c.component[v]) == reach.dist[v].is_some())
+            }) &&
+            c.largest().map(|l| c.sizes[l]) == c.sizes.iter().cloned().max()
+        }
+
+        // Adding the edges one by one agrees with computing the components of
+        // every prefix of them.
+        fn incremental_prefixes(n: u8, edges: Vec<(u8, u8)>) -> bool {
+            let n = n as usize % 32 + 1;
+            let mut g = Graph::new(n);
+            let mut inc = IncrementalComponents::new(n);
+
+            edges.into_iter().all(|(u, v)| {
+                let (u, v) = (u as usize % n, v as usize % n);
+                let before = connected_components(&g).count();
+                g.add_edge(u, v);
+                let c = connected_components(&g);
+                let joined = inc.add_edge(u, v);
+
+                joined == (c.count() < before) && inc.count() == c.count() &&
+                inc.largest_size() == c.sizes[c.largest().unwrap()] &&
+                (0 .. n).all(|w| {
+                    inc.size(w) == c.sizes[c.component[w]] &&
+                    inc.connected(u, w) == (c.component[u] == c.component[w])
+                })
+            })
+        }
+    }
+
+    #[test]
+    fn from_graph() {
+        let mut g = Graph::new(4);
+        g.add_edge(0, 1).add_edge(2, 3).add_edge(3, 2);
+        let mut c = IncrementalComponents::from_graph(&g);
+        assert_eq!((c.count(), c.largest_size()), (2, 2));
+        assert!(c.add_edge(1, 2));
+        assert_eq!((c.count(), c.largest_size()), (1, 4));
+        assert_eq!(IncrementalComponents::new(0).largest_size(), 0);
+    }
+}
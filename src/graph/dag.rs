@@ -0,0 +1,233 @@
+//! Dynamic programming over directed acyclic graphs, in topological order.
+
+use graph::{Adjacency, Weight};
+
+use std::collections::VecDeque;
+use std::ops::Add;
+
+/// Orders the nodes so that every edge goes from an earlier node to a later
+/// one, with Kahn's algorithm: repeatedly takes a node without incoming edges
+/// from the nodes left. Returns `None` if the graph has a cycle, which an
+/// undirected graph with an edge has.
+/// Time: O(number of nodes + number of edges)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{DirectedGraph, topological_order};
+///
+/// let mut g = DirectedGraph::new(4);
+/// g.add_edge(3, 1).add_edge(1, 0).add_edge(3, 2).add_edge(2, 0);
+/// assert_eq!(topological_order(&g), Some(vec![3, 1, 2, 0]));
+///
+/// g.add_edge(0, 3);
+/// assert_eq!(topological_order(&g), None);
+/// ```
+pub fn topological_order<G: Adjacency>(g: &G) -> Option<Vec<usize>> {
+    let n = g.node_count();
+    let mut in_degree = vec![0; n];
+    for u in 0 .. n {
+        for &v in g.successors(u) {
+            in_degree[v] += 1;
+        }
+    }
+
+    let mut queue: VecDeque<usize> = (0 .. n).filter(|&u| in_degree[u] == 0).collect();
+    let mut order = Vec::with_capacity(n);
+    while let Some(u) = queue.pop_front() {
+        order.push(u);
+        for &v in g.successors(u) {
+            in_degree[v] -= 1;
+            if in_degree[v] == 0 {
+                queue.push_back(v);
+            }
+        }
+    }
+
+    if order.len() == n {Some(order)} else {None}
+}
+
+/// Finds a path of the greatest total weight of its nodes in a DAG, where
+/// `weight(u)` is the weight of the node `u`. Returns the weight and the path,
+/// which has at least one node unless the graph is empty. With the sizes of
+/// the components as weights, the longest path in a condensation is the
+/// greatest number of nodes of the original graph on one path.
+///
+/// Going backwards in topological order, the best path from a node is the
+/// node and then the best path from one of its successors, if it's better
+/// than nothing.
+/// Time: O(number of nodes + number of edges)
+///
+/// # Panics
+///
+/// Panics if the graph has a cycle.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{DirectedGraph, condense, longest_path};
+///
+/// // Cycles 0 <-> 1 and 3 <-> 4 <-> 5, both with edges to 2, and an edge 4 -> 0.
+/// let mut g = DirectedGraph::new(6);
+/// g.add_edge(0, 1).add_edge(1, 0).add_edge(3, 4).add_edge(4, 5).add_edge(5, 3)
+///  .add_edge(1, 2).add_edge(5, 2).add_edge(4, 0);
+///
+/// let (dag, component) = condense(&g);
+/// let mut size = vec![0; dag.node_count()];
+/// for &c in &component {
+///     size[c] += 1;
+/// }
+///
+/// let (nodes, path) = longest_path(&dag, |c| size[c]);
+/// assert_eq!(nodes, 6);
+/// assert_eq!(path, vec![component[3], component[0], component[2]]);
+/// ```
+pub fn longest_path<G, W, F>(dag: &G, mut weight: F) -> (W, Vec<usize>)
+    where G: Adjacency, W: Weight, F: FnMut(usize) -> W
+{
+    let n = dag.node_count();
+    let order = topological_order(dag).expect("longest_path: the graph has a cycle");
+
+    let mut best = vec![W::ZERO; n];
+    let mut next = vec![None; n];
+    for &u in order.iter().rev() {
+        let mut tail = W::ZERO;
+        for &v in dag.successors(u) {
+            if best[v] > tail {
+                tail = best[v];
+                next[u] = Some(v);
+            }
+        }
+        best[u] = weight(u) + tail;
+    }
+
+    let mut current = (0 .. n).max_by_key(|&u| best[u]);
+    let total = current.map_or(W::ZERO, |u| best[u]);
+    let mut path = vec![];
+    while let Some(u) = current {
+        path.push(u);
+        current = next[u];
+    }
+
+    (total, path)
+}
+
+/// Counts the paths from `source` to every node of a DAG, including the path
+/// without edges from `source` to itself. The number of paths can grow
+/// exponentially, so `T` should be wide enough or count modulo a number.
+///
+/// Going in topological order, the paths to a node are the paths to its
+/// predecessors, extended by one edge, and every edge counts separately.
+/// Time: O(number of nodes + number of edges)
+///
+/// # Panics
+///
+/// Panics if `source` isn't a node or the graph has a cycle.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{DirectedGraph, count_paths};
+///
+/// // A ladder of 3 diamonds: 0 -> {1, 2} -> 3 -> {4, 5} -> 6 -> {7, 8} -> 9.
+/// let mut g = DirectedGraph::new(10);
+/// for i in 0 .. 3 {
+///     let s = 3 * i;
+///     g.add_edge(s, s + 1).add_edge(s, s + 2).add_edge(s + 1, s + 3).add_edge(s + 2, s + 3);
+/// }
+///
+/// let paths: Vec<u64> = count_paths(&g, 0);
+/// assert_eq!(paths[9], 8);
+/// assert_eq!(paths[4], 2);
+/// ```
+pub fn count_paths<G, T>(dag: &G, source: usize) -> Vec<T>
+    where G: Adjacency, T: Copy + Add<Output = T> + From<u8>
+{
+    let n = dag.node_count();
+    assert!(source < n, "count_paths: source {} out of bounds (nodes {})", source, n);
+    let order = topological_order(dag).expect("count_paths: the graph has a cycle");
+
+    let mut paths = vec![T::from(0); n];
+    paths[source] = T::from(1);
+    for &u in &order {
+        for &v in dag.successors(u) {
+            paths[v] = paths[v] + paths[u];
+        }
+    }
+
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::{condense, DirectedGraph};
+
+    // The greatest weight of a path starting at `u`.
+    fn longest_from(dag: &DirectedGraph, weight: &[i64], u: usize) -> i64 {
+        let tail = dag.successors(u).iter().map(|&v| longest_from(dag, weight, v)).max();
+        weight[u] + tail.unwrap_or(0).max(0)
+    }
+
+    // The number of paths from `u` to `t`.
+    fn paths_to(dag: &DirectedGraph, u: usize, t: usize) -> u64 {
+        (u == t) as u64 + dag.successors(u).iter().map(|&v| paths_to(dag, v, t)).sum::<u64>()
+    }
+
+    quickcheck! {
+        fn topological_order_valid(g: DirectedGraph) -> bool {
+            let (dag, _) = condense(&g);
+            let n = dag.node_count();
+            let order = match topological_order(&dag) {
+                Some(order) => order,
+                None => return false
+            };
+            let mut position = vec![n; n];
+            for (i, &u) in order.iter().enumerate() {
+                position[u] = i;
+            }
+
+            // The arbitrary graphs have no loops, so they have a cycle iff a
+            // component has many nodes.
+            topological_order(&g).is_some() == (n == g.node_count()) &&
+            position.iter().all(|&p| p < n) &&
+            (0 .. n).all(|u| dag.successors(u).iter().all(|&v| position[u] < position[v]))
+        }
+
+        fn longest_path_naive(g: DirectedGraph, weights: Vec<i8>) -> bool {
+            let (dag, _) = condense(&g);
+            let n = dag.node_count();
+            let weight: Vec<i64> = (0 .. n).map(|u| weights.get(u).map_or(1, |&w| w as i64)).collect();
+            let (total, path) = longest_path(&dag, |u| weight[u]);
+            let best = (0 .. n).map(|u| longest_from(&dag, &weight, u)).max();
+
+            total == best.unwrap_or(0) &&
+            path.is_empty() == (n == 0) &&
+            path.iter().map(|&u| weight[u]).sum::<i64>() == total &&
+            path.windows(2).all(|w| dag.has_edge(w[0], w[1]))
+        }
+
+        fn count_paths_naive(g: DirectedGraph, s: usize) -> bool {
+            let (dag, _) = condense(&g);
+            let n = dag.node_count();
+            if n == 0 {
+                return true;
+            }
+            let s = s % n;
+            let paths: Vec<u64> = count_paths(&dag, s);
+
+            (0 .. n).all(|t| paths[t] == paths_to(&dag, s, t))
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn cycle() {
+        let mut g = DirectedGraph::new(2);
+        g.add_edge(0, 1).add_edge(1, 0);
+        longest_path(&g, |_| 1);
+    }
+}
@@ -0,0 +1,240 @@
+//! Depth-first search with discovery and finish times and the classification
+//! of edges.
+
+use graph::Adjacency;
+
+/// The kinds of edges seen by depth-first search, relative to its forest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// An edge to a node discovered through it: an edge of the forest.
+    Tree,
+    /// An edge to an ancestor which isn't finished yet, or a loop. A directed
+    /// graph has a cycle iff it has a back edge.
+    Back,
+    /// An edge to a finished descendant which isn't a child.
+    Forward,
+    /// Any other edge: to a finished node which isn't a descendant.
+    Cross
+}
+
+/// What happens during depth-first search, in order, as reported by
+/// `dfs_with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A node is seen for the first time.
+    Discover(usize),
+    /// The edge from the first node to the second one is followed. For tree
+    /// edges, this comes before the second node is discovered.
+    Edge(usize, usize, EdgeKind),
+    /// All edges out of a node have been followed.
+    Finish(usize)
+}
+
+/// The forest and the times of a depth-first search, as computed by `dfs` and
+/// `dfs_with`. The clock ticks at every discovery and finish, so the times are
+/// distinct numbers in 0 .. 2n. By the parenthesis theorem, `u` is an ancestor
+/// of `v` iff `discovered[u] <= discovered[v] < finished[v] <= finished[u]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dfs {
+    /// The time at which every node was discovered.
+    pub discovered: Vec<usize>,
+
+    /// The time at which every node was finished.
+    pub finished: Vec<usize>,
+
+    /// The parent of every node in the forest, or `None` for the roots.
+    pub parent: Vec<Option<usize>>
+}
+
+/// Depth-first search of the whole graph: starts from every node which isn't
+/// discovered yet, in increasing order.
+/// Time: O(number of nodes + number of edges)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{DirectedGraph, dfs};
+///
+/// let mut g = DirectedGraph::new(4);
+/// g.add_edge(0, 1).add_edge(1, 2).add_edge(0, 2).add_edge(3, 2);
+///
+/// let d = dfs(&g);
+/// assert_eq!(d.discovered, vec![0, 1, 2, 6]);
+/// assert_eq!(d.finished, vec![5, 4, 3, 7]);
+/// assert_eq!(d.parent, vec![None, Some(0), Some(1), None]);
+/// ```
+pub fn dfs<G: Adjacency>(g: &G) -> Dfs {
+    dfs_with(g, |_| {})
+}
+
+/// Like `dfs`, but calls `visit` at every event of the search, in order. Edges
+/// are classified as they're followed.
+///
+/// An undirected graph is searched like a directed graph with edges both ways,
+/// so every edge is followed from both ends: a tree edge `(u, v)` comes back as
+/// a back edge `(v, u)`, and a back edge `(v, w)` as a forward edge `(w, v)`.
+/// There are no cross edges.
+///
+/// The search keeps its own stack, so deep graphs don't overflow the call
+/// stack.
+/// Time: O(number of nodes + number of edges)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{DirectedGraph, EdgeKind, Event, dfs_with};
+///
+/// let mut g = DirectedGraph::new(4);
+/// g.add_edge(0, 1).add_edge(1, 2).add_edge(2, 0).add_edge(0, 3).add_edge(3, 2);
+///
+/// let mut edges = vec![];
+/// let mut postorder = vec![];
+/// dfs_with(&g, |e| match e {
+///     Event::Edge(u, v, kind) => edges.push((u, v, kind)),
+///     Event::Finish(u) => postorder.push(u),
+///     Event::Discover(_) => {}
+/// });
+///
+/// assert_eq!(edges, vec![
+///     (0, 1, EdgeKind::Tree),
+///     (1, 2, EdgeKind::Tree),
+///     (2, 0, EdgeKind::Back),
+///     (0, 3, EdgeKind::Tree),
+///     (3, 2, EdgeKind::Cross)
+/// ]);
+/// assert_eq!(postorder, vec![2, 1, 3, 0]);
+/// ```
+pub fn dfs_with<G, F>(g: &G, mut visit: F) -> Dfs
+    where G: Adjacency, F: FnMut(Event)
+{
+    let n = g.node_count();
+    let mut discovered: Vec<Option<usize>> = vec![None; n];
+    let mut finished: Vec<Option<usize>> = vec![None; n];
+    let mut parent = vec![None; n];
+    let mut time = 0;
+
+    // Nodes being visited, each with the number of successors seen so far.
+    let mut stack = vec![];
+    for s in 0 .. n {
+        if discovered[s].is_some() {
+            continue;
+        }
+        discovered[s] = Some(time);
+        time += 1;
+        visit(Event::Discover(s));
+        stack.push((s, 0));
+
+        while let Some(&mut (u, ref mut i)) = stack.last_mut() {
+            if let Some(&v) = g.successors(u).get(*i) {
+                *i += 1;
+                let kind = match (discovered[v], finished[v]) {
+                    (None, _) => EdgeKind::Tree,
+                    (Some(_), None) => EdgeKind::Back,
+                    (Some(dv), Some(_)) if discovered[u] < Some(dv) => EdgeKind::Forward,
+                    _ => EdgeKind::Cross
+                };
+                visit(Event::Edge(u, v, kind));
+
+                if kind == EdgeKind::Tree {
+                    discovered[v] = Some(time);
+                    time += 1;
+                    parent[v] = Some(u);
+                    visit(Event::Discover(v));
+                    stack.push((v, 0));
+                }
+            } else {
+                stack.pop();
+                finished[u] = Some(time);
+                time += 1;
+                visit(Event::Finish(u));
+            }
+        }
+    }
+
+    Dfs {
+        discovered: discovered.into_iter().map(Option::unwrap).collect(),
+        finished: finished.into_iter().map(Option::unwrap).collect(),
+        parent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::{path_graph, strongly_connected_components, DirectedGraph, Graph};
+
+    fn is_ancestor(d: &Dfs, u: usize, v: usize) -> bool {
+        d.discovered[u] <= d.discovered[v] && d.finished[v] <= d.finished[u]
+    }
+
+    // The times nest like parentheses, and every edge has the kind its times
+    // say.
+    fn consistent<G: Adjacency>(g: &G) -> bool {
+        let n = g.node_count();
+        let mut edges = vec![];
+        let d = dfs_with(g, |e| if let Event::Edge(u, v, kind) = e {
+            edges.push((u, v, kind));
+        });
+
+        let mut times: Vec<usize> = d.discovered.iter().chain(&d.finished).cloned().collect();
+        times.sort();
+        let all_edges: usize = (0 .. n).map(|u| g.successors(u).len()).sum();
+
+        times == (0 .. 2 * n).collect::<Vec<_>>() &&
+        (0 .. n).all(|u| (0 .. n).all(|v| {
+            let (du, fu, dv, fv) = (d.discovered[u], d.finished[u], d.discovered[v], d.finished[v]);
+            fu < dv || fv < du || is_ancestor(&d, u, v) || is_ancestor(&d, v, u)
+        })) &&
+        (0 .. n).all(|v| d.parent[v].is_none_or(|u| {
+            is_ancestor(&d, u, v) && g.successors(u).contains(&v)
+        })) &&
+        edges.len() == all_edges &&
+        edges.iter().all(|&(u, v, kind)| match kind {
+            EdgeKind::Tree => d.parent[v] == Some(u),
+            EdgeKind::Back => is_ancestor(&d, v, u),
+            EdgeKind::Forward => is_ancestor(&d, u, v) && d.parent[v] != Some(u),
+            EdgeKind::Cross => !is_ancestor(&d, u, v) && !is_ancestor(&d, v, u)
+        })
+    }
+
+    quickcheck! {
+        fn dfs_directed(g: DirectedGraph) -> bool {
+            consistent(&g)
+        }
+
+        fn dfs_undirected(g: Graph) -> bool {
+            let mut cross = false;
+            dfs_with(&g, |e| if let Event::Edge(_, _, EdgeKind::Cross) = e {
+                cross = true;
+            });
+
+            consistent(&g) && !cross
+        }
+
+        // The arbitrary graphs have no loops, so a cycle means a component
+        // with more than one node.
+        fn back_edge_iff_cycle(g: DirectedGraph) -> bool {
+            let mut back = false;
+            dfs_with(&g, |e| if let Event::Edge(_, _, EdgeKind::Back) = e {
+                back = true;
+            });
+            let (count, _) = strongly_connected_components(&g);
+
+            back == (count < g.node_count())
+        }
+    }
+
+    // The search reaches the end of the path before finishing any node, so the
+    // stack of open nodes holds all of them at once.
+    #[test]
+    fn deep_path() {
+        let n = 100_000;
+        let g = path_graph(n);
+
+        let d = dfs(&g);
+        assert_eq!(d.finished[0], 2 * n - 1);
+        assert!((1 .. n).all(|u| d.parent[u] == Some(u - 1)));
+    }
+}
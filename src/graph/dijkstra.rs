@@ -0,0 +1,225 @@
+//! Dijkstra's algorithm for shortest paths with non-negative weights.
+//!
+//! There are two versions, which differ in how they keep the nodes waiting to
+//! be visited. The first inserts a node into a plain heap every time a shorter
+//! path to it is found and skips the stale entries when they come out (lazy
+//! deletion), so the heap may hold up to one entry per edge. The second keeps
+//! one entry per node in an indexed heap and decreases its key instead.
+
+use graph::{LabeledGraph, Weight};
+use pq::{Heap, IndexedHeap, PriorityQueue};
+
+/// Shortest paths from a source, as computed by `dijkstra` and
+/// `dijkstra_decrease_key`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShortestPaths<W> {
+    /// For every node, the weight of a shortest path to it from the source, or
+    /// `None` if it isn't reachable.
+    pub dist: Vec<Option<W>>,
+
+    /// For every node, the node before it on a shortest path from the source,
+    /// or `None` for the source and the nodes which aren't reachable.
+    pub pred: Vec<Option<usize>>
+}
+
+impl<W> ShortestPaths<W> {
+    /// Returns a shortest path from the source to `target`, both included, or
+    /// `None` if `target` isn't reachable.
+    /// Time: O(length of the path)
+    pub fn path_to(&self, target: usize) -> Option<Vec<usize>> {
+        self.dist[target].as_ref()?;
+
+        let mut path = vec![target];
+        let mut current = target;
+        while let Some(u) = self.pred[current] {
+            path.push(u);
+            current = u;
+        }
+        path.reverse();
+
+        Some(path)
+    }
+}
+
+fn check_weight<W: Weight>(w: W) -> W {
+    assert!(w >= W::ZERO, "dijkstra: negative edge weight");
+    w
+}
+
+/// Dijkstra's algorithm with lazy deletion from a binary heap. The values of
+/// the edges are their weights.
+/// Time: O(m log m) for m edges
+/// Memory: O(number of nodes + m)
+///
+/// # Panics
+///
+/// Panics if `source` isn't a node or an edge reachable from it has a negative
+/// weight.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{LabeledGraph, dijkstra};
+///
+/// let g: LabeledGraph<(), u32> = LabeledGraph::from_edges(true, 4, vec![
+///     (0, 1, 4), (0, 2, 1), (2, 1, 2), (1, 3, 5), (2, 3, 8)
+/// ]);
+///
+/// let p = dijkstra(&g, 0);
+/// assert_eq!(p.dist, vec![Some(0), Some(3), Some(1), Some(8)]);
+/// assert_eq!(p.path_to(3), Some(vec![0, 2, 1, 3]));
+/// ```
+pub fn dijkstra<N, W: Weight>(g: &LabeledGraph<N, W>, source: usize) -> ShortestPaths<W> {
+    dijkstra_with(g, source, |_, _| true)
+}
+
+/// Like `dijkstra`, but follows only the edges from `u` to `v` for which
+/// `allowed(u, v)` holds, as if the others were removed.
+pub(crate) fn dijkstra_with<N, W, F>(g: &LabeledGraph<N, W>, source: usize, mut allowed: F) -> ShortestPaths<W>
+    where W: Weight, F: FnMut(usize, usize) -> bool
+{
+    let n = g.node_count();
+    let mut dist: Vec<Option<W>> = vec![None; n];
+    let mut pred = vec![None; n];
+    let mut done = vec![false; n];
+    let mut heap = Heap::new();
+
+    dist[source] = Some(W::ZERO);
+    heap.insert((W::ZERO, source));
+    while let Some((d, u)) = heap.del_min() {
+        if done[u] {
+            continue;
+        }
+        done[u] = true;
+
+        for (v, &w) in g.neighbors(u) {
+            if !allowed(u, v) {
+                continue;
+            }
+            let dv = d + check_weight(w);
+            if dist[v].is_none_or(|old| dv < old) {
+                dist[v] = Some(dv);
+                pred[v] = Some(u);
+                heap.insert((dv, v));
+            }
+        }
+    }
+
+    ShortestPaths {dist, pred}
+}
+
+/// Dijkstra's algorithm with an indexed heap, which holds every node at most
+/// once and decreases its key when a shorter path is found. The values of the
+/// edges are their weights.
+/// Time: O(m log n) for n nodes and m edges
+/// Memory: O(n)
+///
+/// # Panics
+///
+/// Panics if `source` isn't a node or an edge reachable from it has a negative
+/// weight.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{LabeledGraph, dijkstra, dijkstra_decrease_key};
+///
+/// let g: LabeledGraph<(), u32> = LabeledGraph::from_edges(false, 5, vec![
+///     (0, 1, 7), (0, 2, 9), (0, 4, 14), (1, 2, 10), (2, 3, 11), (3, 4, 9), (2, 4, 2)
+/// ]);
+///
+/// let p = dijkstra_decrease_key(&g, 0);
+/// assert_eq!(p.dist, vec![Some(0), Some(7), Some(9), Some(20), Some(11)]);
+/// assert_eq!(p, dijkstra(&g, 0));
+/// ```
+pub fn dijkstra_decrease_key<N, W: Weight>(g: &LabeledGraph<N, W>, source: usize) -> ShortestPaths<W> {
+    let n = g.node_count();
+    let mut dist: Vec<Option<W>> = vec![None; n];
+    let mut pred = vec![None; n];
+    let mut heap = IndexedHeap::new(n);
+
+    dist[source] = Some(W::ZERO);
+    heap.insert(source, W::ZERO);
+    while let Some((u, d)) = heap.del_min() {
+        for (v, &w) in g.neighbors(u) {
+            let dv = d + check_weight(w);
+            // Nodes which left the heap have their final distance, which is
+            // at most d, so they're never improved again.
+            if dist[v].is_none_or(|old| dv < old) {
+                dist[v] = Some(dv);
+                pred[v] = Some(u);
+                heap.insert_or_decrease(v, dv);
+            }
+        }
+    }
+
+    ShortestPaths {dist, pred}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widen(g: &LabeledGraph<(), u8>) -> LabeledGraph<(), u64> {
+        LabeledGraph::from_edges(g.is_directed(), g.node_count(), g.edges().map(|(u, v, &w)| (u, v, w as u64)))
+    }
+
+    // Distances by relaxing every edge until nothing changes.
+    fn distances(g: &LabeledGraph<(), u64>, s: usize) -> Vec<Option<u64>> {
+        let mut dist = vec![None; g.node_count()];
+        dist[s] = Some(0);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for u in 0 .. g.node_count() {
+                for (v, &w) in g.neighbors(u) {
+                    if let Some(d) = dist[u] {
+                        if dist[v].is_none_or(|dv| d + w < dv) {
+                            dist[v] = Some(d + w);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        dist
+    }
+
+    // Every path follows edges and weighs as much as the distance.
+    fn paths_valid(g: &LabeledGraph<(), u64>, p: &ShortestPaths<u64>) -> bool {
+        (0 .. g.node_count()).all(|t| match p.path_to(t) {
+            Some(path) => {
+                let weight = path.windows(2).map(|e| {
+                    g.neighbors(e[0]).filter(|&(v, _)| v == e[1]).map(|(_, &w)| w).min()
+                }).sum::<Option<u64>>();
+                weight == p.dist[t]
+            },
+            None => p.dist[t].is_none()
+        })
+    }
+
+    quickcheck! {
+        fn dijkstra_naive(g: LabeledGraph<(), u8>, s: usize) -> bool {
+            if g.node_count() == 0 {
+                return true;
+            }
+            let g = widen(&g);
+            let s = s % g.node_count();
+            let p1 = dijkstra(&g, s);
+            let p2 = dijkstra_decrease_key(&g, s);
+            let dist = distances(&g, s);
+
+            p1.dist == dist && p2.dist == dist && paths_valid(&g, &p1) && paths_valid(&g, &p2)
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn negative_weight() {
+        let g: LabeledGraph<(), i32> = LabeledGraph::from_edges(true, 3, vec![(0, 1, 2), (1, 2, -1)]);
+        dijkstra_decrease_key(&g, 0);
+    }
+}
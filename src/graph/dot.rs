@@ -0,0 +1,325 @@
+//! Writing graphs in the DOT language of Graphviz and reading them from DOT or
+//! plain lists of edges.
+
+use graph::{DirectedGraph, Graph, LabeledGraph};
+
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// The greatest number of a node that `from_edge_list` accepts. The graph has all
+/// the nodes up to the greatest number, so without a limit a single big number
+/// would make it allocate too much memory.
+pub const MAX_NODE: usize = (1 << 24) - 1;
+
+/// The reason why a graph couldn't be read and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The number of the offending line, counted from 1.
+    pub line: usize,
+
+    /// What's wrong.
+    pub message: &'static str
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} on line {}", self.message, self.line)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Writes the nodes, one per line so that isolated ones aren't lost, and then
+/// the edges `(u, v)` with `u <= v` if the graph is undirected.
+fn to_dot(keyword: &str, op: &str, adj: &[Vec<usize>], directed: bool) -> String {
+    let mut dot = format!("{} {{\n", keyword);
+    for u in 0 .. adj.len() {
+        writeln!(dot, "    {};", u).unwrap();
+    }
+    for (u, successors) in adj.iter().enumerate() {
+        for &v in successors {
+            if directed || u <= v {
+                writeln!(dot, "    {} {} {};", u, op, v).unwrap();
+            }
+        }
+    }
+    dot.push_str("}\n");
+
+    dot
+}
+
+/// The number of nodes, the edges in order, and, if they are labeled, their
+/// labels with the numbers of their lines.
+type Parsed<'a> = (usize, Vec<(usize, usize)>, Vec<(usize, &'a str)>);
+
+/// Reads the number of nodes and the edges, in order. If `labeled`, every edge
+/// ends with a label.
+fn parse<'a>(s: &'a str, keyword: &str, op: &str, labeled: bool) -> Result<Parsed<'a>, ParseError> {
+    let mut n = 0;
+    let mut edges = vec![];
+    let mut labels = vec![];
+    // Whether the body of a DOT graph is open, and whether it was closed.
+    let mut open = false;
+    let mut closed = false;
+
+    for (i, line) in s.lines().enumerate() {
+        let error = |message| ParseError {line: i + 1, message};
+        let line = match line.find('#').into_iter().chain(line.find("//")).min() {
+            Some(comment) => &line[.. comment],
+            None => line
+        };
+        let line = line.trim();
+        let line = line.strip_suffix(';').unwrap_or(line).trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        if closed {
+            return Err(error("text after the end of the graph"));
+        }
+
+        if let Some(header) = line.strip_suffix('{') {
+            if open || n > 0 {
+                return Err(error("unexpected '{'"));
+            }
+            let mut words = header.split_whitespace();
+            if words.next() != Some(keyword) || words.count() > 1 {
+                return Err(error(if keyword == "graph" {"expected `graph {`"} else {"expected `digraph {`"}));
+            }
+            open = true;
+            continue;
+        }
+        if line == "}" {
+            if !open {
+                return Err(error("unmatched '}'"));
+            }
+            open = false;
+            closed = true;
+            continue;
+        }
+
+        // A node, an edge, or a chain of edges through many nodes. A labeled
+        // edge is followed by its label and can't be a chain.
+        let (line, label) = match line.rfind(char::is_whitespace) {
+            Some(space) if labeled => (line[.. space].trim_end(), line[space ..].trim_start()),
+            _ => (line, "")
+        };
+        let ends: Vec<&str> = if line.contains(op) {
+            line.split(op).map(str::trim).collect()
+        } else {
+            line.split_whitespace().collect()
+        };
+        if ends.len() > 2 && !line.contains(op) {
+            return Err(error("expected a node or an edge"));
+        }
+        let mut nodes = Vec::with_capacity(ends.len());
+        for end in ends {
+            let u = end.parse::<usize>().map_err(|_| error("expected a node or an edge"))?;
+            if u > MAX_NODE {
+                return Err(error("node number too big"));
+            }
+            nodes.push(u);
+        }
+        if labeled && nodes.len() != if label.is_empty() {1} else {2} {
+            return Err(error("expected a node or an edge with a label"));
+        }
+        if !label.is_empty() {
+            labels.push((i + 1, label));
+        }
+        n = nodes.iter().fold(n, |n, &u| n.max(u + 1));
+        edges.extend(nodes.windows(2).map(|w| (w[0], w[1])));
+    }
+
+    if open {
+        return Err(ParseError {line: s.lines().count(), message: "unclosed '{'"});
+    }
+
+    Ok((n, edges, labels))
+}
+
+impl Graph {
+    /// Writes the graph in the DOT language, for drawing it with Graphviz.
+    /// Every node is declared on its own line, and every edge comes once.
+    /// `from_edge_list` reads it back.
+    /// Time: O(number of nodes + number of edges)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate aisd;
+    /// use aisd::graph::Graph;
+    ///
+    /// let mut g = Graph::new(3);
+    /// g.add_edge(0, 1).add_edge(1, 2);
+    /// assert_eq!(g.to_dot(), "graph {\n    0;\n    1;\n    2;\n    0 -- 1;\n    1 -- 2;\n}\n");
+    /// ```
+    pub fn to_dot(&self) -> String {
+        to_dot("graph", "--", &self.adj, false)
+    }
+
+    /// Reads a graph from a list of edges, one per line as `u v` or `u -- v`,
+    /// with the nodes being numbers. A line with a single number is a node
+    /// without edges, and `u -- v -- w` is a path. Blank lines, comments after
+    /// `#` or `//` and a `;` at the end of a line are skipped, and the list
+    /// may be wrapped in `graph {` ... `}`, so that the output of `to_dot` can
+    /// be read. The graph has the nodes up to the greatest number seen, which
+    /// can be at most 2^24 - 1. Other parts of DOT, like attributes and names
+    /// of nodes, aren't supported.
+    /// Time: O(length of `s` + the greatest number of a node)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate aisd;
+    /// use aisd::graph::Graph;
+    ///
+    /// let g = Graph::from_edge_list("# A triangle and a lone node\n0 1\n1 2\n2 -- 0;\n4\n").unwrap();
+    /// assert_eq!(g.node_count(), 5);
+    /// assert_eq!(g.neighbors(0), &[1, 2]);
+    /// assert_eq!(g.degree(3), 0);
+    ///
+    /// let err = Graph::from_edge_list("0 1\n1 -> 2\n").unwrap_err();
+    /// assert_eq!(err.line, 2);
+    /// ```
+    pub fn from_edge_list(s: &str) -> Result<Graph, ParseError> {
+        let (n, edges, _) = parse(s, "graph", "--", false)?;
+        let mut g = Graph::new(n);
+        for (u, v) in edges {
+            g.add_edge(u, v);
+        }
+
+        Ok(g)
+    }
+}
+
+impl DirectedGraph {
+    /// Writes the graph in the DOT language, for drawing it with Graphviz.
+    /// Every node is declared on its own line. `from_edge_list` reads it back.
+    /// Time: O(number of nodes + number of edges)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate aisd;
+    /// use aisd::graph::DirectedGraph;
+    ///
+    /// let mut g = DirectedGraph::new(2);
+    /// g.add_edge(1, 0);
+    /// assert_eq!(g.to_dot(), "digraph {\n    0;\n    1;\n    1 -> 0;\n}\n");
+    /// ```
+    pub fn to_dot(&self) -> String {
+        to_dot("digraph", "->", &self.adj, true)
+    }
+
+    /// Like `Graph::from_edge_list`, but the edges are written `u v` or
+    /// `u -> v`, and the list may be wrapped in `digraph {` ... `}`.
+    /// Time: O(length of `s` + the greatest number of a node)
+    pub fn from_edge_list(s: &str) -> Result<DirectedGraph, ParseError> {
+        let (n, edges, _) = parse(s, "digraph", "->", false)?;
+        let mut g = DirectedGraph::new(n);
+        for (u, v) in edges {
+            g.add_edge(u, v);
+        }
+
+        Ok(g)
+    }
+}
+
+impl<N: Default, E: FromStr> LabeledGraph<N, E> {
+    /// Like `Graph::from_edge_list`, or `DirectedGraph::from_edge_list` if
+    /// `directed`, but every edge is followed by its label, as `u v label` or
+    /// `u -- v label`, and so can't be part of a path. The label is a single
+    /// word, read into the value of the edge with `E::from_str`. The nodes
+    /// carry the default value.
+    /// Time: O(length of `s` + the greatest number of a node), besides reading
+    /// the labels
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate aisd;
+    /// use aisd::graph::{LabeledGraph, bfs};
+    ///
+    /// let g: LabeledGraph<(), u32> = LabeledGraph::from_edge_list(true, "0 -> 1 7\n1 2 3\n4\n").unwrap();
+    /// assert_eq!(g.node_count(), 5);
+    /// assert_eq!(g.edges().collect::<Vec<_>>(), vec![(0, 1, &7), (1, 2, &3)]);
+    /// assert_eq!(bfs(&g, 0).path_to(2), Some(vec![0, 1, 2]));
+    ///
+    /// let err = LabeledGraph::<(), u32>::from_edge_list(true, "0 1 7\n1 2\n").unwrap_err();
+    /// assert_eq!(err.line, 2);
+    /// ```
+    pub fn from_edge_list(directed: bool, s: &str) -> Result<LabeledGraph<N, E>, ParseError> {
+        let (n, edges, labels) = if directed {
+            parse(s, "digraph", "->", true)?
+        } else {
+            parse(s, "graph", "--", true)?
+        };
+
+        let mut g = LabeledGraph::from_edges(directed, n, vec![]);
+        for ((u, v), (line, label)) in edges.into_iter().zip(labels) {
+            let value = label.parse().map_err(|_| ParseError {line, message: "invalid label"})?;
+            g.add_edge(u, v, value);
+        }
+
+        Ok(g)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::bfs;
+
+    quickcheck! {
+        fn round_trip_undirected(g: Graph) -> bool {
+            Graph::from_edge_list(&g.to_dot()) == Ok(g)
+        }
+
+        fn round_trip_directed(g: DirectedGraph) -> bool {
+            DirectedGraph::from_edge_list(&g.to_dot()) == Ok(g)
+        }
+    }
+
+    #[test]
+    fn named_graph_and_chains() {
+        let g = DirectedGraph::from_edge_list("digraph g {\n  0 -> 1 -> 2; // a path\n  2 0\n}\n").unwrap();
+        let mut expected = DirectedGraph::new(3);
+        expected.add_edge(0, 1).add_edge(1, 2).add_edge(2, 0);
+        assert_eq!(g, expected);
+
+        assert_eq!(Graph::from_edge_list(""), Ok(Graph::new(0)));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |s| Graph::from_edge_list(s).unwrap_err();
+        assert_eq!(error("0 1 2"), ParseError {line: 1, message: "expected a node or an edge"});
+        assert_eq!(error("0 1\n0 -- x"), ParseError {line: 2, message: "expected a node or an edge"});
+        assert_eq!(error("digraph {\n}").message, "expected `graph {`");
+        assert_eq!(error("graph {\n0 1\n").message, "unclosed '{'");
+        assert_eq!(error("0 1\n}").message, "unmatched '}'");
+        assert_eq!(error("graph {\n}\n0").message, "text after the end of the graph");
+        let huge = format!("0 1\n1 {}", usize::MAX);
+        assert_eq!(Graph::from_edge_list(&huge), Err(ParseError {line: 2, message: "node number too big"}));
+        assert_eq!(error("16777216").message, "node number too big");
+    }
+
+    #[test]
+    fn labeled() {
+        let s = "graph {\n  0 -- 1 2.5; // a heavy edge\n  1 2 -1\n  1 1 0\n  4\n}\n";
+        let g: LabeledGraph<(), f64> = LabeledGraph::from_edge_list(false, s).unwrap();
+        assert_eq!(LabeledGraph::from_edges(false, 5, vec![(0, 1, 2.5), (1, 2, -1.0), (1, 1, 0.0)]), g);
+        assert_eq!(bfs(&g, 2).dist, vec![Some(2), Some(1), Some(0), None, None]);
+
+        let error = |s| LabeledGraph::<(), u32>::from_edge_list(true, s).unwrap_err();
+        assert_eq!(error("0 -> 1 2\n1 2"), ParseError {line: 2, message: "expected a node or an edge with a label"});
+        assert_eq!(error("0 1"), ParseError {line: 1, message: "expected a node or an edge with a label"});
+        assert_eq!(error("0->1"), ParseError {line: 1, message: "expected a node or an edge with a label"});
+        assert_eq!(error("0 -> 1 -> 2 3").message, "expected a node or an edge with a label");
+        assert_eq!(error("3 x").message, "expected a node or an edge with a label");
+        assert_eq!(error("0 1 2 3").message, "expected a node or an edge");
+        assert_eq!(error("\n0 1 -2"), ParseError {line: 2, message: "invalid label"});
+        assert_eq!(error("graph {\n}").message, "expected `digraph {`");
+    }
+}
@@ -0,0 +1,279 @@
+//! Maximum flows and minimum cuts.
+
+use graph::Weight;
+
+use std::collections::VecDeque;
+use std::ops::Sub;
+
+/// A directed graph on the nodes {0, ..., n - 1} whose edges have capacities,
+/// with a flow along them. Edges are numbered from 0 in the order they were
+/// added, and multiple edges and edges both ways are allowed.
+///
+/// Every edge is kept together with a reverse one, of capacity zero, which
+/// carries minus its flow. The residual capacity of an edge is how much more
+/// can flow along it: its capacity minus its flow, and for the reverse one,
+/// the flow, which can be sent back.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::FlowNetwork;
+///
+/// let mut net = FlowNetwork::new(3);
+/// net.add_edge(0, 1, 5).add_edge(1, 2, 3);
+/// assert_eq!(net.edge(1), (1, 2, 3));
+/// assert_eq!(net.flow(1), 0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowNetwork<C> {
+    // The edge number i is the half-edge 2i, and its reverse is 2i + 1.
+    to: Vec<usize>,
+    residual: Vec<C>,
+    capacity: Vec<C>,
+    // The half-edges going out of every node.
+    adj: Vec<Vec<usize>>
+}
+
+impl<C: Weight + Sub<Output = C>> FlowNetwork<C> {
+    /// Creates a network with `n` nodes and no edges.
+    pub fn new(n: usize) -> FlowNetwork<C> {
+        FlowNetwork {to: vec![], residual: vec![], capacity: vec![], adj: vec![vec![]; n]}
+    }
+
+    /// Returns the number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.adj.len()
+    }
+
+    /// Returns the number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.capacity.len()
+    }
+
+    /// Adds an edge from `u` to `v` with the given capacity and no flow. Its
+    /// number is the number of edges before. Allows chaining calls.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `u` or `v` isn't a node or the capacity is negative.
+    pub fn add_edge(&mut self, u: usize, v: usize, capacity: C) -> &mut Self {
+        let n = self.node_count();
+        assert!(u < n && v < n, "FlowNetwork: edge ({}, {}) out of bounds (nodes {})", u, v, n);
+        assert!(capacity >= C::ZERO, "FlowNetwork: negative capacity");
+
+        let e = self.to.len();
+        self.to.extend_from_slice(&[v, u]);
+        self.residual.extend_from_slice(&[capacity, C::ZERO]);
+        self.capacity.push(capacity);
+        self.adj[u].push(e);
+        self.adj[v].push(e + 1);
+        self
+    }
+
+    /// Returns the ends and the capacity of the `i`-th edge.
+    pub fn edge(&self, i: usize) -> (usize, usize, C) {
+        (self.to[2 * i + 1], self.to[2 * i], self.capacity[i])
+    }
+
+    /// Returns the flow along the `i`-th edge.
+    pub fn flow(&self, i: usize) -> C {
+        self.residual[2 * i + 1]
+    }
+
+    /// Removes all the flow.
+    pub fn clear_flow(&mut self) {
+        for i in 0 .. self.edge_count() {
+            self.residual[2 * i] = self.capacity[i];
+            self.residual[2 * i + 1] = C::ZERO;
+        }
+    }
+
+    /// The distance of every node from `s` along half-edges with residual
+    /// capacity left.
+    fn levels(&self, s: usize) -> Vec<Option<usize>> {
+        let mut level = vec![None; self.node_count()];
+        let mut queue = VecDeque::new();
+        level[s] = Some(0);
+        queue.push_back(s);
+        while let Some(u) = queue.pop_front() {
+            for &e in &self.adj[u] {
+                let v = self.to[e];
+                if level[v].is_none() && self.residual[e] > C::ZERO {
+                    level[v] = level[u].map(|l| l + 1);
+                    queue.push_back(v);
+                }
+            }
+        }
+
+        level
+    }
+}
+
+/// A maximum flow and a minimum cut, as computed by `dinic`. By the max-flow
+/// min-cut theorem, the value of the flow equals the capacity of the cut,
+/// which proves that both are optimal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaxFlow<C> {
+    /// How much flows from the source to the sink.
+    pub value: C,
+
+    /// The flow along every edge.
+    pub flows: Vec<C>,
+
+    /// For every node, whether it's on the source side of the minimum cut:
+    /// whether more flow could still get to it from the source. The edges
+    /// from the source side to the other side are saturated, and their
+    /// capacities add up to `value`.
+    pub source_side: Vec<bool>
+}
+
+/// Dinic's algorithm: finds a maximum flow from `s` to `t` in phases. Every
+/// phase splits the nodes into levels by their distance from `s` along edges
+/// with residual capacity, and then saturates all the shortest paths to `t`
+/// by sending a blocking flow through the level graph. The distance to `t`
+/// grows with every phase, so there are at most n of them.
+///
+/// The flow of the network is cleared first, and the maximum flow is left in
+/// it.
+/// Time: O(n^2 m) for n nodes and m edges, O(m sqrt(n)) for the unit networks
+/// of bipartite matching
+///
+/// # Panics
+///
+/// Panics if `s` or `t` isn't a node, or they're the same node.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{FlowNetwork, dinic};
+///
+/// let mut net = FlowNetwork::new(4);
+/// net.add_edge(0, 1, 3).add_edge(0, 2, 2).add_edge(1, 2, 5).add_edge(1, 3, 2).add_edge(2, 3, 3);
+///
+/// let f = dinic(&mut net, 0, 3);
+/// assert_eq!(f.value, 5);
+/// assert_eq!(f.flows, vec![3, 2, 1, 2, 3]);
+/// assert_eq!(f.source_side, vec![true, false, false, false]);
+/// assert_eq!(net.flow(2), 1);
+/// ```
+pub fn dinic<C: Weight + Sub<Output = C>>(net: &mut FlowNetwork<C>, s: usize, t: usize) -> MaxFlow<C> {
+    let n = net.node_count();
+    assert!(s < n && t < n && s != t, "dinic: bad source {} or sink {} (nodes {})", s, t, n);
+    net.clear_flow();
+    let mut value = C::ZERO;
+
+    loop {
+        let mut level = net.levels(s);
+        if level[t].is_none() {
+            let flows = (0 .. net.edge_count()).map(|i| net.flow(i)).collect();
+            let source_side = level.iter().map(|l| l.is_some()).collect();
+            return MaxFlow {value, flows, source_side};
+        }
+
+        // The blocking flow, one path at a time. The path goes on from every
+        // node through the half-edge at next[u]; half-edges which don't lead
+        // to t are skipped for the rest of the phase, and dead ends are taken
+        // out of the level graph.
+        let mut next = vec![0; n];
+        let mut path: Vec<usize> = vec![];
+        let mut u = s;
+        loop {
+            if u == t {
+                let bottleneck = path.iter().map(|&e| net.residual[e]).min().unwrap();
+                for &e in &path {
+                    net.residual[e] = net.residual[e] - bottleneck;
+                    net.residual[e ^ 1] = net.residual[e ^ 1] + bottleneck;
+                }
+                value = value + bottleneck;
+
+                // Back to the tail of the first saturated half-edge.
+                let k = path.iter().position(|&e| net.residual[e] == C::ZERO).unwrap();
+                path.truncate(k);
+                u = path.last().map_or(s, |&e| net.to[e]);
+                continue;
+            }
+
+            let admissible = net.adj[u][next[u] ..].iter().position(|&e| {
+                net.residual[e] > C::ZERO && level[net.to[e]] == level[u].map(|l| l + 1)
+            });
+            match admissible {
+                Some(k) => {
+                    next[u] += k;
+                    let e = net.adj[u][next[u]];
+                    path.push(e);
+                    u = net.to[e];
+                },
+                None => {
+                    next[u] = net.adj[u].len();
+                    level[u] = None;
+                    match path.pop() {
+                        Some(_) => {
+                            u = path.last().map_or(s, |&e| net.to[e]);
+                            next[u] += 1;
+                        },
+                        None => break
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::LabeledGraph;
+
+    fn network(g: &LabeledGraph<(), u8>) -> FlowNetwork<u64> {
+        let mut net = FlowNetwork::new(g.node_count());
+        for (u, v, &c) in g.edges() {
+            net.add_edge(u, v, c as u64);
+        }
+
+        net
+    }
+
+    quickcheck! {
+        // A flow and a cut with the same value are both optimal.
+        fn flow_equals_cut(g: LabeledGraph<(), u8>, s: usize, t: usize) -> bool {
+            let n = g.node_count();
+            if n < 2 {
+                return true;
+            }
+            let (s, t) = (s % n, t % n);
+            let t = if s == t {(t + 1) % n} else {t};
+            let mut net = network(&g);
+            let f = dinic(&mut net, s, t);
+
+            let mut balance = vec![0i64; n];
+            let mut cut = 0;
+            for i in 0 .. net.edge_count() {
+                let (u, v, c) = net.edge(i);
+                balance[u] -= f.flows[i] as i64;
+                balance[v] += f.flows[i] as i64;
+                if f.source_side[u] && !f.source_side[v] {
+                    cut += c;
+                }
+                if f.flows[i] > c || f.flows[i] != net.flow(i) {
+                    return false;
+                }
+            }
+
+            f.value == cut && f.source_side[s] && !f.source_side[t] &&
+            balance[t] == f.value as i64 && balance[s] == -(f.value as i64) &&
+            (0 .. n).all(|v| v == s || v == t || balance[v] == 0)
+        }
+    }
+
+    // Running again gives the same flow, not a doubled one.
+    #[test]
+    fn rerun() {
+        let mut net = FlowNetwork::new(3);
+        net.add_edge(0, 1, 4).add_edge(1, 2, 3).add_edge(0, 2, 1);
+        assert_eq!(dinic(&mut net, 0, 2).value, 4);
+        assert_eq!(dinic(&mut net, 0, 2).value, 4);
+        assert_eq!(dinic(&mut net, 1, 0).value, 0);
+    }
+}
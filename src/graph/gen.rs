@@ -0,0 +1,241 @@
+//! Random graph generators.
+//!
+//! All generators take the random number generator as an argument, so seeding it
+//! gives reproducible graphs:
+//!
+//! ```
+//! extern crate aisd;
+//!
+//! use aisd::graph::gen::barabasi_albert;
+//! use aisd::rng::Xoshiro256;
+//!
+//! let g1 = barabasi_albert(100, 2, &mut Xoshiro256::seed_from_u64(7));
+//! let g2 = barabasi_albert(100, 2, &mut Xoshiro256::seed_from_u64(7));
+//!
+//! assert_eq!(g1, g2);
+//! ```
+
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use graph::Graph;
+
+/// Builds a graph from a set of edges (u, v) with u < v, adding them in sorted
+/// order so that the result doesn't depend on the iteration order of the set.
+fn from_edges(n: usize, edges: HashSet<(usize, usize)>) -> Graph {
+    let mut edges: Vec<(usize, usize)> = edges.into_iter().collect();
+    edges.sort();
+
+    let mut g = Graph::new(n);
+    for (u, v) in edges {
+        g.add_edge(u, v);
+    }
+
+    g
+}
+
+/// The Erdős–Rényi model G(n, p): each of the n(n - 1)/2 possible edges is present
+/// independently with probability `p`.
+/// Time: O(n^2)
+pub fn erdos_renyi<R: Rng>(n: usize, p: f64, rng: &mut R) -> Graph {
+    let mut g = Graph::new(n);
+
+    for u in 0 .. n {
+        for v in u + 1 .. n {
+            if rng.gen_bool(p) {
+                g.add_edge(u, v);
+            }
+        }
+    }
+
+    g
+}
+
+/// The Barabási–Albert preferential attachment model. We start with a complete
+/// graph on `m + 1` nodes. Then each new node is connected to `m` distinct older
+/// nodes, each picked with probability proportional to its degree. The result has
+/// a few hubs of very high degree, like many real-world networks.
+/// Time: O(n * m) expected
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+///
+/// use aisd::graph::gen::barabasi_albert;
+/// use aisd::rng::Xoshiro256;
+///
+/// let g = barabasi_albert(1000, 3, &mut Xoshiro256::seed_from_u64(1));
+///
+/// // The first 4 nodes form a clique, every later node adds 3 edges.
+/// assert_eq!(g.edge_count(), 6 + (1000 - 4) * 3);
+/// assert!((0 .. 1000).all(|u| g.degree(u) >= 3));
+/// ```
+pub fn barabasi_albert<R: Rng>(n: usize, m: usize, rng: &mut R) -> Graph {
+    let mut edges = HashSet::new();
+
+    // Every node appears here once for each edge incident to it, so picking
+    // uniformly from this list picks nodes proportionally to their degree.
+    let mut endpoints = vec![];
+
+    let start = n.min(m + 1);
+    for u in 0 .. start {
+        for v in u + 1 .. start {
+            edges.insert((u, v));
+            endpoints.push(u);
+            endpoints.push(v);
+        }
+    }
+
+    for u in start .. n {
+        // A Vec rather than a HashSet, so that the order doesn't depend on hashing.
+        let mut targets = Vec::with_capacity(m);
+        while targets.len() < m {
+            let v = endpoints[rng.gen_range(0, endpoints.len())];
+            if !targets.contains(&v) {
+                targets.push(v);
+            }
+        }
+
+        for v in targets {
+            edges.insert((v, u));
+            endpoints.push(u);
+            endpoints.push(v);
+        }
+    }
+
+    from_edges(n, edges)
+}
+
+/// The Watts–Strogatz small-world model. We start with a ring in which every node
+/// is connected to its `k` nearest neighbours (`k / 2` on each side). Then each edge
+/// is rewired with probability `beta`: its far end is moved to a random node,
+/// avoiding loops and multiple edges. Small `beta` gives graphs that are highly
+/// clustered, yet have short paths between all nodes.
+/// Time: O(n * k) expected
+///
+/// # Panics
+///
+/// Panics if `k` is odd or if `k >= n`.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+///
+/// use aisd::graph::gen::watts_strogatz;
+/// use aisd::rng::Xoshiro256;
+///
+/// let g = watts_strogatz(100, 4, 0.1, &mut Xoshiro256::seed_from_u64(1));
+///
+/// // Rewiring doesn't change the number of edges.
+/// assert_eq!(g.edge_count(), 200);
+///
+/// // With beta = 0 we get the ring lattice.
+/// let g = watts_strogatz(10, 2, 0.0, &mut Xoshiro256::seed_from_u64(1));
+/// assert!((0 .. 10).all(|u| g.has_edge(u, (u + 1) % 10)));
+/// ```
+pub fn watts_strogatz<R: Rng>(n: usize, k: usize, beta: f64, rng: &mut R) -> Graph {
+    assert!(k.is_multiple_of(2), "watts_strogatz: k must be even");
+    assert!(k < n || n == 0, "watts_strogatz: k must be less than n");
+
+    let edge = |u: usize, v: usize| if u < v {(u, v)} else {(v, u)};
+
+    let mut edges = HashSet::new();
+    for u in 0 .. n {
+        for j in 1 .. k / 2 + 1 {
+            edges.insert(edge(u, (u + j) % n));
+        }
+    }
+    let mut degrees = vec![k; n];
+
+    for j in 1 .. k / 2 + 1 {
+        for u in 0 .. n {
+            let v = (u + j) % n;
+
+            // Don't rewire if u is already connected to everything.
+            if rng.gen_bool(beta) && degrees[u] < n - 1 {
+                let mut w = rng.gen_range(0, n);
+                while w == u || edges.contains(&edge(u, w)) {
+                    w = rng.gen_range(0, n);
+                }
+
+                edges.remove(&edge(u, v));
+                edges.insert(edge(u, w));
+                degrees[v] -= 1;
+                degrees[w] += 1;
+            }
+        }
+    }
+
+    from_edges(n, edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::Graph;
+    use graph::gen::*;
+
+    use rand::SeedableRng;
+    use rand::prng::XorShiftRng;
+
+    fn rng(seed: u8) -> XorShiftRng {
+        XorShiftRng::from_seed([seed; 16])
+    }
+
+    fn is_simple(g: &Graph) -> bool {
+        (0 .. g.node_count()).all(|u| {
+            let mut ns = g.neighbors(u).to_vec();
+            ns.sort();
+            ns.dedup();
+            ns.len() == g.degree(u) && !ns.contains(&u)
+        })
+    }
+
+    quickcheck! {
+        fn erdos_renyi_empty_full(n: u8, seed: u8) -> bool {
+            let n = n as usize % 30;
+            erdos_renyi(n, 0.0, &mut rng(seed)).edge_count() == 0 &&
+            erdos_renyi(n, 1.0, &mut rng(seed)).edge_count() == n * n.saturating_sub(1) / 2
+        }
+
+        fn erdos_renyi_seeded(n: u8, seed: u8) -> bool {
+            let n = n as usize % 30;
+            erdos_renyi(n, 0.5, &mut rng(seed)) == erdos_renyi(n, 0.5, &mut rng(seed))
+        }
+
+        fn barabasi_albert_seeded(n: u8, seed: u8) -> bool {
+            let n = n as usize % 60;
+            barabasi_albert(n, 2, &mut rng(seed)) == barabasi_albert(n, 2, &mut rng(seed))
+        }
+
+        fn watts_strogatz_seeded(n: u8, seed: u8) -> bool {
+            let n = n as usize % 40 + 5;
+            watts_strogatz(n, 4, 0.3, &mut rng(seed)) == watts_strogatz(n, 4, 0.3, &mut rng(seed))
+        }
+
+        fn barabasi_albert_edges(n: u8, m: u8, seed: u8) -> bool {
+            let n = n as usize % 60;
+            let m = m as usize % 5 + 1;
+            let g = barabasi_albert(n, m, &mut rng(seed));
+
+            let start = n.min(m + 1);
+            is_simple(&g) &&
+            g.node_count() == n &&
+            g.edge_count() == start * start.saturating_sub(1) / 2 + (n - start) * m
+        }
+
+        fn watts_strogatz_edges(n: u8, k: u8, seed: u8, beta: u8) -> bool {
+            let n = n as usize % 40 + 1;
+            let k = 2 * (k as usize % 4);
+            if k >= n {
+                return true;
+            }
+
+            let g = watts_strogatz(n, k, beta as f64 / 255.0, &mut rng(seed));
+
+            is_simple(&g) && g.node_count() == n && g.edge_count() == n * k / 2
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Realizing degree sequences with the Havel–Hakimi algorithm.
+
+use depq::{DEPQ, DoubleHeap};
+
+use graph::Graph;
+
+/// Builds a simple graph whose `i`-th node has degree `degrees[i]`, or returns `None`
+/// if there's no such graph (i.e. the sequence is not graphical).
+///
+/// The Havel–Hakimi algorithm repeatedly takes the node with the largest remaining
+/// degree d and connects it to the d nodes with the next largest remaining degrees.
+/// These are picked with a `DoubleHeap`.
+/// Time: O(sum of degrees * log(number of nodes))
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::realize_degree_sequence;
+///
+/// let g = realize_degree_sequence(&[3, 2, 2, 1]).unwrap();
+///
+/// assert_eq!(g.edge_count(), 4);
+/// assert_eq!(g.degree(0), 3);
+/// assert_eq!(g.degree(3), 1);
+///
+/// // The sum of degrees must be even...
+/// assert_eq!(realize_degree_sequence(&[2, 1, 1, 1]), None);
+///
+/// // ...but that's not enough.
+/// assert_eq!(realize_degree_sequence(&[3, 3, 1, 1]), None);
+/// ```
+pub fn realize_degree_sequence(degrees: &[usize]) -> Option<Graph> {
+    let n = degrees.len();
+    if degrees.iter().sum::<usize>() % 2 != 0 || degrees.iter().any(|&d| d >= n) {
+        return None;
+    }
+
+    let mut g = Graph::new(n);
+
+    // Pairs (remaining degree, node) for nodes whose remaining degree is positive.
+    let mut q = DoubleHeap::make_heap(
+        degrees.iter().cloned().zip(0 ..).filter(|&(d, _)| d > 0).collect());
+
+    while let Some((d, u)) = q.del_max() {
+        let mut neighbors = Vec::with_capacity(d);
+        for _ in 0 .. d {
+            match q.del_max() {
+                Some(x) => neighbors.push(x),
+                None => return None
+            }
+        }
+
+        for (dv, v) in neighbors {
+            g.add_edge(u, v);
+            if dv > 1 {
+                q.ins((dv - 1, v));
+            }
+        }
+    }
+
+    Some(g)
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::*;
+
+    fn degrees(g: &Graph) -> Vec<usize> {
+        (0 .. g.node_count()).map(|u| g.degree(u)).collect()
+    }
+
+    fn is_simple(g: &Graph) -> bool {
+        (0 .. g.node_count()).all(|u| {
+            let mut ns = g.neighbors(u).to_vec();
+            ns.sort();
+            ns.dedup();
+            ns.len() == g.degree(u) && !ns.contains(&u)
+        })
+    }
+
+    quickcheck! {
+        // Degrees of a simple graph can always be realized, and the result has the
+        // same degrees.
+        fn realize_graph(g: Graph) -> bool {
+            match realize_degree_sequence(&degrees(&g)) {
+                Some(h) => degrees(&h) == degrees(&g) && is_simple(&h),
+                None => false
+            }
+        }
+
+        // Whatever we realize is simple and has the requested degrees.
+        fn realize_arbitrary(v: Vec<usize>) -> bool {
+            let v: Vec<usize> = v.into_iter().map(|d| d % 8).collect();
+            match realize_degree_sequence(&v) {
+                Some(h) => degrees(&h) == v && is_simple(&h),
+                None => true
+            }
+        }
+
+        fn realize_odd_sum(v: Vec<usize>) -> bool {
+            let mut v = v.clone();
+            if v.iter().sum::<usize>() % 2 == 0 {
+                v.push(1);
+            }
+
+            realize_degree_sequence(&v) == None
+        }
+    }
+
+    #[test]
+    fn realize_empty() {
+        assert_eq!(realize_degree_sequence(&[]), Some(Graph::new(0)));
+    }
+
+    #[test]
+    fn realize_not_graphical() {
+        assert_eq!(realize_degree_sequence(&[3, 3, 3, 1]), None);
+        assert_eq!(realize_degree_sequence(&[1, 1, 2, 4]), None);
+    }
+}
@@ -0,0 +1,316 @@
+//! Graphs with data on the nodes and the edges, directed or undirected.
+
+use graph::Adjacency;
+
+use quickcheck::{Arbitrary, Gen};
+
+use rand;
+use rand::Rng;
+
+use rng::Xoshiro256;
+
+/// A directed or undirected graph whose nodes carry values of type `N` and
+/// edges values of type `E`, like weights, stored as adjacency lists. Nodes
+/// and edges are numbered from 0 in the order they were added. Loops and
+/// multiple edges are allowed.
+///
+/// It implements `Adjacency`, so algorithms which only follow edges, like
+/// `bfs`, work on it too.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::LabeledGraph;
+///
+/// let mut g = LabeledGraph::undirected();
+/// let a = g.add_node("a");
+/// let b = g.add_node("b");
+/// let c = g.add_node("c");
+/// g.add_edge(a, b, 5).add_edge(b, c, 2);
+///
+/// let around_b: Vec<(&str, u32)> = g.neighbors(b).map(|(v, &w)| (*g.node(v), w)).collect();
+/// assert_eq!(around_b, vec![("a", 5), ("c", 2)]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabeledGraph<N, E> {
+    directed: bool,
+    nodes: Vec<N>,
+    edges: Vec<(usize, usize, E)>,
+    // The numbers of the edges going out of every node. An undirected edge
+    // goes out of both its ends, and a loop only once.
+    adj: Vec<Vec<usize>>,
+    // The other ends of these edges, in the same order, for `Adjacency`.
+    succ: Vec<Vec<usize>>
+}
+
+impl<N, E> LabeledGraph<N, E> {
+    /// Creates a directed graph without nodes.
+    pub fn directed() -> LabeledGraph<N, E> {
+        LabeledGraph {directed: true, nodes: vec![], edges: vec![], adj: vec![], succ: vec![]}
+    }
+
+    /// Creates an undirected graph without nodes.
+    pub fn undirected() -> LabeledGraph<N, E> {
+        LabeledGraph {directed: false, ..LabeledGraph::directed()}
+    }
+
+    /// Creates a graph with the nodes {0, ..., n - 1}, all carrying the default
+    /// value, and the edges `(u, v, e)` from `edges`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an edge has an end which isn't less than `n`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate aisd;
+    /// use aisd::graph::LabeledGraph;
+    ///
+    /// let g: LabeledGraph<(), f64> = LabeledGraph::from_edges(true, 3, vec![(0, 1, 0.5), (1, 2, 1.5)]);
+    /// assert_eq!((g.node_count(), g.edge_count()), (3, 2));
+    /// assert_eq!(g.neighbors(1).collect::<Vec<_>>(), vec![(2, &1.5)]);
+    /// ```
+    pub fn from_edges<I>(directed: bool, n: usize, edges: I) -> LabeledGraph<N, E>
+        where N: Default, I: IntoIterator<Item = (usize, usize, E)>
+    {
+        let mut g = if directed {LabeledGraph::directed()} else {LabeledGraph::undirected()};
+        for _ in 0 .. n {
+            g.add_node(N::default());
+        }
+        for (u, v, e) in edges {
+            g.add_edge(u, v, e);
+        }
+
+        g
+    }
+
+    /// Checks whether the edges are directed.
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// Returns the number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Adds a node carrying `value` and returns its number.
+    pub fn add_node(&mut self, value: N) -> usize {
+        self.nodes.push(value);
+        self.adj.push(vec![]);
+        self.succ.push(vec![]);
+        self.nodes.len() - 1
+    }
+
+    /// Adds an edge from `u` to `v`, or between them if the graph is
+    /// undirected, carrying `value`. Its number is the number of edges before.
+    /// Allows chaining calls.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `u` or `v` isn't a node.
+    pub fn add_edge(&mut self, u: usize, v: usize, value: E) -> &mut Self {
+        let n = self.node_count();
+        assert!(u < n && v < n, "LabeledGraph: edge ({}, {}) out of bounds (nodes {})", u, v, n);
+
+        let i = self.edges.len();
+        self.edges.push((u, v, value));
+        self.adj[u].push(i);
+        self.succ[u].push(v);
+        if !self.directed && u != v {
+            self.adj[v].push(i);
+            self.succ[v].push(u);
+        }
+        self
+    }
+
+    /// Returns the value of the node `u`.
+    pub fn node(&self, u: usize) -> &N {
+        &self.nodes[u]
+    }
+
+    /// Returns the value of the node `u` for changing.
+    pub fn node_mut(&mut self, u: usize) -> &mut N {
+        &mut self.nodes[u]
+    }
+
+    /// Returns the ends and the value of the `i`-th edge.
+    pub fn edge(&self, i: usize) -> (usize, usize, &E) {
+        let (u, v, ref e) = self.edges[i];
+        (u, v, e)
+    }
+
+    /// Returns the value of the `i`-th edge for changing.
+    pub fn edge_mut(&mut self, i: usize) -> &mut E {
+        &mut self.edges[i].2
+    }
+
+    /// Returns all the edges as `(u, v, value)`, in the order they were added.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, &E)> {
+        self.edges.iter().map(|&(u, v, ref e)| (u, v, e))
+    }
+
+    /// Returns the nodes which `u` has edges to, with the values of the edges,
+    /// in the order the edges were added.
+    pub fn neighbors(&self, u: usize) -> impl Iterator<Item = (usize, &E)> {
+        self.out_edges(u).map(move |i| {
+            let (a, b, e) = self.edge(i);
+            (if a == u {b} else {a}, e)
+        })
+    }
+
+    /// Returns the numbers of the edges going out of `u`.
+    pub fn out_edges(&self, u: usize) -> impl Iterator<Item = usize> + '_ {
+        self.adj[u].iter().cloned()
+    }
+
+    /// Returns the number of edges going out of `u`. A loop in an undirected
+    /// graph counts once.
+    pub fn degree(&self, u: usize) -> usize {
+        self.adj[u].len()
+    }
+}
+
+impl<N, E> Adjacency for LabeledGraph<N, E> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn successors(&self, u: usize) -> &[usize] {
+        &self.succ[u]
+    }
+}
+
+/// Generates a directed or undirected graph with arbitrary values on the
+/// edges, without loops and multiple edges.
+impl<E: Arbitrary> Arbitrary for LabeledGraph<(), E> {
+    fn arbitrary<G: Gen>(g: &mut G) -> LabeledGraph<(), E> {
+        // `E::arbitrary` may take an odd number of 32-bit words from
+        // `thread_rng`, after which rand_core 0.2 reads a 64-bit one from a
+        // misaligned address. So the graph is drawn from another generator,
+        // seeded with 32-bit words.
+        let seed = (0 .. 2).fold(0, |s, _| s << 32 | u64::from(rand::thread_rng().gen::<u32>()));
+        let mut rng = Xoshiro256::seed_from_u64(seed);
+
+        let n = rng.gen_range(0, g.size() + 1);
+        let directed = rng.gen();
+        let mut graph = LabeledGraph::from_edges(directed, n, vec![]);
+        let p = if n == 0 {0.0} else {(3.0 / n as f64).min(1.0)};
+
+        for u in 0 .. n {
+            for v in if directed {0} else {u + 1} .. n {
+                if u != v && rng.gen_bool(p) {
+                    graph.add_edge(u, v, E::arbitrary(g));
+                }
+            }
+        }
+
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::{bfs, DirectedGraph, Graph};
+
+    quickcheck! {
+        // Every edge is seen from its tail, and from its head if the graph
+        // is undirected.
+        fn neighbors_edges(g: LabeledGraph<(), u8>) -> bool {
+            let mut from_neighbors: Vec<(usize, usize, u8)> = vec![];
+            for u in 0 .. g.node_count() {
+                for (v, &e) in g.neighbors(u) {
+                    if g.is_directed() || u < v {
+                        from_neighbors.push((u, v, e));
+                    }
+                }
+            }
+            let mut edges: Vec<(usize, usize, u8)> = g.edges().map(|(u, v, &e)| (u, v, e)).collect();
+            from_neighbors.sort();
+            edges.sort();
+
+            let degrees: usize = (0 .. g.node_count()).map(|u| g.degree(u)).sum();
+            let per_edge = if g.is_directed() {1} else {2};
+
+            from_neighbors == edges && degrees == per_edge * g.edge_count()
+        }
+
+        fn from_edges_same(g: LabeledGraph<(), u8>) -> bool {
+            let edges: Vec<(usize, usize, u8)> = g.edges().map(|(u, v, &e)| (u, v, e)).collect();
+            LabeledGraph::from_edges(g.is_directed(), g.node_count(), edges) == g
+        }
+
+        // The successors are the neighbours without the values, and `bfs` finds
+        // the same distances as in the graph without them.
+        fn bfs_ignores_values(g: LabeledGraph<(), u8>, s: usize) -> bool {
+            let n = g.node_count();
+            let same_successors = (0 .. n).all(|u| {
+                g.successors(u).iter().cloned().eq(g.neighbors(u).map(|(v, _)| v))
+            });
+            if n == 0 {
+                return same_successors;
+            }
+
+            let dist = if g.is_directed() {
+                let mut h = DirectedGraph::new(n);
+                for (u, v, _) in g.edges() {
+                    h.add_edge(u, v);
+                }
+                bfs(&h, s % n).dist
+            } else {
+                let mut h = Graph::new(n);
+                for (u, v, _) in g.edges() {
+                    h.add_edge(u, v);
+                }
+                bfs(&h, s % n).dist
+            };
+
+            same_successors && bfs(&g, s % n).dist == dist
+        }
+    }
+
+    #[test]
+    fn loops_and_payloads() {
+        let mut g = LabeledGraph::undirected();
+        let a = g.add_node(String::from("a"));
+        let b = g.add_node(String::from("b"));
+        g.add_edge(a, a, 1).add_edge(a, b, 2).add_edge(a, b, 3);
+
+        assert_eq!(g.degree(a), 3);
+        assert_eq!(g.neighbors(a).collect::<Vec<_>>(), vec![(a, &1), (b, &2), (b, &3)]);
+        assert_eq!(g.neighbors(b).collect::<Vec<_>>(), vec![(a, &2), (a, &3)]);
+
+        g.node_mut(b).push('!');
+        *g.edge_mut(2) += 10;
+        assert_eq!(g.node(b), "b!");
+        assert_eq!(g.edge(2), (a, b, &13));
+    }
+
+    #[test]
+    fn bfs_directed_and_loops() {
+        let mut g = LabeledGraph::directed();
+        let a = g.add_node('a');
+        let b = g.add_node('b');
+        let c = g.add_node('c');
+        g.add_edge(a, a, 1.5).add_edge(c, b, 2.0).add_edge(a, c, 0.5);
+
+        assert_eq!(g.successors(a), &[a, c]);
+        assert_eq!(bfs(&g, a).path_to(b), Some(vec![a, c, b]));
+        assert_eq!(bfs(&g, b).dist, vec![None, Some(0), None]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn edge_out_of_bounds() {
+        let mut g: LabeledGraph<(), ()> = LabeledGraph::from_edges(true, 2, vec![]);
+        g.add_edge(0, 2, ());
+    }
+}
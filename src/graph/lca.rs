@@ -0,0 +1,140 @@
+//! Lowest common ancestors in rooted trees.
+
+use disjoint_set::{Auto, DisjointSet};
+use graph::Graph;
+
+/// Tarjan's offline algorithm: answers a batch of queries for the lowest
+/// common ancestors of pairs of nodes in the tree `tree`, rooted at `root`,
+/// with one depth-first search. Returns the answer to every query, or `None`
+/// if one of the nodes isn't in the component of `root`.
+///
+/// The search keeps the subtrees it has finished in union-find, every one
+/// merged into the set of its parent, and the set of the node being visited
+/// remembers that node. When the search finishes `u`, for every query `(u, v)`
+/// where `v` was finished before, the set of `v` is that of the ancestor of
+/// `v` being visited, which is also the lowest ancestor of `u`.
+///
+/// The search keeps its own stack, so deep trees don't overflow the call
+/// stack. If `tree` has cycles, the answers are for a depth-first search tree.
+/// Time: O((n + q) α(n)) for n nodes and q queries
+///
+/// # Panics
+///
+/// Panics if `root` or a node in a query isn't a node.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::Graph;
+/// use aisd::graph::lca::tarjan_offline;
+///
+/// //     0
+/// //    / \
+/// //   1   2
+/// //  / \   \
+/// // 3   4   5
+/// let mut tree = Graph::new(7);
+/// tree.add_edge(0, 1).add_edge(0, 2).add_edge(1, 3).add_edge(1, 4).add_edge(2, 5);
+///
+/// let answers = tarjan_offline(&tree, 0, &[(3, 4), (3, 5), (4, 1), (2, 2), (3, 6)]);
+/// assert_eq!(answers, vec![Some(1), Some(0), Some(1), Some(2), None]);
+/// ```
+pub fn tarjan_offline(tree: &Graph, root: usize, queries: &[(usize, usize)]) -> Vec<Option<usize>> {
+    let n = tree.node_count();
+    let mut asked: Vec<Vec<(usize, usize)>> = vec![vec![]; n];
+    for (i, &(u, v)) in queries.iter().enumerate() {
+        asked[u].push((v, i));
+        asked[v].push((u, i));
+    }
+
+    let mut sets = Auto::new(n);
+    let mut ancestor: Vec<usize> = (0 .. n).collect();
+    let mut visited = vec![false; n];
+    let mut finished = vec![false; n];
+    let mut answers = vec![None; queries.len()];
+
+    // Nodes being visited, each with the number of neighbours seen so far.
+    let mut stack = vec![(root, 0)];
+    visited[root] = true;
+    while let Some(&mut (u, ref mut i)) = stack.last_mut() {
+        if let Some(&v) = tree.neighbors(u).get(*i) {
+            *i += 1;
+            if !visited[v] {
+                visited[v] = true;
+                stack.push((v, 0));
+            }
+            continue;
+        }
+
+        stack.pop();
+        finished[u] = true;
+        for &(v, q) in &asked[u] {
+            if finished[v] {
+                answers[q] = sets.find(v).map(|s| ancestor[s]);
+            }
+        }
+        if let Some(&(p, _)) = stack.last() {
+            sets.union(p, u);
+            let s = sets.find(p).unwrap();
+            ancestor[s] = p;
+        }
+    }
+
+    answers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::path_graph;
+
+    // A tree where the parent of every node but 0 is a node before it.
+    fn tree(parents: &[usize]) -> (Graph, Vec<Option<usize>>) {
+        let n = parents.len() + 1;
+        let mut g = Graph::new(n);
+        let mut parent = vec![None];
+        for (i, &p) in parents.iter().enumerate() {
+            let p = p % (i + 1);
+            g.add_edge(p, i + 1);
+            parent.push(Some(p));
+        }
+
+        (g, parent)
+    }
+
+    fn lca_naive(parent: &[Option<usize>], u: usize, v: usize) -> usize {
+        let mut ancestors = vec![u];
+        while let Some(p) = parent[*ancestors.last().unwrap()] {
+            ancestors.push(p);
+        }
+        let mut w = v;
+        while !ancestors.contains(&w) {
+            w = parent[w].unwrap();
+        }
+
+        w
+    }
+
+    quickcheck! {
+        fn tarjan_offline_naive(parents: Vec<usize>, queries: Vec<(usize, usize)>) -> bool {
+            let (g, parent) = tree(&parents);
+            let n = g.node_count();
+            let queries: Vec<(usize, usize)> = queries.into_iter().map(|(u, v)| (u % n, v % n)).collect();
+            let answers = tarjan_offline(&g, 0, &queries);
+
+            queries.iter().zip(answers).all(|(&(u, v), a)| a == Some(lca_naive(&parent, u, v)))
+        }
+    }
+
+    // Rooted at an end, every node of the path is an ancestor of the ones after
+    // it; rooted in the middle, the answers are all the root.
+    #[test]
+    fn deep_path() {
+        let n = 100_000;
+        let g = path_graph(n);
+
+        assert_eq!(tarjan_offline(&g, 0, &[(n - 1, n / 2), (7, n - 3)]), vec![Some(n / 2), Some(7)]);
+        assert_eq!(tarjan_offline(&g, n / 2, &[(0, n - 1)]), vec![Some(n / 2)]);
+    }
+}
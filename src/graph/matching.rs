@@ -0,0 +1,291 @@
+//! Maximum matchings in bipartite graphs.
+
+use quickcheck::{Arbitrary, Gen};
+
+use rand;
+use rand::Rng;
+
+use std::collections::VecDeque;
+
+/// A bipartite graph with the left nodes {0, ..., l - 1} and the right nodes
+/// {0, ..., r - 1}, where every edge joins a left node with a right node.
+/// Multiple edges are allowed.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::BipartiteGraph;
+///
+/// let mut g = BipartiteGraph::new(2, 3);
+/// g.add_edge(0, 1).add_edge(0, 2).add_edge(1, 2);
+/// assert_eq!(g.neighbors(0), &[1, 2]);
+/// assert_eq!(g.edge_count(), 3);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BipartiteGraph {
+    right: usize,
+    // The right neighbours of every left node.
+    adj: Vec<Vec<usize>>
+}
+
+impl BipartiteGraph {
+    /// Creates a graph with `left` left nodes, `right` right nodes and no
+    /// edges.
+    pub fn new(left: usize, right: usize) -> BipartiteGraph {
+        BipartiteGraph {right, adj: vec![vec![]; left]}
+    }
+
+    /// Returns the number of left nodes.
+    pub fn left_count(&self) -> usize {
+        self.adj.len()
+    }
+
+    /// Returns the number of right nodes.
+    pub fn right_count(&self) -> usize {
+        self.right
+    }
+
+    /// Returns the number of edges.
+    /// Time: O(number of left nodes)
+    pub fn edge_count(&self) -> usize {
+        self.adj.iter().map(|n| n.len()).sum()
+    }
+
+    /// Adds an edge between the left node `l` and the right node `r`. Allows
+    /// chaining calls.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l` isn't a left node or `r` isn't a right node.
+    pub fn add_edge(&mut self, l: usize, r: usize) -> &mut Self {
+        assert!(r < self.right, "BipartiteGraph: right node {} out of bounds ({})", r, self.right);
+        self.adj[l].push(r);
+        self
+    }
+
+    /// Returns the right neighbours of the left node `l`.
+    pub fn neighbors(&self, l: usize) -> &[usize] {
+        &self.adj[l]
+    }
+}
+
+/// Generates a graph without multiple edges.
+impl Arbitrary for BipartiteGraph {
+    fn arbitrary<G: Gen>(g: &mut G) -> BipartiteGraph {
+        let mut rng = rand::thread_rng();
+
+        let left = rng.gen_range(0, g.size() + 1);
+        let right = rng.gen_range(0, g.size() + 1);
+        let mut graph = BipartiteGraph::new(left, right);
+        let p = if right == 0 {0.0} else {(2.0 / right as f64).min(1.0)};
+
+        for l in 0 .. left {
+            for r in 0 .. right {
+                if rng.gen_bool(p) {
+                    graph.add_edge(l, r);
+                }
+            }
+        }
+
+        graph
+    }
+}
+
+/// A maximum matching of a bipartite graph together with a minimum vertex
+/// cover, as computed by `hopcroft_karp`. By Kőnig's theorem, they have the
+/// same size, which proves that both are optimal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matching {
+    /// The right node matched with every left node, if any.
+    pub left_mate: Vec<Option<usize>>,
+
+    /// The left node matched with every right node, if any.
+    pub right_mate: Vec<Option<usize>>,
+
+    /// The left nodes of the vertex cover, in increasing order.
+    pub cover_left: Vec<usize>,
+
+    /// The right nodes of the vertex cover, in increasing order.
+    pub cover_right: Vec<usize>
+}
+
+impl Matching {
+    /// Returns the number of matched pairs.
+    pub fn size(&self) -> usize {
+        self.left_mate.iter().filter(|m| m.is_some()).count()
+    }
+
+    /// Returns the matched pairs `(l, r)`, in increasing order of `l`.
+    pub fn pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.left_mate.iter().enumerate().filter_map(|(l, m)| m.map(|r| (l, r)))
+    }
+}
+
+/// The Hopcroft-Karp algorithm: finds a maximum matching in phases. Every
+/// phase finds the length of the shortest augmenting paths by breadth-first
+/// search from the free left nodes, and then a maximal set of disjoint
+/// augmenting paths of that length by depth-first search, and flips them.
+/// There are only O(sqrt(V)) phases.
+///
+/// The vertex cover comes from Kőnig's theorem: with Z the nodes reachable
+/// from the free left nodes by alternating paths, it's the left nodes outside
+/// Z and the right nodes in Z.
+/// Time: O(E sqrt(V)) for V nodes and E edges
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{BipartiteGraph, hopcroft_karp};
+///
+/// // Workers and the jobs they can do.
+/// let mut g = BipartiteGraph::new(3, 3);
+/// g.add_edge(0, 0).add_edge(0, 1).add_edge(1, 0).add_edge(2, 0);
+///
+/// let m = hopcroft_karp(&g);
+/// assert_eq!(m.size(), 2);
+/// assert_eq!(m.pairs().collect::<Vec<_>>(), vec![(0, 1), (1, 0)]);
+/// assert_eq!((m.cover_left, m.cover_right), (vec![0], vec![0]));
+/// ```
+pub fn hopcroft_karp(g: &BipartiteGraph) -> Matching {
+    let (nl, nr) = (g.left_count(), g.right_count());
+    let mut left_mate: Vec<Option<usize>> = vec![None; nl];
+    let mut right_mate: Vec<Option<usize>> = vec![None; nr];
+
+    loop {
+        // Layers of left nodes, by the length of the shortest alternating path
+        // from a free one, up to the length of the shortest augmenting path.
+        let mut dist: Vec<Option<usize>> = vec![None; nl];
+        let mut queue = VecDeque::new();
+        for l in 0 .. nl {
+            if left_mate[l].is_none() {
+                dist[l] = Some(0);
+                queue.push_back(l);
+            }
+        }
+        let mut shortest = None;
+        while let Some(l) = queue.pop_front() {
+            let d = dist[l].unwrap();
+            if shortest.is_some_and(|s| d >= s) {
+                continue;
+            }
+            for &r in g.neighbors(l) {
+                match right_mate[r] {
+                    None => shortest = shortest.or(Some(d + 1)),
+                    Some(m) if dist[m].is_none() => {
+                        dist[m] = Some(d + 1);
+                        queue.push_back(m);
+                    },
+                    Some(_) => {}
+                }
+            }
+        }
+        let shortest = match shortest {
+            Some(s) => s,
+            None => break
+        };
+
+        // Augmenting paths along the layers, with a stack of left nodes. The
+        // path goes on through the edge at next[l]; edges which lead nowhere
+        // are skipped for the rest of the phase, and so are dead ends.
+        let mut next = vec![0; nl];
+        for s in 0 .. nl {
+            if left_mate[s].is_some() {
+                continue;
+            }
+            let mut stack = vec![s];
+            while let Some(&l) = stack.last() {
+                let d = dist[l].unwrap();
+                let r = match g.neighbors(l).get(next[l]) {
+                    Some(&r) => r,
+                    None => {
+                        dist[l] = None;
+                        stack.pop();
+                        if let Some(&p) = stack.last() {
+                            next[p] += 1;
+                        }
+                        continue;
+                    }
+                };
+                match right_mate[r] {
+                    None if d + 1 == shortest => {
+                        for &l in &stack {
+                            let r = g.neighbors(l)[next[l]];
+                            left_mate[l] = Some(r);
+                            right_mate[r] = Some(l);
+                        }
+                        break;
+                    },
+                    Some(m) if dist[m] == Some(d + 1) => stack.push(m),
+                    _ => next[l] += 1
+                }
+            }
+        }
+    }
+
+    // Z: the nodes reachable from the free left nodes by alternating paths.
+    let mut z_left = vec![false; nl];
+    let mut z_right = vec![false; nr];
+    let mut stack: Vec<usize> = (0 .. nl).filter(|&l| left_mate[l].is_none()).collect();
+    for &l in &stack {
+        z_left[l] = true;
+    }
+    while let Some(l) = stack.pop() {
+        for &r in g.neighbors(l) {
+            if !z_right[r] {
+                z_right[r] = true;
+                if let Some(m) = right_mate[r] {
+                    if !z_left[m] {
+                        z_left[m] = true;
+                        stack.push(m);
+                    }
+                }
+            }
+        }
+    }
+
+    Matching {
+        cover_left: (0 .. nl).filter(|&l| !z_left[l]).collect(),
+        cover_right: (0 .. nr).filter(|&r| z_right[r]).collect(),
+        left_mate,
+        right_mate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    quickcheck! {
+        // A matching and a vertex cover of the same size are both optimal.
+        fn matching_cover_same_size(g: BipartiteGraph) -> bool {
+            let m = hopcroft_karp(&g);
+            let matched_right = m.right_mate.iter().filter(|x| x.is_some()).count();
+
+            m.pairs().all(|(l, r)| g.neighbors(l).contains(&r) && m.right_mate[r] == Some(l)) &&
+            matched_right == m.size() &&
+            (0 .. g.left_count()).all(|l| g.neighbors(l).iter().all(|r| {
+                m.cover_left.contains(&l) || m.cover_right.contains(r)
+            })) &&
+            m.cover_left.len() + m.cover_right.len() == m.size()
+        }
+    }
+
+    // The first phase matches every left node l with the right node l + 1,
+    // but the last one, and the only augmenting path then goes through all
+    // the nodes, too deep for a recursive search.
+    #[test]
+    fn long_augmenting_path() {
+        let n = 100_000;
+        let mut g = BipartiteGraph::new(n, n);
+        for l in 0 .. n {
+            if l + 1 < n {
+                g.add_edge(l, l + 1);
+            }
+            g.add_edge(l, l);
+        }
+
+        assert_eq!(hopcroft_karp(&g).size(), n);
+    }
+}
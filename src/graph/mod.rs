@@ -0,0 +1,251 @@
+//! Graphs and graph algorithms.
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use rand;
+use rand::Rng;
+
+use std::ops::Add;
+
+pub mod gen;
+pub mod lca;
+pub mod tree;
+
+mod bellman_ford;
+mod bfs;
+mod bipartite;
+mod cliques;
+mod closure;
+mod coloring;
+mod components;
+mod condensation;
+mod dag;
+mod dfs;
+mod dijkstra;
+mod dot;
+mod flow;
+mod havel_hakimi;
+mod labeled;
+mod matching;
+mod mst;
+mod tsp;
+mod yen;
+
+pub use self::bellman_ford::{bellman_ford, negative_cycle, NegativeCycle};
+pub use self::bfs::{bfs, bfs_multi, Bfs};
+pub use self::bipartite::{bipartition, OddCycle, Side};
+pub use self::cliques::maximal_cliques;
+pub use self::closure::{transitive_closure, TransitiveClosure};
+pub use self::coloring::{color_dsatur, color_greedy, Coloring};
+pub use self::components::{connected_components, Components, IncrementalComponents};
+pub use self::condensation::{strongly_connected_components, condense, condense_and_analyze, Condensation};
+pub use self::dag::{count_paths, longest_path, topological_order};
+pub use self::dfs::{dfs, dfs_with, Dfs, EdgeKind, Event};
+pub use self::dijkstra::{dijkstra, dijkstra_decrease_key, ShortestPaths};
+pub use self::dot::{ParseError, MAX_NODE};
+pub use self::flow::{dinic, FlowNetwork, MaxFlow};
+pub use self::havel_hakimi::realize_degree_sequence;
+pub use self::labeled::LabeledGraph;
+pub use self::matching::{hopcroft_karp, BipartiteGraph, Matching};
+pub use self::mst::{kruskal, min_arborescence, prim, Arborescence, SpanningForest};
+pub use self::tsp::{hamiltonian_path, tsp_held_karp};
+pub use self::yen::k_shortest_paths;
+
+/// Graphs on the nodes {0, ..., n - 1} stored as adjacency lists, for
+/// algorithms which only follow edges and so work the same whether they're
+/// directed or not.
+pub trait Adjacency {
+    /// Returns the number of nodes.
+    fn node_count(&self) -> usize;
+
+    /// Returns the nodes which `u` has edges to. In an undirected graph, these
+    /// are its neighbours.
+    fn successors(&self, u: usize) -> &[usize];
+}
+
+/// Weights of edges which add up along paths, like lengths or costs.
+pub trait Weight: Copy + Ord + Add<Output = Self> {
+    /// The weight of a path without edges.
+    const ZERO: Self;
+}
+
+macro_rules! impl_weight {
+    ($($t:ty)*) => {
+        $(impl Weight for $t {
+            const ZERO: $t = 0;
+        })*
+    };
+}
+
+impl_weight!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+/// An undirected graph on the nodes {0, ..., n - 1}, stored as adjacency lists.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Graph {
+    adj: Vec<Vec<usize>>
+}
+
+impl Graph {
+    /// Creates a graph with `n` nodes and no edges.
+    pub fn new(n: usize) -> Graph {
+        Graph {adj: vec![vec![]; n]}
+    }
+
+    /// Returns the number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.adj.len()
+    }
+
+    /// Returns the number of edges.
+    /// Time: O(number of nodes)
+    pub fn edge_count(&self) -> usize {
+        self.adj.iter().map(|n| n.len()).sum::<usize>() / 2
+    }
+
+    /// Adds an edge between `u` and `v`. Allows chaining calls.
+    pub fn add_edge(&mut self, u: usize, v: usize) -> &mut Self {
+        self.adj[u].push(v);
+        if u != v {
+            self.adj[v].push(u);
+        }
+        self
+    }
+
+    /// Returns the neighbours of `u`.
+    pub fn neighbors(&self, u: usize) -> &[usize] {
+        &self.adj[u]
+    }
+
+    /// Returns the number of edges incident to `u`.
+    pub fn degree(&self, u: usize) -> usize {
+        self.adj[u].len()
+    }
+
+    /// Checks whether there's an edge between `u` and `v`.
+    /// Time: O(degree of `u`)
+    pub fn has_edge(&self, u: usize, v: usize) -> bool {
+        self.adj[u].contains(&v)
+    }
+}
+
+impl Adjacency for Graph {
+    fn node_count(&self) -> usize {
+        self.adj.len()
+    }
+
+    fn successors(&self, u: usize) -> &[usize] {
+        &self.adj[u]
+    }
+}
+
+/// Generates a simple graph (no loops, no multiple edges).
+impl Arbitrary for Graph {
+    fn arbitrary<G: Gen>(g: &mut G) -> Graph {
+        let mut rng = rand::thread_rng();
+
+        let n = rng.gen_range(0, g.size() + 1);
+        let mut graph = Graph::new(n);
+
+        for u in 0 .. n {
+            for v in u + 1 .. n {
+                if rng.gen_bool(0.3) {
+                    graph.add_edge(u, v);
+                }
+            }
+        }
+
+        graph
+    }
+}
+
+/// The path 0 - 1 - ... - (n - 1), the deepest graph on n nodes, for testing
+/// that searches don't recurse.
+#[cfg(test)]
+pub(crate) fn path_graph(n: usize) -> Graph {
+    let mut g = Graph::new(n);
+    for u in 1 .. n {
+        g.add_edge(u - 1, u);
+    }
+
+    g
+}
+
+/// A directed graph on the nodes {0, ..., n - 1}, stored as adjacency lists.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectedGraph {
+    adj: Vec<Vec<usize>>
+}
+
+impl DirectedGraph {
+    /// Creates a graph with `n` nodes and no edges.
+    pub fn new(n: usize) -> DirectedGraph {
+        DirectedGraph {adj: vec![vec![]; n]}
+    }
+
+    /// Returns the number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.adj.len()
+    }
+
+    /// Returns the number of edges.
+    /// Time: O(number of nodes)
+    pub fn edge_count(&self) -> usize {
+        self.adj.iter().map(|n| n.len()).sum()
+    }
+
+    /// Adds an edge from `u` to `v`. Allows chaining calls.
+    pub fn add_edge(&mut self, u: usize, v: usize) -> &mut Self {
+        self.adj[u].push(v);
+        self
+    }
+
+    /// Returns the nodes which `u` has edges to.
+    pub fn successors(&self, u: usize) -> &[usize] {
+        &self.adj[u]
+    }
+
+    /// Returns the number of edges going out of `u`.
+    pub fn out_degree(&self, u: usize) -> usize {
+        self.adj[u].len()
+    }
+
+    /// Checks whether there's an edge from `u` to `v`.
+    /// Time: O(out-degree of `u`)
+    pub fn has_edge(&self, u: usize, v: usize) -> bool {
+        self.adj[u].contains(&v)
+    }
+}
+
+impl Adjacency for DirectedGraph {
+    fn node_count(&self) -> usize {
+        self.adj.len()
+    }
+
+    fn successors(&self, u: usize) -> &[usize] {
+        &self.adj[u]
+    }
+}
+
+/// Generates a graph without loops and multiple edges. It's sparser than the
+/// undirected one, so that there are both big and small strongly connected
+/// components.
+impl Arbitrary for DirectedGraph {
+    fn arbitrary<G: Gen>(g: &mut G) -> DirectedGraph {
+        let mut rng = rand::thread_rng();
+
+        let n = rng.gen_range(0, g.size() + 1);
+        let mut graph = DirectedGraph::new(n);
+        let p = if n == 0 {0.0} else {(1.5 / n as f64).min(1.0)};
+
+        for u in 0 .. n {
+            for v in 0 .. n {
+                if u != v && rng.gen_bool(p) {
+                    graph.add_edge(u, v);
+                }
+            }
+        }
+
+        graph
+    }
+}
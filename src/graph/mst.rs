@@ -0,0 +1,415 @@
+//! Minimum spanning trees, and their directed counterparts, minimum spanning
+//! arborescences.
+
+use disjoint_set::{Auto, DisjointSet};
+use graph::{LabeledGraph, Weight};
+use pq::IndexedHeap;
+use sort;
+
+use std::ops::Sub;
+
+/// A minimum spanning forest, as computed by `kruskal`: a minimum spanning
+/// tree of every connected component.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanningForest<W> {
+    /// The numbers of the edges in the forest, from the lightest.
+    pub edges: Vec<usize>,
+
+    /// The total weight of the edges.
+    pub weight: W
+}
+
+/// Kruskal's algorithm: goes through the edges from the lightest, and takes
+/// every one which doesn't close a cycle with those taken before, which is
+/// checked with union-find. The values of the edges are their weights, and
+/// their directions, if any, are ignored. Of edges with equal weights, those
+/// added to the graph earlier are taken first.
+/// Time: O(m log m) for m edges
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{LabeledGraph, kruskal};
+///
+/// let g: LabeledGraph<(), u32> = LabeledGraph::from_edges(false, 5, vec![
+///     (0, 1, 4), (0, 2, 1), (1, 2, 2), (1, 3, 5), (2, 3, 8), (3, 4, 3)
+/// ]);
+///
+/// let f = kruskal(&g);
+/// assert_eq!(f.edges, vec![1, 2, 5, 3]);
+/// assert_eq!(f.weight, 11);
+/// ```
+pub fn kruskal<N, W: Weight>(g: &LabeledGraph<N, W>) -> SpanningForest<W> {
+    let mut order: Vec<usize> = (0 .. g.edge_count()).collect();
+    sort::mergesort_by_key(&mut order, |&i| *g.edge(i).2);
+
+    let mut components = Auto::new(g.node_count());
+    let mut edges = vec![];
+    let mut weight = W::ZERO;
+    for i in order {
+        // A forest on n nodes has at most n - 1 edges.
+        if components.set_count() == 1 {
+            break;
+        }
+        let (u, v, &w) = g.edge(i);
+        if !components.same_set(u, v) {
+            components.union(u, v);
+            edges.push(i);
+            weight = weight + w;
+        }
+    }
+
+    SpanningForest {edges, weight}
+}
+
+/// Prim's algorithm: grows a tree from `root`, adding the lightest edge from
+/// the tree to a node outside it, until no such edge is left. The nodes
+/// outside the tree are kept in an indexed heap by the weight of their
+/// lightest edge to the tree. The values of the edges are their weights.
+/// Returns the parent of every node in a minimum spanning tree of the
+/// component of `root`, rooted at it, or `None` for the root and the nodes of
+/// other components.
+///
+/// It follows the same edges as Dijkstra's algorithm, but the key of a node is
+/// the weight of a single edge instead of a whole path.
+/// Time: O(m log n) for n nodes and m edges
+///
+/// # Panics
+///
+/// Panics if the graph is directed or `root` isn't a node.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{LabeledGraph, prim};
+///
+/// let g: LabeledGraph<(), u32> = LabeledGraph::from_edges(false, 5, vec![
+///     (0, 1, 4), (0, 2, 1), (1, 2, 2), (1, 3, 5), (2, 3, 8), (3, 4, 3)
+/// ]);
+///
+/// assert_eq!(prim(&g, 0), vec![None, Some(2), Some(0), Some(1), Some(3)]);
+/// ```
+pub fn prim<N, W: Weight>(g: &LabeledGraph<N, W>, root: usize) -> Vec<Option<usize>> {
+    assert!(!g.is_directed(), "prim: the graph is directed");
+    let n = g.node_count();
+    let mut parent = vec![None; n];
+    let mut in_tree = vec![false; n];
+    let mut heap = IndexedHeap::new(n);
+
+    heap.insert(root, W::ZERO);
+    while let Some((u, _)) = heap.del_min() {
+        in_tree[u] = true;
+        for (v, &w) in g.neighbors(u) {
+            if !in_tree[v] && heap.insert_or_decrease(v, w) {
+                parent[v] = Some(u);
+            }
+        }
+    }
+
+    parent
+}
+
+/// A minimum spanning arborescence, as computed by `min_arborescence`: a tree
+/// of edges directed away from the root, by which it reaches every node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arborescence<W> {
+    /// The number of the edge into every node, or `None` for the root.
+    pub parent_edge: Vec<Option<usize>>,
+
+    /// The total weight of the edges.
+    pub weight: W
+}
+
+// An edge of a contracted graph: its ends, its weight and its number in the
+// graph before the contraction.
+type Contracted<W> = (usize, usize, W, usize);
+
+/// The Chu-Liu/Edmonds algorithm for a minimum spanning arborescence of a
+/// directed graph rooted at `root`: the lightest set of edges by which `root`
+/// reaches every node along a single path. Returns `None` if some node isn't
+/// reachable from `root`. The values of the edges are their weights.
+///
+/// Every node but the root picks its lightest incoming edge. If these make no
+/// cycle, they're the answer. Otherwise every cycle is contracted into a single
+/// node, and an edge entering it at `v` gets lighter by the weight of the edge
+/// `v` picked, which is the cost of entering the cycle there and dropping that
+/// edge instead. The answer for the smaller graph is then expanded back by
+/// opening the cycles, each at the node its entering edge leads to.
+/// Time: O(n m) for n nodes and m edges
+///
+/// # Panics
+///
+/// Panics if the graph is undirected or `root` isn't a node.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{LabeledGraph, min_arborescence};
+///
+/// // The lightest edges into 1 and 2 make a cycle 1 -> 2 -> 1, which is best
+/// // entered at 2.
+/// let g: LabeledGraph<(), u32> = LabeledGraph::from_edges(true, 4, vec![
+///     (0, 1, 10), (0, 2, 8), (1, 2, 1), (2, 1, 2), (2, 3, 4), (1, 3, 6)
+/// ]);
+///
+/// let a = min_arborescence(&g, 0).unwrap();
+/// assert_eq!(a.parent_edge, vec![None, Some(3), Some(1), Some(4)]);
+/// assert_eq!(a.weight, 14);
+/// assert_eq!(min_arborescence(&g, 3), None);
+/// ```
+pub fn min_arborescence<N, W>(g: &LabeledGraph<N, W>, root: usize) -> Option<Arborescence<W>>
+    where W: Weight + Sub<Output = W>
+{
+    assert!(g.is_directed(), "min_arborescence: the graph is undirected");
+    let n = g.node_count();
+    assert!(root < n, "min_arborescence: root {} out of bounds (nodes {})", root, n);
+
+    // Loops and edges into the root are never in an arborescence.
+    let mut edges: Vec<Contracted<W>> = g.edges().enumerate()
+        .filter(|&(_, (u, v, _))| u != v && v != root)
+        .map(|(i, (u, v, &w))| (u, v, w, i))
+        .collect();
+    let (mut count, mut root) = (n, root);
+    // For every contraction: the edges picked before it, the node every node
+    // went to, the number of cycles, which are the first nodes, and the edges
+    // before it.
+    let mut levels = vec![];
+
+    let mut picked = loop {
+        let mut best: Vec<Option<usize>> = vec![None; count];
+        for (i, &(_, v, w, _)) in edges.iter().enumerate() {
+            if best[v].is_none_or(|b| w < edges[b].2) {
+                best[v] = Some(i);
+            }
+        }
+        if (0 .. count).any(|v| v != root && best[v].is_none()) {
+            return None;
+        }
+
+        // Follow the picked edges backwards from every node, until the root,
+        // a node seen before, or a node seen in this walk, which is on a new
+        // cycle.
+        let mut id: Vec<Option<usize>> = vec![None; count];
+        let mut walk = vec![None; count];
+        let mut cycles = 0;
+        for s in 0 .. count {
+            let mut v = s;
+            while v != root && walk[v].is_none() {
+                walk[v] = Some(s);
+                v = edges[best[v].unwrap()].0;
+            }
+            if v != root && walk[v] == Some(s) && id[v].is_none() {
+                let mut x = v;
+                loop {
+                    id[x] = Some(cycles);
+                    x = edges[best[x].unwrap()].0;
+                    if x == v {
+                        break;
+                    }
+                }
+                cycles += 1;
+            }
+        }
+        if cycles == 0 {
+            break best;
+        }
+
+        let mut next = cycles;
+        let id: Vec<usize> = id.into_iter().map(|c| c.unwrap_or_else(|| {
+            next += 1;
+            next - 1
+        })).collect();
+        let contracted = edges.iter().enumerate()
+            .filter(|&(_, &(u, v, _, _))| id[u] != id[v])
+            .map(|(i, &(u, v, w, _))| {
+                let w = if id[v] < cycles {w - edges[best[v].unwrap()].2} else {w};
+                (id[u], id[v], w, i)
+            })
+            .collect();
+
+        root = id[root];
+        count = next;
+        levels.push((best, id, cycles, edges));
+        edges = contracted;
+    };
+
+    // Open the cycles: their nodes keep the edges they picked, except for the
+    // node which the edge entering the cycle leads to.
+    while let Some((best, id, cycles, before)) = levels.pop() {
+        let mut opened: Vec<Option<usize>> = (0 .. best.len())
+            .map(|x| if id[x] < cycles {best[x]} else {None})
+            .collect();
+        for &i in picked.iter().flatten() {
+            let e = edges[i].3;
+            opened[before[e].1] = Some(e);
+        }
+        picked = opened;
+        edges = before;
+    }
+
+    let parent_edge: Vec<Option<usize>> = picked.iter().map(|p| p.map(|i| edges[i].3)).collect();
+    let weight = parent_edge.iter().flatten().fold(W::ZERO, |total, &i| total + *g.edge(i).2);
+
+    Some(Arborescence {parent_edge, weight})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widen(g: &LabeledGraph<(), u8>) -> LabeledGraph<(), u64> {
+        LabeledGraph::from_edges(g.is_directed(), g.node_count(), g.edges().map(|(u, v, &w)| (u, v, w as u64)))
+    }
+
+    // The heaviest edge on the path between u and v in the forest, if they're
+    // connected.
+    fn heaviest_on_path(g: &LabeledGraph<(), u64>, forest: &[usize], u: usize, v: usize) -> Option<Option<u64>> {
+        let mut best: Vec<Option<Option<u64>>> = vec![None; g.node_count()];
+        best[u] = Some(None);
+        let mut stack = vec![u];
+        while let Some(x) = stack.pop() {
+            for &i in forest {
+                let (a, b, &w) = g.edge(i);
+                let y = if a == x {b} else if b == x {a} else {continue};
+                if best[y].is_none() {
+                    best[y] = Some(best[x].unwrap().max(Some(w)));
+                    stack.push(y);
+                }
+            }
+        }
+
+        best[v]
+    }
+
+    // All the ways of picking an edge into every node but the root, of which
+    // those where every node reaches the root by its picked edges are
+    // arborescences. Returns the least weight of one.
+    fn min_arborescence_naive(g: &LabeledGraph<(), u64>, root: usize) -> Option<u64> {
+        let n = g.node_count();
+        let incoming: Vec<Vec<usize>> = (0 .. n).map(|v| {
+            if v == root {
+                return vec![];
+            }
+            (0 .. g.edge_count()).filter(|&i| g.edge(i).1 == v && g.edge(i).0 != v).collect()
+        }).collect();
+        if (0 .. n).any(|v| v != root && incoming[v].is_empty()) {
+            return None;
+        }
+
+        let mut best = None;
+        let mut choice = vec![0; n];
+        loop {
+            let parent = |v: usize| g.edge(incoming[v][choice[v]]).0;
+            let reaches_root = (0 .. n).all(|v| {
+                let mut x = v;
+                for _ in 0 .. n {
+                    if x == root {
+                        return true;
+                    }
+                    x = parent(x);
+                }
+                x == root
+            });
+            if reaches_root {
+                let weight = (0 .. n).filter(|&v| v != root).map(|v| *g.edge(incoming[v][choice[v]]).2).sum();
+                best = best.into_iter().chain(Some(weight)).min();
+            }
+
+            // The next choice, like counting with mixed digits.
+            let mut v = 0;
+            while v < n && (incoming[v].is_empty() || choice[v] + 1 == incoming[v].len()) {
+                choice[v] = 0;
+                v += 1;
+            }
+            if v == n {
+                return best;
+            }
+            choice[v] += 1;
+        }
+    }
+
+    quickcheck! {
+        // A spanning forest is minimum iff every other edge is at least as
+        // heavy as every edge on the path between its ends in the forest.
+        fn kruskal_cycle_property(g: LabeledGraph<(), u8>) -> bool {
+            let g = widen(&g);
+            let f = kruskal(&g);
+            let in_forest = |i: usize| f.edges.contains(&i);
+            let total: u64 = f.edges.iter().map(|&i| *g.edge(i).2).sum();
+
+            f.weight == total &&
+            g.edges().enumerate().all(|(i, (u, v, &w))| {
+                match heaviest_on_path(&g, &f.edges, u, v) {
+                    Some(heaviest) => in_forest(i) || heaviest <= Some(w),
+                    None => false
+                }
+            }) &&
+            f.edges.iter().all(|&i| {
+                // Without the edge, its ends are disconnected.
+                let (u, v, _) = g.edge(i);
+                let rest: Vec<usize> = f.edges.iter().cloned().filter(|&j| j != i).collect();
+                heaviest_on_path(&g, &rest, u, v).is_none()
+            })
+        }
+
+        fn prim_kruskal(g: LabeledGraph<(), u8>, root: usize) -> bool {
+            if g.node_count() == 0 || g.is_directed() {
+                return true;
+            }
+            let g = widen(&g);
+            let root = root % g.node_count();
+            let parent = prim(&g, root);
+            let in_tree = |u: usize| u == root || parent[u].is_some();
+
+            let mut weight = 0;
+            for (v, p) in parent.iter().enumerate() {
+                if let Some(u) = *p {
+                    match g.neighbors(v).filter(|&(x, _)| x == u).map(|(_, &w)| w).min() {
+                        Some(w) => weight += w,
+                        None => return false
+                    }
+                }
+            }
+            let f = kruskal(&g);
+            let component: u64 = f.edges.iter().map(|&i| g.edge(i)).filter(|e| in_tree(e.0)).map(|e| *e.2).sum();
+
+            parent[root].is_none() && weight == component &&
+            (0 .. g.node_count()).all(|u| in_tree(u) == heaviest_on_path(&g, &f.edges, root, u).is_some())
+        }
+
+        fn min_arborescence_naive_small(g: LabeledGraph<(), u8>, root: usize) -> bool {
+            // At most 6 nodes, so that all the choices can be tried.
+            let n = g.node_count().min(6);
+            if n == 0 {
+                return true;
+            }
+            let edges = g.edges().filter(|&(u, v, _)| u < n && v < n).map(|(u, v, &w)| (u, v, w as u64));
+            let g = LabeledGraph::from_edges(true, n, edges);
+            let root = root % n;
+
+            match (min_arborescence(&g, root), min_arborescence_naive(&g, root)) {
+                (Some(a), Some(best)) => {
+                    let total: u64 = a.parent_edge.iter().flatten().map(|&i| *g.edge(i).2).sum();
+                    a.weight == best && total == best &&
+                    (0 .. n).all(|v| match a.parent_edge[v] {
+                        Some(i) => v != root && g.edge(i).1 == v,
+                        None => v == root
+                    }) &&
+                    (0 .. n).all(|v| {
+                        // Following the parents leads to the root.
+                        let mut x = v;
+                        for _ in 0 .. n {
+                            x = a.parent_edge[x].map_or(x, |i| g.edge(i).0);
+                        }
+                        x == root
+                    })
+                },
+                (None, None) => true,
+                _ => false
+            }
+        }
+    }
+}
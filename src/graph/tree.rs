@@ -0,0 +1,293 @@
+//! Unrooted trees, stored as `Graph`s: their diameter, center and centroid
+//! decomposition.
+
+use graph::{bfs, Graph};
+
+fn assert_tree(g: &Graph, name: &str) {
+    let n = g.node_count();
+    let connected = n == 0 || bfs(g, 0).dist.iter().all(|d| d.is_some());
+    assert!(connected && g.edge_count() + 1 == n.max(1), "{}: the graph isn't a tree", name);
+}
+
+/// Finds a longest path in a tree, as the list of its nodes. Its number of
+/// edges is the diameter of the tree.
+///
+/// The node farthest from any node is an end of a longest path, so the
+/// farthest node from it is the other end.
+/// Time: O(number of nodes)
+///
+/// # Panics
+///
+/// Panics if `tree` isn't a tree.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::Graph;
+/// use aisd::graph::tree::diameter;
+///
+/// let mut tree = Graph::new(6);
+/// tree.add_edge(0, 1).add_edge(1, 2).add_edge(1, 3).add_edge(3, 4).add_edge(4, 5);
+/// assert_eq!(diameter(&tree), vec![5, 4, 3, 1, 2]);
+/// ```
+pub fn diameter(tree: &Graph) -> Vec<usize> {
+    assert_tree(tree, "diameter");
+    if tree.node_count() == 0 {
+        return vec![];
+    }
+
+    let farthest = |s: usize| {
+        let b = bfs(tree, s);
+        let end = (0 .. tree.node_count()).max_by_key(|&v| b.dist[v]).unwrap();
+        (end, b)
+    };
+    let (a, _) = farthest(0);
+    let (b, paths) = farthest(a);
+
+    paths.path_to(b).unwrap()
+}
+
+/// Finds the center of a tree: the nodes whose greatest distance to another
+/// node is the least. There are one or two of them, in the middle of every
+/// longest path.
+/// Time: O(number of nodes)
+///
+/// # Panics
+///
+/// Panics if `tree` isn't a tree.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::Graph;
+/// use aisd::graph::tree::center;
+///
+/// let mut tree = Graph::new(6);
+/// tree.add_edge(0, 1).add_edge(1, 2).add_edge(1, 3).add_edge(3, 4).add_edge(4, 5);
+/// assert_eq!(center(&tree), vec![3]);
+///
+/// tree = Graph::new(2);
+/// tree.add_edge(0, 1);
+/// assert_eq!(center(&tree), vec![0, 1]);
+/// ```
+pub fn center(tree: &Graph) -> Vec<usize> {
+    let path = diameter(tree);
+    let k = path.len();
+    let mut middle = path[k.saturating_sub(1) / 2 .. k / 2 + k.min(1)].to_vec();
+    middle.sort();
+    middle
+}
+
+/// The centroid decomposition of a tree, as computed by
+/// `centroid_decomposition`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CentroidTree {
+    /// The first centroid, of the whole tree, or `None` if it's empty.
+    pub root: Option<usize>,
+
+    /// For every node, the centroid of the part of the tree it was chosen in,
+    /// or `None` for the root.
+    pub parent: Vec<Option<usize>>,
+
+    /// For every node, its depth in the centroid tree. It's at most log2 n.
+    pub level: Vec<usize>
+}
+
+/// Centroid decomposition: picks a centroid of the tree, a node whose removal
+/// leaves parts of at most half the nodes, and decomposes the parts the same
+/// way. The centroids make a tree of depth at most log2 n, where the centroid
+/// of every part is the parent of the centroids of its parts. A path between
+/// two nodes goes through their lowest common ancestor in the centroid tree,
+/// which splits problems about all paths into O(log n) levels.
+/// Time: O(n log n) for n nodes
+///
+/// # Panics
+///
+/// Panics if `tree` isn't a tree.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::Graph;
+/// use aisd::graph::tree::centroid_decomposition;
+///
+/// // A path 0 - 1 - 2 - 3 - 4 - 5 - 6.
+/// let mut tree = Graph::new(7);
+/// for u in 1 .. 7 {
+///     tree.add_edge(u - 1, u);
+/// }
+///
+/// let c = centroid_decomposition(&tree);
+/// assert_eq!(c.root, Some(3));
+/// assert_eq!(c.parent, vec![Some(1), Some(3), Some(1), None, Some(5), Some(3), Some(5)]);
+/// assert_eq!(c.level, vec![2, 1, 2, 0, 2, 1, 2]);
+/// ```
+pub fn centroid_decomposition(tree: &Graph) -> CentroidTree {
+    assert_tree(tree, "centroid_decomposition");
+    let n = tree.node_count();
+    let mut removed = vec![false; n];
+    let mut parent = vec![None; n];
+    let mut level = vec![0; n];
+    let mut root = None;
+
+    let mut size = vec![0; n];
+    let mut up = vec![0; n];
+    // Parts still to decompose: a node of the part and the centroid above it.
+    let mut parts = if n == 0 {vec![]} else {vec![(0, None)]};
+    while let Some((s, above)) = parts.pop() {
+        // The nodes of the part in breadth-first order, with their parents,
+        // and then the sizes of their subtrees, from the bottom.
+        let mut order = vec![s];
+        up[s] = s;
+        let mut i = 0;
+        while i < order.len() {
+            let u = order[i];
+            for &v in tree.neighbors(u) {
+                if !removed[v] && v != up[u] {
+                    up[v] = u;
+                    order.push(v);
+                }
+            }
+            i += 1;
+        }
+        for &u in order.iter().rev() {
+            size[u] = 1 + tree.neighbors(u).iter()
+                .filter(|&&v| !removed[v] && v != up[u])
+                .map(|&v| size[v])
+                .sum::<usize>();
+        }
+
+        // Go down into a subtree with more than half of the nodes while
+        // there is one.
+        let total = order.len();
+        let mut c = s;
+        while let Some(&v) = tree.neighbors(c).iter()
+            .find(|&&v| !removed[v] && v != up[c] && 2 * size[v] > total)
+        {
+            c = v;
+        }
+
+        removed[c] = true;
+        parent[c] = above;
+        level[c] = above.map_or(0, |a| level[a] + 1);
+        if above.is_none() {
+            root = Some(c);
+        }
+        for &v in tree.neighbors(c) {
+            if !removed[v] {
+                parts.push((v, Some(c)));
+            }
+        }
+    }
+
+    CentroidTree {root, parent, level}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::path_graph;
+
+    // A tree where the parent of every node but 0 is a node before it.
+    fn tree(parents: &[usize]) -> Graph {
+        let mut g = Graph::new(parents.len() + 1);
+        for (i, &p) in parents.iter().enumerate() {
+            g.add_edge(p % (i + 1), i + 1);
+        }
+
+        g
+    }
+
+    fn eccentricity(g: &Graph, u: usize) -> usize {
+        bfs(g, u).dist.iter().map(|d| d.unwrap()).max().unwrap()
+    }
+
+    // The nodes reachable from `s` within `allowed`.
+    fn reachable(g: &Graph, s: usize, allowed: &[bool]) -> Vec<usize> {
+        let mut seen = vec![false; g.node_count()];
+        let mut stack = vec![s];
+        seen[s] = true;
+        let mut found = vec![];
+        while let Some(u) = stack.pop() {
+            found.push(u);
+            for &v in g.neighbors(u) {
+                if allowed[v] && !seen[v] {
+                    seen[v] = true;
+                    stack.push(v);
+                }
+            }
+        }
+
+        found
+    }
+
+    quickcheck! {
+        fn diameter_center_naive(parents: Vec<usize>) -> bool {
+            let g = tree(&parents);
+            let n = g.node_count();
+            let ecc: Vec<usize> = (0 .. n).map(|u| eccentricity(&g, u)).collect();
+            let radius = *ecc.iter().min().unwrap();
+            let path = diameter(&g);
+
+            path.len() == ecc.iter().max().unwrap() + 1 &&
+            path.windows(2).all(|w| g.has_edge(w[0], w[1])) &&
+            center(&g) == (0 .. n).filter(|&u| ecc[u] == radius).collect::<Vec<_>>()
+        }
+
+        // Every centroid splits the part of the tree it was chosen in, which
+        // is connected, into parts of at most half its size.
+        fn centroids_split_in_half(parents: Vec<usize>) -> bool {
+            let g = tree(&parents);
+            let n = g.node_count();
+            let c = centroid_decomposition(&g);
+            let in_part = |u: usize, centroid: usize| {
+                let mut a = Some(u);
+                while let Some(x) = a {
+                    if x == centroid {
+                        return true;
+                    }
+                    a = c.parent[x];
+                }
+                false
+            };
+
+            let roots: Vec<usize> = (0 .. n).filter(|&u| c.parent[u].is_none()).collect();
+            roots == vec![c.root.unwrap()] &&
+            (0 .. n).all(|x| {
+                let part: Vec<bool> = (0 .. n).map(|u| in_part(u, x)).collect();
+                let size = part.iter().filter(|&&p| p).count();
+                let mut rest = part.clone();
+                rest[x] = false;
+
+                reachable(&g, x, &part).len() == size &&
+                g.neighbors(x).iter().all(|&v| !rest[v] || 2 * reachable(&g, v, &rest).len() <= size) &&
+                c.level[x] == c.parent[x].map_or(0, |p| c.level[p] + 1) &&
+                1 << c.level[x] <= n
+            })
+        }
+    }
+
+    // The longest tree on n nodes: its diameter is the whole path, and the
+    // centroid decomposition halves it at every level.
+    #[test]
+    fn deep_path() {
+        let n = 100_000;
+        let g = path_graph(n);
+
+        assert_eq!(diameter(&g), (0 .. n).rev().collect::<Vec<_>>());
+        assert_eq!(center(&g), vec![n / 2 - 1, n / 2]);
+        let c = centroid_decomposition(&g);
+        assert!(c.level.iter().all(|&l| 1 << l <= n));
+    }
+
+    #[test]
+    #[should_panic]
+    fn not_a_tree() {
+        let mut g = Graph::new(3);
+        g.add_edge(0, 1).add_edge(1, 2).add_edge(2, 0);
+        diameter(&g);
+    }
+}
@@ -0,0 +1,243 @@
+//! The travelling salesman problem and Hamiltonian paths, solved exactly by
+//! dynamic programming over subsets of nodes (Bellman, Held and Karp).
+//!
+//! Both go through all the subsets of nodes, as bitmasks, together with the
+//! node where a path through the subset ends, so they're only practical for
+//! about 20 nodes. That's still far more than the n! orders of the nodes.
+
+use graph::{Adjacency, Weight};
+use matrix::Matrix;
+
+/// The most nodes the subsets, as bitmasks, allow.
+const MAX: usize = 30;
+
+/// The Held-Karp algorithm: finds a shortest closed tour through all the
+/// nodes, where `dist[(u, v)]` is the length of the way from `u` to `v`,
+/// which doesn't need to be symmetric. Returns the length and the order of
+/// the nodes, starting at 0; the tour goes back from the last node to 0.
+///
+/// For every subset of nodes and every node in it, it finds the shortest path
+/// from 0 through the subset ending at the node, extending the paths through
+/// subsets with one node less.
+/// Time: O(2^n n^2)
+/// Memory: O(2^n n)
+///
+/// # Panics
+///
+/// Panics if `dist` isn't square or has more than 30 rows.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::tsp_held_karp;
+/// use aisd::matrix::Matrix;
+///
+/// let dist = Matrix::from_vec(4, 4, vec![
+///      0, 10, 15, 20,
+///     10,  0, 35, 25,
+///     15, 35,  0, 30,
+///     20, 25, 30,  0
+/// ]);
+/// assert_eq!(tsp_held_karp(&dist), (80, vec![0, 2, 3, 1]));
+/// ```
+pub fn tsp_held_karp<W: Weight>(dist: &Matrix<W>) -> (W, Vec<usize>) {
+    let n = dist.rows();
+    assert!(n == dist.cols(), "tsp_held_karp: the matrix isn't square");
+    assert!(n <= MAX, "tsp_held_karp: too many nodes ({})", n);
+    if n <= 1 {
+        return (W::ZERO, (0 .. n).collect());
+    }
+
+    // Subsets of the nodes other than 0, with node v + 1 as bit v. best[mask *
+    // m + v] is the length of a shortest path from 0 through the nodes of
+    // `mask`, ending at v + 1, and the node before it, if it's in `mask`.
+    let m = n - 1;
+    let mut best: Vec<Option<(W, usize)>> = vec![None; m << m];
+    for v in 0 .. m {
+        best[(1 << v) * m + v] = Some((dist[(0, v + 1)], 0));
+    }
+    for mask in 1usize .. 1 << m {
+        for v in 0 .. m {
+            let (d, _) = match best[mask * m + v] {
+                Some(entry) if mask >> v & 1 == 1 => entry,
+                _ => continue
+            };
+            for w in 0 .. m {
+                if mask >> w & 1 == 0 {
+                    let entry = &mut best[(mask | 1 << w) * m + w];
+                    let dw = d + dist[(v + 1, w + 1)];
+                    if entry.is_none_or(|(old, _)| dw < old) {
+                        *entry = Some((dw, v + 1));
+                    }
+                }
+            }
+        }
+    }
+
+    let full = (1 << m) - 1;
+    let (length, last) = (0 .. m)
+        .map(|v| (best[full * m + v].unwrap().0 + dist[(v + 1, 0)], v + 1))
+        .min()
+        .unwrap();
+
+    let mut tour = vec![];
+    let (mut mask, mut v) = (full, last);
+    while v != 0 {
+        tour.push(v);
+        let before = best[mask * m + v - 1].unwrap().1;
+        mask ^= 1 << (v - 1);
+        v = before;
+    }
+    tour.push(0);
+    tour.reverse();
+
+    (length, tour)
+}
+
+/// Finds a path which goes through every node exactly once, if there is one,
+/// by going through the subsets of nodes and the nodes where a path through
+/// them can end.
+/// Time: O(2^n n^2)
+/// Memory: O(2^n)
+///
+/// # Panics
+///
+/// Panics if the graph has more than 30 nodes.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{Graph, hamiltonian_path};
+///
+/// // A star has no Hamiltonian path.
+/// let mut g = Graph::new(4);
+/// g.add_edge(0, 1).add_edge(0, 2).add_edge(0, 3);
+/// assert_eq!(hamiltonian_path(&g), None);
+///
+/// g.add_edge(1, 2);
+/// assert_eq!(hamiltonian_path(&g), Some(vec![3, 0, 2, 1]));
+/// ```
+pub fn hamiltonian_path<G: Adjacency>(g: &G) -> Option<Vec<usize>> {
+    let n = g.node_count();
+    assert!(n <= MAX, "hamiltonian_path: too many nodes ({})", n);
+    if n == 0 {
+        return Some(vec![]);
+    }
+
+    // The nodes with an edge to every node, and the nodes where a path
+    // through every subset of the nodes can end, as bitmasks.
+    let mut pred = vec![0usize; n];
+    for u in 0 .. n {
+        for &v in g.successors(u) {
+            pred[v] |= 1 << u;
+        }
+    }
+    let mut ends = vec![0usize; 1 << n];
+    for v in 0 .. n {
+        ends[1 << v] = 1 << v;
+    }
+    for mask in 1usize .. 1 << n {
+        for v in 0 .. n {
+            if mask >> v & 1 == 1 && ends[mask ^ 1 << v] & pred[v] != 0 {
+                ends[mask] |= 1 << v;
+            }
+        }
+    }
+
+    let full = (1 << n) - 1;
+    if ends[full] == 0 {
+        return None;
+    }
+    let mut path = vec![];
+    let mut mask = full;
+    let mut v = ends[full].trailing_zeros() as usize;
+    loop {
+        path.push(v);
+        mask ^= 1 << v;
+        if mask == 0 {
+            break;
+        }
+        v = (ends[mask] & pred[v]).trailing_zeros() as usize;
+    }
+    path.reverse();
+
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::{DirectedGraph, Graph};
+
+    // All the orders of the nodes.
+    fn permutations(n: usize) -> Vec<Vec<usize>> {
+        if n == 0 {
+            return vec![vec![]];
+        }
+        let mut all = vec![];
+        for p in permutations(n - 1) {
+            for i in 0 ..= p.len() {
+                let mut q = p.clone();
+                q.insert(i, n - 1);
+                all.push(q);
+            }
+        }
+
+        all
+    }
+
+    // A tour of a single node goes nowhere.
+    fn tour_length(dist: &Matrix<u32>, tour: &[usize]) -> u32 {
+        let n = tour.len();
+        if n == 1 {
+            return 0;
+        }
+        (0 .. n).map(|i| dist[(tour[i], tour[(i + 1) % n])]).sum()
+    }
+
+    fn is_path<G: Adjacency>(g: &G, path: &[usize]) -> bool {
+        let mut nodes = path.to_vec();
+        nodes.sort();
+
+        nodes == (0 .. g.node_count()).collect::<Vec<_>>() &&
+        path.windows(2).all(|w| g.successors(w[0]).contains(&w[1]))
+    }
+
+    fn small(g: &Graph) -> DirectedGraph {
+        let n = g.node_count().min(8);
+        let mut d = DirectedGraph::new(n);
+        for u in 0 .. n {
+            for &v in g.neighbors(u) {
+                if v < n && (u + v) % 3 != 0 {
+                    d.add_edge(u, v);
+                }
+            }
+        }
+
+        d
+    }
+
+    quickcheck! {
+        fn held_karp_brute_force(n: usize, lengths: Vec<u8>) -> bool {
+            let n = n % 8;
+            let lengths: Vec<u32> = (0 .. n * n).map(|i| lengths.get(i).map_or(1, |&l| l as u32)).collect();
+            let dist = Matrix::from_vec(n, n, lengths);
+            let (length, tour) = tsp_held_karp(&dist);
+            let shortest = permutations(n).iter().map(|p| tour_length(&dist, p)).min().unwrap();
+
+            length == shortest && tour_length(&dist, &tour) == length && tour.first().is_none_or(|&s| s == 0)
+        }
+
+        fn hamiltonian_path_brute_force(g: Graph) -> bool {
+            let d = small(&g);
+            let exists = permutations(d.node_count()).iter().any(|p| is_path(&d, p));
+
+            match hamiltonian_path(&d) {
+                Some(path) => exists && is_path(&d, &path),
+                None => !exists
+            }
+        }
+    }
+}
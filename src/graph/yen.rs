@@ -0,0 +1,175 @@
+//! Yen's algorithm for the k shortest loopless paths.
+
+use graph::dijkstra::dijkstra_with;
+use graph::{LabeledGraph, Weight};
+use pq::{Heap, PriorityQueue};
+
+use std::collections::HashSet;
+
+/// The weight of the lightest edge from `u` to `v`.
+fn edge_weight<N, W: Weight>(g: &LabeledGraph<N, W>, u: usize, v: usize) -> W {
+    g.neighbors(u).filter(|&(x, _)| x == v).map(|(_, &w)| w).min().unwrap()
+}
+
+/// Finds up to `k` shortest loopless paths from `s` to `t` with Yen's
+/// algorithm, in the order of increasing weight, as their weights and nodes.
+/// The values of the edges are their weights, which must not be negative.
+/// Paths are sequences of nodes, so of many edges between the same nodes only
+/// the lightest counts.
+///
+/// The first path is a shortest one. Every next one leaves one of the paths
+/// found so far at some node, the spur node, and then follows a shortest path
+/// to `t` which neither goes back to the path's earlier nodes nor leaves the
+/// spur node by the next edge of any path found so far which starts the same
+/// way. These are found with Dijkstra's algorithm and kept as candidates, and
+/// the lightest candidate is the next path.
+/// Time: O(k n (m log m)) for n nodes and m edges
+///
+/// # Panics
+///
+/// Panics if `s` or `t` isn't a node, or an edge reachable from `s` has a
+/// negative weight.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::graph::{LabeledGraph, k_shortest_paths};
+///
+/// let g: LabeledGraph<(), u32> = LabeledGraph::from_edges(true, 6, vec![
+///     (0, 1, 3), (0, 2, 2), (1, 3, 4), (2, 1, 1), (2, 3, 2),
+///     (2, 4, 3), (3, 4, 2), (3, 5, 1), (4, 5, 2)
+/// ]);
+///
+/// assert_eq!(k_shortest_paths(&g, 0, 5, 3), vec![
+///     (5, vec![0, 2, 3, 5]),
+///     (7, vec![0, 2, 4, 5]),
+///     (8, vec![0, 1, 3, 5])
+/// ]);
+/// ```
+pub fn k_shortest_paths<N, W: Weight>(g: &LabeledGraph<N, W>, s: usize, t: usize, k: usize) -> Vec<(W, Vec<usize>)> {
+    let n = g.node_count();
+    assert!(s < n && t < n, "k_shortest_paths: nodes ({}, {}) out of bounds (nodes {})", s, t, n);
+
+    let mut paths: Vec<(W, Vec<usize>)> = vec![];
+    if k == 0 {
+        return paths;
+    }
+    let first = dijkstra_with(g, s, |_, _| true);
+    match (first.dist[t], first.path_to(t)) {
+        (Some(w), Some(path)) => paths.push((w, path)),
+        _ => return paths
+    }
+
+    // Candidates, ordered by weight and then by nodes, and all the paths seen,
+    // so that no path becomes a candidate twice.
+    let mut candidates = Heap::new();
+    let mut seen: HashSet<Vec<usize>> = HashSet::new();
+    seen.insert(paths[0].1.clone());
+    let mut removed = vec![false; n];
+
+    while paths.len() < k {
+        let last = paths[paths.len() - 1].1.clone();
+        let mut root_weight = W::ZERO;
+        for i in 0 .. last.len() - 1 {
+            let spur = last[i];
+            let root = &last[..= i];
+            let blocked: Vec<usize> = paths.iter()
+                .map(|p| &p.1)
+                .filter(|p| p.len() > i + 1 && p[..= i] == *root)
+                .map(|p| p[i + 1])
+                .collect();
+
+            for &u in &root[.. i] {
+                removed[u] = true;
+            }
+            let spur_paths = dijkstra_with(g, spur, |u, v| {
+                !(removed[v] || u == spur && blocked.contains(&v))
+            });
+            for &u in &root[.. i] {
+                removed[u] = false;
+            }
+
+            if let (Some(w), Some(tail)) = (spur_paths.dist[t], spur_paths.path_to(t)) {
+                let mut path = root[.. i].to_vec();
+                path.extend(tail);
+                if seen.insert(path.clone()) {
+                    candidates.insert((root_weight + w, path));
+                }
+            }
+            root_weight = root_weight + edge_weight(g, spur, last[i + 1]);
+        }
+
+        match candidates.del_min() {
+            Some(next) => paths.push(next),
+            None => break
+        }
+    }
+
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // At most 7 nodes, so that all the paths can be listed.
+    fn small(g: &LabeledGraph<(), u8>) -> LabeledGraph<(), u64> {
+        let n = g.node_count().min(7);
+        let edges = g.edges().filter(|&(u, v, _)| u < n && v < n).map(|(u, v, &w)| (u, v, w as u64));
+        LabeledGraph::from_edges(g.is_directed(), n, edges)
+    }
+
+    // The weights of all the loopless paths from `u` to `t`.
+    fn all_paths(g: &LabeledGraph<(), u64>, path: &mut Vec<usize>, t: usize, weights: &mut Vec<u64>) {
+        let u = path[path.len() - 1];
+        if u == t {
+            weights.push(path.windows(2).map(|e| edge_weight(g, e[0], e[1])).sum());
+            return;
+        }
+        let mut next: Vec<usize> = g.neighbors(u).map(|(v, _)| v).collect();
+        next.sort();
+        next.dedup();
+        for v in next {
+            if !path.contains(&v) {
+                path.push(v);
+                all_paths(g, path, t, weights);
+                path.pop();
+            }
+        }
+    }
+
+    quickcheck! {
+        fn k_shortest_paths_naive(g: LabeledGraph<(), u8>, s: usize, t: usize, k: usize) -> bool {
+            let g = small(&g);
+            let n = g.node_count();
+            if n == 0 {
+                return true;
+            }
+            let (s, t, k) = (s % n, t % n, k % 10);
+            let mut weights = vec![];
+            all_paths(&g, &mut vec![s], t, &mut weights);
+            weights.sort();
+            weights.truncate(k);
+
+            let paths = k_shortest_paths(&g, s, t, k);
+            let distinct: HashSet<&Vec<usize>> = paths.iter().map(|p| &p.1).collect();
+
+            paths.iter().map(|p| p.0).collect::<Vec<_>>() == weights &&
+            distinct.len() == paths.len() &&
+            paths.iter().all(|&(w, ref path)| {
+                let nodes: HashSet<&usize> = path.iter().collect();
+                path[0] == s && path[path.len() - 1] == t && nodes.len() == path.len() &&
+                path.windows(2).map(|e| edge_weight(&g, e[0], e[1])).sum::<u64>() == w
+            })
+        }
+    }
+
+    #[test]
+    fn same_ends_and_unreachable() {
+        let g: LabeledGraph<(), u32> = LabeledGraph::from_edges(true, 3, vec![(0, 1, 1), (1, 0, 1)]);
+        assert_eq!(k_shortest_paths(&g, 0, 0, 5), vec![(0, vec![0])]);
+        assert_eq!(k_shortest_paths(&g, 0, 2, 5), vec![]);
+        assert_eq!(k_shortest_paths(&g, 0, 1, 0), vec![]);
+    }
+}
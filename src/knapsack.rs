@@ -0,0 +1,456 @@
+//! The knapsack problem: pick items of the greatest total value whose total
+//! weight is at most the capacity of the knapsack.
+//!
+//! Items are pairs `(weight, value)`. All the variants fill a table indexed by
+//! capacities from 0 to `capacity`, so they take pseudo-polynomial time, and
+//! return the best value together with the indices of the chosen items.
+//! `subset_sum` is the special case where values are weights and the
+//! knapsack has to be filled exactly.
+
+use std::convert::TryFrom;
+
+/// A part of an item for the 0/1 algorithm: `count` copies of item `item`,
+/// which weigh `weight` and are worth `value` together.
+#[derive(Debug, Clone, Copy)]
+struct Piece {
+    item: usize,
+    count: usize,
+    weight: usize,
+    value: u64
+}
+
+/// Splits `count` into 1, 2, 4, ..., and the rest, so that every number from 0
+/// to `count` is a sum of some of the parts, and there are O(log count) parts.
+/// This turns a bounded number of copies into a 0/1 choice for every part.
+pub(crate) fn binary_split(mut count: usize) -> impl Iterator<Item = usize> {
+    let mut k = 1;
+    ::std::iter::from_fn(move || {
+        if count == 0 {
+            return None;
+        }
+        let part = k.min(count);
+        count -= part;
+        k *= 2;
+        Some(part)
+    })
+}
+
+/// The 0/1 knapsack on pieces. `best[c]` is the best value of weight at most
+/// `c` using the pieces so far, and `taken[i][c]` says that piece `i` improved
+/// it, so that walking the pieces backwards from `capacity` finds the choice.
+/// Time: O(number of pieces * capacity)
+/// Memory: the same
+fn zero_one(pieces: &[Piece], capacity: usize) -> (u64, Vec<usize>) {
+    let mut best = vec![0; capacity + 1];
+    let mut taken = Vec::with_capacity(pieces.len());
+    for p in pieces {
+        let mut row = vec![false; capacity + 1];
+        // Downwards, so that best[c - weight] doesn't use this piece yet.
+        for c in (p.weight ..= capacity).rev() {
+            let with = best[c - p.weight] + p.value;
+            if with > best[c] {
+                best[c] = with;
+                row[c] = true;
+            }
+        }
+        taken.push(row);
+    }
+
+    let mut chosen = vec![];
+    let mut c = capacity;
+    for (p, row) in pieces.iter().zip(&taken).rev() {
+        if row[c] {
+            chosen.extend(::std::iter::repeat_n(p.item, p.count));
+            c -= p.weight;
+        }
+    }
+    chosen.sort();
+
+    (best[capacity], chosen)
+}
+
+/// Solves the 0/1 knapsack problem: every item can be taken at most once.
+/// Returns the best value and the indices of the chosen items, in increasing
+/// order.
+/// Time: O(number of items * capacity)
+/// Memory: the same
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::knapsack::knapsack;
+///
+/// let items = [(1, 1), (3, 4), (4, 5), (5, 7)];
+/// assert_eq!(knapsack(&items, 7), (9, vec![1, 2]));
+/// assert_eq!(knapsack(&items, 0), (0, vec![]));
+/// ```
+pub fn knapsack(items: &[(usize, u64)], capacity: usize) -> (u64, Vec<usize>) {
+    let pieces: Vec<Piece> = items.iter().enumerate()
+        .map(|(item, &(weight, value))| Piece {item, count: 1, weight, value})
+        .collect();
+
+    zero_one(&pieces, capacity)
+}
+
+/// Like `knapsack`, but item `i` can be taken up to `count` times, where
+/// `items[i]` is `(weight, value, count)`. The counts are split with
+/// `binary_split`, so that it's a 0/1 knapsack on O(log count) pieces of every
+/// item. The indices of items taken many times are repeated.
+/// Time: O(sum of log(count + 1) * capacity)
+/// Memory: the same
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::knapsack::knapsack_bounded;
+///
+/// // Only two of the best items are there.
+/// let items = [(2, 5, 2), (3, 6, 10)];
+/// assert_eq!(knapsack_bounded(&items, 10), (22, vec![0, 0, 1, 1]));
+/// ```
+pub fn knapsack_bounded(items: &[(usize, u64, usize)], capacity: usize) -> (u64, Vec<usize>) {
+    let mut pieces = vec![];
+    for (item, &(weight, value, count)) in items.iter().enumerate() {
+        for k in binary_split(count) {
+            let weight = weight.saturating_mul(k);
+            if weight <= capacity {
+                pieces.push(Piece {item, count: k, weight, value: value * k as u64});
+            }
+        }
+    }
+
+    zero_one(&pieces, capacity)
+}
+
+/// Like `knapsack`, but every item can be taken any number of times. Here
+/// `best[c]` doesn't depend on which items were used, so one row is enough:
+/// `last[c]` is the last item of the best choice for `c`, or `None` if it's the
+/// same as for `c - 1`. The indices of items taken many times are repeated.
+/// Time: O(number of items * capacity)
+/// Memory: O(capacity)
+///
+/// # Panics
+///
+/// Panics if an item of weight 0 has a positive value, as then there's no
+/// best choice.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::knapsack::knapsack_unbounded;
+///
+/// let items = [(5, 10), (4, 7), (2, 3)];
+/// assert_eq!(knapsack_unbounded(&items, 8), (14, vec![1, 1]));
+/// assert_eq!(knapsack_unbounded(&items, 10), (20, vec![0, 0]));
+/// ```
+pub fn knapsack_unbounded(items: &[(usize, u64)], capacity: usize) -> (u64, Vec<usize>) {
+    assert!(items.iter().all(|&(w, v)| w > 0 || v == 0),
+            "knapsack_unbounded: an item of weight 0 has a positive value");
+
+    let mut best = vec![0; capacity + 1];
+    let mut last = vec![None; capacity + 1];
+    for c in 1 ..= capacity {
+        best[c] = best[c - 1];
+        for (i, &(w, v)) in items.iter().enumerate() {
+            if w > 0 && w <= c && best[c - w] + v > best[c] {
+                best[c] = best[c - w] + v;
+                last[c] = Some(i);
+            }
+        }
+    }
+
+    let mut chosen = vec![];
+    let mut c = capacity;
+    while c > 0 {
+        match last[c] {
+            Some(i) => {
+                chosen.push(i);
+                c -= items[i].0;
+            },
+            None => c -= 1
+        }
+    }
+    chosen.sort();
+
+    (best[capacity], chosen)
+}
+
+/// Cuts a rod of the given length into pieces of the greatest total price,
+/// where a piece of length `l` sells for `prices[l - 1]`. Returns the price and
+/// the lengths of the pieces in increasing order. Pieces longer than
+/// `prices.len()` don't sell; if the best cut leaves such a piece (or one whose
+/// price is 0), it's listed too, so that the lengths sum to `length`.
+/// Time: O(length * prices.len())
+/// Memory: O(length)
+///
+/// It's the unbounded knapsack with an item of weight `l` and value
+/// `prices[l - 1]` for every length `l`.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::knapsack::rod_cutting;
+///
+/// let prices = [1, 5, 8, 9, 10, 17, 17, 20];
+/// assert_eq!(rod_cutting(&prices, 4), (10, vec![2, 2]));
+/// assert_eq!(rod_cutting(&prices, 8), (22, vec![2, 6]));
+///
+/// // Only pieces of length 3 sell.
+/// assert_eq!(rod_cutting(&[0, 0, 7], 7), (14, vec![1, 3, 3]));
+/// ```
+pub fn rod_cutting(prices: &[u64], length: usize) -> (u64, Vec<usize>) {
+    let items: Vec<(usize, u64)> = prices.iter().enumerate().map(|(i, &p)| (i + 1, p)).collect();
+    let (price, chosen) = knapsack_unbounded(&items, length);
+
+    let mut lengths: Vec<usize> = chosen.into_iter().map(|i| i + 1).collect();
+    let rest = length - lengths.iter().sum::<usize>();
+    if rest > 0 {
+        lengths.push(rest);
+        lengths.sort();
+    }
+
+    (price, lengths)
+}
+
+/// Adds `v` to every sum in the bitset `reachable`, keeping the old sums and
+/// dropping those over `target`.
+fn add_to_sums(reachable: &mut [u64], v: u64, target: usize) {
+    if v == 0 || v > target as u64 {
+        return;
+    }
+    let len = reachable.len();
+    let (q, r) = (v as usize / 64, v as usize % 64);
+    // The bits of the last word past `target`.
+    let last_mask = if (target + 1).is_multiple_of(64) {!0} else {(1 << ((target + 1) % 64)) - 1};
+
+    // From the top, so that the words read are still the old ones.
+    for w in (q .. len).rev() {
+        let mut shifted = reachable[w - q] << r;
+        if r > 0 && w > q {
+            shifted |= reachable[w - q - 1] >> (64 - r);
+        }
+        if w == len - 1 {
+            shifted &= last_mask;
+        }
+        reachable[w] |= shifted;
+    }
+}
+
+/// Checks whether the sum `s` is in the bitset `reachable`.
+fn has_sum(reachable: &[u64], s: usize) -> bool {
+    reachable[s / 64] >> (s % 64) & 1 == 1
+}
+
+/// Finds a subset of `values` summing exactly to `target`, as indices in
+/// increasing order, or `None` if there's none.
+/// Time: O(n * target / 64 + n)
+/// Memory: O(sqrt(n) * target / 64)
+///
+/// The sums reachable with the values so far are kept as bits of `u64` words,
+/// so adding a value is a shift and an or on whole words, 64 sums at a time.
+/// The first pass stops once `target` is reachable and saves the bitset before
+/// every block of sqrt(n) values. The second pass walks the blocks backwards:
+/// it replays a block from its saved bitset, and goes down from `target`
+/// taking every value without which the current sum isn't reachable.
+///
+/// # Panics
+///
+/// Panics if `target` doesn't fit in `usize`.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::knapsack::subset_sum;
+///
+/// let values = [8, 6, 7, 5, 3, 10, 9];
+/// let s = subset_sum(&values, 15).unwrap();
+/// assert_eq!(s.iter().map(|&i| values[i]).sum::<u64>(), 15);
+/// assert_eq!(subset_sum(&[2, 4, 6], 5), None);
+/// assert_eq!(subset_sum(&[2, 4, 6], 0), Some(vec![]));
+/// ```
+pub fn subset_sum(values: &[u64], target: u64) -> Option<Vec<usize>> {
+    let target = usize::try_from(target).expect("subset_sum: the target is too big");
+    let block = values.len().isqrt() + 1;
+
+    let mut reachable = vec![0u64; target / 64 + 1];
+    reachable[0] = 1;
+    // The bitset before values[b * block], for every block b used.
+    let mut saved = vec![];
+    // The number of values needed to reach `target`.
+    let mut used = 0;
+    while !has_sum(&reachable, target) {
+        if used == values.len() {
+            return None;
+        }
+        if used.is_multiple_of(block) {
+            saved.push(reachable.clone());
+        }
+        add_to_sums(&mut reachable, values[used], target);
+        used += 1;
+    }
+
+    // The current sum is reachable with the values before `i + 1`. If it isn't
+    // with those before `i`, values[i] is in.
+    let mut chosen = vec![];
+    let mut s = target;
+    for (b, start) in saved.into_iter().enumerate().rev() {
+        let first = b * block;
+        let last = (first + block).min(used);
+        let mut before = vec![start];
+        for i in first .. last - 1 {
+            let mut next = before[i - first].clone();
+            add_to_sums(&mut next, values[i], target);
+            before.push(next);
+        }
+
+        for i in (first .. last).rev() {
+            if !has_sum(&before[i - first], s) {
+                chosen.push(i);
+                s -= values[i] as usize;
+            }
+        }
+    }
+    chosen.reverse();
+
+    Some(chosen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{binary_split, knapsack, knapsack_bounded, knapsack_unbounded, rod_cutting,
+                subset_sum};
+
+    // The weight and value of a choice, if it's within the capacity.
+    fn check(items: &[(usize, u64)], capacity: usize, chosen: &[usize]) -> Option<u64> {
+        let weight: usize = chosen.iter().map(|&i| items[i].0).sum();
+        let value = chosen.iter().map(|&i| items[i].1).sum();
+        if weight <= capacity {Some(value)} else {None}
+    }
+
+    fn small(items: Vec<(u8, u8)>) -> Vec<(usize, u64)> {
+        items.into_iter().take(10).map(|(w, v)| (w as usize % 16, v as u64)).collect()
+    }
+
+    quickcheck! {
+        // The parts are powers of two and the rest, which is at most the next
+        // power, so every number up to `count` is a sum of some of them.
+        fn binary_split_parts(count: u16) -> bool {
+            let parts: Vec<usize> = binary_split(count as usize).collect();
+            let n = parts.len();
+
+            parts.iter().sum::<usize>() == count as usize &&
+            parts.iter().enumerate().all(|(i, &p)| if i + 1 < n {p == 1 << i} else {p <= 1 << i})
+        }
+
+        fn knapsack_brute_force(items: Vec<(u8, u8)>, capacity: u8) -> bool {
+            let items = small(items);
+            let capacity = capacity as usize % 40;
+
+            let brute = (0 .. 1usize << items.len()).filter_map(|mask| {
+                let chosen: Vec<usize> = (0 .. items.len()).filter(|i| mask >> i & 1 == 1).collect();
+                check(&items, capacity, &chosen)
+            }).max();
+
+            let (value, chosen) = knapsack(&items, capacity);
+            Some(value) == brute && check(&items, capacity, &chosen) == Some(value) &&
+            chosen.windows(2).all(|w| w[0] < w[1])
+        }
+
+        // With a copy of every item for every unit of capacity, the bound
+        // doesn't matter.
+        fn bounded_unlimited(items: Vec<(u8, u8)>, capacity: u8) -> bool {
+            let items: Vec<(usize, u64)> = small(items).into_iter().filter(|x| x.0 > 0).collect();
+            let capacity = capacity as usize % 40;
+            let bounded: Vec<_> = items.iter().map(|&(w, v)| (w, v, capacity)).collect();
+
+            let (value, chosen) = knapsack_bounded(&bounded, capacity);
+            value == knapsack_unbounded(&items, capacity).0 &&
+            check(&items, capacity, &chosen) == Some(value)
+        }
+
+        // The same as the 0/1 knapsack on the items repeated.
+        fn bounded_repeated(items: Vec<(u8, u8, u8)>, capacity: u8) -> bool {
+            let items: Vec<(usize, u64, usize)> = items.into_iter().take(4)
+                .map(|(w, v, c)| (w as usize % 16, v as u64, c as usize % 4)).collect();
+            let capacity = capacity as usize % 40;
+
+            let mut repeated = vec![];
+            for &(w, v, c) in &items {
+                repeated.extend(::std::iter::repeat_n((w, v), c));
+            }
+
+            let (value, chosen) = knapsack_bounded(&items, capacity);
+            value == knapsack(&repeated, capacity).0 &&
+            items.iter().enumerate().all(|(i, x)| chosen.iter().filter(|&&j| j == i).count() <= x.2) &&
+            check(&items.iter().map(|x| (x.0, x.1)).collect::<Vec<_>>(), capacity, &chosen) == Some(value)
+        }
+
+        fn unbounded_dp(items: Vec<(u8, u8)>, capacity: u8) -> bool {
+            let items: Vec<(usize, u64)> = small(items).into_iter().filter(|x| x.0 > 0).collect();
+            let capacity = capacity as usize % 60;
+
+            // The best value for every capacity, by the recurrence on the first
+            // item taken.
+            let mut naive = vec![0; capacity + 1];
+            for c in 0 ..= capacity {
+                for &(w, v) in &items {
+                    if w <= c {
+                        naive[c] = naive[c].max(naive[c - w] + v);
+                    }
+                }
+            }
+
+            let (value, chosen) = knapsack_unbounded(&items, capacity);
+            value == naive[capacity] && check(&items, capacity, &chosen) == Some(value)
+        }
+
+        fn rod_cutting_naive(prices: Vec<u8>, length: u8) -> bool {
+            let prices: Vec<u64> = prices.into_iter().take(10).map(|p| p as u64).collect();
+            let length = length as usize % 30;
+
+            // best[n] is the best price of a rod of length n, by the first cut.
+            let mut best = vec![0; length + 1];
+            for n in 1 ..= length {
+                for l in 1 ..= n.min(prices.len()) {
+                    best[n] = best[n].max(prices[l - 1] + best[n - l]);
+                }
+            }
+
+            let (price, lengths) = rod_cutting(&prices, length);
+            let sold: u64 = lengths.iter().filter(|&&l| l <= prices.len()).map(|&l| prices[l - 1]).sum();
+            price == best[length] && sold == price && lengths.iter().sum::<usize>() == length
+        }
+
+        fn subset_sum_brute_force(values: Vec<u8>, target: u8) -> bool {
+            let values: Vec<u64> = values.into_iter().take(12).map(|v| v as u64 % 100).collect();
+            let target = target as u64 * 2;
+
+            let exists = (0 .. 1usize << values.len()).any(|mask| {
+                (0 .. values.len()).filter(|i| mask >> i & 1 == 1).map(|i| values[i]).sum::<u64>() == target
+            });
+
+            match subset_sum(&values, target) {
+                None => !exists,
+                Some(s) => exists && s.windows(2).all(|w| w[0] < w[1]) &&
+                           s.iter().map(|&i| values[i]).sum::<u64>() == target
+            }
+        }
+    }
+
+    // Sums spread over many words, with values not multiples of 64.
+    #[test]
+    fn subset_sum_big() {
+        let values: Vec<u64> = (0 .. 300).map(|i| 1000 + 37 * i).collect();
+        let target = 1_000_003;
+
+        let s = subset_sum(&values, target).unwrap();
+        assert_eq!(s.iter().map(|&i| values[i]).sum::<u64>(), target);
+        assert_eq!(subset_sum(&values, values.iter().sum::<u64>() + 1), None);
+        assert_eq!(subset_sum(&[64, 128, 1], 193), Some(vec![0, 1, 2]));
+    }
+}
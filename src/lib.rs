@@ -1,16 +1,56 @@
 //! A crate with various algorithms (don't expect much).
 
+// Tests state properties like `h.is_empty() == false` on purpose.
+#![cfg_attr(test, allow(clippy::bool_comparison, clippy::partialeq_to_none))]
+// Deprecated modules keep their tests.
+#![cfg_attr(test, allow(deprecated))]
+
 pub mod pq;
 pub mod depq;
 
+pub mod sort;
+// Tests count comparisons too.
+#[cfg(any(test, feature = "stats"))]
+pub mod stats;
+
 pub mod coin_change;
+pub mod knapsack;
+pub mod subsequence;
+pub mod scheduling;
 
+pub mod disjoint_set;
+#[deprecated(note = "use `disjoint_set::Auto` instead")]
 pub mod union_by_size;
+#[deprecated(note = "use `disjoint_set::Auto` instead")]
 pub mod union_by_rank;
 
 pub mod map;
 
-#[macro_use]
+pub mod graph;
+
+pub mod automata;
+pub mod strings;
+
+pub mod bitvec;
+pub mod matrix;
+pub mod range_query;
+pub mod trees;
+
+pub mod seq;
+
+pub mod ratelimit;
+
+pub mod rng;
+pub mod probabilistic;
+pub mod sampling;
+pub mod two_pointers;
+
+#[cfg(feature = "report")]
+pub mod report;
+
+#[cfg_attr(test, macro_use)]
 extern crate quickcheck;
 
-extern crate rand;
\ No newline at end of file
+extern crate rand;
+#[cfg(feature = "parallel")]
+extern crate rayon;
\ No newline at end of file
@@ -1,24 +1,602 @@
-use map::Map;
+use map::{Entry, Map};
+use map::ordered::BstNode;
+use map::render;
 
 use quickcheck::Arbitrary;
 use quickcheck::Gen;
 
+/// A node of a binary search tree, or the empty tree. Nodes can be put together
+/// by hand with `N` and `E`, and made into a `Tree` with `Tree::from`.
 #[derive(Clone, Debug)]
-pub enum Tree<K, V> {
+pub enum Node<K, V> {
     E,
-    N(K, V, Box<Tree<K, V>>, Box<Tree<K, V>>)
+    N(K, V, Box<Node<K, V>>, Box<Node<K, V>>)
 }
 
-use self::Tree::{E, N};
+use self::Node::{E, N};
 
 use std::cmp::Ord;
 use std::cmp::Ordering::*;
+use std::fmt;
+use std::fmt::Display;
+use std::iter::FromIterator;
+use std::mem;
+use std::mem::ManuallyDrop;
+use std::ptr;
 
-impl<K, V> Tree<K, V> {
-    fn size(&self) -> usize {
+/// The key, value and subtrees of a node.
+pub(crate) type Parts<K, V> = (K, V, Node<K, V>, Node<K, V>);
+
+/// An unbalanced binary search tree: its root node together with the number of
+/// entries, so that `len` doesn't have to count them.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::Map;
+/// use aisd::map::bst::Tree;
+///
+/// let mut t = Tree::new();
+/// t.ins(2, "b").ins(1, "a").ins(3, "c");
+/// assert_eq!(t.len(), 3);
+/// assert_eq!(t.find(&1), Some(&"a"));
+///
+/// t.del(&2);
+/// assert_eq!(t.keys().collect::<Vec<_>>(), vec![&1, &3]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Tree<K, V> {
+    root: Node<K, V>,
+    len: usize
+}
+
+/// Numbers describing the shape of a tree, computed by `Tree::shape_report`.
+/// They show how far an unbalanced tree has degenerated: random insertions give
+/// an average depth of about 1.39 log_2 n, while sorted ones give a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShapeReport {
+    /// The number of entries.
+    pub size: usize,
+
+    /// The number of nodes on the longest path from the root. The empty tree has
+    /// height 0.
+    pub height: usize,
+
+    /// The sum of the depths of all the nodes, where the root has depth 0.
+    pub internal_path_length: usize,
+
+    /// The least height of a tree of the same size, that of a perfectly balanced
+    /// one.
+    pub optimal_height: usize
+}
+
+impl ShapeReport {
+    /// The average depth of a node, that is the average number of comparisons
+    /// of a successful `find` minus one. 0 for the empty tree.
+    pub fn average_depth(&self) -> f64 {
+        if self.size == 0 {
+            0.0
+        } else {
+            self.internal_path_length as f64 / self.size as f64
+        }
+    }
+}
+
+impl<K, V> Node<K, V> {
+    /// Compute the number of entries in the tree.
+    /// Time: O(size of the tree)
+    pub fn size(&self) -> usize {
+        let mut size = 0;
+        let mut stack = vec![self];
+        while let Some(t) = stack.pop() {
+            if let N(_, _, l, r) = t {
+                size += 1;
+                stack.push(l);
+                stack.push(r);
+            }
+        }
+
+        size
+    }
+
+    /// Calls `f` with the depth of every node, with an explicit stack, so that
+    /// degenerate trees don't overflow the call stack.
+    fn visit_depths<F: FnMut(usize)>(&self, mut f: F) {
+        let mut stack = vec![(self, 0)];
+        while let Some((t, depth)) = stack.pop() {
+            if let N(_, _, l, r) = t {
+                f(depth);
+                stack.push((l, depth + 1));
+                stack.push((r, depth + 1));
+            }
+        }
+    }
+
+    /// Compute the height of the tree. The empty tree has height 0.
+    /// Time: O(size of the tree)
+    pub fn height(&self) -> usize {
+        self.shape_report().height
+    }
+
+    /// Compute the sum of the depths of all the nodes, where the root has
+    /// depth 0.
+    /// Time: O(size of the tree)
+    pub fn internal_path_length(&self) -> usize {
+        self.shape_report().internal_path_length
+    }
+
+    /// Compute the size, height and internal path length of the tree in one go.
+    /// Time: O(size of the tree)
+    pub fn shape_report(&self) -> ShapeReport {
+        let (mut size, mut height, mut internal_path_length) = (0usize, 0, 0);
+        self.visit_depths(|depth| {
+            size += 1;
+            height = height.max(depth + 1);
+            internal_path_length += depth;
+        });
+
+        let optimal_height = (usize::BITS - size.leading_zeros()) as usize;
+        ShapeReport {size, height, internal_path_length, optimal_height}
+    }
+
+    /// Iterates over the entries in order of their keys.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut it = Iter {stack: vec![]};
+        it.descend(self);
+        it
+    }
+
+    /// Iterates over the keys in increasing order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Iterates over the values in order of their keys.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// The root node, unless the tree is empty.
+    pub(crate) fn as_node(&self) -> Option<&Node<K, V>> {
         match self {
-            E => 0,
-            N(_, _, l, r) => 1 + l.size() + r.size()
+            E => None,
+            n => Some(n)
+        }
+    }
+
+    /// Takes a nonempty tree apart into its key, value and subtrees.
+    pub(crate) fn into_parts(self) -> Option<Parts<K, V>> {
+        // Moving fields out of a type that implements `Drop` isn't allowed, so
+        // they are copied out and the original is forgotten instead.
+        let this = ManuallyDrop::new(self);
+        match &*this {
+            E => None,
+            // Safe, because `this` is never used or dropped again, so every field
+            // ends up with exactly one owner.
+            N(k, v, l, r) => unsafe {
+                Some((ptr::read(k), ptr::read(v), *ptr::read(l), *ptr::read(r)))
+            }
+        }
+    }
+
+    /// Replace the tree with its root's entry removed, returning the entry.
+    /// `replacement` computes the new tree from the root's subtrees.
+    fn pop_root<F>(&mut self, replacement: F) -> Option<(K, V)>
+        where F: FnOnce(Node<K, V>, Node<K, V>) -> Node<K, V>
+    {
+        let (k, v, l, r) = mem::replace(self, E).into_parts()?;
+        *self = replacement(l, r);
+
+        Some((k, v))
+    }
+
+    /// Remove the entry with the least key from the tree and return it.
+    /// Time: O(height of the tree)
+    fn del_min(&mut self) -> Option<(K, V)> {
+        let mut current = self;
+        while let N(_, _, l, _) = current {
+            if let E = **l {
+                break;
+            }
+            current = match current {
+                N(_, _, l, _) => l,
+                E => unreachable!()
+            };
+        }
+
+        current.pop_root(|_, r| r)
+    }
+
+    /// Build a perfectly balanced tree from the first `n` entries of a sorted iterator.
+    /// Time: O(n)
+    pub(crate) fn build<I: Iterator<Item = (K, V)>>(it: &mut I, n: usize) -> Node<K, V> {
+        if n == 0 {
+            return E;
+        }
+
+        let l = Node::build(it, n / 2);
+        match it.next() {
+            Some((k, v)) => {
+                let r = Node::build(it, n - n / 2 - 1);
+                N(k, v, Box::new(l), Box::new(r))
+            },
+            None => l
+        }
+    }
+
+    /// Join two trees such that all keys in `l` are less than all keys in `r`.
+    /// Time: O(height of `r`)
+    fn join(l: Node<K, V>, mut r: Node<K, V>) -> Node<K, V> {
+        match r.del_min() {
+            None => l,
+            Some((k, v)) => N(k, v, Box::new(l), Box::new(r))
+        }
+    }
+}
+
+impl<K: Ord, V> Node<K, V> {
+    /// Check whether the keys increase from left to right, as they should in
+    /// a binary search tree. Trees built with `ins` always pass; trees built
+    /// directly from `N` and `E` (or with `from_sorted` from unsorted data)
+    /// may not.
+    /// Time: O(size of the tree)
+    pub fn is_bst(&self) -> bool {
+        let mut keys = self.keys().peekable();
+        while let Some(k) = keys.next() {
+            if keys.peek().is_some_and(|next| k >= *next) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Renders the tree in the DOT language of Graphviz, to be drawn with
+    /// `dot -Tsvg`. Missing children of nodes with one child are drawn as
+    /// points, so the picture shows which side a child is on.
+    /// Time: O(size of the tree)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate aisd;
+    /// use aisd::map::bst::Tree;
+    ///
+    /// let t = Tree::from_sorted(vec![(1, "a"), (2, "b")]);
+    /// let dot = t.to_dot();
+    /// assert!(dot.starts_with("digraph {"));
+    /// assert!(dot.contains("[label=\"2: b\"]"));
+    /// ```
+    pub fn to_dot(&self) -> String where K: Display, V: Display {
+        render::to_dot(self.as_node())
+    }
+
+    /// Find the subtree whose root has the given key, or the empty subtree where
+    /// it should be inserted.
+    /// Time: O(height of the tree)
+    pub(crate) fn slot(&mut self, key: &K) -> &mut Node<K, V> {
+        let mut current = self;
+        loop {
+            let ord = match current {
+                N(k, _, _, _) => key.cmp(k),
+                E => return current
+            };
+
+            current = match (ord, current) {
+                (Less, N(_, _, l, _)) => l,
+                (Greater, N(_, _, _, r)) => r,
+                (_, t) => return t
+            };
+        }
+    }
+
+    pub(crate) fn find(&self, key: &K) -> Option<&V> {
+        let mut current = self;
+        while let N(k, v, l, r) = current {
+            match key.cmp(k) {
+                Less => current = l,
+                Equal => return Some(v),
+                Greater => current = r
+            }
+        }
+
+        None
+    }
+
+    pub(crate) fn find_mut(&mut self, key: &K) -> Option<&mut V> {
+        match self.slot(key) {
+            N(_, v, _, _) => Some(v),
+            E => None
+        }
+    }
+
+    pub(crate) fn del(&mut self, key: &K) -> Option<V> {
+        self.slot(key).pop_root(Node::join).map(|(_, v)| v)
+    }
+}
+
+impl<K, V> Tree<K, V> {
+    /// A tree of the nodes of `root`, of which there are `len`.
+    pub(crate) fn from_parts(root: Node<K, V>, len: usize) -> Tree<K, V> {
+        Tree {root, len}
+    }
+
+    /// Returns the root node, to look at the shape of the tree.
+    pub fn root(&self) -> &Node<K, V> {
+        &self.root
+    }
+
+    /// Returns the number of entries in the tree, like `len`.
+    /// Time: O(1)
+    pub fn size(&self) -> usize {
+        self.len
+    }
+
+    /// Compute the height of the tree. The empty tree has height 0.
+    /// Time: O(size of the tree)
+    pub fn height(&self) -> usize {
+        self.root.height()
+    }
+
+    /// Compute the sum of the depths of all the nodes, where the root has
+    /// depth 0.
+    /// Time: O(size of the tree)
+    pub fn internal_path_length(&self) -> usize {
+        self.root.internal_path_length()
+    }
+
+    /// Compute the size, height and internal path length of the tree in one go.
+    /// Time: O(size of the tree)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate aisd;
+    /// use aisd::map::bst::Tree;
+    ///
+    /// let t: Tree<u32, ()> = (0 .. 100).map(|i| (i, ())).collect();
+    /// let shape = t.shape_report();
+    /// assert_eq!(shape.height, 100);
+    /// assert_eq!(shape.optimal_height, 7);
+    /// assert_eq!(shape.average_depth(), 49.5);
+    /// ```
+    pub fn shape_report(&self) -> ShapeReport {
+        self.root.shape_report()
+    }
+
+    /// Iterates over the entries in order of their keys.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.root.iter()
+    }
+
+    /// Iterates over the keys in increasing order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.root.keys()
+    }
+
+    /// Iterates over the values in order of their keys.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.root.values()
+    }
+}
+
+impl<K: Ord, V> Tree<K, V> {
+    /// Check whether the keys increase from left to right, as they should in
+    /// a binary search tree. Trees built with `ins` always pass; trees made
+    /// from nodes put together by hand (or with `from_sorted` from unsorted
+    /// data) may not.
+    /// Time: O(size of the tree)
+    pub fn is_bst(&self) -> bool {
+        self.root.is_bst()
+    }
+
+    /// Builds a perfectly balanced tree from entries sorted by strictly
+    /// increasing keys, without comparing them.
+    /// Time: O(n)
+    ///
+    /// The order is checked only in debug builds; in release builds unsorted
+    /// input gives a tree which isn't a search tree.
+    pub fn from_sorted(v: Vec<(K, V)>) -> Tree<K, V> {
+        debug_assert!(v.windows(2).all(|w| w[0].0 < w[1].0),
+                      "Tree::from_sorted: the keys aren't strictly increasing");
+
+        let n = v.len();
+        Tree::from_parts(Node::build(&mut v.into_iter(), n), n)
+    }
+
+    /// Combines the entries of two trees into a perfectly balanced tree. When
+    /// both have the same key, `f` gets the key and the values from `self` and
+    /// `other`, and computes the value of the result.
+    /// Time: O(n + m), where n and m are the sizes of the trees
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate aisd;
+    /// use aisd::map::Map;
+    /// use aisd::map::bst::Tree;
+    ///
+    /// let a = Tree::from_sorted(vec![(1, 10), (2, 20)]);
+    /// let b = Tree::from_sorted(vec![(2, 2), (3, 3)]);
+    /// let m = a.merge(b, |_, x, y| x + y);
+    /// assert_eq!(m.into_iter().collect::<Vec<_>>(), vec![(1, 10), (2, 22), (3, 3)]);
+    /// ```
+    pub fn merge<F: FnMut(&K, V, V) -> V>(self, other: Tree<K, V>, mut f: F) -> Tree<K, V> {
+        let mut v = vec![];
+        let mut a = self.into_iter().peekable();
+        let mut b = other.into_iter().peekable();
+        loop {
+            let ord = match (a.peek(), b.peek()) {
+                (Some((k1, _)), Some((k2, _))) => k1.cmp(k2),
+                (Some(_), None) => Less,
+                (None, Some(_)) => Greater,
+                (None, None) => break
+            };
+
+            match (ord, a.next_if(|_| ord != Greater), b.next_if(|_| ord != Less)) {
+                (Equal, Some((k, x)), Some((_, y))) => {
+                    let z = f(&k, x, y);
+                    v.push((k, z));
+                },
+                (_, entry, None) | (_, None, entry) => v.extend(entry),
+                _ => unreachable!()
+            }
+        }
+
+        let n = v.len();
+        Tree::from_parts(Node::build(&mut v.into_iter(), n), n)
+    }
+
+    /// Renders the tree in the DOT language of Graphviz, like `Node::to_dot`.
+    /// Time: O(size of the tree)
+    pub fn to_dot(&self) -> String where K: Display, V: Display {
+        self.root.to_dot()
+    }
+}
+
+/// Counts the entries of the nodes.
+/// Time: O(size of the tree)
+impl<K, V> From<Node<K, V>> for Tree<K, V> {
+    fn from(root: Node<K, V>) -> Tree<K, V> {
+        let len = root.size();
+        Tree {root, len}
+    }
+}
+
+/// Draws the tree as indented lines, one per node, the left child above the
+/// right one.
+///
+/// ```text
+/// 2: b
+/// ├── 1: a
+/// └── 3: c
+/// ```
+impl<K: Ord + Display, V: Display> fmt::Display for Node<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        render::fmt_tree(self.as_node(), f)
+    }
+}
+
+impl<K: Ord + Display, V: Display> fmt::Display for Tree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.root.fmt(f)
+    }
+}
+
+/// The tree is dropped with an explicit stack, because the recursion of the
+/// automatic `Drop` would overflow the call stack on degenerate trees.
+impl<K, V> Drop for Node<K, V> {
+    fn drop(&mut self) {
+        let mut stack = vec![];
+        if let N(_, _, l, r) = self {
+            stack.push(mem::replace(&mut **l, E));
+            stack.push(mem::replace(&mut **r, E));
+        }
+
+        while let Some(mut t) = stack.pop() {
+            if let N(_, _, l, r) = &mut t {
+                stack.push(mem::replace(&mut **l, E));
+                stack.push(mem::replace(&mut **r, E));
+            }
+            // Now `t` has no children, so dropping it doesn't recurse.
+        }
+    }
+}
+
+/// An in-order iterator over the entries of a `Tree`. Instead of recursing, it
+/// keeps a stack of the nodes whose entries and right subtrees are still to be
+/// visited, so it needs O(height of the tree) memory.
+#[derive(Clone, Debug)]
+pub struct Iter<'a, K: 'a, V: 'a> {
+    stack: Vec<&'a Node<K, V>>
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    /// Push the path to the least key of `t`.
+    fn descend(&mut self, mut t: &'a Node<K, V>) {
+        while let N(_, _, l, _) = t {
+            self.stack.push(t);
+            t = l;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        match self.stack.pop()? {
+            N(k, v, _, r) => {
+                self.descend(r);
+                Some((k, v))
+            },
+            E => None
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a Tree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+/// A consuming in-order iterator over the entries of a `Tree`. The stack holds
+/// the entries still to be returned, each with its right subtree.
+#[derive(Debug)]
+pub struct IntoIter<K, V> {
+    stack: Vec<(K, V, Node<K, V>)>
+}
+
+impl<K, V> IntoIter<K, V> {
+    /// Push the path to the least key of `t`.
+    fn descend(&mut self, mut t: Node<K, V>) {
+        while let Some((k, v, l, r)) = t.into_parts() {
+            self.stack.push((k, v, r));
+            t = l;
+        }
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        let (k, v, r) = self.stack.pop()?;
+        self.descend(r);
+
+        Some((k, v))
+    }
+}
+
+impl<K, V> IntoIterator for Tree<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        let mut it = IntoIter {stack: vec![]};
+        it.descend(self.root);
+        it
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for Tree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Tree<K, V> {
+        let mut t = Tree::new();
+        t.extend(iter);
+        t
+    }
+}
+
+/// Later entries overwrite earlier ones with the same key, like `ins` does.
+impl<K: Ord, V> Extend<(K, V)> for Tree<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.ins(k, v);
         }
     }
 }
@@ -28,27 +606,23 @@ impl<K: Ord, V> Map for Tree<K, V> {
     type Value = V;
 
     fn new() -> Tree<K, V> {
-        Tree::E
+        Tree {root: E, len: 0}
     }
 
     fn find(&self, key: &K) -> Option<&V> {
-        match self {
-            E => None,
-            N(k, v, l, r) => match Ord::cmp(key, &k) {
-                Less => l.find(key),
-                Equal => Some(&v),
-                Greater => r.find(key)
-            }
-        }
+        self.root.find(key)
+    }
+
+    fn find_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.root.find_mut(key)
     }
 
     fn ins(&mut self, key: K, value: V) -> &mut Self {
-        match self {
-            E => {*self = N(key, value, Box::new(E), Box::new(E));},
-            N(k, v, l, r) => match key.cmp(k) {
-                Less => {l.ins(key, value);}
-                Equal => {*v = value;}
-                Greater => {r.ins(key, value);}
+        match self.root.slot(&key) {
+            N(_, v, _, _) => *v = value,
+            slot => {
+                *slot = N(key, value, Box::new(E), Box::new(E));
+                self.len += 1;
             }
         }
 
@@ -56,77 +630,279 @@ impl<K: Ord, V> Map for Tree<K, V> {
     }
 
     fn del(&mut self, key: &K) -> Option<V> {
-        None
+        let v = self.root.del(key)?;
+        self.len -= 1;
+
+        Some(v)
+    }
+
+    /// Time: O(1)
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        *self = Tree::new();
+    }
+
+    /// The vacant entry holds the empty subtree where the key goes.
+    fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let len = &mut self.len;
+        match self.root.slot(&key) {
+            N(_, v, _, _) => Entry::occupied(key, v),
+            slot => Entry::vacant(key, move |k, v| {
+                *len += 1;
+                *slot = N(k, v, Box::new(E), Box::new(E));
+                match slot {
+                    N(_, v, _, _) => v,
+                    E => unreachable!()
+                }
+            })
+        }
     }
 }
 
-/*impl<T: Clone> Clone for Tree<T> {
-    fn clone(&self) -> Self {
+/// Only nonempty trees are viewed as nodes.
+impl<K: Ord, V> BstNode for Node<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn key(&self) -> &K {
         match self {
-            E => E,
-            N(v, l, r) => N(v.clone(), l.clone(), r.clone())
+            N(k, _, _, _) => k,
+            E => unreachable!()
         }
     }
-}*/
+
+    fn value(&self) -> &V {
+        match self {
+            N(_, v, _, _) => v,
+            E => unreachable!()
+        }
+    }
+
+    fn left(&self) -> Option<&Node<K, V>> {
+        match self {
+            N(_, _, l, _) => l.as_node(),
+            E => None
+        }
+    }
+
+    fn right(&self) -> Option<&Node<K, V>> {
+        match self {
+            N(_, _, _, r) => r.as_node(),
+            E => None
+        }
+    }
+}
+
+impl_ordered_map!(Tree, |t| t.root.as_node());
 
 impl<K: Ord + Arbitrary, V: Arbitrary> Arbitrary for Tree<K, V> {
     fn arbitrary<G: Gen>(g: &mut G) -> Tree<K, V> {
         let data: Vec<(K, V)> = Arbitrary::arbitrary(g);
-        
-        let mut t = E;
-        for (k, v) in data {
-            t.ins(k, v);
-        }
 
-        t
+        data.into_iter().collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use map::Map;
-    use super::Tree;
-    use super::Tree::*;
+    use map::{Map, OrderedMap};
+    use super::{Node, Tree};
+    use super::Node::{E, N};
+
+    use std::collections::BTreeMap;
+
+    // Inserting 1 000 000 sorted keys one by one takes quadratic time (see
+    // `sorted_ins` for fewer of them), so the tree they'd produce, a path going
+    // right, is built directly. A recursive find, size or drop would overflow
+    // the stack on it.
+    #[test]
+    fn deep_path() {
+        let n = 1_000_000;
+        let mut path = E;
+        for i in (0 .. n).rev() {
+            path = N(i, i, Box::new(E), Box::new(path));
+        }
+
+        let mut t = Tree::from(path);
+        assert_eq!(t.size(), n);
+        assert_eq!(t.height(), n);
+        assert_eq!(t.internal_path_length(), n * (n - 1) / 2);
+        assert!(t.is_bst());
+        assert_eq!(t.find(&(n - 1)), Some(&(n - 1)));
+        assert_eq!(t.find(&n), None);
+
+        t.ins(n, n);
+        *t.entry(n + 1).or_insert(0) += 1;
+        assert_eq!(t.find(&(n + 1)), Some(&1));
+
+        assert_eq!(t.del(&(n - 1)), Some(n - 1));
+        assert_eq!(t.del(&0), Some(0));
+        assert_eq!(t.size(), n);
+        assert_eq!(t.max(), Some((&(n + 1), &1)));
+    }
+
+    // Sorted keys inserted through `ins` make a path 30 000 nodes deep, enough
+    // for a recursive insert or drop to overflow the stack of a test thread.
+    // Every insert walks the whole path, so there aren't more of them.
+    #[test]
+    fn sorted_ins() {
+        let n = 30_000;
+        let mut t = Tree::new();
+        for i in 0 .. n {
+            t.ins(i, i);
+        }
+
+        assert_eq!(t.size(), n);
+        assert!(t.into_iter().map(|(k, _)| k).eq(0 .. n));
+    }
+
+    #[test]
+    fn from_sorted_big() {
+        let n = 100_000;
+        let t = Tree::from_sorted((0 .. n).map(|i| (i, i)).collect());
+
+        assert_eq!(t.height(), 17);
+        assert!(t.into_iter().map(|(k, _)| k).eq(0 .. n));
+    }
+
+    #[test]
+    fn is_bst_unordered() {
+        let leaf = |k| Box::new(N(k, (), Box::new(E), Box::new(E)));
+
+        assert!(N(1, (), leaf(0), leaf(2)).is_bst());
+        assert!(!N(1, (), leaf(2), leaf(3)).is_bst());
+        assert!(!N(1, (), leaf(0), leaf(1)).is_bst());
+        // The grandchild is right of its parent but left of the root.
+        assert!(!N(5, (), Box::new(N(1, (), Box::new(E), leaf(7))), Box::new(E)).is_bst());
+    }
+
+    #[test]
+    fn display_and_dot() {
+        let leaf = |k, v| Box::new(N(k, v, Box::new(E), Box::new(E)));
+        let t = N(2, "b", leaf(1, "a"), Box::new(N(3, "c\"", Box::new(E), leaf(4, "d"))));
+
+        assert_eq!(t.to_string(), "2: b\n├── 1: a\n└── 3: c\"\n    ├── ·\n    └── 4: d\n");
+        assert_eq!(E::<u8, u8>.to_string(), "·\n");
+        assert_eq!(t.to_dot(), "digraph {\n    node [shape=box];\n    \
+                                n0 [label=\"2: b\"];\n    n0 -> n1;\n    n0 -> n2;\n    \
+                                n2 [label=\"3: c\\\"\"];\n    nil2 [shape=point];\n    \
+                                n2 -> nil2;\n    n2 -> n3;\n    n3 [label=\"4: d\"];\n    \
+                                n1 [label=\"1: a\"];\n}\n");
+        assert_eq!(E::<u8, u8>.to_dot(), "digraph {\n    node [shape=box];\n}\n");
+    }
+
+    // Every node but the last has an edge to its child and to a point.
+    #[test]
+    fn dot_deep_path() {
+        let n = 100_000;
+        let mut t = E;
+        for i in (0 .. n).rev() {
+            t = N(i, i, Box::new(E), Box::new(t));
+        }
+
+        assert_eq!(t.to_dot().lines().count(), 3 + n + 3 * (n - 1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_sorted_unsorted() {
+        Tree::from_sorted(vec![(2, ()), (1, ())]);
+    }
+
+    map_tests!(Tree<usize, usize>);
+    ordered_map_tests!(Tree<usize, usize>);
 
     quickcheck! {
-        fn find_ins(t: Tree<usize, usize>, k: usize, v: usize) -> bool {
-            let mut t = t.clone();
-            t.ins(k, v);
-            t.find(&k) == Some(&v)
+        fn from_sorted_ins(ks: Vec<(usize, usize)>) -> bool {
+            let m: BTreeMap<usize, usize> = ks.iter().cloned().collect();
+            let t = Tree::from_sorted(m.iter().map(|(&k, &v)| (k, v)).collect());
+
+            // A perfectly balanced tree has the least possible height.
+            let n = m.len();
+            t.height() == (usize::BITS - n.leading_zeros()) as usize &&
+            t.iter().eq(m.iter())
         }
 
-        fn del_ins(t: Tree<usize, usize>, k: usize, v: usize) -> bool {
-            let mut t = t.clone();
-            t.ins(k, v);
-            t.del(&k) == Some(v)
+        fn merge_btreemap(ks1: Vec<(u8, usize)>, ks2: Vec<(u8, usize)>) -> bool {
+            let t1: Tree<u8, usize> = ks1.iter().cloned().collect();
+            let t2: Tree<u8, usize> = ks2.iter().cloned().collect();
+            let mut m: BTreeMap<u8, usize> = ks1.into_iter().collect();
+            for (k, v) in ks2.into_iter().collect::<BTreeMap<_, _>>() {
+                let w = m.get(&k).map_or(v, |&x| x.wrapping_sub(v).wrapping_add(k as usize));
+                m.insert(k, w);
+            }
+
+            let t = t1.merge(t2, |&k, x, y| x.wrapping_sub(y).wrapping_add(k as usize));
+            t.height() == t.shape_report().optimal_height && t.iter().eq(m.iter())
         }
 
-        fn find_is_del(t: Tree<usize, usize>, k: usize) -> bool {
-            let mut t1 = t.clone();
-            let mut t2 = t.clone();
+        fn is_bst_arbitrary(t: Tree<usize, usize>) -> bool {
+            t.is_bst()
+        }
+
+        // The shape agrees with the recursive definitions.
+        fn shape_recursive(t: Tree<u8, ()>) -> bool {
+            // (size, height, internal path length)
+            fn shape(t: &Node<u8, ()>) -> (usize, usize, usize) {
+                match t {
+                    E => (0, 0, 0),
+                    N(_, _, l, r) => {
+                        let (ls, lh, lp) = shape(l);
+                        let (rs, rh, rp) = shape(r);
+                        (ls + rs + 1, lh.max(rh) + 1, lp + ls + rp + rs)
+                    }
+                }
+            }
 
-            let f = t1.find(&k);
-            let d = t2.del(&k);
+            let report = t.shape_report();
+            (report.size, report.height, report.internal_path_length) == shape(t.root()) &&
+            report.optimal_height <= report.height
+        }
 
-            match (f, d) {
-                (Some(&v1), Some(v2)) => v1 == v2,
-                (_, _) => false
+        fn iter_sorted(ks: Vec<(usize, usize)>) -> bool {
+            let mut t = Tree::new();
+            let mut m = BTreeMap::new();
+            for (k, v) in ks {
+                t.ins(k, v);
+                m.insert(k, v);
             }
+
+            t.iter().eq(m.iter())
         }
 
-        fn find_del(t: Tree<usize, usize>, k: usize) -> bool {
-            let mut t = t.clone();
-            t.del(&k);
+        fn iter_size(t: Tree<usize, usize>) -> bool {
+            t.iter().count() == t.size()
+        }
 
-            t.find(&k) == None
+        fn keys_values(t: Tree<usize, usize>) -> bool {
+            t.keys().zip(t.values()).eq(t.iter())
         }
 
-        fn find_new(k: usize) -> bool {
-            (Tree::new() as Tree<usize, usize>).find(&k) == None
+        fn collect_into_iter(ks: Vec<(usize, usize)>) -> bool {
+            let t: Tree<usize, usize> = ks.iter().cloned().collect();
+            let m: BTreeMap<usize, usize> = ks.into_iter().collect();
+
+            t.into_iter().eq(m)
+        }
+
+        fn into_iter_iter(t: Tree<usize, usize>) -> bool {
+            let v: Vec<(usize, usize)> = t.iter().map(|(&k, &v)| (k, v)).collect();
+
+            t.into_iter().eq(v)
         }
 
-        fn del_new(k: usize) -> bool {
-            (Tree::new() as Tree<usize, usize>).del(&k) == None
+        fn extend_ins(t: Tree<usize, usize>, ks: Vec<(usize, usize)>) -> bool {
+            let mut t1 = t.clone();
+            let mut t2 = t;
+            t1.extend(ks.iter().cloned());
+            for (k, v) in ks {
+                t2.ins(k, v);
+            }
+
+            t1.into_iter().eq(t2)
         }
 
         /*fn ins_new(k: usize, v: usize) -> bool {
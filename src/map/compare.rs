@@ -0,0 +1,38 @@
+//! Orders of keys given at run time, for maps which don't use `Ord`.
+
+use std::cmp::Ord;
+use std::cmp::Ordering;
+
+/// A total order on `K`.
+///
+/// Closures `Fn(&K, &K) -> Ordering` are comparators, so keys can be ordered,
+/// for example, by one of their fields or case-insensitively.
+pub trait Compare<K> {
+    fn compare(&self, a: &K, b: &K) -> Ordering;
+}
+
+impl<K, F: Fn(&K, &K) -> Ordering> Compare<K> for F {
+    fn compare(&self, a: &K, b: &K) -> Ordering {
+        self(a, b)
+    }
+}
+
+/// The order given by `Ord`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Natural;
+
+impl<K: Ord> Compare<K> for Natural {
+    fn compare(&self, a: &K, b: &K) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+/// The order given by `Ord`, reversed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Reversed;
+
+impl<K: Ord> Compare<K> for Reversed {
+    fn compare(&self, a: &K, b: &K) -> Ordering {
+        b.cmp(a)
+    }
+}
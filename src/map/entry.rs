@@ -0,0 +1,162 @@
+//! The entry API: look up a key once and then read, modify or insert its value.
+
+/// A key of a map, which may or may not be present yet. Created by `Map::entry`,
+/// which looks the key up once: an occupied entry holds the value and a vacant
+/// one holds whatever the map needs to insert the key, so using the entry never
+/// searches for the key again.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::{Entry, Map};
+/// use aisd::map::bst::Tree;
+///
+/// // Count the letters.
+/// let mut t = Tree::new();
+/// for c in "abracadabra".chars() {
+///     *t.entry(c).or_insert(0) += 1;
+/// }
+///
+/// assert_eq!(t.find(&'a'), Some(&5));
+/// assert_eq!(t.find(&'c'), Some(&1));
+///
+/// // Look at the entry.
+/// match t.entry('b') {
+///     Entry::Occupied(e) => assert_eq!(*e.get(), 2),
+///     Entry::Vacant(_) => unreachable!()
+/// }
+///
+/// // Modify the value only if it's there, insert otherwise.
+/// t.entry('a').and_modify(|n| *n *= 10).or_insert(0);
+/// t.entry('z').and_modify(|n| *n *= 10).or_insert(0);
+///
+/// assert_eq!(t.find(&'a'), Some(&50));
+/// assert_eq!(t.find(&'z'), Some(&0));
+/// ```
+pub enum Entry<'a, K: 'a, V: 'a> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>)
+}
+
+/// An entry whose key is in the map.
+pub struct OccupiedEntry<'a, K: 'a, V: 'a> {
+    key: K,
+    value: &'a mut V
+}
+
+/// An entry whose key isn't in the map. It inserts with a function given by the
+/// map, which holds the place where the key goes.
+pub struct VacantEntry<'a, K: 'a, V: 'a> {
+    key: K,
+    insert: Box<dyn FnOnce(K, V) -> &'a mut V + 'a>
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    pub(crate) fn occupied(key: K, value: &'a mut V) -> Entry<'a, K, V> {
+        Entry::Occupied(OccupiedEntry {key, value})
+    }
+
+    pub(crate) fn vacant<F>(key: K, insert: F) -> Entry<'a, K, V>
+        where F: FnOnce(K, V) -> &'a mut V + 'a
+    {
+        Entry::Vacant(VacantEntry {key, insert: Box::new(insert)})
+    }
+
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key()
+        }
+    }
+
+    /// Modifies the value, if there is one.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Entry<'a, K, V> {
+        if let Entry::Occupied(ref mut e) = self {
+            f(e.get_mut());
+        }
+
+        self
+    }
+
+    /// Returns the value, inserting `value` first if there's none.
+    pub fn or_insert(self, value: V) -> &'a mut V {
+        self.or_insert_with(|| value)
+    }
+
+    /// Returns the value, inserting the result of `f` first if there's none.
+    /// `f` is called only if it's needed.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, f: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(f())
+        }
+    }
+
+    /// Returns the value, inserting the default one first if there's none.
+    pub fn or_default(self) -> &'a mut V where V: Default {
+        self.or_insert_with(Default::default)
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the value.
+    pub fn get(&self) -> &V {
+        self.value
+    }
+
+    /// Returns the value, for modifying it.
+    pub fn get_mut(&mut self) -> &mut V {
+        self.value
+    }
+
+    /// Returns the value, borrowed for as long as the map.
+    pub fn into_mut(self) -> &'a mut V {
+        self.value
+    }
+
+    /// Replaces the value and returns the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        ::std::mem::replace(self.value, value)
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Gives the key back, without inserting it.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Inserts the key with the given value and returns the value.
+    pub fn insert(self, value: V) -> &'a mut V {
+        (self.insert)(self.key, value)
+    }
+}
+
+/// The entry of `key` in a map which needs all of itself to insert, like a tree
+/// which rebalances along the path from its root. `find` looks the key up, and if
+/// it's missing, the vacant entry holds the map and inserts with `insert`, which
+/// is only given keys that aren't in the map, so it needn't check for them.
+pub(crate) fn lookup<'a, M, K, V>(map: &'a mut M, key: K,
+                                  find: for<'m> fn(&'m mut M, &K) -> Option<&'m mut V>,
+                                  insert: fn(&'a mut M, K, V) -> &'a mut V) -> Entry<'a, K, V>
+{
+    match find(map, &key).map(|v| v as *mut V) {
+        // The value is borrowed from the map for 'a, but then the borrow checker
+        // (unlike Polonius) would keep the map borrowed in the other arm too, so
+        // the value goes through a pointer. The map isn't touched in this arm.
+        Some(v) => Entry::occupied(key, unsafe { &mut *v }),
+        None => Entry::vacant(key, move |k, v| insert(map, k, v))
+    }
+}
@@ -0,0 +1,447 @@
+//! Interval trees.
+
+use map::{Entry, Map};
+use map::entry;
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use rand::Rng;
+
+use rng;
+use rng::Xoshiro256;
+
+use std::cmp::Ord;
+use std::cmp::Ordering::*;
+
+#[derive(Clone, Debug)]
+struct Node<T, V> {
+    key: (T, T),
+    value: V,
+    priority: u64,
+    // The biggest upper end of an interval in the subtree.
+    max: T,
+    left: Link<T, V>,
+    right: Link<T, V>
+}
+
+type Link<T, V> = Option<Box<Node<T, V>>>;
+
+/// A map from closed intervals [low, high] which finds the intervals containing a
+/// point or overlapping another interval.
+///
+/// It's a treap ordered by (low, high) whose every node also stores the biggest
+/// upper end in its subtree. A search skips the subtrees whose biggest upper end
+/// is below the query and, thanks to the order, everything right of a node whose
+/// lower end is above the query.
+///
+/// Time: `find`, `ins` and `del` are O(log n) expected, `stab` and `overlapping`
+/// are O(min(n, (k + 1) log n)) expected for k intervals found. That's worse
+/// than O(log n + k), because the biggest upper ends don't tell exactly which
+/// subtrees hold none of them.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::Map;
+/// use aisd::map::interval::IntervalTree;
+///
+/// let mut t = IntervalTree::with_seed(42);
+/// t.ins((1, 5), "a").ins((3, 8), "b").ins((10, 12), "c");
+///
+/// assert_eq!(t.stab(&4).map(|(_, v)| *v).collect::<Vec<_>>(), vec!["a", "b"]);
+/// assert_eq!(t.stab(&9).count(), 0);
+/// assert_eq!(t.overlapping(&6, &10).map(|(_, v)| *v).collect::<Vec<_>>(), vec!["b", "c"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct IntervalTree<T, V> {
+    root: Link<T, V>,
+    len: usize,
+    rng: Xoshiro256
+}
+
+fn update<T: Ord + Clone, V>(h: &mut Node<T, V>) {
+    let mut max = &h.key.1;
+    for child in [&h.left, &h.right].iter().filter_map(|c| c.as_ref()) {
+        if child.max > *max {
+            max = &child.max;
+        }
+    }
+    h.max = max.clone();
+}
+
+/// Split `h` into the parts with keys less and greater than `key` (which is not in `h`).
+fn split<T: Ord + Clone, V>(h: Link<T, V>, key: &(T, T)) -> (Link<T, V>, Link<T, V>) {
+    match h {
+        None => (None, None),
+        Some(mut n) => {
+            if n.key < *key {
+                let (l, r) = split(n.right.take(), key);
+                n.right = l;
+                update(&mut n);
+                (Some(n), r)
+            } else {
+                let (l, r) = split(n.left.take(), key);
+                n.left = r;
+                update(&mut n);
+                (l, Some(n))
+            }
+        }
+    }
+}
+
+/// Insert `x` (whose key is not in the tree) below `slot` and return its value.
+/// Like in `Treap`, go down to where the new node belongs and split the subtree
+/// found there. The nodes on the way may get a bigger upper end.
+fn insert<T: Ord + Clone, V>(slot: &mut Link<T, V>, mut x: Box<Node<T, V>>) -> &mut V {
+    // Equal means that `x` goes right here (its key isn't in the tree).
+    let ord = match slot {
+        Some(n) if n.priority >= x.priority => x.key.cmp(&n.key),
+        _ => Equal
+    };
+
+    match (ord, slot) {
+        (Less, Some(n)) => {
+            if x.max > n.max {
+                n.max = x.max.clone();
+            }
+            insert(&mut n.left, x)
+        },
+        (Greater, Some(n)) => {
+            if x.max > n.max {
+                n.max = x.max.clone();
+            }
+            insert(&mut n.right, x)
+        },
+        (_, slot) => {
+            let (l, r) = split(slot.take(), &x.key);
+            x.left = l;
+            x.right = r;
+            update(&mut x);
+            &mut slot.insert(x).value
+        }
+    }
+}
+
+/// Merge two treaps such that all keys in `l` are less than all keys in `r`.
+fn merge<T: Ord + Clone, V>(l: Link<T, V>, r: Link<T, V>) -> Link<T, V> {
+    match (l, r) {
+        (None, r) => r,
+        (l, None) => l,
+        (Some(mut l), Some(mut r)) => {
+            if l.priority > r.priority {
+                l.right = merge(l.right.take(), Some(r));
+                update(&mut l);
+                Some(l)
+            } else {
+                r.left = merge(Some(l), r.left.take());
+                update(&mut r);
+                Some(r)
+            }
+        }
+    }
+}
+
+fn delete<T: Ord + Clone, V>(h: &mut Link<T, V>, key: &(T, T)) -> Option<V> {
+    let ord = match h {
+        None => return None,
+        Some(n) => key.cmp(&n.key)
+    };
+
+    match (ord, h) {
+        (Less, Some(n)) => {
+            let result = delete(&mut n.left, key);
+            update(n);
+            result
+        },
+        (Greater, Some(n)) => {
+            let result = delete(&mut n.right, key);
+            update(n);
+            result
+        },
+        (_, h) => h.take().map(|n| {
+            let n = *n;
+            *h = merge(n.left, n.right);
+            n.value
+        })
+    }
+}
+
+/// Pushes the entries of the subtree of `h` overlapping [low, high] onto `found`,
+/// in order.
+fn search<'a, T: Ord, V>(h: &'a Link<T, V>, low: &T, high: &T, found: &mut Vec<(&'a (T, T), &'a V)>) {
+    let n = match h {
+        Some(n) if n.max >= *low => n,
+        _ => return
+    };
+
+    search(&n.left, low, high, found);
+    // Nodes to the right start no earlier than this one.
+    if n.key.0 > *high {
+        return;
+    }
+    if n.key.1 >= *low {
+        found.push((&n.key, &n.value));
+    }
+    search(&n.right, low, high, found);
+}
+
+impl<T, V> IntervalTree<T, V> {
+    /// Creates an empty tree whose priorities are drawn from a generator with
+    /// the given seed, so that its shape is reproducible.
+    pub fn with_seed(seed: u64) -> IntervalTree<T, V> {
+        IntervalTree {
+            root: None,
+            len: 0,
+            rng: Xoshiro256::seed_from_u64(seed)
+        }
+    }
+
+    /// Creates an empty tree whose generator is seeded from `rng`, like
+    /// `Treap::with_rng`.
+    pub fn with_rng<R: Rng>(rng: &mut R) -> IntervalTree<T, V> {
+        IntervalTree::with_seed(rng.gen())
+    }
+
+    /// Checks whether there are no intervals.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+}
+
+impl<T: Ord + Clone, V> IntervalTree<T, V> {
+    /// Iterates over the intervals which contain `point`, in increasing order.
+    pub fn stab(&self, point: &T) -> impl Iterator<Item = (&(T, T), &V)> {
+        self.overlapping(point, point)
+    }
+
+    /// Iterates over the intervals which have a point in common with
+    /// [low, high], in increasing order.
+    pub fn overlapping(&self, low: &T, high: &T) -> impl Iterator<Item = (&(T, T), &V)> {
+        let mut found = vec![];
+        search(&self.root, low, high, &mut found);
+        found.into_iter()
+    }
+
+    /// Checks that keys form a BST, priorities form a max-heap and the maximal
+    /// upper ends are right.
+    /// Time: O(size of the tree)
+    pub fn is_valid(&self) -> bool {
+        fn check<'a, T: Ord, V>(h: &'a Link<T, V>, low: Option<&'a (T, T)>, high: Option<&'a (T, T)>,
+                                max: u64) -> bool {
+            match h {
+                None => true,
+                Some(n) => {
+                    let children = [&n.left, &n.right];
+                    let biggest = children.iter().filter_map(|c| c.as_ref()).map(|c| &c.max)
+                        .fold(&n.key.1, |m, x| m.max(x));
+
+                    low.is_none_or(|k| *k < n.key) && high.is_none_or(|k| n.key < *k) &&
+                    n.priority <= max && n.max == *biggest &&
+                    check(&n.left, low, Some(&n.key), n.priority) &&
+                    check(&n.right, Some(&n.key), high, n.priority)
+                }
+            }
+        }
+
+        check(&self.root, None, None, u64::MAX)
+    }
+
+    /// Insert a key which isn't in the tree yet and return its value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interval is empty.
+    fn ins_new(&mut self, key: (T, T), value: V) -> &mut V {
+        assert!(key.0 <= key.1, "IntervalTree: the interval is empty");
+
+        let priority = self.rng.gen();
+        let max = key.1.clone();
+        self.len += 1;
+        insert(&mut self.root, Box::new(Node {key, value, priority, max, left: None, right: None}))
+    }
+}
+
+impl<T: Ord + Clone, V> Map for IntervalTree<T, V> {
+    type Key = (T, T);
+    type Value = V;
+
+    /// Creates an empty tree with the generator of `rng::default_rng`, so that
+    /// the same operations give the same shape in every run.
+    fn new() -> IntervalTree<T, V> {
+        IntervalTree::with_seed(rng::DEFAULT_SEED)
+    }
+
+    fn find(&self, key: &(T, T)) -> Option<&V> {
+        let mut current = &self.root;
+        while let Some(n) = current {
+            match key.cmp(&n.key) {
+                Less => current = &n.left,
+                Equal => return Some(&n.value),
+                Greater => current = &n.right
+            }
+        }
+
+        None
+    }
+
+    fn find_mut(&mut self, key: &(T, T)) -> Option<&mut V> {
+        let mut current = &mut self.root;
+        while let Some(n) = current {
+            match key.cmp(&n.key) {
+                Less => current = &mut n.left,
+                Equal => return Some(&mut n.value),
+                Greater => current = &mut n.right
+            }
+        }
+
+        None
+    }
+
+    /// Inserts the interval [key.0, key.1].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interval is empty, that is if `key.0 > key.1`.
+    fn ins(&mut self, key: (T, T), value: V) -> &mut Self {
+        assert!(key.0 <= key.1, "IntervalTree: the interval is empty");
+        if let Some(v) = self.find_mut(&key) {
+            *v = value;
+            return self;
+        }
+
+        // Splitting assumes the key isn't there yet.
+        if let Some(v) = self.find_mut(&key) {
+            *v = value;
+            return self;
+        }
+
+        self.ins_new(key, value);
+        self
+    }
+
+    fn del(&mut self, key: &(T, T)) -> Option<V> {
+        let result = delete(&mut self.root, key);
+        if result.is_some() {
+            self.len -= 1;
+        }
+
+        result
+    }
+
+    /// Time: O(1)
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Keeps the generator, so that a seeded tree stays reproducible.
+    fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// # Panics
+    ///
+    /// Inserting through the entry panics if the interval is empty.
+    fn entry(&mut self, key: (T, T)) -> Entry<'_, (T, T), V> {
+        entry::lookup(self, key, IntervalTree::find_mut, IntervalTree::ins_new)
+    }
+}
+
+impl<T: Ord + Clone + Arbitrary, V: Arbitrary> Arbitrary for IntervalTree<T, V> {
+    fn arbitrary<G: Gen>(g: &mut G) -> IntervalTree<T, V> {
+        let data: Vec<(T, T, V)> = Arbitrary::arbitrary(g);
+
+        let mut t = IntervalTree::new();
+        for (a, b, v) in data {
+            if a <= b {
+                t.ins((a, b), v);
+            } else {
+                t.ins((b, a), v);
+            }
+        }
+
+        t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map::Map;
+    use super::IntervalTree;
+
+    use std::collections::BTreeMap;
+
+    fn model(ks: &[(u8, u8, usize)]) -> (IntervalTree<u8, usize>, BTreeMap<(u8, u8), usize>) {
+        let mut t = IntervalTree::new();
+        let mut m = BTreeMap::new();
+        for &(a, b, v) in ks {
+            let key = (a.min(b), a.max(b));
+            t.ins(key, v);
+            m.insert(key, v);
+        }
+
+        (t, m)
+    }
+
+    quickcheck! {
+        fn is_valid_arbitrary(t: IntervalTree<u8, usize>) -> bool {
+            t.is_valid()
+        }
+
+        fn is_valid_del(t: IntervalTree<u8, usize>, ks: Vec<(u8, u8)>) -> bool {
+            let mut t = t.clone();
+            ks.iter().all(|&(a, b)| {
+                t.del(&(a.min(b), a.max(b)));
+                t.is_valid()
+            })
+        }
+
+        fn is_valid_entry(t: IntervalTree<u8, usize>, ks: Vec<(u8, u8)>) -> bool {
+            let mut t = t.clone();
+            ks.iter().all(|&(a, b)| {
+                *t.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+                t.is_valid()
+            })
+        }
+
+        fn same_as_btreemap(ops: Vec<(bool, u8, u8, usize)>) -> bool {
+            let mut t = IntervalTree::new();
+            let mut m = BTreeMap::new();
+
+            ops.into_iter().all(|(ins, a, b, v)| {
+                let (a, b) = (a % 16, b % 16);
+                let key = (a.min(b), a.max(b));
+                let agree = if ins {
+                    t.ins(key, v);
+                    m.insert(key, v);
+                    true
+                } else {
+                    t.del(&key) == m.remove(&key)
+                };
+
+                agree && t.find(&key) == m.get(&key)
+            })
+        }
+
+        fn stab_naive(ks: Vec<(u8, u8, usize)>, p: u8) -> bool {
+            let (t, m) = model(&ks);
+
+            t.stab(&p).eq(m.iter().filter(|(k, _)| k.0 <= p && p <= k.1))
+        }
+
+        fn overlapping_naive(ks: Vec<(u8, u8, usize)>, a: u8, b: u8) -> bool {
+            let (t, m) = model(&ks);
+            let (low, high) = (a.min(b), a.max(b));
+
+            t.overlapping(&low, &high).eq(m.iter().filter(|(k, _)| k.0 <= high && low <= k.1))
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn ins_empty_interval() {
+        IntervalTree::new().ins((2, 1), ());
+    }
+}
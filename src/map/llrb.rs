@@ -0,0 +1,575 @@
+//! Left-leaning red-black trees.
+
+use map::{Entry, Map};
+use map::entry;
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use std::cmp::Ord;
+use std::cmp::Ordering;
+use std::cmp::Ordering::*;
+use std::mem;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Color {
+    Red,
+    Black
+}
+
+use self::Color::{Red, Black};
+
+impl Color {
+    fn flip(self) -> Color {
+        match self {
+            Red => Black,
+            Black => Red
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    color: Color,
+    left: Link<K, V>,
+    right: Link<K, V>
+}
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+/// The way from the root of a subtree down to one of its nodes, last step first.
+type Way = Vec<Ordering>;
+
+impl_bst_node!(Node);
+
+/// Sedgewick's left-leaning red-black tree. It's a binary search tree that encodes
+/// a 2-3 tree: a 3-node is a black node with a red left child. This gives two
+/// invariants that keep the height below 2 log n:
+///
+/// - red links lean left and no node has two red links in a row,
+/// - every path from the root to an empty subtree has the same number of black links.
+///
+/// In debug builds both invariants (and the ordering of keys) are checked after
+/// every `ins` and `del`.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::Map;
+/// use aisd::map::llrb::Llrb;
+///
+/// let mut t = Llrb::new();
+/// for i in 0 .. 1000 {
+///     t.ins(i, i * i);
+/// }
+///
+/// assert_eq!(t.find(&30), Some(&900));
+/// assert!(t.height() <= 20);
+///
+/// assert_eq!(t.del(&30), Some(900));
+/// assert_eq!(t.find(&30), None);
+/// assert!(t.is_valid());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Llrb<K, V> {
+    root: Link<K, V>,
+    len: usize
+}
+
+fn is_red<K, V>(h: &Link<K, V>) -> bool {
+    match h {
+        Some(n) => n.color == Red,
+        None => false
+    }
+}
+
+fn is_left_red<K, V>(h: &Link<K, V>) -> bool {
+    match h {
+        Some(n) => is_red(&n.left),
+        None => false
+    }
+}
+
+/// Turn a right-leaning red link into a left-leaning one.
+fn rotate_left<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    match h.right.take() {
+        None => h,
+        Some(mut x) => {
+            h.right = x.left.take();
+            x.color = h.color;
+            h.color = Red;
+            x.left = Some(h);
+            x
+        }
+    }
+}
+
+/// Turn a left-leaning red link into a right-leaning one.
+fn rotate_right<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    match h.left.take() {
+        None => h,
+        Some(mut x) => {
+            h.left = x.right.take();
+            x.color = h.color;
+            h.color = Red;
+            x.right = Some(h);
+            x
+        }
+    }
+}
+
+/// Keep `way` leading to the same node after the child on the given side of the
+/// subtree's root got rotated up into its place.
+fn rotated(way: &mut Way, side: Ordering) {
+    match way.pop() {
+        // The old root is now a child of the new one.
+        None => way.push(side.reverse()),
+        // The other subtree moved one level down.
+        Some(first) if first != side => {
+            way.push(first);
+            way.push(first);
+        },
+        Some(_) => match way.pop() {
+            // The new root itself.
+            None => {},
+            // The inner subtree of the new root moved to the old one.
+            Some(second) if second != side => {
+                way.push(side);
+                way.push(second);
+            },
+            // The outer subtree of the new root moved one level up.
+            Some(second) => way.push(second)
+        }
+    }
+}
+
+/// Split a temporary 4-node (or, when deleting, join into one).
+fn flip_colors<K, V>(h: &mut Node<K, V>) {
+    h.color = h.color.flip();
+    if let Some(ref mut l) = h.left {
+        l.color = l.color.flip();
+    }
+    if let Some(ref mut r) = h.right {
+        r.color = r.color.flip();
+    }
+}
+
+/// Restore the invariants on the way up, keeping `way` (if given) leading to the
+/// same node.
+fn fix_up<K, V>(mut h: Box<Node<K, V>>, mut way: Option<&mut Way>) -> Box<Node<K, V>> {
+    if is_red(&h.right) && !is_red(&h.left) {
+        h = rotate_left(h);
+        if let Some(ref mut way) = way {
+            rotated(way, Greater);
+        }
+    }
+    if is_red(&h.left) && is_left_red(&h.left) {
+        h = rotate_right(h);
+        if let Some(ref mut way) = way {
+            rotated(way, Less);
+        }
+    }
+    if is_red(&h.left) && is_red(&h.right) {
+        flip_colors(&mut h);
+    }
+
+    h
+}
+
+/// Make sure that the left child of `h` or one of its children is red.
+fn move_red_left<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    flip_colors(&mut h);
+    if is_left_red(&h.right) {
+        h.right = h.right.take().map(rotate_right);
+        h = rotate_left(h);
+        flip_colors(&mut h);
+    }
+
+    h
+}
+
+/// Make sure that the right child of `h` or one of its children is red.
+fn move_red_right<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    flip_colors(&mut h);
+    if is_left_red(&h.left) {
+        h = rotate_right(h);
+        flip_colors(&mut h);
+    }
+
+    h
+}
+
+/// Insert into the subtree rooted at `h`, setting `way` to the way to the entry.
+fn insert<K: Ord, V>(h: Link<K, V>, key: K, value: V, way: &mut Way) -> Box<Node<K, V>> {
+    match h {
+        None => Box::new(Node {key, value, color: Red, left: None, right: None}),
+        Some(mut h) => {
+            let ord = key.cmp(&h.key);
+            match ord {
+                Less => h.left = Some(insert(h.left.take(), key, value, way)),
+                Equal => h.value = value,
+                Greater => h.right = Some(insert(h.right.take(), key, value, way))
+            }
+            if ord != Equal {
+                way.push(ord);
+            }
+
+            fix_up(h, Some(way))
+        }
+    }
+}
+
+fn delete_min<K, V>(mut h: Box<Node<K, V>>) -> (Link<K, V>, (K, V)) {
+    if h.left.is_none() {
+        // In a left-leaning tree, a node without a left child has no right child.
+        let h = *h;
+        return (None, (h.key, h.value));
+    }
+
+    if !is_red(&h.left) && !is_left_red(&h.left) {
+        h = move_red_left(h);
+    }
+
+    let (l, min) = match h.left.take() {
+        Some(l) => delete_min(l),
+        None => unreachable!()
+    };
+    h.left = l;
+
+    (Some(fix_up(h, None)), min)
+}
+
+/// Delete `key`, which has to be present in the tree rooted at `h`.
+fn delete<K: Ord, V>(mut h: Box<Node<K, V>>, key: &K) -> (Link<K, V>, Option<V>) {
+    let result;
+
+    if *key < h.key {
+        if !is_red(&h.left) && !is_left_red(&h.left) {
+            h = move_red_left(h);
+        }
+
+        let (l, r) = match h.left.take() {
+            Some(l) => delete(l, key),
+            None => (None, None)
+        };
+        h.left = l;
+        result = r;
+    } else {
+        if is_red(&h.left) {
+            h = rotate_right(h);
+        }
+
+        if *key == h.key && h.right.is_none() {
+            return (None, Some(h.value));
+        }
+
+        if !is_red(&h.right) && !is_left_red(&h.right) {
+            h = move_red_right(h);
+        }
+
+        if *key == h.key {
+            // Replace the node's entry with the least entry from the right subtree.
+            let (r, (k, v)) = match h.right.take() {
+                Some(r) => delete_min(r),
+                None => unreachable!()
+            };
+            h.right = r;
+            h.key = k;
+            result = Some(mem::replace(&mut h.value, v));
+        } else {
+            let (r, res) = match h.right.take() {
+                Some(r) => delete(r, key),
+                None => (None, None)
+            };
+            h.right = r;
+            result = res;
+        }
+    }
+
+    (Some(fix_up(h, None)), result)
+}
+
+impl<K, V> Llrb<K, V> {
+    /// Computes the height of the tree. The empty tree has height 0.
+    /// Time: O(size of the tree)
+    pub fn height(&self) -> usize {
+        fn height<K, V>(h: &Link<K, V>) -> usize {
+            match h {
+                None => 0,
+                Some(n) => 1 + height(&n.left).max(height(&n.right))
+            }
+        }
+
+        height(&self.root)
+    }
+
+    /// Returns the number of entries in the tree.
+    /// Time: O(1)
+    pub fn size(&self) -> usize {
+        self.len
+    }
+}
+
+impl<K: Ord, V> Llrb<K, V> {
+    /// Insert a key which isn't in the tree yet and return the way to it.
+    fn insert(&mut self, key: K, value: V) -> Way {
+        let mut way = vec![];
+        let mut root = insert(self.root.take(), key, value, &mut way);
+        root.color = Black;
+        self.root = Some(root);
+        self.len += 1;
+
+        debug_assert!(self.is_valid());
+        way
+    }
+
+    /// Insert a key which isn't in the tree yet and return its value.
+    fn ins_new(&mut self, key: K, value: V) -> &mut V {
+        let way = self.insert(key, value);
+
+        let mut current = &mut self.root;
+        for ord in way.into_iter().rev() {
+            current = match (ord, current) {
+                (Less, Some(n)) => &mut n.left,
+                (_, Some(n)) => &mut n.right,
+                (_, None) => unreachable!()
+            };
+        }
+
+        match current {
+            Some(n) => &mut n.value,
+            None => unreachable!()
+        }
+    }
+
+    /// Checks all the invariants: keys are ordered, the root is black, red links lean
+    /// left, there are no two red links in a row and the tree is perfectly black-balanced.
+    /// Time: O(size of the tree)
+    pub fn is_valid(&self) -> bool {
+        /// Returns the black height of the subtree if it's valid.
+        fn check<'a, K: Ord, V>(h: &'a Link<K, V>, low: Option<&'a K>, high: Option<&'a K>)
+            -> Option<usize>
+        {
+            match h {
+                None => Some(0),
+                Some(n) => {
+                    let ordered = low.is_none_or(|k| *k < n.key) &&
+                                  high.is_none_or(|k| n.key < *k);
+                    let leans_left = !is_red(&n.right);
+                    let no_double_red = !(n.color == Red && is_red(&n.left));
+
+                    if !(ordered && leans_left && no_double_red) {
+                        return None;
+                    }
+
+                    let l = check(&n.left, low, Some(&n.key))?;
+                    let r = check(&n.right, Some(&n.key), high)?;
+
+                    if l != r {
+                        None
+                    } else if n.color == Black {
+                        Some(l + 1)
+                    } else {
+                        Some(l)
+                    }
+                }
+            }
+        }
+
+        !is_red(&self.root) && check(&self.root, None, None).is_some()
+    }
+}
+
+impl<K: Ord, V> Map for Llrb<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn new() -> Llrb<K, V> {
+        Llrb {root: None, len: 0}
+    }
+
+    fn find(&self, key: &K) -> Option<&V> {
+        let mut current = &self.root;
+        while let Some(n) = current {
+            match key.cmp(&n.key) {
+                Less => current = &n.left,
+                Equal => return Some(&n.value),
+                Greater => current = &n.right
+            }
+        }
+
+        None
+    }
+
+    fn find_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut current = &mut self.root;
+        while let Some(n) = current {
+            match key.cmp(&n.key) {
+                Less => current = &mut n.left,
+                Equal => return Some(&mut n.value),
+                Greater => current = &mut n.right
+            }
+        }
+
+        None
+    }
+
+    fn ins(&mut self, key: K, value: V) -> &mut Self {
+        if let Some(v) = self.find_mut(&key) {
+            *v = value;
+            return self;
+        }
+
+        self.insert(key, value);
+        self
+    }
+
+    fn del(&mut self, key: &K) -> Option<V> {
+        // Deleting assumes that the key is present.
+        self.find(key)?;
+
+        let result = match self.root.take() {
+            None => None,
+            Some(mut root) => {
+                if !is_red(&root.left) && !is_red(&root.right) {
+                    root.color = Red;
+                }
+
+                let (root, result) = delete(root, key);
+                self.root = root;
+                result
+            }
+        };
+
+        if let Some(ref mut root) = self.root {
+            root.color = Black;
+        }
+        if result.is_some() {
+            self.len -= 1;
+        }
+
+        debug_assert!(self.is_valid());
+        result
+    }
+
+    /// Time: O(1)
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// A vacant entry inserts from the root, because of the rebalancing.
+    fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        entry::lookup(self, key, Llrb::find_mut, Llrb::ins_new)
+    }
+}
+
+impl_ordered_map!(Llrb, |t| t.root.as_deref());
+
+impl<K: Ord + Arbitrary, V: Arbitrary> Arbitrary for Llrb<K, V> {
+    fn arbitrary<G: Gen>(g: &mut G) -> Llrb<K, V> {
+        let data: Vec<(K, V)> = Arbitrary::arbitrary(g);
+
+        let mut t = Llrb::new();
+        for (k, v) in data {
+            t.ins(k, v);
+        }
+
+        t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map::Map;
+    use super::Llrb;
+
+    map_tests!(Llrb<usize, usize>);
+    ordered_map_tests!(Llrb<usize, usize>);
+
+    quickcheck! {
+        fn is_valid_new() -> bool {
+            (Llrb::new() as Llrb<usize, usize>).is_valid()
+        }
+
+        fn is_valid_arbitrary(t: Llrb<usize, usize>) -> bool {
+            t.is_valid()
+        }
+
+        fn is_valid_ins(t: Llrb<usize, usize>, k: usize, v: usize) -> bool {
+            t.clone().ins(k, v).is_valid()
+        }
+
+        fn is_valid_del(t: Llrb<usize, usize>, ks: Vec<usize>) -> bool {
+            let mut t = t.clone();
+            for k in ks {
+                t.del(&k);
+                if !t.is_valid() {
+                    return false;
+                }
+            }
+
+            true
+        }
+
+        // Delete keys that are present, in random order.
+        fn is_valid_del_all(ks: Vec<u16>) -> bool {
+            let mut t = Llrb::new();
+            for &k in &ks {
+                t.ins(k, ());
+            }
+
+            ks.iter().rev().all(|k| {
+                t.del(k);
+                t.is_valid() && t.find(k) == None
+            }) && t.size() == 0
+        }
+
+        fn size_ins(t: Llrb<usize, usize>, k: usize, v: usize) -> bool {
+            let n = t.size();
+            let present = t.find(&k).is_some();
+
+            t.clone().ins(k, v).size() == if present {n} else {n + 1}
+        }
+
+        // A red-black tree with n nodes has height at most 2 log(n + 1).
+        fn height_sorted(n: u16) -> bool {
+            let mut t = Llrb::new();
+            for i in 0 .. n {
+                t.ins(i, ());
+            }
+
+            t.height() as f64 <= 2.0 * ((n as f64) + 1.0).log2()
+        }
+    }
+
+    // The checker has to catch broken trees, too.
+    #[test]
+    fn is_valid_broken() {
+        use super::{Node, Red, Black};
+
+        let leaf = |k, color| Some(Box::new(Node {key: k, value: (), color, left: None, right: None}));
+
+        // A right-leaning red link.
+        let t = Llrb {root: Some(Box::new(Node {key: 1, value: (), color: Black,
+                                                left: None, right: leaf(2, Red)})), len: 2};
+        assert!(!t.is_valid());
+
+        // Black imbalance.
+        let t = Llrb {root: Some(Box::new(Node {key: 1, value: (), color: Black,
+                                                left: leaf(0, Black), right: None})), len: 2};
+        assert!(!t.is_valid());
+
+        // Keys out of order.
+        let t = Llrb {root: Some(Box::new(Node {key: 1, value: (), color: Black,
+                                                left: leaf(2, Red), right: None})), len: 2};
+        assert!(!t.is_valid());
+
+        // A valid 3-node.
+        let t = Llrb {root: Some(Box::new(Node {key: 1, value: (), color: Black,
+                                                left: leaf(0, Red), right: None})), len: 2};
+        assert!(t.is_valid());
+    }
+}
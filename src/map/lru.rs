@@ -0,0 +1,277 @@
+//! Least recently used caches.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Clone, Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // The neighbours in the recency list: `prev` was used more recently.
+    prev: Option<usize>,
+    next: Option<usize>
+}
+
+/// A map holding at most `capacity` entries, which makes room for new entries by
+/// evicting the least recently used one.
+///
+/// The entries live in a vector and are linked into a doubly linked list by
+/// indices, from the most to the least recently used, while a hash map finds the
+/// node of a key. So using an entry (unlinking it and linking it at the front)
+/// and evicting the last one take O(1). A removed node's slot is filled with
+/// the last node, so the vector has no holes.
+///
+/// Time: `get`, `put`, `peek` and `pop_lru` are O(1) expected.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::lru::LruCache;
+///
+/// let mut cache = LruCache::new(2);
+/// cache.put("a", 1);
+/// cache.put("b", 2);
+/// assert_eq!(cache.get(&"a"), Some(&1));
+///
+/// // "b" is the least recently used, so it makes room for "c".
+/// assert_eq!(cache.put("c", 3), Some(("b", 2)));
+/// assert_eq!(cache.peek(&"b"), None);
+/// assert_eq!(cache.iter().collect::<Vec<_>>(), vec![(&"c", &3), (&"a", &1)]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LruCache<K, V> {
+    capacity: usize,
+    index: HashMap<K, usize>,
+    nodes: Vec<Node<K, V>>,
+    // The most and the least recently used entry.
+    head: Option<usize>,
+    tail: Option<usize>
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Creates an empty cache for at most `capacity` entries. Nothing is
+    /// allocated up front: the cache grows as entries come.
+    pub fn new(capacity: usize) -> LruCache<K, V> {
+        LruCache {
+            capacity,
+            index: HashMap::new(),
+            nodes: Vec::new(),
+            head: None,
+            tail: None
+        }
+    }
+
+    /// Returns the greatest number of entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Checks whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Takes node `i` out of the recency list.
+    fn unlink(&mut self, i: usize) {
+        let (prev, next) = (self.nodes[i].prev, self.nodes[i].next);
+        match prev {
+            Some(p) => self.nodes[p].next = next,
+            None => self.head = next
+        }
+        match next {
+            Some(n) => self.nodes[n].prev = prev,
+            None => self.tail = prev
+        }
+    }
+
+    /// Puts node `i` at the front of the recency list.
+    fn push_front(&mut self, i: usize) {
+        self.nodes[i].prev = None;
+        self.nodes[i].next = self.head;
+        match self.head {
+            Some(h) => self.nodes[h].prev = Some(i),
+            None => self.tail = Some(i)
+        }
+        self.head = Some(i);
+    }
+
+    /// Marks node `i` as the most recently used.
+    fn touch(&mut self, i: usize) {
+        if self.head != Some(i) {
+            self.unlink(i);
+            self.push_front(i);
+        }
+    }
+
+    /// Returns the value of `key` and marks it as the most recently used.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let i = *self.index.get(key)?;
+        self.touch(i);
+
+        Some(&self.nodes[i].value)
+    }
+
+    /// Like `get`, but the value can be changed.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let i = *self.index.get(key)?;
+        self.touch(i);
+
+        Some(&mut self.nodes[i].value)
+    }
+
+    /// Returns the value of `key` without marking it as used.
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.index.get(key).map(|&i| &self.nodes[i].value)
+    }
+
+    /// Checks whether there's an entry with the given key, without marking it
+    /// as used.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// Inserts the entry as the most recently used one and returns the entry
+    /// it pushed out: the old entry of the same key, or else the least recently
+    /// used one if the cache was full. With capacity 0 the new entry itself is
+    /// returned.
+    pub fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if let Some(&i) = self.index.get(&key) {
+            self.touch(i);
+            let old = ::std::mem::replace(&mut self.nodes[i].value, value);
+            return Some((key, old));
+        }
+        if self.capacity == 0 {
+            return Some((key, value));
+        }
+
+        let evicted = if self.len() == self.capacity {self.pop_lru()} else {None};
+
+        let i = self.nodes.len();
+        self.nodes.push(Node {key: key.clone(), value, prev: None, next: None});
+        self.index.insert(key, i);
+        self.push_front(i);
+
+        evicted
+    }
+
+    /// Removes the entry of `key` and returns its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let i = self.index.remove(key)?;
+        self.unlink(i);
+
+        let node = self.nodes.swap_remove(i);
+        if i < self.nodes.len() {
+            // The last node moved to slot `i`, so the links to it have to follow.
+            let (prev, next) = (self.nodes[i].prev, self.nodes[i].next);
+            match prev {
+                Some(p) => self.nodes[p].next = Some(i),
+                None => self.head = Some(i)
+            }
+            match next {
+                Some(n) => self.nodes[n].prev = Some(i),
+                None => self.tail = Some(i)
+            }
+            if let Some(j) = self.index.get_mut(&self.nodes[i].key) {
+                *j = i;
+            }
+        }
+
+        Some(node.value)
+    }
+
+    /// Removes the least recently used entry and returns it.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let i = self.tail?;
+        let key = self.nodes[i].key.clone();
+        self.remove(&key).map(|v| (key, v))
+    }
+
+    /// Iterates over the entries from the most to the least recently used,
+    /// without marking them as used.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let mut current = self.head;
+        ::std::iter::from_fn(move || {
+            let n = &self.nodes[current?];
+            current = n.next;
+            Some((&n.key, &n.value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LruCache;
+
+    // The entries from the most to the least recently used.
+    type Model = Vec<(u8, u32)>;
+
+    fn get(model: &mut Model, k: u8) -> Option<u32> {
+        let i = model.iter().position(|e| e.0 == k)?;
+        let e = model.remove(i);
+        model.insert(0, e);
+        Some(e.1)
+    }
+
+    quickcheck! {
+        fn same_as_model(capacity: usize, ops: Vec<(u8, u8, u32)>) -> bool {
+            let capacity = capacity % 8;
+            let mut cache = LruCache::new(capacity);
+            let mut model: Model = vec![];
+
+            ops.into_iter().all(|(op, k, v)| {
+                let k = k % 16;
+                let agree = match op % 4 {
+                    0 => cache.get(&k).cloned() == get(&mut model, k),
+                    1 => cache.peek(&k).cloned() == model.iter().find(|e| e.0 == k).map(|e| e.1),
+                    2 => {
+                        let expected = match model.iter().position(|e| e.0 == k) {
+                            Some(i) => Some(model.remove(i)),
+                            None if capacity == 0 => Some((k, v)),
+                            None if model.len() == capacity => model.pop(),
+                            None => None
+                        };
+                        if capacity > 0 {
+                            model.insert(0, (k, v));
+                        }
+                        cache.put(k, v) == expected
+                    },
+                    _ => cache.pop_lru() == model.pop()
+                };
+
+                agree && cache.len() == model.len() &&
+                cache.iter().map(|(&k, &v)| (k, v)).eq(model.iter().cloned())
+            })
+        }
+
+        fn remove_keeps_order(ks: Vec<u8>, k: u8) -> bool {
+            let mut cache = LruCache::new(8);
+            for &x in &ks {
+                cache.put(x % 16, x as u32);
+            }
+            let before: Vec<(u8, u32)> = cache.iter().map(|(&k, &v)| (k, v)).collect();
+            let k = k % 16;
+
+            let removed = cache.remove(&k);
+            let after: Vec<(u8, u32)> = cache.iter().map(|(&k, &v)| (k, v)).collect();
+            removed == before.iter().find(|e| e.0 == k).map(|e| e.1) &&
+            after == before.into_iter().filter(|e| e.0 != k).collect::<Vec<_>>()
+        }
+    }
+
+    #[test]
+    fn huge_capacity() {
+        let mut cache = LruCache::new(usize::MAX);
+        for i in 0 .. 100 {
+            cache.put(i, i);
+        }
+
+        assert_eq!(cache.len(), 100);
+        assert_eq!(cache.pop_lru(), Some((0, 0)));
+    }
+}
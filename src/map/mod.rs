@@ -1,11 +1,304 @@
+/// Tests that every `Map` implementation has to pass. The type given as the argument
+/// has to map `usize` to `usize` and implement `Arbitrary` and `Clone`.
+#[cfg(test)]
+macro_rules! map_tests {
+    ($t:ty) => {
+        mod map_tests {
+            use super::*;
+            use map::{Entry, Map};
+
+            use std::collections::BTreeMap;
+
+            quickcheck! {
+                fn find_ins(t: $t, k: usize, v: usize) -> bool {
+                    let mut t = t.clone();
+                    t.ins(k, v);
+                    t.find(&k) == Some(&v)
+                }
+
+                fn del_ins(t: $t, k: usize, v: usize) -> bool {
+                    let mut t = t.clone();
+                    t.ins(k, v);
+                    t.del(&k) == Some(v)
+                }
+
+                fn find_is_del(t: $t, k: usize) -> bool {
+                    let t1 = t.clone();
+                    let mut t2 = t.clone();
+
+                    let f = t1.find(&k);
+                    let d = t2.del(&k);
+
+                    match (f, d) {
+                        (Some(&v1), Some(v2)) => v1 == v2,
+                        (None, None) => true,
+                        (_, _) => false
+                    }
+                }
+
+                fn find_del(t: $t, k: usize) -> bool {
+                    let mut t = t.clone();
+                    t.del(&k);
+
+                    t.find(&k) == None
+                }
+
+                fn find_new(k: usize) -> bool {
+                    <$t as Map>::new().find(&k) == None
+                }
+
+                fn del_new(k: usize) -> bool {
+                    <$t as Map>::new().del(&k) == None
+                }
+
+                fn find_mut_find(t: $t, k: usize, v: usize) -> bool {
+                    let mut t = t.clone();
+                    let found = t.find(&k).is_some();
+                    if let Some(x) = t.find_mut(&k) {
+                        *x = v;
+                    }
+
+                    (t.find(&k) == Some(&v)) == found
+                }
+
+                // Count keys with the entry API and compare with `BTreeMap`.
+                fn entry_counts(ks: Vec<u8>) -> bool {
+                    let mut t = <$t as Map>::new();
+                    let mut m = BTreeMap::new();
+                    for k in ks {
+                        let k = k as usize % 32;
+                        *t.entry(k).or_insert(0) += 1;
+                        *m.entry(k).or_insert(0) += 1;
+                    }
+
+                    (0 .. 32).all(|k| t.find(&k) == m.get(&k))
+                }
+
+                fn entry_and_modify(t: $t, k: usize, v: usize) -> bool {
+                    let mut t = t.clone();
+                    let old = t.find(&k).cloned();
+                    t.entry(k).and_modify(|x| *x = x.wrapping_add(1)).or_insert(v);
+
+                    t.find(&k) == Some(&old.map_or(v, |x| x.wrapping_add(1)))
+                }
+
+                fn get_or_insert_counts(ks: Vec<u8>) -> bool {
+                    let mut t = <$t as Map>::new();
+                    let mut m = BTreeMap::new();
+                    for k in ks {
+                        let k = k as usize % 32;
+                        *t.get_or_insert(k, 0) += 1;
+                        *m.entry(k).or_insert(0) += 1;
+                    }
+
+                    t.len() == m.len() && (0 .. 32).all(|k| t.find(&k) == m.get(&k))
+                }
+
+                fn contains_key_find(t: $t, k: usize) -> bool {
+                    t.contains_key(&k) == t.find(&k).is_some()
+                }
+
+                fn len_ins_del(t: $t, k: usize, v: usize) -> bool {
+                    let mut t = t.clone();
+                    let n = t.len();
+                    let present = t.contains_key(&k);
+                    t.ins(k, v);
+                    let after_ins = t.len() == if present {n} else {n + 1};
+                    t.del(&k);
+
+                    after_ins && t.len() == if present {n - 1} else {n} && t.is_empty() == (t.len() == 0)
+                }
+
+                fn clear_empty(t: $t, k: usize, v: usize) -> bool {
+                    let mut t = t.clone();
+                    t.clear();
+                    let cleared = t.is_empty() && t.len() == 0 && !t.contains_key(&k);
+                    t.ins(k, v);
+
+                    cleared && t.len() == 1 && t.find(&k) == Some(&v)
+                }
+
+                fn entry_or_insert_with_lazy(t: $t, k: usize) -> bool {
+                    let mut t = t.clone();
+                    let present = t.find(&k).is_some();
+                    let mut called = false;
+                    t.entry(k).or_insert_with(|| {called = true; 0});
+
+                    called != present
+                }
+
+                // The entry is occupied exactly when the key is there, and
+                // inserting through it returns the value that ends up in the map.
+                fn entry_occupied_vacant(t: $t, k: usize, v: usize) -> bool {
+                    let mut t = t.clone();
+                    let old = t.find(&k).cloned();
+                    let ok = match t.entry(k) {
+                        Entry::Occupied(mut e) => Some(e.insert(v)) == old,
+                        Entry::Vacant(e) => {
+                            *e.insert(0) = v;
+                            old.is_none()
+                        }
+                    };
+
+                    ok && t.find(&k) == Some(&v)
+                }
+
+                // Run a sequence of operations on the map and on std's `BTreeMap`
+                // and check that they agree all the time. Keys are small, so
+                // that operations often hit the same keys.
+                fn same_as_btreemap(ops: Vec<(bool, u8, usize)>) -> bool {
+                    let mut t = <$t as Map>::new();
+                    let mut m = BTreeMap::new();
+
+                    for (ins, k, v) in ops {
+                        let k = k as usize % 32;
+                        if ins {
+                            t.ins(k, v);
+                            m.insert(k, v);
+                        } else if t.del(&k) != m.remove(&k) {
+                            return false;
+                        }
+
+                        if t.len() != m.len() || (0 .. 32).any(|k| t.find(&k) != m.get(&k)) {
+                            return false;
+                        }
+                    }
+
+                    true
+                }
+            }
+        }
+    };
+}
+
+/// Tests that every `OrderedMap` implementation has to pass, with the same
+/// requirements as `map_tests`.
+#[cfg(test)]
+macro_rules! ordered_map_tests {
+    ($t:ty) => {
+        mod ordered_map_tests {
+            use super::*;
+            use map::{Map, OrderedMap};
+
+            use std::collections::BTreeMap;
+
+            fn model(ks: &[(u8, usize)]) -> ($t, BTreeMap<usize, usize>) {
+                let mut t = <$t as Map>::new();
+                let mut m = BTreeMap::new();
+                for &(k, v) in ks {
+                    t.ins(k as usize, v);
+                    m.insert(k as usize, v);
+                }
+
+                (t, m)
+            }
+
+            quickcheck! {
+                fn min_max(ks: Vec<(u8, usize)>) -> bool {
+                    let (t, m) = model(&ks);
+
+                    t.min() == m.iter().next() && t.max() == m.iter().next_back()
+                }
+
+                fn floor_ceiling(ks: Vec<(u8, usize)>, k: u8) -> bool {
+                    let (t, m) = model(&ks);
+                    let k = k as usize;
+
+                    t.floor(&k) == m.range(.. k + 1).next_back() &&
+                    t.ceiling(&k) == m.range(k ..).next()
+                }
+
+                fn pred_succ(ks: Vec<(u8, usize)>, k: u8) -> bool {
+                    let (t, m) = model(&ks);
+                    let k = k as usize;
+
+                    t.pred(&k) == m.range(.. k).next_back() &&
+                    t.succ(&k) == m.range(k + 1 ..).next()
+                }
+
+                fn range(ks: Vec<(u8, usize)>, a: u8, b: u8) -> bool {
+                    let (t, m) = model(&ks);
+                    let (low, high) = (a.min(b) as usize, a.max(b) as usize);
+
+                    t.range(low ..= high).eq(m.range(low ..= high)) &&
+                    t.range(low .. high).eq(m.range(low .. high)) &&
+                    t.range(low ..).eq(m.range(low ..)) &&
+                    t.range(..).eq(m.iter())
+                }
+
+                fn range_empty(ks: Vec<(u8, usize)>, a: u8, b: u8) -> bool {
+                    let (t, _) = model(&ks);
+
+                    a <= b || t.range(a as usize ..= b as usize).next() == None
+                }
+            }
+        }
+    };
+}
+
+#[macro_use]
+mod ordered;
+mod entry;
+mod set;
+mod compare;
+mod render;
+
+pub use self::ordered::OrderedMap;
+pub use self::entry::{Entry, OccupiedEntry, VacantEntry};
+pub use self::set::{Set, TreeSet};
+pub use self::compare::{Compare, Natural, Reversed};
+pub use self::treap::TreeBy;
+
 pub mod bst;
+pub mod scapegoat;
+pub mod llrb;
+pub mod treap;
+pub mod zip;
+pub mod order_stat;
+pub mod tst;
+pub mod interval;
+pub mod radix;
+pub mod veb;
+pub mod lru;
+
 
 pub trait Map {
     type Key;
     type Value;
 
     fn new() -> Self;
-    fn find(&self, &Self::Key) -> Option<&Self::Value>;
-    fn ins(&mut self, Self::Key, Self::Value) -> &mut Self;
-    fn del(&mut self, &Self::Key) -> Option<Self::Value>;
-}
\ No newline at end of file
+    fn find(&self, key: &Self::Key) -> Option<&Self::Value>;
+    fn find_mut(&mut self, key: &Self::Key) -> Option<&mut Self::Value>;
+    fn ins(&mut self, key: Self::Key, value: Self::Value) -> &mut Self;
+    fn del(&mut self, key: &Self::Key) -> Option<Self::Value>;
+
+    /// Returns the number of entries. There's no default: every map keeps its
+    /// own count, so that this takes O(1).
+    fn len(&self) -> usize;
+
+    /// Checks whether there are no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks whether there's an entry with the given key.
+    fn contains_key(&self, key: &Self::Key) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Removes all the entries. The default implementation replaces the map
+    /// with a new one.
+    fn clear(&mut self) where Self: Sized {
+        *self = Self::new();
+    }
+
+    /// Gets the entry of `key`, for in-place manipulation, looking the key up
+    /// only once.
+    fn entry(&mut self, key: Self::Key) -> Entry<'_, Self::Key, Self::Value>;
+
+    /// Returns the value of `key`, inserting `value` first if there's none.
+    /// Time: like `entry`
+    fn get_or_insert(&mut self, key: Self::Key, value: Self::Value) -> &mut Self::Value {
+        self.entry(key).or_insert(value)
+    }
+}
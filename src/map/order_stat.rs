@@ -0,0 +1,214 @@
+//! Order-statistic trees.
+
+use map::{Entry, Map};
+use map::treap::{size, Treap};
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use rand::Rng;
+
+use std::cmp::Ord;
+use std::cmp::Ordering::*;
+
+/// A `Treap` which also answers queries by rank. Its nodes store the sizes of
+/// their subtrees, which is enough to find the k-th smallest key (`select`) and
+/// to count the keys smaller than a given one (`rank`) by walking down a single
+/// path.
+///
+/// Time: all operations are O(log n) expected.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::Map;
+/// use aisd::map::order_stat::OrderStatTree;
+///
+/// let mut t = OrderStatTree::with_seed(42);
+/// for &x in &[50, 10, 40, 20, 30] {
+///     t.ins(x, ());
+/// }
+///
+/// // The median.
+/// assert_eq!(t.select(t.len() / 2), Some((&30, &())));
+///
+/// assert_eq!(t.rank(&40), 3);
+/// assert_eq!(t.rank(&35), 3);
+/// assert_eq!(t.select(5), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct OrderStatTree<K, V> {
+    treap: Treap<K, V>
+}
+
+impl<K, V> OrderStatTree<K, V> {
+    /// Creates an empty tree whose priorities are drawn from a generator with
+    /// the given seed, so that its shape is reproducible.
+    pub fn with_seed(seed: u64) -> OrderStatTree<K, V> {
+        OrderStatTree {treap: Treap::with_seed(seed)}
+    }
+
+    /// Creates an empty tree whose generator is seeded from `rng`, like
+    /// `Treap::with_rng`.
+    pub fn with_rng<R: Rng>(rng: &mut R) -> OrderStatTree<K, V> {
+        OrderStatTree {treap: Treap::with_rng(rng)}
+    }
+
+    /// Returns the number of entries.
+    /// Time: O(1)
+    pub fn len(&self) -> usize {
+        size(&self.treap.root)
+    }
+
+    /// Checks whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.treap.root.is_none()
+    }
+
+    /// Returns the entry with the k-th smallest key (counting from 0).
+    /// Time: O(log n) expected
+    pub fn select(&self, mut k: usize) -> Option<(&K, &V)> {
+        let mut current = &self.treap.root;
+        while let Some(n) = current {
+            let l = size(&n.left);
+            match k.cmp(&l) {
+                Less => current = &n.left,
+                Equal => return Some((&n.key, &n.value)),
+                Greater => {
+                    k -= l + 1;
+                    current = &n.right;
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<K: Ord, V> OrderStatTree<K, V> {
+    /// Counts the keys smaller than `key`.
+    /// Time: O(log n) expected
+    pub fn rank(&self, key: &K) -> usize {
+        let mut rank = 0;
+        let mut current = &self.treap.root;
+        while let Some(n) = current {
+            if *key <= n.key {
+                current = &n.left;
+            } else {
+                rank += size(&n.left) + 1;
+                current = &n.right;
+            }
+        }
+
+        rank
+    }
+
+    /// Checks that keys form a BST, priorities form a max-heap and sizes are right.
+    /// Time: O(size of the tree)
+    pub fn is_valid(&self) -> bool {
+        self.treap.is_valid()
+    }
+}
+
+impl<K: Ord, V> Map for OrderStatTree<K, V> {
+    type Key = K;
+    type Value = V;
+
+    /// Creates an empty tree with the generator of `rng::default_rng`, like
+    /// `Treap::new`.
+    fn new() -> OrderStatTree<K, V> {
+        OrderStatTree {treap: Treap::new()}
+    }
+
+    fn find(&self, key: &K) -> Option<&V> {
+        self.treap.find(key)
+    }
+
+    fn find_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.treap.find_mut(key)
+    }
+
+    fn ins(&mut self, key: K, value: V) -> &mut Self {
+        self.treap.ins(key, value);
+        self
+    }
+
+    fn del(&mut self, key: &K) -> Option<V> {
+        self.treap.del(key)
+    }
+
+    /// Time: O(1)
+    fn len(&self) -> usize {
+        self.treap.len()
+    }
+
+    /// Keeps the generator, so that a seeded tree stays reproducible.
+    fn clear(&mut self) {
+        self.treap.clear();
+    }
+
+    fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        self.treap.entry(key)
+    }
+}
+
+impl_ordered_map!(OrderStatTree, |t| t.treap.root.as_deref());
+
+impl<K: Ord + Arbitrary, V: Arbitrary> Arbitrary for OrderStatTree<K, V> {
+    fn arbitrary<G: Gen>(g: &mut G) -> OrderStatTree<K, V> {
+        OrderStatTree {treap: Arbitrary::arbitrary(g)}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map::Map;
+    use super::OrderStatTree;
+
+    map_tests!(OrderStatTree<usize, usize>);
+    ordered_map_tests!(OrderStatTree<usize, usize>);
+
+    // The keys in increasing order.
+    fn keys(t: &OrderStatTree<usize, usize>) -> Vec<usize> {
+        (0 .. t.len()).map(|k| *t.select(k).unwrap().0).collect()
+    }
+
+    quickcheck! {
+        fn is_valid_arbitrary(t: OrderStatTree<usize, usize>) -> bool {
+            t.is_valid()
+        }
+
+        fn is_valid_del(t: OrderStatTree<usize, usize>, ks: Vec<usize>) -> bool {
+            let mut t = t.clone();
+            ks.iter().all(|k| {
+                t.del(k);
+                t.is_valid()
+            })
+        }
+
+        fn select_sorted(ks: Vec<usize>) -> bool {
+            let mut t = OrderStatTree::new();
+            for &k in &ks {
+                t.ins(k, k);
+            }
+
+            let mut ks = ks;
+            ks.sort();
+            ks.dedup();
+
+            keys(&t) == ks && t.select(ks.len()) == None
+        }
+
+        fn rank_naive(t: OrderStatTree<usize, usize>, k: usize) -> bool {
+            t.rank(&k) == keys(&t).iter().filter(|&&x| x < k).count()
+        }
+
+        fn rank_select(t: OrderStatTree<usize, usize>, k: usize) -> bool {
+            match t.select(k) {
+                Some((key, _)) => t.rank(key) == k,
+                None => k >= t.len()
+            }
+        }
+    }
+}
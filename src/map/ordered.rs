@@ -0,0 +1,265 @@
+//! Maps whose keys are ordered.
+
+use map::Map;
+
+use std::cmp::Ord;
+use std::cmp::Ordering::*;
+use std::ops::Bound::*;
+use std::ops::RangeBounds;
+
+/// A map that can answer questions about the order of its keys.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::{Map, OrderedMap};
+/// use aisd::map::llrb::Llrb;
+///
+/// let mut t = Llrb::new();
+/// t.ins(10, "a").ins(20, "b").ins(30, "c");
+///
+/// assert_eq!(t.min(), Some((&10, &"a")));
+/// assert_eq!(t.floor(&25), Some((&20, &"b")));
+/// assert_eq!(t.ceiling(&20), Some((&20, &"b")));
+/// assert_eq!(t.succ(&20), Some((&30, &"c")));
+/// assert_eq!(t.pred(&10), None);
+///
+/// let keys: Vec<_> = t.range(15 ..= 30).map(|(k, _)| *k).collect();
+/// assert_eq!(keys, vec![20, 30]);
+/// ```
+pub trait OrderedMap: Map {
+    /// Returns the entry with the least key.
+    fn min(&self) -> Option<(&Self::Key, &Self::Value)>;
+
+    /// Returns the entry with the greatest key.
+    fn max(&self) -> Option<(&Self::Key, &Self::Value)>;
+
+    /// Returns the entry with the greatest key less than or equal to `key`.
+    fn floor(&self, key: &Self::Key) -> Option<(&Self::Key, &Self::Value)>;
+
+    /// Returns the entry with the least key greater than or equal to `key`.
+    fn ceiling(&self, key: &Self::Key) -> Option<(&Self::Key, &Self::Value)>;
+
+    /// Returns the entry with the greatest key strictly less than `key`.
+    fn pred(&self, key: &Self::Key) -> Option<(&Self::Key, &Self::Value)>;
+
+    /// Returns the entry with the least key strictly greater than `key`.
+    fn succ(&self, key: &Self::Key) -> Option<(&Self::Key, &Self::Value)>;
+
+    /// Iterates, in order, over the entries whose keys are in `range`, for example
+    /// `low ..= high`. Only the subtrees which may contain such keys are visited,
+    /// so it takes O(h + k) for a tree of height h and k entries in the range.
+    fn range<R: RangeBounds<Self::Key>>(&self, range: R)
+        -> impl Iterator<Item = (&Self::Key, &Self::Value)>;
+}
+
+/// A view of a node of a binary search tree, so that the trees can share the
+/// implementation of `OrderedMap`.
+pub(crate) trait BstNode {
+    type Key: Ord;
+    type Value;
+
+    fn key(&self) -> &Self::Key;
+    fn value(&self) -> &Self::Value;
+    fn left(&self) -> Option<&Self>;
+    fn right(&self) -> Option<&Self>;
+}
+
+fn entry<N: BstNode>(n: &N) -> (&N::Key, &N::Value) {
+    (n.key(), n.value())
+}
+
+pub(crate) fn min<N: BstNode>(root: Option<&N>) -> Option<(&N::Key, &N::Value)> {
+    let mut current = root?;
+    while let Some(l) = current.left() {
+        current = l;
+    }
+
+    Some(entry(current))
+}
+
+pub(crate) fn max<N: BstNode>(root: Option<&N>) -> Option<(&N::Key, &N::Value)> {
+    let mut current = root?;
+    while let Some(r) = current.right() {
+        current = r;
+    }
+
+    Some(entry(current))
+}
+
+/// The last entry whose key is less than `key` (or equal, if `or_equal` is true).
+/// Whenever we go right, the current node is the best candidate so far.
+pub(crate) fn below<'a, N: BstNode>(root: Option<&'a N>, key: &N::Key, or_equal: bool)
+    -> Option<(&'a N::Key, &'a N::Value)>
+{
+    let mut best = None;
+    let mut current = root;
+
+    while let Some(n) = current {
+        match n.key().cmp(key) {
+            Less => {
+                best = Some(n);
+                current = n.right();
+            },
+            Equal if or_equal => return Some(entry(n)),
+            _ => current = n.left()
+        }
+    }
+
+    best.map(entry)
+}
+
+/// The first entry whose key is greater than `key` (or equal, if `or_equal` is true).
+pub(crate) fn above<'a, N: BstNode>(root: Option<&'a N>, key: &N::Key, or_equal: bool)
+    -> Option<(&'a N::Key, &'a N::Value)>
+{
+    let mut best = None;
+    let mut current = root;
+
+    while let Some(n) = current {
+        match n.key().cmp(key) {
+            Greater => {
+                best = Some(n);
+                current = n.left();
+            },
+            Equal if or_equal => return Some(entry(n)),
+            _ => current = n.right()
+        }
+    }
+
+    best.map(entry)
+}
+
+/// An iterator over the entries of a tree whose keys are in a range. The stack
+/// holds the nodes whose entries and right subtrees are still to be visited.
+pub(crate) struct Range<'a, N: 'a, R> {
+    stack: Vec<&'a N>,
+    range: R
+}
+
+impl<'a, N: BstNode, R: RangeBounds<N::Key>> Range<'a, N, R> {
+    pub(crate) fn new(root: Option<&'a N>, range: R) -> Range<'a, N, R> {
+        let mut it = Range {stack: vec![], range};
+        it.descend(root);
+        it
+    }
+
+    /// Is `key` below the start of the range?
+    fn too_small(&self, key: &N::Key) -> bool {
+        match self.range.start_bound() {
+            Included(low) => key < low,
+            Excluded(low) => key <= low,
+            Unbounded => false
+        }
+    }
+
+    /// Is `key` beyond the end of the range?
+    fn too_big(&self, key: &N::Key) -> bool {
+        match self.range.end_bound() {
+            Included(high) => key > high,
+            Excluded(high) => key >= high,
+            Unbounded => false
+        }
+    }
+
+    /// Push the path to the least key in the range, skipping the nodes that are
+    /// too small (and their left subtrees).
+    fn descend(&mut self, mut current: Option<&'a N>) {
+        while let Some(n) = current {
+            if self.too_small(n.key()) {
+                current = n.right();
+            } else {
+                self.stack.push(n);
+                current = n.left();
+            }
+        }
+    }
+}
+
+impl<'a, N: BstNode, R: RangeBounds<N::Key>> Iterator for Range<'a, N, R> {
+    type Item = (&'a N::Key, &'a N::Value);
+
+    fn next(&mut self) -> Option<(&'a N::Key, &'a N::Value)> {
+        let n = self.stack.pop()?;
+        if self.too_big(n.key()) {
+            self.stack.clear();
+            return None;
+        }
+
+        self.descend(n.right());
+        Some(entry(n))
+    }
+}
+
+/// Implements `OrderedMap` for a tree, given an expression which turns `self`
+/// into the `Option<&N>` of its root.
+macro_rules! impl_ordered_map {
+    ($t:ident, |$s:ident| $root:expr) => {
+        impl<K: Ord, V> ::map::OrderedMap for $t<K, V> {
+            fn min(&self) -> Option<(&K, &V)> {
+                let $s = self;
+                ::map::ordered::min($root)
+            }
+
+            fn max(&self) -> Option<(&K, &V)> {
+                let $s = self;
+                ::map::ordered::max($root)
+            }
+
+            fn floor(&self, key: &K) -> Option<(&K, &V)> {
+                let $s = self;
+                ::map::ordered::below($root, key, true)
+            }
+
+            fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+                let $s = self;
+                ::map::ordered::above($root, key, true)
+            }
+
+            fn pred(&self, key: &K) -> Option<(&K, &V)> {
+                let $s = self;
+                ::map::ordered::below($root, key, false)
+            }
+
+            fn succ(&self, key: &K) -> Option<(&K, &V)> {
+                let $s = self;
+                ::map::ordered::above($root, key, false)
+            }
+
+            fn range<R: ::std::ops::RangeBounds<K>>(&self, range: R)
+                -> impl Iterator<Item = (&K, &V)>
+            {
+                let $s = self;
+                ::map::ordered::Range::new($root, range)
+            }
+        }
+    };
+}
+
+/// Implements `BstNode` for a node type with fields `key`, `value`, `left` and
+/// `right`, the last two being `Option<Box<Node>>`.
+macro_rules! impl_bst_node {
+    ($node:ident) => {
+        impl<K: Ord, V> ::map::ordered::BstNode for $node<K, V> {
+            type Key = K;
+            type Value = V;
+
+            fn key(&self) -> &K {
+                &self.key
+            }
+
+            fn value(&self) -> &V {
+                &self.value
+            }
+
+            fn left(&self) -> Option<&$node<K, V>> {
+                self.left.as_deref()
+            }
+
+            fn right(&self) -> Option<&$node<K, V>> {
+                self.right.as_deref()
+            }
+        }
+    };
+}
@@ -0,0 +1,362 @@
+//! Radix trees.
+
+use map::{Entry, Map};
+use map::entry;
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+#[derive(Clone, Debug)]
+struct Node<V> {
+    // The bytes on the edge from the parent to this node.
+    label: Vec<u8>,
+    value: Option<V>,
+    // Sorted by the first byte of their labels, which are all different.
+    children: Vec<Node<V>>
+}
+
+/// A map from byte strings, stored as a trie whose chains of nodes with one
+/// child and no value are merged into single edges labelled with many bytes.
+/// So every node either holds a value or branches, there are at most 2n nodes,
+/// and a lookup visits one node per branching instead of one per byte.
+///
+/// Besides the `Map` operations, it finds the longest key which is a prefix of a
+/// given string, which is what routing tables do with IP prefixes.
+///
+/// Time: `find`, `ins`, `del` and `longest_prefix_match` are O(length of the key
+/// * log of the size of the alphabet).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::Map;
+/// use aisd::map::radix::RadixTree;
+///
+/// let mut routes = RadixTree::new();
+/// routes.ins(vec![10], "internal").ins(vec![10, 1], "lab").ins(vec![], "default");
+///
+/// assert_eq!(routes.longest_prefix_match(&[10, 1, 7, 3]), Some((&[10, 1][..], &"lab")));
+/// assert_eq!(routes.longest_prefix_match(&[10, 2, 0, 1]), Some((&[10][..], &"internal")));
+/// assert_eq!(routes.longest_prefix_match(&[192, 168]), Some((&[][..], &"default")));
+/// ```
+#[derive(Clone, Debug)]
+pub struct RadixTree<V> {
+    root: Node<V>,
+    len: usize
+}
+
+impl<V> Default for RadixTree<V> {
+    fn default() -> RadixTree<V> {
+        RadixTree::new()
+    }
+}
+
+/// The length of the longest common prefix of `a` and `b`.
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+impl<V> Node<V> {
+    fn new(label: Vec<u8>, value: Option<V>) -> Node<V> {
+        Node {label, value, children: vec![]}
+    }
+
+    /// The place of the child whose label starts with `byte`, or where it would go.
+    fn child(&self, byte: u8) -> Result<usize, usize> {
+        self.children.binary_search_by_key(&byte, |c| c.label[0])
+    }
+
+    fn find(&self, key: &[u8]) -> Option<&V> {
+        let mut node = self;
+        let mut rest = key;
+        while let Some(&b) = rest.first() {
+            let c = &node.children[node.child(b).ok()?];
+            rest = rest.strip_prefix(&c.label[..])?;
+            node = c;
+        }
+
+        node.value.as_ref()
+    }
+
+    fn find_mut(&mut self, key: &[u8]) -> Option<&mut V> {
+        let mut node = self;
+        let mut rest = key;
+        while let Some(&b) = rest.first() {
+            let i = node.child(b).ok()?;
+            let c = &mut node.children[i];
+            rest = rest.strip_prefix(&c.label[..])?;
+            node = c;
+        }
+
+        node.value.as_mut()
+    }
+
+    /// The place for the value of `key`, relative to this node. The edge on the
+    /// way is split or the missing child added, so call it only to put a value there.
+    fn slot(&mut self, key: &[u8]) -> &mut Option<V> {
+        let b = match key.first() {
+            Some(&b) => b,
+            None => return &mut self.value
+        };
+
+        let i = match self.child(b) {
+            Ok(i) => i,
+            Err(i) => {
+                self.children.insert(i, Node::new(key.to_vec(), None));
+                return &mut self.children[i].value;
+            }
+        };
+
+        let c = &mut self.children[i];
+        let l = common_prefix(&c.label, key);
+        if l < c.label.len() {
+            // Split the edge: the common part leads to a new node, which gets
+            // the old child below it.
+            let rest = c.label.split_off(l);
+            let label = ::std::mem::replace(&mut c.label, rest);
+            let old = ::std::mem::replace(c, Node::new(label, None));
+            c.children.push(old);
+        }
+
+        c.slot(&key[l ..])
+    }
+
+    /// Removes `key`, relative to this node, and returns its value. Children left
+    /// without a value and with at most one child are removed or merged with
+    /// their child.
+    fn delete(&mut self, key: &[u8]) -> Option<V> {
+        let b = match key.first() {
+            Some(&b) => b,
+            None => return self.value.take()
+        };
+
+        let i = self.child(b).ok()?;
+        let c = &mut self.children[i];
+        let rest = key.strip_prefix(&c.label[..])?;
+        let result = c.delete(rest);
+
+        if c.value.is_none() {
+            match c.children.len() {
+                0 => {
+                    self.children.remove(i);
+                },
+                1 => {
+                    let mut only = c.children.pop().unwrap();
+                    let mut label = ::std::mem::take(&mut c.label);
+                    label.append(&mut only.label);
+                    only.label = label;
+                    *c = only;
+                },
+                _ => {}
+            }
+        }
+
+        result
+    }
+
+    /// Calls `f` with every key below this node (prefixed with `prefix`) and its
+    /// value, in increasing order.
+    fn visit<'a, F: FnMut(&[u8], &'a V)>(&'a self, prefix: &mut Vec<u8>, f: &mut F) {
+        prefix.extend_from_slice(&self.label);
+        if let Some(v) = &self.value {
+            f(prefix, v);
+        }
+        for c in &self.children {
+            c.visit(prefix, f);
+        }
+        prefix.truncate(prefix.len() - self.label.len());
+    }
+}
+
+impl<V> RadixTree<V> {
+    /// Finds the longest key which is a prefix of `s` and returns it (as a part
+    /// of `s`) with its value.
+    /// Time: O(length of `s` * log of the size of the alphabet)
+    pub fn longest_prefix_match<'a>(&self, s: &'a [u8]) -> Option<(&'a [u8], &V)> {
+        let mut node = &self.root;
+        let mut depth = 0;
+        let mut best = node.value.as_ref().map(|v| (0, v));
+        while let Some(&b) = s.get(depth) {
+            let c = match node.child(b) {
+                Ok(i) => &node.children[i],
+                Err(_) => break
+            };
+            if !s[depth ..].starts_with(&c.label) {
+                break;
+            }
+
+            depth += c.label.len();
+            node = c;
+            if let Some(v) = &node.value {
+                best = Some((depth, v));
+            }
+        }
+
+        best.map(|(l, v)| (&s[.. l], v))
+    }
+
+    /// Iterates over all the keys and their values, in increasing order of keys.
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<u8>, &V)> {
+        let mut found = vec![];
+        self.root.visit(&mut vec![], &mut |k, v| found.push((k.to_vec(), v)));
+        found.into_iter()
+    }
+
+    /// Insert a key which isn't in the tree yet and return its value.
+    fn ins_new(&mut self, key: Vec<u8>, value: V) -> &mut V {
+        self.len += 1;
+        self.root.slot(&key).insert(value)
+    }
+}
+
+impl<V> Map for RadixTree<V> {
+    type Key = Vec<u8>;
+    type Value = V;
+
+    fn new() -> RadixTree<V> {
+        RadixTree {root: Node::new(vec![], None), len: 0}
+    }
+
+    /// Time: O(1)
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn find(&self, key: &Vec<u8>) -> Option<&V> {
+        self.root.find(key)
+    }
+
+    fn find_mut(&mut self, key: &Vec<u8>) -> Option<&mut V> {
+        self.root.find_mut(key)
+    }
+
+    fn ins(&mut self, key: Vec<u8>, value: V) -> &mut Self {
+        if self.root.slot(&key).replace(value).is_none() {
+            self.len += 1;
+        }
+
+        self
+    }
+
+    fn del(&mut self, key: &Vec<u8>) -> Option<V> {
+        let result = self.root.delete(key);
+        if result.is_some() {
+            self.len -= 1;
+        }
+
+        result
+    }
+
+    fn entry(&mut self, key: Vec<u8>) -> Entry<'_, Vec<u8>, V> {
+        entry::lookup(self, key, RadixTree::find_mut, RadixTree::ins_new)
+    }
+}
+
+impl<V: Arbitrary> Arbitrary for RadixTree<V> {
+    fn arbitrary<G: Gen>(g: &mut G) -> RadixTree<V> {
+        let data: Vec<(Vec<u8>, V)> = Arbitrary::arbitrary(g);
+
+        let mut t = RadixTree::new();
+        for (k, v) in data {
+            t.ins(k, v);
+        }
+
+        t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map::Map;
+    use super::{Node, RadixTree};
+
+    use std::collections::BTreeMap;
+
+    // Keys over a small alphabet, so that they share prefixes.
+    fn key(k: &[u8]) -> Vec<u8> {
+        k.iter().take(6).map(|&c| c % 3).collect()
+    }
+
+    // Every node but the root has a nonempty label and either a value or two
+    // children, and children are sorted by distinct first bytes.
+    fn is_compressed<V>(t: &RadixTree<V>) -> bool {
+        fn check<V>(n: &Node<V>, root: bool) -> bool {
+            (root || (!n.label.is_empty() && (n.value.is_some() || n.children.len() >= 2))) &&
+            n.children.windows(2).all(|w| w[0].label[0] < w[1].label[0]) &&
+            n.children.iter().all(|c| check(c, false))
+        }
+
+        check(&t.root, true)
+    }
+
+    quickcheck! {
+        fn same_as_btreemap(ops: Vec<(bool, Vec<u8>, usize)>) -> bool {
+            let mut t = RadixTree::new();
+            let mut m = BTreeMap::new();
+
+            ops.into_iter().all(|(ins, k, v)| {
+                let k = key(&k);
+                let agree = if ins {
+                    t.ins(k.clone(), v);
+                    m.insert(k.clone(), v);
+                    true
+                } else {
+                    t.del(&k) == m.remove(&k)
+                };
+
+                agree && t.len() == m.len() && t.find(&k) == m.get(&k) && is_compressed(&t)
+            })
+        }
+
+        fn iter_sorted(ks: Vec<(Vec<u8>, usize)>) -> bool {
+            let t: RadixTree<usize> = ks.iter().fold(RadixTree::new(), |mut t, (k, v)| {t.ins(key(k), *v); t});
+            let m: BTreeMap<Vec<u8>, usize> = ks.iter().map(|(k, v)| (key(k), *v)).collect();
+
+            t.iter().eq(m.iter().map(|(k, v)| (k.clone(), v)))
+        }
+
+        fn longest_prefix_match_naive(ks: Vec<(Vec<u8>, usize)>, s: Vec<u8>) -> bool {
+            let mut t = RadixTree::new();
+            for (k, v) in &ks {
+                t.ins(key(k), *v);
+            }
+            let s = key(&s);
+
+            let naive = (0 ..= s.len()).rev().map(|l| &s[.. l]).find_map(|p| t.find(&p.to_vec()).map(|v| (p, v)));
+            t.longest_prefix_match(&s) == naive
+        }
+
+        fn del_all(t: RadixTree<usize>) -> bool {
+            let mut t = t.clone();
+            let keys: Vec<Vec<u8>> = t.iter().map(|(k, _)| k).collect();
+            for k in &keys {
+                t.del(k);
+            }
+
+            t.is_empty() && t.root.children.is_empty()
+        }
+
+        fn entry_counts(ks: Vec<Vec<u8>>) -> bool {
+            let mut t = RadixTree::new();
+            let mut m = BTreeMap::new();
+            for k in &ks {
+                *t.entry(key(k)).or_insert(0) += 1;
+                *m.entry(key(k)).or_insert(0) += 1;
+            }
+
+            t.len() == m.len() && t.iter().eq(m.iter().map(|(k, v)| (k.clone(), v))) &&
+            is_compressed(&t)
+        }
+
+        fn find_mut_find(t: RadixTree<usize>, k: Vec<u8>, v: usize) -> bool {
+            let mut t = t.clone();
+            let found = t.find(&k).is_some();
+            if let Some(x) = t.find_mut(&k) {
+                *x = v;
+            }
+
+            (t.find(&k) == Some(&v)) == found
+        }
+    }
+}
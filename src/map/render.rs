@@ -0,0 +1,96 @@
+//! Drawings of binary search trees, for debugging and teaching.
+//!
+//! Both work on any tree viewed through `BstNode`, so every tree can offer them
+//! with a few lines. They walk the tree with an explicit stack, so degenerate
+//! trees don't overflow the call stack.
+
+use map::ordered::BstNode;
+
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Write;
+
+/// Writes the tree as indented lines `key: value`, one per node, the root first
+/// and every node's left child above its right one. A missing child whose
+/// sibling exists is drawn as `·`, so that left and right can be told apart.
+///
+/// ```text
+/// 2: b
+/// ├── 1: a
+/// └── 3: c
+///     ├── ·
+///     └── 4: d
+/// ```
+pub(crate) fn fmt_tree<N>(root: Option<&N>, f: &mut fmt::Formatter) -> fmt::Result
+    where N: BstNode, N::Key: Display, N::Value: Display
+{
+    let root = match root {
+        None => return writeln!(f, "·"),
+        Some(r) => r
+    };
+
+    // The nodes to draw, with the prefix of their line and that of their
+    // children's lines.
+    let mut stack = vec![(Some(root), String::new(), String::new())];
+    while let Some((n, prefix, indent)) = stack.pop() {
+        let n = match n {
+            None => {
+                writeln!(f, "{}·", prefix)?;
+                continue;
+            },
+            Some(n) => n
+        };
+        writeln!(f, "{}{}: {}", prefix, n.key(), n.value())?;
+
+        if n.left().is_some() || n.right().is_some() {
+            stack.push((n.right(), format!("{}└── ", indent), format!("{}    ", indent)));
+            stack.push((n.left(), format!("{}├── ", indent), format!("{}│   ", indent)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Escapes a label for a double-quoted DOT string.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Renders the tree in the DOT language of Graphviz. Nodes are labeled
+/// `key: value`, and missing children of nodes with one child are drawn as
+/// points, so that the layout keeps left and right apart.
+pub(crate) fn to_dot<N>(root: Option<&N>) -> String
+    where N: BstNode, N::Key: Display, N::Value: Display
+{
+    let mut out = String::from("digraph {\n    node [shape=box];\n");
+
+    // Every node gets its number when its parent writes the edge to it, so the
+    // edges of a node are written left to right, which is how Graphviz lays
+    // them out.
+    let mut next = 1;
+    let mut stack: Vec<(&N, usize)> = root.into_iter().map(|r| (r, 0)).collect();
+    while let Some((n, id)) = stack.pop() {
+        let label = escape(&format!("{}: {}", n.key(), n.value()));
+        let _ = writeln!(out, "    n{} [label=\"{}\"];", id, label);
+        if n.left().is_none() && n.right().is_none() {
+            continue;
+        }
+
+        for child in [n.left(), n.right()].iter() {
+            match child {
+                Some(c) => {
+                    let _ = writeln!(out, "    n{} -> n{};", id, next);
+                    stack.push((c, next));
+                    next += 1;
+                },
+                None => {
+                    let _ = writeln!(out, "    nil{} [shape=point];", id);
+                    let _ = writeln!(out, "    n{} -> nil{};", id, id);
+                }
+            }
+        }
+    }
+
+    out.push_str("}\n");
+    out
+}
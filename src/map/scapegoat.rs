@@ -0,0 +1,392 @@
+//! Scapegoat trees.
+
+use map::{Entry, Map};
+use map::entry;
+use map::bst::Node;
+use map::bst::Node::{E, N};
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use std::cmp::Ord;
+use std::cmp::Ordering;
+use std::cmp::Ordering::*;
+use std::mem;
+
+/// A node is α-weight-balanced if none of its subtrees has more than α times as
+/// many nodes as the node itself.
+const ALPHA: f64 = 2.0 / 3.0;
+
+/// A scapegoat tree is a binary search tree that keeps its height logarithmic
+/// without storing anything in its nodes (it uses the plain `bst::Node`). Instead
+/// of rotating, it rebuilds whole subtrees:
+///
+/// - When an insertion creates a node deeper than log_{1/α}(max size), we walk
+///   back up to find a node that isn't α-weight-balanced (the scapegoat) and
+///   rebuild its subtree into a perfectly balanced one.
+/// - When deletions shrink the tree below α times its max size, we rebuild
+///   the whole tree.
+///
+/// Time: `find` is O(log n), `ins` and `del` are O(log n) amortized.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::Map;
+/// use aisd::map::scapegoat::ScapegoatTree;
+///
+/// let mut t = ScapegoatTree::new();
+///
+/// // Sorted insertions degenerate a plain BST into a list, but not a scapegoat tree.
+/// for i in 0 .. 1000 {
+///     t.ins(i, i * i);
+/// }
+///
+/// assert_eq!(t.find(&30), Some(&900));
+/// assert!(t.height() <= 19);
+///
+/// assert_eq!(t.del(&30), Some(900));
+/// assert_eq!(t.find(&30), None);
+/// assert_eq!(t.size(), 999);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ScapegoatTree<K, V> {
+    tree: Node<K, V>,
+    size: usize,
+    max_size: usize
+}
+
+/// The way from the root of a subtree down to one of its nodes, last step first.
+type Way = Vec<Ordering>;
+
+/// The outcome of inserting into a subtree.
+enum Ins {
+    /// The key was already present and its value got replaced.
+    Replaced,
+    /// A new node was inserted and no rebuilding is needed (anymore). Carries
+    /// the way to the new node.
+    Inserted(Way),
+    /// A new node was inserted too deep and we're looking for the scapegoat.
+    /// Carries the way to the new node, the size of the current subtree and
+    /// the index of the new node in it.
+    TooDeep(Way, usize, usize)
+}
+
+/// The way to the entry with index `i` in a tree built by `build` from `n` entries.
+fn built_way(mut n: usize, mut i: usize) -> Way {
+    let mut way = vec![];
+    while i != n / 2 {
+        if i < n / 2 {
+            way.push(Less);
+            n /= 2;
+        } else {
+            way.push(Greater);
+            i -= n / 2 + 1;
+            n -= n / 2 + 1;
+        }
+    }
+
+    way.reverse();
+    way
+}
+
+/// The value of the node at the end of the way.
+fn follow<K, V>(mut t: &mut Node<K, V>, way: Way) -> &mut V {
+    for ord in way.into_iter().rev() {
+        t = match (ord, t) {
+            (Less, N(_, _, l, _)) => l,
+            (_, N(_, _, _, r)) => r,
+            (_, E) => unreachable!()
+        };
+    }
+
+    match t {
+        N(_, v, _, _) => v,
+        E => unreachable!()
+    }
+}
+
+impl<K, V> ScapegoatTree<K, V> {
+    /// Returns the number of entries in the tree.
+    /// Time: O(1)
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Computes the height of the tree. The empty tree has height 0.
+    /// Time: O(size of the tree)
+    pub fn height(&self) -> usize {
+        self.tree.height()
+    }
+
+    /// The maximal allowed depth of a node in a tree with the given max size.
+    fn depth_limit(max_size: usize) -> usize {
+        ((max_size as f64).ln() / (1.0 / ALPHA).ln()).floor() as usize
+    }
+
+    /// Move all entries of `t` into `acc`, in order.
+    fn flatten(t: Node<K, V>, acc: &mut Vec<(K, V)>) {
+        if let Some((k, v, l, r)) = t.into_parts() {
+            ScapegoatTree::flatten(l, acc);
+            acc.push((k, v));
+            ScapegoatTree::flatten(r, acc);
+        }
+    }
+
+    /// Rebuild `t`, which has `n` nodes, into a perfectly balanced tree.
+    /// Time: O(n)
+    fn rebuild(t: &mut Node<K, V>, n: usize) {
+        let mut v = Vec::with_capacity(n);
+        ScapegoatTree::flatten(mem::replace(t, E), &mut v);
+        *t = Node::build(&mut v.into_iter(), n);
+    }
+}
+
+impl<K: Ord, V> ScapegoatTree<K, V> {
+    /// Builds a perfectly balanced tree from entries sorted by strictly
+    /// increasing keys, without comparing them.
+    /// Time: O(n)
+    ///
+    /// The order is checked only in debug builds; in release builds unsorted
+    /// input gives a tree which isn't a search tree.
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> ScapegoatTree<K, V> {
+        let v: Vec<(K, V)> = iter.into_iter().collect();
+        debug_assert!(v.windows(2).all(|w| w[0].0 < w[1].0),
+                      "ScapegoatTree::from_sorted_iter: the keys aren't strictly increasing");
+
+        let size = v.len();
+        ScapegoatTree {
+            tree: Node::build(&mut v.into_iter(), size),
+            size,
+            max_size: size
+        }
+    }
+
+    /// Insert into `t`, whose root is at the given depth, rebuilding the scapegoat
+    /// if the new node ends up deeper than `limit`.
+    fn ins_aux(t: &mut Node<K, V>, key: K, value: V, depth: usize, limit: usize) -> Ins {
+        match t {
+            E => {
+                *t = N(key, value, Box::new(E), Box::new(E));
+                if depth > limit {Ins::TooDeep(vec![], 1, 0)} else {Ins::Inserted(vec![])}
+            },
+            N(k, v, l, r) => {
+                let ord = key.cmp(k);
+                let (result, sibling) = match ord {
+                    Less => (ScapegoatTree::ins_aux(l, key, value, depth + 1, limit), r),
+                    Greater => (ScapegoatTree::ins_aux(r, key, value, depth + 1, limit), l),
+                    Equal => {
+                        *v = value;
+                        return Ins::Replaced;
+                    }
+                };
+
+                match result {
+                    Ins::TooDeep(mut way, s, i) => {
+                        let m = sibling.size();
+                        let n = s + m + 1;
+                        let i = if ord == Less {i} else {m + 1 + i};
+                        if s as f64 > ALPHA * n as f64 {
+                            ScapegoatTree::rebuild(t, n);
+                            Ins::Inserted(built_way(n, i))
+                        } else {
+                            way.push(ord);
+                            Ins::TooDeep(way, n, i)
+                        }
+                    },
+                    Ins::Inserted(mut way) => {
+                        way.push(ord);
+                        Ins::Inserted(way)
+                    },
+                    Ins::Replaced => Ins::Replaced
+                }
+            }
+        }
+    }
+
+    /// The depth limit for the next insertion.
+    fn limit(&self) -> usize {
+        ScapegoatTree::<K, V>::depth_limit(self.max_size.max(self.size + 1))
+    }
+
+    /// Insert a key which isn't in the tree yet and return its value.
+    fn ins_new(&mut self, key: K, value: V) -> &mut V {
+        let limit = self.limit();
+        self.size += 1;
+        self.max_size = self.max_size.max(self.size);
+
+        match ScapegoatTree::ins_aux(&mut self.tree, key, value, 0, limit) {
+            Ins::Inserted(way) | Ins::TooDeep(way, _, _) => follow(&mut self.tree, way),
+            Ins::Replaced => unreachable!()
+        }
+    }
+}
+
+impl<K: Ord, V> Map for ScapegoatTree<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn new() -> ScapegoatTree<K, V> {
+        ScapegoatTree {
+            tree: E,
+            size: 0,
+            max_size: 0
+        }
+    }
+
+    fn find(&self, key: &K) -> Option<&V> {
+        self.tree.find(key)
+    }
+
+    fn find_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.tree.find_mut(key)
+    }
+
+    fn ins(&mut self, key: K, value: V) -> &mut Self {
+        let limit = self.limit();
+
+        match ScapegoatTree::ins_aux(&mut self.tree, key, value, 0, limit) {
+            Ins::Replaced => {},
+            _ => {
+                self.size += 1;
+                self.max_size = self.max_size.max(self.size);
+            }
+        }
+
+        self
+    }
+
+    fn del(&mut self, key: &K) -> Option<V> {
+        let result = self.tree.del(key);
+
+        if result.is_some() {
+            self.size -= 1;
+            if (self.size as f64) < ALPHA * self.max_size as f64 {
+                ScapegoatTree::rebuild(&mut self.tree, self.size);
+                self.max_size = self.size;
+            }
+        }
+
+        result
+    }
+
+    /// Time: O(1)
+    fn len(&self) -> usize {
+        self.size
+    }
+
+    /// A vacant entry inserts from the root, because the tree may get rebuilt.
+    fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        entry::lookup(self, key, ScapegoatTree::find_mut, ScapegoatTree::ins_new)
+    }
+}
+
+impl_ordered_map!(ScapegoatTree, |t| t.tree.as_node());
+
+impl<K: Ord + Arbitrary, V: Arbitrary> Arbitrary for ScapegoatTree<K, V> {
+    fn arbitrary<G: Gen>(g: &mut G) -> ScapegoatTree<K, V> {
+        let data: Vec<(K, V)> = Arbitrary::arbitrary(g);
+
+        let mut t = ScapegoatTree::new();
+        for (k, v) in data {
+            t.ins(k, v);
+        }
+
+        t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map::Map;
+    use super::ScapegoatTree;
+
+    map_tests!(ScapegoatTree<usize, usize>);
+    ordered_map_tests!(ScapegoatTree<usize, usize>);
+
+    // The height is logarithmic in the size.
+    fn balanced(t: &ScapegoatTree<usize, usize>) -> bool {
+        t.height() <= ScapegoatTree::<usize, usize>::depth_limit(t.size) + 2
+    }
+
+    quickcheck! {
+        fn size_new() -> bool {
+            (ScapegoatTree::new() as ScapegoatTree<usize, usize>).size() == 0
+        }
+
+        fn size_is_size(t: ScapegoatTree<usize, usize>) -> bool {
+            t.size() == t.tree.size()
+        }
+
+        fn size_ins(t: ScapegoatTree<usize, usize>, k: usize, v: usize) -> bool {
+            let mut t = t.clone();
+            let n = t.size();
+            let present = t.find(&k).is_some();
+
+            t.ins(k, v).size() == if present {n} else {n + 1}
+        }
+
+        fn size_del(t: ScapegoatTree<usize, usize>, k: usize) -> bool {
+            let mut t = t.clone();
+            let n = t.size();
+
+            match t.del(&k) {
+                Some(_) => t.size() + 1 == n,
+                None => t.size() == n
+            }
+        }
+
+        fn balanced_arbitrary(t: ScapegoatTree<usize, usize>) -> bool {
+            balanced(&t)
+        }
+
+        fn balanced_sorted(n: usize) -> bool {
+            let mut t = ScapegoatTree::new();
+            for i in 0 .. n {
+                t.ins(i, i);
+            }
+
+            balanced(&t)
+        }
+
+        fn from_sorted_iter_ins(ks: Vec<(usize, usize)>) -> bool {
+            let mut sorted = ks.clone();
+            sorted.sort_by_key(|e| e.0);
+            sorted.dedup_by_key(|e| e.0);
+            let t = ScapegoatTree::from_sorted_iter(sorted.iter().cloned());
+
+            let n = sorted.len();
+            let perfect = (usize::BITS - n.leading_zeros()) as usize;
+
+            t.size() == n && t.height() == perfect && t.tree.iter().eq(sorted.iter().map(|(k, v)| (k, v)))
+        }
+
+        fn from_sorted_iter_then_ops(n: u8, ops: Vec<(bool, u8)>) -> bool {
+            let mut t = ScapegoatTree::from_sorted_iter((0 .. n as usize).map(|i| (i, i)));
+            ops.iter().all(|&(ins, k)| {
+                if ins {
+                    t.ins(k as usize, 0);
+                } else {
+                    t.del(&(k as usize));
+                }
+
+                balanced(&t) && t.size() == t.tree.size()
+            })
+        }
+
+        #[should_panic]
+        fn from_sorted_iter_unsorted() -> bool {
+            ScapegoatTree::from_sorted_iter(vec![(2, ()), (1, ())]).size() == 2
+        }
+
+        fn balanced_del(t: ScapegoatTree<usize, usize>, ks: Vec<usize>) -> bool {
+            let mut t = t.clone();
+            for k in ks {
+                t.del(&k);
+            }
+
+            balanced(&t)
+        }
+    }
+}
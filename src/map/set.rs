@@ -0,0 +1,258 @@
+//! Sets, and sets of ordered elements with set algebra by merging.
+
+use map::{Entry, OrderedMap};
+use map::llrb::Llrb;
+
+use std::cmp::Ordering::*;
+use std::iter::{FromIterator, Peekable};
+use std::marker::PhantomData;
+
+/// A set of elements.
+pub trait Set {
+    type Elem;
+
+    /// Creates an empty set.
+    fn new() -> Self;
+
+    /// Checks whether `x` is in the set.
+    fn contains(&self, x: &Self::Elem) -> bool;
+
+    /// Adds `x` to the set. Returns whether it wasn't there before.
+    fn insert(&mut self, x: Self::Elem) -> bool;
+
+    /// Removes `x` from the set. Returns whether it was there.
+    fn remove(&mut self, x: &Self::Elem) -> bool;
+
+    /// Returns the number of elements.
+    fn len(&self) -> usize;
+
+    /// Checks whether there are no elements.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A set of ordered elements, kept as the keys of an ordered map with `()`
+/// values, by default a left-leaning red-black tree. Any `OrderedMap` will do,
+/// like `TreeSet<K, Treap<K, ()>>`.
+///
+/// The elements come out of the map in order, so the set algebra merges two
+/// ordered sequences, like the merge of mergesort, in O(n + m) time for sets
+/// of n and m elements, instead of looking every element of one up in the
+/// other. The union, intersection and difference are iterators, which can be
+/// collected into a new set.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::{Set, TreeSet};
+///
+/// let a: TreeSet<u32> = vec![1, 2, 3, 5, 8].into_iter().collect();
+/// let b: TreeSet<u32> = vec![2, 3, 5, 7].into_iter().collect();
+///
+/// assert_eq!(a.union(&b).cloned().collect::<Vec<_>>(), vec![1, 2, 3, 5, 7, 8]);
+/// assert_eq!(a.intersection(&b).cloned().collect::<Vec<_>>(), vec![2, 3, 5]);
+/// assert_eq!(a.difference(&b).cloned().collect::<Vec<_>>(), vec![1, 8]);
+///
+/// let c: TreeSet<u32> = a.intersection(&b).cloned().collect();
+/// assert!(c.is_subset(&a) && c.is_subset(&b) && !a.is_subset(&b));
+/// assert!(c.contains(&5) && c.len() == 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TreeSet<K, M = Llrb<K, ()>> {
+    map: M,
+    elem: PhantomData<K>
+}
+
+impl<K: Ord, M: OrderedMap<Key = K, Value = ()>> Set for TreeSet<K, M> {
+    type Elem = K;
+
+    fn new() -> Self {
+        TreeSet {map: M::new(), elem: PhantomData}
+    }
+
+    fn contains(&self, x: &K) -> bool {
+        self.map.contains_key(x)
+    }
+
+    fn insert(&mut self, x: K) -> bool {
+        match self.map.entry(x) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(e) => {
+                e.insert(());
+                true
+            }
+        }
+    }
+
+    fn remove(&mut self, x: &K) -> bool {
+        self.map.del(x).is_some()
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+}
+
+impl<K: Ord, M: OrderedMap<Key = K, Value = ()>> TreeSet<K, M> {
+    /// Iterates over the elements in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = &K> {
+        self.map.range(..).map(|(k, _)| k)
+    }
+
+    /// Returns the least element.
+    pub fn min(&self) -> Option<&K> {
+        self.map.min().map(|(k, _)| k)
+    }
+
+    /// Returns the greatest element.
+    pub fn max(&self) -> Option<&K> {
+        self.map.max().map(|(k, _)| k)
+    }
+
+    /// Iterates over the elements of either set, in increasing order.
+    /// Time: O(n + m)
+    pub fn union<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a K> {
+        Merge::new(self.iter(), other.iter(), Op::Union)
+    }
+
+    /// Iterates over the elements of both sets, in increasing order.
+    /// Time: O(n + m)
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a K> {
+        Merge::new(self.iter(), other.iter(), Op::Intersection)
+    }
+
+    /// Iterates over the elements of this set which aren't in `other`, in
+    /// increasing order.
+    /// Time: O(n + m)
+    pub fn difference<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a K> {
+        Merge::new(self.iter(), other.iter(), Op::Difference)
+    }
+
+    /// Checks whether every element of this set is in `other`.
+    /// Time: O(n + m)
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.difference(other).next().is_none()
+    }
+}
+
+impl<K: Ord, M: OrderedMap<Key = K, Value = ()>> FromIterator<K> for TreeSet<K, M> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut set = TreeSet::new();
+        for x in iter {
+            set.insert(x);
+        }
+        set
+    }
+}
+
+impl<K: Ord, M: OrderedMap<Key = K, Value = ()>> PartialEq for TreeSet<K, M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Union,
+    Intersection,
+    Difference
+}
+
+/// Merges two increasing sequences without repeats, keeping the elements
+/// which `op` asks for.
+struct Merge<I: Iterator, J: Iterator> {
+    a: Peekable<I>,
+    b: Peekable<J>,
+    op: Op
+}
+
+impl<I: Iterator, J: Iterator> Merge<I, J> {
+    fn new(a: I, b: J, op: Op) -> Merge<I, J> {
+        Merge {a: a.peekable(), b: b.peekable(), op}
+    }
+}
+
+impl<'a, K: Ord + 'a, I, J> Iterator for Merge<I, J>
+    where I: Iterator<Item = &'a K>, J: Iterator<Item = &'a K>
+{
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<&'a K> {
+        loop {
+            let order = match (self.a.peek(), self.b.peek()) {
+                (None, None) => return None,
+                // Nothing more can be kept.
+                (None, Some(_)) if self.op != Op::Union => return None,
+                (Some(_), None) if self.op == Op::Intersection => return None,
+                (Some(_), None) => Less,
+                (None, Some(_)) => Greater,
+                (Some(x), Some(y)) => x.cmp(y)
+            };
+            match (order, self.op) {
+                // Only in the first sequence.
+                (Less, Op::Intersection) => {self.a.next();},
+                (Less, _) => return self.a.next(),
+                // Only in the second one.
+                (Greater, Op::Union) => return self.b.next(),
+                (Greater, _) => {self.b.next();},
+                // In both.
+                (Equal, Op::Difference) => {
+                    self.a.next();
+                    self.b.next();
+                },
+                (Equal, _) => {
+                    self.b.next();
+                    return self.a.next();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map::treap::Treap;
+
+    use std::collections::BTreeSet;
+
+    fn sets(a: &[u8], b: &[u8]) -> (TreeSet<u8>, TreeSet<u8>, BTreeSet<u8>, BTreeSet<u8>) {
+        (a.iter().cloned().collect(), b.iter().cloned().collect(),
+         a.iter().cloned().collect(), b.iter().cloned().collect())
+    }
+
+    quickcheck! {
+        fn algebra_btreeset(a: Vec<u8>, b: Vec<u8>) -> bool {
+            let (s, t, x, y) = sets(&a, &b);
+
+            s.union(&t).eq(x.union(&y)) &&
+            s.intersection(&t).eq(x.intersection(&y)) &&
+            s.difference(&t).eq(x.difference(&y)) &&
+            t.difference(&s).eq(y.difference(&x)) &&
+            s.is_subset(&t) == x.is_subset(&y) &&
+            t.is_subset(&s) == y.is_subset(&x)
+        }
+
+        fn subset_of_union(a: Vec<u8>, b: Vec<u8>) -> bool {
+            let (s, t, _, _) = sets(&a, &b);
+            let u: TreeSet<u8> = s.union(&t).cloned().collect();
+            let i: TreeSet<u8> = s.intersection(&t).cloned().collect();
+
+            s.is_subset(&u) && t.is_subset(&u) && i.is_subset(&s) && i.is_subset(&t) &&
+            u.len() + i.len() == s.len() + t.len()
+        }
+
+        fn insert_remove_btreeset(ops: Vec<(bool, u8)>) -> bool {
+            let mut s: TreeSet<u8, Treap<u8, ()>> = TreeSet::new();
+            let mut x = BTreeSet::new();
+            ops.into_iter().all(|(ins, k)| {
+                let k = k % 32;
+                let same = if ins {s.insert(k) == x.insert(k)} else {s.remove(&k) == x.remove(&k)};
+                same && s.len() == x.len() && s.iter().eq(x.iter()) &&
+                s.min() == x.iter().next() && s.max() == x.iter().next_back()
+            })
+        }
+    }
+}
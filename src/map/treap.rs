@@ -0,0 +1,661 @@
+//! Treaps.
+
+use map::{Compare, Entry, Map, Natural};
+use map::{entry, render};
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use rand::Rng;
+
+use rng;
+use rng::Xoshiro256;
+
+use std::cmp::Ord;
+use std::cmp::Ordering::*;
+use std::fmt;
+use std::fmt::Display;
+
+/// Nodes also store the sizes of their subtrees, which gives `len` in O(1) and
+/// lets `OrderStatTree` find keys by their ranks.
+#[derive(Clone, Debug)]
+pub(crate) struct Node<K, V> {
+    pub(crate) key: K,
+    pub(crate) value: V,
+    priority: u64,
+    size: usize,
+    pub(crate) left: Link<K, V>,
+    pub(crate) right: Link<K, V>
+}
+
+pub(crate) type Link<K, V> = Option<Box<Node<K, V>>>;
+
+impl_bst_node!(Node);
+
+/// The number of nodes in `h`.
+pub(crate) fn size<K, V>(h: &Link<K, V>) -> usize {
+    h.as_ref().map_or(0, |n| n.size)
+}
+
+fn update<K, V>(h: &mut Node<K, V>) {
+    h.size = 1 + size(&h.left) + size(&h.right);
+}
+
+/// A treap is a binary search tree on keys and a heap on random priorities drawn at
+/// insertion. Its shape is that of a BST built by inserting the keys in random order,
+/// so its expected height is O(log n) whatever the order of insertions.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::Map;
+/// use aisd::map::treap::Treap;
+///
+/// let mut t = Treap::with_seed(42);
+/// for i in 0 .. 1000 {
+///     t.ins(i, i * i);
+/// }
+///
+/// assert_eq!(t.find(&30), Some(&900));
+/// assert_eq!(t.del(&30), Some(900));
+/// assert_eq!(t.find(&30), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Treap<K, V> {
+    pub(crate) root: Link<K, V>,
+    rng: Xoshiro256
+}
+
+/// Split `h` into the parts with keys less and greater than `key` (which is not in `h`).
+fn split<K, V, C: Compare<K>>(h: Link<K, V>, key: &K, cmp: &C) -> (Link<K, V>, Link<K, V>) {
+    match h {
+        None => (None, None),
+        Some(mut n) => {
+            if cmp.compare(&n.key, key) == Less {
+                let (l, r) = split(n.right.take(), key, cmp);
+                n.right = l;
+                update(&mut n);
+                (Some(n), r)
+            } else {
+                let (l, r) = split(n.left.take(), key, cmp);
+                n.left = r;
+                update(&mut n);
+                (l, Some(n))
+            }
+        }
+    }
+}
+
+/// Insert `x` (whose key is not in the treap) below `slot` and return its value.
+/// Instead of adding a leaf and rotating it up while its priority is bigger than
+/// its parent's, go down to where the rotations would stop and split the subtree
+/// found there. The resulting shape is the same. Every node on the way gets one
+/// more descendant.
+fn insert<'a, K, V, C: Compare<K>>(slot: &'a mut Link<K, V>, mut x: Box<Node<K, V>>,
+                                   cmp: &C) -> &'a mut V {
+    // Equal means that `x` goes right here (its key isn't in the tree).
+    let ord = match slot {
+        Some(n) if n.priority >= x.priority => cmp.compare(&x.key, &n.key),
+        _ => Equal
+    };
+
+    match (ord, slot) {
+        (Less, Some(n)) => {
+            n.size += 1;
+            insert(&mut n.left, x, cmp)
+        },
+        (Greater, Some(n)) => {
+            n.size += 1;
+            insert(&mut n.right, x, cmp)
+        },
+        (_, slot) => {
+            let (l, r) = split(slot.take(), &x.key, cmp);
+            x.left = l;
+            x.right = r;
+            update(&mut x);
+            &mut slot.insert(x).value
+        }
+    }
+}
+
+/// Merge two treaps such that all keys in `l` are less than all keys in `r`.
+fn merge<K, V>(l: Link<K, V>, r: Link<K, V>) -> Link<K, V> {
+    match (l, r) {
+        (None, r) => r,
+        (l, None) => l,
+        (Some(mut l), Some(mut r)) => {
+            if l.priority > r.priority {
+                l.right = merge(l.right.take(), Some(r));
+                update(&mut l);
+                Some(l)
+            } else {
+                r.left = merge(Some(l), r.left.take());
+                update(&mut r);
+                Some(r)
+            }
+        }
+    }
+}
+
+fn delete<K, V, C: Compare<K>>(h: &mut Link<K, V>, key: &K, cmp: &C) -> Option<V> {
+    let ord = match h {
+        None => return None,
+        Some(n) => cmp.compare(key, &n.key)
+    };
+
+    match (ord, h) {
+        (Less, Some(n)) => {
+            let result = delete(&mut n.left, key, cmp);
+            update(n);
+            result
+        },
+        (Greater, Some(n)) => {
+            let result = delete(&mut n.right, key, cmp);
+            update(n);
+            result
+        },
+        (_, h) => h.take().map(|n| {
+            let n = *n;
+            *h = merge(n.left, n.right);
+            n.value
+        })
+    }
+}
+
+impl<K, V> Treap<K, V> {
+    /// Creates an empty treap whose priorities are drawn from a generator with
+    /// the given seed, so that its shape is reproducible.
+    pub fn with_seed(seed: u64) -> Treap<K, V> {
+        Treap {
+            root: None,
+            rng: Xoshiro256::seed_from_u64(seed)
+        }
+    }
+
+    /// Creates an empty treap whose generator is seeded from `rng`. Use it with
+    /// `rand::thread_rng()` when the keys may be chosen by an adversary who
+    /// knows the default seed.
+    pub fn with_rng<R: Rng>(rng: &mut R) -> Treap<K, V> {
+        Treap::with_seed(rng.gen())
+    }
+
+    /// Computes the height of the treap. The empty treap has height 0.
+    /// Time: O(size of the treap)
+    pub fn height(&self) -> usize {
+        fn height<K, V>(h: &Link<K, V>) -> usize {
+            match h {
+                None => 0,
+                Some(n) => 1 + height(&n.left).max(height(&n.right))
+            }
+        }
+
+        height(&self.root)
+    }
+}
+
+impl<K: Ord, V> Treap<K, V> {
+    /// Checks that keys form a BST, priorities form a max-heap and sizes are right.
+    /// Time: O(size of the treap)
+    pub fn is_valid(&self) -> bool {
+        fn check<'a, K: Ord, V>(h: &'a Link<K, V>, low: Option<&'a K>, high: Option<&'a K>,
+                                max: u64) -> bool {
+            match h {
+                None => true,
+                Some(n) => {
+                    low.is_none_or(|k| *k < n.key) && high.is_none_or(|k| n.key < *k) &&
+                    n.priority <= max &&
+                    n.size == 1 + size(&n.left) + size(&n.right) &&
+                    check(&n.left, low, Some(&n.key), n.priority) &&
+                    check(&n.right, Some(&n.key), high, n.priority)
+                }
+            }
+        }
+
+        check(&self.root, None, None, u64::MAX)
+    }
+
+    /// Insert a key which isn't in the treap yet and return its value.
+    fn ins_new(&mut self, key: K, value: V) -> &mut V {
+        let x = Box::new(Node {key, value, priority: self.rng.gen(), size: 1, left: None, right: None});
+        insert(&mut self.root, x, &Natural)
+    }
+
+    /// Renders the treap in the DOT language of Graphviz, like
+    /// `bst::Tree::to_dot`.
+    /// Time: O(size of the treap)
+    pub fn to_dot(&self) -> String where K: Display, V: Display {
+        render::to_dot(self.root.as_deref())
+    }
+}
+
+/// Draws the treap as indented lines, like `bst::Tree`.
+impl<K: Ord + Display, V: Display> fmt::Display for Treap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        render::fmt_tree(self.root.as_deref(), f)
+    }
+}
+
+impl<K: Ord, V> Map for Treap<K, V> {
+    type Key = K;
+    type Value = V;
+
+    /// Creates an empty treap with the generator of `rng::default_rng`, so
+    /// that the same operations give the same shape in every run.
+    fn new() -> Treap<K, V> {
+        Treap::with_seed(rng::DEFAULT_SEED)
+    }
+
+    fn find(&self, key: &K) -> Option<&V> {
+        let mut current = &self.root;
+        while let Some(n) = current {
+            match key.cmp(&n.key) {
+                Less => current = &n.left,
+                Equal => return Some(&n.value),
+                Greater => current = &n.right
+            }
+        }
+
+        None
+    }
+
+    fn find_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut current = &mut self.root;
+        while let Some(n) = current {
+            match key.cmp(&n.key) {
+                Less => current = &mut n.left,
+                Equal => return Some(&mut n.value),
+                Greater => current = &mut n.right
+            }
+        }
+
+        None
+    }
+
+    fn ins(&mut self, key: K, value: V) -> &mut Self {
+        // Splitting assumes the key isn't there yet.
+        if let Some(v) = self.find_mut(&key) {
+            *v = value;
+            return self;
+        }
+
+        self.ins_new(key, value);
+        self
+    }
+
+    fn del(&mut self, key: &K) -> Option<V> {
+        delete(&mut self.root, key, &Natural)
+    }
+
+    /// Time: O(1)
+    fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Keeps the generator, so that a seeded treap stays reproducible.
+    fn clear(&mut self) {
+        self.root = None;
+    }
+
+    fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        entry::lookup(self, key, Treap::find_mut, Treap::ins_new)
+    }
+}
+
+impl_ordered_map!(Treap, |t| t.root.as_deref());
+
+impl<K: Ord + Arbitrary, V: Arbitrary> Arbitrary for Treap<K, V> {
+    fn arbitrary<G: Gen>(g: &mut G) -> Treap<K, V> {
+        let data: Vec<(K, V)> = Arbitrary::arbitrary(g);
+
+        let mut t = Treap::new();
+        for (k, v) in data {
+            t.ins(k, v);
+        }
+
+        t
+    }
+}
+
+/// A treap ordering its keys by the comparator `F` instead of `Ord`, for
+/// example case-insensitively or by one of their fields. The comparator is kept
+/// in the tree and must be a total order, which doesn't change while it's used.
+///
+/// Closures have no `Default`, so they don't make a `Map` (whose `new` takes
+/// no arguments); all the operations are also available as inherent methods.
+/// Comparators with a `Default`, like `Natural` and `Reversed`, do.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::TreeBy;
+///
+/// let mut t = TreeBy::new(|a: &&str, b: &&str| a.to_lowercase().cmp(&b.to_lowercase()));
+/// t.ins("Banana", 1).ins("apple", 2).ins("Cherry", 3);
+///
+/// assert_eq!(t.find(&"BANANA"), Some(&1));
+/// assert_eq!(t.ins("APPLE", 4).len(), 3);
+/// assert_eq!(t.iter().map(|(_, v)| *v).collect::<Vec<_>>(), vec![4, 1, 3]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TreeBy<K, V, F> {
+    root: Link<K, V>,
+    rng: Xoshiro256,
+    cmp: F
+}
+
+impl<K, V, F: Compare<K>> TreeBy<K, V, F> {
+    /// Creates an empty tree ordered by `cmp`, with the generator of
+    /// `rng::default_rng`, like `Treap::new`.
+    pub fn new(cmp: F) -> TreeBy<K, V, F> {
+        TreeBy::with_seed(cmp, rng::DEFAULT_SEED)
+    }
+
+    /// Creates an empty tree ordered by `cmp`, whose generator is seeded from
+    /// `rng`, like `Treap::with_rng`.
+    pub fn with_rng<R: Rng>(cmp: F, rng: &mut R) -> TreeBy<K, V, F> {
+        TreeBy::with_seed(cmp, rng.gen())
+    }
+
+    /// Creates an empty tree ordered by `cmp`, whose priorities are drawn from a
+    /// generator with the given seed.
+    pub fn with_seed(cmp: F, seed: u64) -> TreeBy<K, V, F> {
+        TreeBy {
+            root: None,
+            rng: Xoshiro256::seed_from_u64(seed),
+            cmp
+        }
+    }
+
+    /// Returns the comparator.
+    pub fn comparator(&self) -> &F {
+        &self.cmp
+    }
+
+    /// Returns the value of the key equal to `key` in the tree's order.
+    pub fn find(&self, key: &K) -> Option<&V> {
+        let mut current = &self.root;
+        while let Some(n) = current {
+            match self.cmp.compare(key, &n.key) {
+                Less => current = &n.left,
+                Equal => return Some(&n.value),
+                Greater => current = &n.right
+            }
+        }
+
+        None
+    }
+
+    pub fn find_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut current = &mut self.root;
+        while let Some(n) = current {
+            match self.cmp.compare(key, &n.key) {
+                Less => current = &mut n.left,
+                Equal => return Some(&mut n.value),
+                Greater => current = &mut n.right
+            }
+        }
+
+        None
+    }
+
+    /// Inserts the entry. If an equal key is already there, only its value is
+    /// replaced and the old key stays.
+    pub fn ins(&mut self, key: K, value: V) -> &mut Self {
+        if let Some(v) = self.find_mut(&key) {
+            *v = value;
+            return self;
+        }
+
+        self.ins_new(key, value);
+        self
+    }
+
+    /// Insert a key which isn't in the tree yet and return its value.
+    fn ins_new(&mut self, key: K, value: V) -> &mut V {
+        let x = Box::new(Node {key, value, priority: self.rng.gen(), size: 1, left: None, right: None});
+        insert(&mut self.root, x, &self.cmp)
+    }
+
+    /// Returns the entry of the key equal to `key` in the tree's order, for
+    /// in-place updates. A vacant entry inserts `key` itself.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        entry::lookup(self, key, TreeBy::find_mut, TreeBy::ins_new)
+    }
+
+    pub fn del(&mut self, key: &K) -> Option<V> {
+        delete(&mut self.root, key, &self.cmp)
+    }
+
+    /// Time: O(1)
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns the entry with the least key in the tree's order.
+    pub fn min(&self) -> Option<(&K, &V)> {
+        let mut current = self.root.as_ref()?;
+        while let Some(l) = &current.left {
+            current = l;
+        }
+
+        Some((&current.key, &current.value))
+    }
+
+    /// Returns the entry with the greatest key in the tree's order.
+    pub fn max(&self) -> Option<(&K, &V)> {
+        let mut current = self.root.as_ref()?;
+        while let Some(r) = &current.right {
+            current = r;
+        }
+
+        Some((&current.key, &current.value))
+    }
+
+    /// Iterates over the entries in the tree's order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let mut stack: Vec<&Node<K, V>> = vec![];
+        let mut current = self.root.as_deref();
+        ::std::iter::from_fn(move || {
+            while let Some(n) = current {
+                stack.push(n);
+                current = n.left.as_deref();
+            }
+
+            let n = stack.pop()?;
+            current = n.right.as_deref();
+            Some((&n.key, &n.value))
+        })
+    }
+
+    /// Checks that keys form a BST in the tree's order and priorities form a
+    /// max-heap.
+    /// Time: O(size of the tree)
+    pub fn is_valid(&self) -> bool {
+        let keys: Vec<&K> = self.iter().map(|(k, _)| k).collect();
+
+        fn heap<K, V>(h: &Link<K, V>, max: u64) -> bool {
+            match h {
+                None => true,
+                Some(n) => n.priority <= max && heap(&n.left, n.priority) && heap(&n.right, n.priority)
+            }
+        }
+
+        keys.windows(2).all(|w| self.cmp.compare(w[0], w[1]) == Less) && heap(&self.root, u64::MAX)
+    }
+}
+
+impl<K, V, F: Compare<K> + Default> Map for TreeBy<K, V, F> {
+    type Key = K;
+    type Value = V;
+
+    fn new() -> TreeBy<K, V, F> {
+        TreeBy::new(F::default())
+    }
+
+    fn find(&self, key: &K) -> Option<&V> {
+        TreeBy::find(self, key)
+    }
+
+    fn find_mut(&mut self, key: &K) -> Option<&mut V> {
+        TreeBy::find_mut(self, key)
+    }
+
+    fn ins(&mut self, key: K, value: V) -> &mut Self {
+        TreeBy::ins(self, key, value)
+    }
+
+    fn del(&mut self, key: &K) -> Option<V> {
+        TreeBy::del(self, key)
+    }
+
+    fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Keeps the generator and the comparator.
+    fn clear(&mut self) {
+        self.root = None;
+    }
+
+    fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        TreeBy::entry(self, key)
+    }
+}
+
+impl<K, V, F> Arbitrary for TreeBy<K, V, F>
+    where K: Arbitrary, V: Arbitrary, F: Compare<K> + Default + Clone + Send + 'static
+{
+    fn arbitrary<G: Gen>(g: &mut G) -> TreeBy<K, V, F> {
+        let data: Vec<(K, V)> = Arbitrary::arbitrary(g);
+
+        let mut t = TreeBy::new(F::default());
+        for (k, v) in data {
+            t.ins(k, v);
+        }
+
+        t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map::{Map, Natural, OrderedMap, Reversed};
+    use super::{Treap, TreeBy};
+
+    map_tests!(Treap<usize, usize>);
+    ordered_map_tests!(Treap<usize, usize>);
+
+    mod tree_by {
+        use map::Natural;
+        use super::TreeBy;
+
+        map_tests!(TreeBy<usize, usize, Natural>);
+    }
+
+    quickcheck! {
+        fn is_valid_arbitrary(t: Treap<usize, usize>) -> bool {
+            t.is_valid()
+        }
+
+        fn is_valid_del(t: Treap<usize, usize>, ks: Vec<usize>) -> bool {
+            let mut t = t.clone();
+            ks.iter().all(|k| {
+                t.del(k);
+                t.is_valid()
+            })
+        }
+
+        // Same seed, same operations, same shape.
+        fn seeded(seed: u64, ks: Vec<u16>) -> bool {
+            let mut t1 = Treap::with_seed(seed);
+            let mut t2 = Treap::with_seed(seed);
+            for &k in &ks {
+                t1.ins(k, ());
+                t2.ins(k, ());
+            }
+
+            format!("{:?}", t1.root) == format!("{:?}", t2.root)
+        }
+
+        // Without a seed, trees are still the same in every run.
+        fn new_reproducible(ks: Vec<u16>) -> bool {
+            let mut t1 = Treap::new();
+            let mut t2 = Treap::new();
+            for &k in &ks {
+                t1.ins(k, ());
+                t2.ins(k, ());
+            }
+
+            format!("{:?}", t1.root) == format!("{:?}", t2.root)
+        }
+
+        // The expected height is about 3 log n; this leaves lots of slack.
+        fn height_sorted(n: u16, seed: u64) -> bool {
+            let mut t = Treap::with_seed(seed);
+            for i in 0 .. n {
+                t.ins(i, ());
+            }
+
+            t.height() as f64 <= 8.0 * ((n as f64) + 1.0).log2() + 1.0
+        }
+
+        // The drawing has a line per entry, in preorder.
+        fn display_lines(t: Treap<usize, usize>) -> bool {
+            let s = t.to_string();
+            let mut keys: Vec<usize> = s.lines().filter(|l| !l.ends_with('·'))
+                .map(|l| l.trim_start_matches(|c| "│├└─ ".contains(c)))
+                .map(|l| l.split(':').next().unwrap().parse().unwrap()).collect();
+            keys.sort();
+
+            keys.len() == t.len() && keys.into_iter().eq(t.range(..).map(|(k, _)| *k))
+        }
+
+        fn tree_by_reversed(ks: Vec<(u8, u8)>, ds: Vec<u8>) -> bool {
+            let mut t: TreeBy<u8, u8, Reversed> = TreeBy::new(Reversed);
+            let mut model = ::std::collections::BTreeMap::new();
+            for &(k, v) in &ks {
+                t.ins(k, v);
+                model.insert(k, v);
+            }
+            for d in &ds {
+                t.del(d);
+                model.remove(d);
+            }
+
+            t.is_valid() && t.len() == model.len() && t.iter().eq(model.iter().rev()) &&
+            t.min() == model.iter().next_back() && t.max() == model.iter().next()
+        }
+
+        // Keys equal in the comparator's order are the same key.
+        fn tree_by_field(ks: Vec<(u8, u8)>) -> bool {
+            let mut t = TreeBy::new(|a: &(u8, u8), b: &(u8, u8)| a.1.cmp(&b.1));
+            for &k in &ks {
+                t.ins(k, k.0);
+            }
+
+            let mut fields: Vec<u8> = ks.iter().map(|k| k.1).collect();
+            fields.sort();
+            fields.dedup();
+
+            t.is_valid() && t.iter().map(|(k, _)| k.1).eq(fields.iter().cloned()) &&
+            ks.iter().all(|k| t.find(&(0, k.1)) == ks.iter().rev().find(|j| j.1 == k.1).map(|j| &j.0))
+        }
+
+        fn tree_by_natural_same_as_treap(ks: Vec<u16>) -> bool {
+            let mut t1 = Treap::with_seed(7);
+            let mut t2 = TreeBy::with_seed(Natural, 7);
+            for &k in &ks {
+                t1.ins(k, ());
+                t2.ins(k, ());
+            }
+
+            format!("{:?}", t1.root) == format!("{:?}", t2.root)
+        }
+    }
+}
@@ -0,0 +1,401 @@
+//! Ternary search trees.
+
+use map::{Entry, Map};
+use map::entry;
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use std::cmp::Ordering::*;
+
+#[derive(Clone, Debug)]
+struct Node<V> {
+    ch: char,
+    // The value of the key spelled by the path to this node, ending with `ch`.
+    value: Option<V>,
+    lo: Link<V>,
+    eq: Link<V>,
+    hi: Link<V>
+}
+
+type Link<V> = Option<Box<Node<V>>>;
+
+/// A map from strings, stored as a trie whose nodes keep their children in a
+/// binary search tree on characters. Every node holds one character and has three
+/// children: `lo` and `hi` for smaller and bigger characters at the same position
+/// and `eq` for the next position. So a node costs three pointers, no matter how
+/// big the alphabet is, instead of one pointer per letter of the alphabet.
+///
+/// Time: `find`, `ins` and `del` are O(length of the key + log of the number of
+/// keys) when the trees on characters are balanced, which they are on average
+/// for keys inserted in random order.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::Map;
+/// use aisd::map::tst::TernarySearchTree;
+///
+/// let mut t = TernarySearchTree::new();
+/// for (i, w) in ["car", "cat", "cart", "dog", "cab"].iter().enumerate() {
+///     t.ins(w.to_string(), i);
+/// }
+///
+/// assert_eq!(t.find(&"cat".to_string()), Some(&1));
+/// assert_eq!(t.with_prefix("car").map(|(k, _)| k).collect::<Vec<_>>(), vec!["car", "cart"]);
+/// assert_eq!(t.matching("ca.").map(|(k, _)| k).collect::<Vec<_>>(), vec!["cab", "car", "cat"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TernarySearchTree<V> {
+    root: Link<V>,
+    // The value of the empty string, which has no node to live in.
+    empty: Option<V>,
+    len: usize
+}
+
+impl<V> Default for TernarySearchTree<V> {
+    fn default() -> TernarySearchTree<V> {
+        TernarySearchTree::new()
+    }
+}
+
+/// Calls `f` with every key in the subtree of `h` (prefixed with `prefix`) and its
+/// value, in increasing order. If `pattern` is given, only keys matching it are
+/// visited; `pattern` covers the part of the key after `prefix`.
+fn visit<'a, V, F>(h: &'a Link<V>, prefix: &mut String, pattern: Option<&[char]>, f: &mut F)
+    where F: FnMut(String, &'a V)
+{
+    let n = match h {
+        Some(n) => n,
+        None => return
+    };
+    let (p, rest) = match pattern {
+        Some([p, rest @ ..]) => (Some(*p), Some(rest)),
+        Some([]) => return,
+        None => (None, None)
+    };
+    let any = p.is_none_or(|p| p == '.');
+
+    if any || p < Some(n.ch) {
+        visit(&n.lo, prefix, pattern, f);
+    }
+    if any || p == Some(n.ch) {
+        prefix.push(n.ch);
+        if let Some(v) = &n.value {
+            if rest.is_none_or(|r| r.is_empty()) {
+                f(prefix.clone(), v);
+            }
+        }
+        visit(&n.eq, prefix, rest, f);
+        prefix.pop();
+    }
+    if any || p > Some(n.ch) {
+        visit(&n.hi, prefix, pattern, f);
+    }
+}
+
+/// Removes the key spelled by `key` from the subtree of `h`, then removes the
+/// nodes which became useless on the way back up.
+fn delete<V>(h: &mut Link<V>, key: &[char]) -> Option<V> {
+    let (c, rest) = key.split_first()?;
+    let n = h.as_mut()?;
+
+    let result = match c.cmp(&n.ch) {
+        Less => delete(&mut n.lo, key),
+        Greater => delete(&mut n.hi, key),
+        Equal if rest.is_empty() => n.value.take(),
+        Equal => delete(&mut n.eq, rest)
+    };
+
+    // A node with no value and nothing below it can be replaced by its `lo` or
+    // `hi` child, as long as it doesn't have both.
+    if n.value.is_none() && n.eq.is_none() {
+        match (n.lo.take(), n.hi.take()) {
+            (lo, None) => *h = lo,
+            (None, hi) => *h = hi,
+            (lo, hi) => {
+                n.lo = lo;
+                n.hi = hi;
+            }
+        }
+    }
+
+    result
+}
+
+impl<V> TernarySearchTree<V> {
+    /// The node of the last character of `key`, if there's one.
+    fn node(&self, key: &str) -> Option<&Node<V>> {
+        let mut current = &self.root;
+        let mut chars = key.chars().peekable();
+        while let Some(n) = current {
+            let c = *chars.peek()?;
+            match c.cmp(&n.ch) {
+                Less => current = &n.lo,
+                Greater => current = &n.hi,
+                Equal => {
+                    chars.next();
+                    if chars.peek().is_none() {
+                        return Some(n);
+                    }
+                    current = &n.eq;
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Iterates over the keys starting with `prefix` and their values, in
+    /// increasing order of keys.
+    /// Time: O(length of `prefix` + log n + size of the subtree holding the keys)
+    pub fn with_prefix(&self, prefix: &str) -> impl Iterator<Item = (String, &V)> {
+        let mut found = vec![];
+        if prefix.is_empty() {
+            found.extend(self.empty.as_ref().map(|v| (String::new(), v)));
+            visit(&self.root, &mut String::new(), None, &mut |k, v| found.push((k, v)));
+        } else if let Some(n) = self.node(prefix) {
+            found.extend(n.value.as_ref().map(|v| (prefix.to_string(), v)));
+            visit(&n.eq, &mut prefix.to_string(), None, &mut |k, v| found.push((k, v)));
+        }
+
+        found.into_iter()
+    }
+
+    /// Iterates over the keys matching `pattern`, where `.` matches any single
+    /// character and other characters match themselves, in increasing order of
+    /// keys. Only the subtrees which can hold matching keys are visited.
+    pub fn matching(&self, pattern: &str) -> impl Iterator<Item = (String, &V)> {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let mut found = vec![];
+        if pattern.is_empty() {
+            found.extend(self.empty.as_ref().map(|v| (String::new(), v)));
+        } else {
+            visit(&self.root, &mut String::new(), Some(&pattern), &mut |k, v| found.push((k, v)));
+        }
+
+        found.into_iter()
+    }
+
+    /// Iterates over all the keys and their values, in increasing order of keys.
+    pub fn iter(&self) -> impl Iterator<Item = (String, &V)> {
+        self.with_prefix("")
+    }
+
+    /// The place for the value of `key`, creating the nodes on the way to it.
+    fn slot(&mut self, key: &str) -> &mut Option<V> {
+        let mut slot = &mut self.empty;
+        let mut current = &mut self.root;
+        let mut chars = key.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            let n = current.get_or_insert_with(|| {
+                Box::new(Node {ch: c, value: None, lo: None, eq: None, hi: None})
+            });
+            match c.cmp(&n.ch) {
+                Less => current = &mut n.lo,
+                Greater => current = &mut n.hi,
+                Equal => {
+                    chars.next();
+                    slot = &mut n.value;
+                    current = &mut n.eq;
+                }
+            }
+        }
+
+        slot
+    }
+
+    /// Insert a key which isn't in the tree yet and return its value.
+    fn ins_new(&mut self, key: String, value: V) -> &mut V {
+        self.len += 1;
+        self.slot(&key).get_or_insert(value)
+    }
+}
+
+impl<V> Map for TernarySearchTree<V> {
+    type Key = String;
+    type Value = V;
+
+    fn new() -> TernarySearchTree<V> {
+        TernarySearchTree {root: None, empty: None, len: 0}
+    }
+
+    /// Time: O(1)
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn find(&self, key: &String) -> Option<&V> {
+        if key.is_empty() {
+            self.empty.as_ref()
+        } else {
+            self.node(key)?.value.as_ref()
+        }
+    }
+
+    fn find_mut(&mut self, key: &String) -> Option<&mut V> {
+        if key.is_empty() {
+            return self.empty.as_mut();
+        }
+
+        let mut current = &mut self.root;
+        let mut chars = key.chars().peekable();
+        while let Some(n) = current {
+            let c = *chars.peek()?;
+            match c.cmp(&n.ch) {
+                Less => current = &mut n.lo,
+                Greater => current = &mut n.hi,
+                Equal => {
+                    chars.next();
+                    if chars.peek().is_none() {
+                        return n.value.as_mut();
+                    }
+                    current = &mut n.eq;
+                }
+            }
+        }
+
+        None
+    }
+
+    fn ins(&mut self, key: String, value: V) -> &mut Self {
+        if self.slot(&key).replace(value).is_none() {
+            self.len += 1;
+        }
+
+        self
+    }
+
+    fn del(&mut self, key: &String) -> Option<V> {
+        let result = if key.is_empty() {
+            self.empty.take()
+        } else {
+            let chars: Vec<char> = key.chars().collect();
+            delete(&mut self.root, &chars)
+        };
+
+        if result.is_some() {
+            self.len -= 1;
+        }
+
+        result
+    }
+
+    fn entry(&mut self, key: String) -> Entry<'_, String, V> {
+        entry::lookup(self, key, TernarySearchTree::find_mut, TernarySearchTree::ins_new)
+    }
+}
+
+impl<V: Arbitrary> Arbitrary for TernarySearchTree<V> {
+    fn arbitrary<G: Gen>(g: &mut G) -> TernarySearchTree<V> {
+        let data: Vec<(String, V)> = Arbitrary::arbitrary(g);
+
+        let mut t = TernarySearchTree::new();
+        for (k, v) in data {
+            t.ins(k, v);
+        }
+
+        t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map::Map;
+    use super::TernarySearchTree;
+
+    use std::collections::BTreeMap;
+
+    // Keys over a small alphabet, so that they share prefixes.
+    fn key(k: &[u8]) -> String {
+        k.iter().take(5).map(|&c| (b'a' + c % 3) as char).collect()
+    }
+
+    fn model(ks: &[(Vec<u8>, usize)]) -> (TernarySearchTree<usize>, BTreeMap<String, usize>) {
+        let mut t = TernarySearchTree::new();
+        let mut m = BTreeMap::new();
+        for (k, v) in ks {
+            t.ins(key(k), *v);
+            m.insert(key(k), *v);
+        }
+
+        (t, m)
+    }
+
+    fn matches(k: &str, pattern: &str) -> bool {
+        k.chars().count() == pattern.chars().count() &&
+        k.chars().zip(pattern.chars()).all(|(c, p)| p == '.' || c == p)
+    }
+
+    quickcheck! {
+        fn same_as_btreemap(ops: Vec<(bool, Vec<u8>, usize)>) -> bool {
+            let mut t = TernarySearchTree::new();
+            let mut m = BTreeMap::new();
+
+            ops.into_iter().all(|(ins, k, v)| {
+                let k = key(&k);
+                let agree = if ins {
+                    t.ins(k.clone(), v);
+                    m.insert(k.clone(), v);
+                    true
+                } else {
+                    t.del(&k) == m.remove(&k)
+                };
+
+                agree && t.len() == m.len() && t.find(&k) == m.get(&k)
+            })
+        }
+
+        fn iter_sorted(ks: Vec<(Vec<u8>, usize)>) -> bool {
+            let (t, m) = model(&ks);
+
+            t.iter().eq(m.iter().map(|(k, v)| (k.clone(), v)))
+        }
+
+        fn with_prefix_naive(ks: Vec<(Vec<u8>, usize)>, p: Vec<u8>) -> bool {
+            let (t, m) = model(&ks);
+            let p = key(&p);
+
+            t.with_prefix(&p).eq(m.iter().filter(|(k, _)| k.starts_with(&p)).map(|(k, v)| (k.clone(), v)))
+        }
+
+        fn matching_naive(ks: Vec<(Vec<u8>, usize)>, p: Vec<u8>) -> bool {
+            let (t, m) = model(&ks);
+            let p: String = p.iter().take(5).map(|&c| if c % 4 == 0 {'.'} else {(b'a' + c % 3) as char}).collect();
+
+            t.matching(&p).eq(m.iter().filter(|(k, _)| matches(k, &p)).map(|(k, v)| (k.clone(), v)))
+        }
+
+        // After deleting everything, no nodes are left.
+        fn del_all(ks: Vec<(Vec<u8>, usize)>) -> bool {
+            let (mut t, m) = model(&ks);
+            for k in m.keys() {
+                t.del(k);
+            }
+
+            t.is_empty() && t.root.is_none()
+        }
+
+        fn entry_counts(ks: Vec<Vec<u8>>) -> bool {
+            let mut t = TernarySearchTree::new();
+            let mut m = BTreeMap::new();
+            for k in &ks {
+                *t.entry(key(k)).or_insert(0) += 1;
+                *m.entry(key(k)).or_insert(0) += 1;
+            }
+
+            t.len() == m.len() && t.iter().eq(m.iter().map(|(k, v)| (k.clone(), v)))
+        }
+
+        fn find_mut_find(t: TernarySearchTree<usize>, k: String, v: usize) -> bool {
+            let mut t = t.clone();
+            let found = t.find(&k).is_some();
+            if let Some(x) = t.find_mut(&k) {
+                *x = v;
+            }
+
+            (t.find(&k) == Some(&v)) == found
+        }
+    }
+}
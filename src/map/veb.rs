@@ -0,0 +1,436 @@
+//! van Emde Boas trees.
+
+use map::{Entry, Map, OrderedMap};
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use std::collections::HashMap;
+use std::collections::hash_map;
+use std::ops::Bound::*;
+use std::ops::RangeBounds;
+
+/// A set of numbers below 2^bits. A number is split into its high and low half
+/// of bits; the numbers with the same high half form a cluster, a set of low
+/// halves, and the high halves of the nonempty clusters form the summary. Both
+/// have half the bits, so a path down the structure has O(log bits) levels.
+///
+/// The least element is kept only in `min_max`, not in the clusters, so inserting
+/// into an empty cluster and deleting its only element take O(1) and every
+/// operation recurses into one cluster or the summary, not both. Only nonempty
+/// clusters exist, in a hash map, so the set takes O(n log bits) memory instead
+/// of O(2^bits).
+#[derive(Clone, Debug)]
+struct Set {
+    bits: u32,
+    // The least and the greatest element, if the set isn't empty.
+    min_max: Option<(u64, u64)>,
+    summary: Option<Box<Set>>,
+    clusters: HashMap<u64, Set>
+}
+
+impl Set {
+    fn new(bits: u32) -> Set {
+        Set {bits, min_max: None, summary: None, clusters: HashMap::new()}
+    }
+
+    fn singleton(bits: u32, x: u64) -> Set {
+        Set {min_max: Some((x, x)), ..Set::new(bits)}
+    }
+
+    fn low_bits(&self) -> u32 {
+        self.bits / 2
+    }
+
+    fn split(&self, x: u64) -> (u64, u64) {
+        (x >> self.low_bits(), x & ((1 << self.low_bits()) - 1))
+    }
+
+    fn join(&self, high: u64, low: u64) -> u64 {
+        (high << self.low_bits()) | low
+    }
+
+    fn min(&self) -> Option<u64> {
+        self.min_max.map(|(min, _)| min)
+    }
+
+    fn max(&self) -> Option<u64> {
+        self.min_max.map(|(_, max)| max)
+    }
+
+    /// Inserts `x`, which must not be in the set.
+    fn insert(&mut self, mut x: u64) {
+        let (min, max) = match self.min_max {
+            None => {
+                self.min_max = Some((x, x));
+                return;
+            },
+            Some(mm) => mm
+        };
+
+        // The new element may become the minimum, which isn't stored below.
+        if x < min {
+            self.min_max = Some((x, max));
+            x = min;
+        }
+
+        if self.bits > 1 {
+            let (high, low) = self.split(x);
+            let low_bits = self.low_bits();
+            match self.clusters.get_mut(&high) {
+                Some(c) => c.insert(low),
+                None => {
+                    let high_bits = self.bits - low_bits;
+                    self.summary.get_or_insert_with(|| Box::new(Set::new(high_bits))).insert(high);
+                    self.clusters.insert(high, Set::singleton(low_bits, low));
+                }
+            }
+        }
+
+        if let Some((_, max)) = &mut self.min_max {
+            *max = (*max).max(x);
+        }
+    }
+
+    /// Deletes `x`, which must be in the set.
+    fn delete(&mut self, mut x: u64) {
+        let (min, max) = match self.min_max {
+            Some(mm) => mm,
+            None => return
+        };
+
+        if min == max {
+            self.min_max = None;
+            return;
+        }
+        if self.bits == 1 {
+            // Both 0 and 1 are there, and the other one stays.
+            let other = 1 - x;
+            self.min_max = Some((other, other));
+            return;
+        }
+
+        // Deleting the minimum pulls the least element out of the clusters to
+        // take its place, and deletes that one from its cluster instead.
+        let mut min = min;
+        if x == min {
+            let first = self.summary.as_ref().and_then(|s| s.min()).unwrap();
+            x = self.join(first, self.clusters[&first].min().unwrap());
+            min = x;
+        }
+
+        let (high, low) = self.split(x);
+        let cluster = self.clusters.get_mut(&high).unwrap();
+        cluster.delete(low);
+        if cluster.min_max.is_none() {
+            self.clusters.remove(&high);
+            let summary = self.summary.as_mut().unwrap();
+            summary.delete(high);
+            if summary.min_max.is_none() {
+                self.summary = None;
+            }
+        }
+
+        let max = if x != max {
+            max
+        } else {
+            match self.summary.as_ref().and_then(|s| s.max()) {
+                Some(last) => self.join(last, self.clusters[&last].max().unwrap()),
+                None => min
+            }
+        };
+        self.min_max = Some((min, max));
+    }
+
+    /// The least element greater than `x`.
+    fn succ(&self, x: u64) -> Option<u64> {
+        let (min, max) = self.min_max?;
+        if x < min {
+            return Some(min);
+        }
+        if x >= max {
+            return None;
+        }
+        if self.bits == 1 {
+            return Some(1);
+        }
+
+        let (high, low) = self.split(x);
+        match self.clusters.get(&high) {
+            Some(c) if c.max().is_some_and(|m| low < m) => Some(self.join(high, c.succ(low)?)),
+            _ => {
+                let next = self.summary.as_ref()?.succ(high)?;
+                Some(self.join(next, self.clusters[&next].min()?))
+            }
+        }
+    }
+
+    /// The greatest element less than `x`.
+    fn pred(&self, x: u64) -> Option<u64> {
+        let (min, max) = self.min_max?;
+        if x > max {
+            return Some(max);
+        }
+        if x <= min {
+            return None;
+        }
+        if self.bits == 1 {
+            return Some(0);
+        }
+
+        // The minimum isn't in the clusters, so it's the answer if they have none.
+        let (high, low) = self.split(x);
+        match self.clusters.get(&high) {
+            Some(c) if c.min().is_some_and(|m| m < low) => Some(self.join(high, c.pred(low)?)),
+            _ => match self.summary.as_ref().and_then(|s| s.pred(high)) {
+                Some(prev) => Some(self.join(prev, self.clusters[&prev].max()?)),
+                None => Some(min)
+            }
+        }
+    }
+}
+
+/// An ordered map from integers below 2^bits (at most 64), which finds
+/// predecessors and successors in O(log bits) expected time, that is O(log log U)
+/// for a universe of size U, instead of the O(log n) of comparison-based trees.
+/// That's 6 steps for any `u64` keys.
+///
+/// The keys are kept in a van Emde Boas tree whose clusters are stored in hash
+/// maps, so it takes O(n log bits) memory, and the values in a hash map, so
+/// `find` is O(1) expected.
+///
+/// Time: `find` is O(1), `ins`, `del`, `pred`, `succ`, `floor` and `ceiling` are
+/// O(log bits), all expected.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::{Map, OrderedMap};
+/// use aisd::map::veb::VebTree;
+///
+/// let mut t = VebTree::with_bits(16);
+/// t.ins(1000, "a").ins(7, "b").ins(40000, "c");
+///
+/// assert_eq!(t.succ(&7), Some((&1000, &"a")));
+/// assert_eq!(t.pred(&7), None);
+/// assert_eq!(t.floor(&39999), Some((&1000, &"a")));
+/// assert_eq!(t.max(), Some((&40000, &"c")));
+/// ```
+#[derive(Clone, Debug)]
+pub struct VebTree<V> {
+    keys: Set,
+    values: HashMap<usize, V>
+}
+
+impl<V> VebTree<V> {
+    /// Creates an empty map for keys below 2^bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is 0 or more than the bits of `usize`.
+    pub fn with_bits(bits: u32) -> VebTree<V> {
+        assert!(bits > 0 && bits <= usize::BITS, "VebTree: the number of bits must be between 1 and usize::BITS");
+        VebTree {keys: Set::new(bits), values: HashMap::new()}
+    }
+
+    /// Returns the number of bits of the keys.
+    pub fn bits(&self) -> u32 {
+        self.keys.bits
+    }
+
+    fn with_value(&self, key: Option<u64>) -> Option<(&usize, &V)> {
+        self.values.get_key_value(&(key? as usize))
+    }
+}
+
+impl<V> Default for VebTree<V> {
+    fn default() -> VebTree<V> {
+        VebTree::new()
+    }
+}
+
+impl<V> Map for VebTree<V> {
+    type Key = usize;
+    type Value = V;
+
+    /// Creates an empty map for all `usize` keys.
+    fn new() -> VebTree<V> {
+        VebTree::with_bits(usize::BITS)
+    }
+
+    fn find(&self, key: &usize) -> Option<&V> {
+        self.values.get(key)
+    }
+
+    fn find_mut(&mut self, key: &usize) -> Option<&mut V> {
+        self.values.get_mut(key)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the key doesn't fit in the number of bits.
+    fn ins(&mut self, key: usize, value: V) -> &mut Self {
+        assert!(self.bits() == usize::BITS || key >> self.bits() == 0, "VebTree: the key is too big");
+        if self.values.insert(key, value).is_none() {
+            self.keys.insert(key as u64);
+        }
+
+        self
+    }
+
+    fn del(&mut self, key: &usize) -> Option<V> {
+        let value = self.values.remove(key)?;
+        self.keys.delete(*key as u64);
+
+        Some(value)
+    }
+
+    /// Time: O(1)
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The vacant entry holds the hash map's own entry, and adds the key to the
+    /// set when a value is inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key doesn't fit in the number of bits.
+    fn entry(&mut self, key: usize) -> Entry<'_, usize, V> {
+        assert!(self.bits() == usize::BITS || key >> self.bits() == 0, "VebTree: the key is too big");
+        let keys = &mut self.keys;
+        match self.values.entry(key) {
+            hash_map::Entry::Occupied(e) => Entry::occupied(key, e.into_mut()),
+            hash_map::Entry::Vacant(e) => Entry::vacant(key, move |k, v| {
+                keys.insert(k as u64);
+                e.insert(v)
+            })
+        }
+    }
+
+    fn clear(&mut self) {
+        let bits = self.bits();
+        *self = VebTree::with_bits(bits);
+    }
+}
+
+impl<V> OrderedMap for VebTree<V> {
+    fn min(&self) -> Option<(&usize, &V)> {
+        self.with_value(self.keys.min())
+    }
+
+    fn max(&self) -> Option<(&usize, &V)> {
+        self.with_value(self.keys.max())
+    }
+
+    fn floor(&self, key: &usize) -> Option<(&usize, &V)> {
+        self.values.get_key_value(key).or_else(|| self.pred(key))
+    }
+
+    fn ceiling(&self, key: &usize) -> Option<(&usize, &V)> {
+        self.values.get_key_value(key).or_else(|| self.succ(key))
+    }
+
+    fn pred(&self, key: &usize) -> Option<(&usize, &V)> {
+        self.with_value(self.keys.pred(*key as u64))
+    }
+
+    fn succ(&self, key: &usize) -> Option<(&usize, &V)> {
+        self.with_value(self.keys.succ(*key as u64))
+    }
+
+    /// Takes O(log bits) per entry.
+    fn range<R: RangeBounds<usize>>(&self, range: R) -> impl Iterator<Item = (&usize, &V)> {
+        let first = match range.start_bound() {
+            Included(k) => self.ceiling(k),
+            Excluded(k) => self.succ(k),
+            Unbounded => OrderedMap::min(self)
+        };
+        let end = match range.end_bound() {
+            Included(&k) => Included(k),
+            Excluded(&k) => Excluded(k),
+            Unbounded => Unbounded
+        };
+
+        let mut next = first;
+        ::std::iter::from_fn(move || {
+            let (k, v) = next.filter(|(k, _)| match end {
+                Included(e) => **k <= e,
+                Excluded(e) => **k < e,
+                Unbounded => true
+            })?;
+            next = self.succ(k);
+            Some((k, v))
+        })
+    }
+}
+
+impl<V: Arbitrary> Arbitrary for VebTree<V> {
+    fn arbitrary<G: Gen>(g: &mut G) -> VebTree<V> {
+        let data: Vec<(usize, V)> = Arbitrary::arbitrary(g);
+
+        let mut t = VebTree::new();
+        for (k, v) in data {
+            t.ins(k, v);
+        }
+
+        t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map::{Map, OrderedMap};
+    use super::VebTree;
+
+    use std::collections::BTreeSet;
+
+    map_tests!(VebTree<usize>);
+    ordered_map_tests!(VebTree<usize>);
+
+    quickcheck! {
+        // Small universes, where keys collide and clusters empty often.
+        fn same_as_btreeset(bits: u8, ops: Vec<(bool, usize)>) -> bool {
+            let bits = bits as u32 % 8 + 1;
+            let mut t = VebTree::with_bits(bits);
+            let mut s = BTreeSet::new();
+
+            ops.into_iter().all(|(ins, k)| {
+                let k = k % (1 << bits);
+                if ins {
+                    t.ins(k, ());
+                    s.insert(k);
+                } else {
+                    t.del(&k);
+                    s.remove(&k);
+                }
+
+                (0 .. 1 << bits).all(|x| {
+                    t.succ(&x).map(|e| *e.0) == s.range(x + 1 ..).next().cloned() &&
+                    t.pred(&x).map(|e| *e.0) == s.range(.. x).next_back().cloned()
+                }) && OrderedMap::min(&t).map(|e| *e.0) == s.iter().next().cloned() &&
+                OrderedMap::max(&t).map(|e| *e.0) == s.iter().next_back().cloned()
+            })
+        }
+
+        fn extreme_keys(ks: Vec<bool>) -> bool {
+            let mut t = VebTree::new();
+            let keys: Vec<usize> = ks.iter().enumerate().map(|(i, &b)| if b {usize::MAX - i} else {i}).collect();
+            for &k in &keys {
+                t.ins(k, k);
+            }
+
+            let mut sorted = keys.clone();
+            sorted.sort();
+            sorted.dedup();
+            t.range(..).map(|(k, _)| *k).eq(sorted.into_iter())
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn key_too_big() {
+        VebTree::with_bits(8).ins(256, ());
+    }
+}
@@ -0,0 +1,355 @@
+//! Zip trees.
+
+use map::{Entry, Map};
+use map::entry;
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use rand::Rng;
+
+use rng;
+use rng::Xoshiro256;
+
+use std::cmp::Ord;
+use std::cmp::Ordering::*;
+
+#[derive(Clone, Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    rank: u32,
+    left: Link<K, V>,
+    right: Link<K, V>
+}
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+impl_bst_node!(Node);
+
+/// A zip tree (Tarjan, Levy and Timmel) is a binary search tree in which every
+/// node has a random rank drawn from the geometric distribution. Ranks are
+/// heap-ordered, ties being broken in favour of the smaller key, so a zip tree
+/// is a treap with a special choice of priorities.
+///
+/// The difference is in how it's updated: instead of rotating a new node up,
+/// `ins` finds its final position and *unzips* the subtree found there into two
+/// paths (keys less and greater than the new one). `del` does the opposite:
+/// it *zips* the two subtrees of the deleted node together.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::Map;
+/// use aisd::map::zip::ZipTree;
+///
+/// let mut t = ZipTree::with_seed(42);
+/// for i in 0 .. 1000 {
+///     t.ins(i, i * i);
+/// }
+///
+/// assert_eq!(t.find(&30), Some(&900));
+/// assert_eq!(t.del(&30), Some(900));
+/// assert_eq!(t.find(&30), None);
+/// assert!(t.is_valid());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ZipTree<K, V> {
+    root: Link<K, V>,
+    len: usize,
+    rng: Xoshiro256
+}
+
+/// Checks whether `n` belongs above a node with the given rank and key.
+fn above<K: Ord, V>(n: &Node<K, V>, rank: u32, key: &K) -> bool {
+    n.rank > rank || (n.rank == rank && n.key < *key)
+}
+
+/// Split `t` into the parts with keys less and greater than `key` (which is not in `t`).
+fn unzip<K: Ord, V>(t: Link<K, V>, key: &K) -> (Link<K, V>, Link<K, V>) {
+    match t {
+        None => (None, None),
+        Some(mut n) => {
+            if n.key < *key {
+                let (l, r) = unzip(n.right.take(), key);
+                n.right = l;
+                (Some(n), r)
+            } else {
+                let (l, r) = unzip(n.left.take(), key);
+                n.left = r;
+                (l, Some(n))
+            }
+        }
+    }
+}
+
+/// Join `l` and `r` (all keys in `l` less than all keys in `r`) by merging the right
+/// spine of `l` with the left spine of `r`.
+fn zip<K: Ord, V>(l: Link<K, V>, r: Link<K, V>) -> Link<K, V> {
+    match (l, r) {
+        (None, r) => r,
+        (l, None) => l,
+        (Some(mut l), Some(mut r)) => {
+            if above(&l, r.rank, &r.key) {
+                l.right = zip(l.right.take(), Some(r));
+                Some(l)
+            } else {
+                r.left = zip(Some(l), r.left.take());
+                Some(r)
+            }
+        }
+    }
+}
+
+/// Insert `x` (whose key is not in the tree) below `slot` and return its value.
+fn insert<K: Ord, V>(slot: &mut Link<K, V>, mut x: Box<Node<K, V>>) -> &mut V {
+    // Equal means that `x` goes right here (its key isn't in the tree).
+    let ord = match slot {
+        Some(n) if above(n, x.rank, &x.key) => x.key.cmp(&n.key),
+        _ => Equal
+    };
+
+    match (ord, slot) {
+        (Less, Some(n)) => insert(&mut n.left, x),
+        (Greater, Some(n)) => insert(&mut n.right, x),
+        (_, slot) => {
+            let (l, r) = unzip(slot.take(), &x.key);
+            x.left = l;
+            x.right = r;
+            &mut slot.insert(x).value
+        }
+    }
+}
+
+fn delete<K: Ord, V>(slot: &mut Link<K, V>, key: &K) -> Option<V> {
+    let ord = match slot {
+        None => return None,
+        Some(n) => key.cmp(&n.key)
+    };
+
+    match (ord, slot) {
+        (Less, Some(n)) => delete(&mut n.left, key),
+        (Greater, Some(n)) => delete(&mut n.right, key),
+        (_, slot) => slot.take().map(|n| {
+            let n = *n;
+            *slot = zip(n.left, n.right);
+            n.value
+        })
+    }
+}
+
+impl<K, V> ZipTree<K, V> {
+    /// Creates an empty zip tree whose ranks are drawn from a generator with
+    /// the given seed, so that its shape is reproducible.
+    pub fn with_seed(seed: u64) -> ZipTree<K, V> {
+        ZipTree {
+            root: None,
+            len: 0,
+            rng: Xoshiro256::seed_from_u64(seed)
+        }
+    }
+
+    /// Creates an empty zip tree whose generator is seeded from `rng`, like
+    /// `Treap::with_rng`.
+    pub fn with_rng<R: Rng>(rng: &mut R) -> ZipTree<K, V> {
+        ZipTree::with_seed(rng.gen())
+    }
+
+    /// Computes the height of the tree. The empty tree has height 0.
+    /// Time: O(size of the tree)
+    pub fn height(&self) -> usize {
+        fn height<K, V>(h: &Link<K, V>) -> usize {
+            match h {
+                None => 0,
+                Some(n) => 1 + height(&n.left).max(height(&n.right))
+            }
+        }
+
+        height(&self.root)
+    }
+}
+
+impl<K: Ord, V> ZipTree<K, V> {
+    /// Checks that keys form a BST and that no node is below a node it should be above.
+    /// Time: O(size of the tree)
+    pub fn is_valid(&self) -> bool {
+        fn check<'a, K: Ord, V>(h: &'a Link<K, V>, low: Option<&'a K>, high: Option<&'a K>,
+                                parent: Option<&'a Node<K, V>>) -> bool {
+            match h {
+                None => true,
+                Some(n) => {
+                    low.is_none_or(|k| *k < n.key) && high.is_none_or(|k| n.key < *k) &&
+                    parent.is_none_or(|p| above(p, n.rank, &n.key)) &&
+                    check(&n.left, low, Some(&n.key), Some(n)) &&
+                    check(&n.right, Some(&n.key), high, Some(n))
+                }
+            }
+        }
+
+        check(&self.root, None, None, None)
+    }
+
+    /// Insert a key which isn't in the tree yet and return its value.
+    fn ins_new(&mut self, key: K, value: V) -> &mut V {
+        // The number of heads before the first tail.
+        let rank = self.rng.gen::<u64>().trailing_zeros();
+        self.len += 1;
+        insert(&mut self.root, Box::new(Node {key, value, rank, left: None, right: None}))
+    }
+}
+
+impl<K: Ord, V> Map for ZipTree<K, V> {
+    type Key = K;
+    type Value = V;
+
+    /// Creates an empty zip tree with the generator of `rng::default_rng`, so
+    /// that the same operations give the same shape in every run.
+    fn new() -> ZipTree<K, V> {
+        ZipTree::with_seed(rng::DEFAULT_SEED)
+    }
+
+    fn find(&self, key: &K) -> Option<&V> {
+        let mut current = &self.root;
+        while let Some(n) = current {
+            match key.cmp(&n.key) {
+                Less => current = &n.left,
+                Equal => return Some(&n.value),
+                Greater => current = &n.right
+            }
+        }
+
+        None
+    }
+
+    fn find_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut current = &mut self.root;
+        while let Some(n) = current {
+            match key.cmp(&n.key) {
+                Less => current = &mut n.left,
+                Equal => return Some(&mut n.value),
+                Greater => current = &mut n.right
+            }
+        }
+
+        None
+    }
+
+    fn ins(&mut self, key: K, value: V) -> &mut Self {
+        // Unzipping assumes the key isn't there yet.
+        if let Some(v) = self.find_mut(&key) {
+            *v = value;
+            return self;
+        }
+
+        self.ins_new(key, value);
+        self
+    }
+
+    fn del(&mut self, key: &K) -> Option<V> {
+        let result = delete(&mut self.root, key);
+        if result.is_some() {
+            self.len -= 1;
+        }
+
+        result
+    }
+
+    /// Time: O(1)
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Keeps the generator, so that a seeded zip tree stays reproducible.
+    fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        entry::lookup(self, key, ZipTree::find_mut, ZipTree::ins_new)
+    }
+}
+
+impl_ordered_map!(ZipTree, |t| t.root.as_deref());
+
+impl<K: Ord + Arbitrary, V: Arbitrary> Arbitrary for ZipTree<K, V> {
+    fn arbitrary<G: Gen>(g: &mut G) -> ZipTree<K, V> {
+        let data: Vec<(K, V)> = Arbitrary::arbitrary(g);
+
+        let mut t = ZipTree::new();
+        for (k, v) in data {
+            t.ins(k, v);
+        }
+
+        t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map::Map;
+    use map::treap::Treap;
+    use super::ZipTree;
+
+    map_tests!(ZipTree<usize, usize>);
+    ordered_map_tests!(ZipTree<usize, usize>);
+
+    quickcheck! {
+        fn is_valid_arbitrary(t: ZipTree<usize, usize>) -> bool {
+            t.is_valid()
+        }
+
+        fn is_valid_del(t: ZipTree<usize, usize>, ks: Vec<usize>) -> bool {
+            let mut t = t.clone();
+            ks.iter().all(|k| {
+                t.del(k);
+                t.is_valid()
+            })
+        }
+
+        // A zip tree and a treap given the same operations give the same answers.
+        fn same_as_treap(ops: Vec<(bool, u8, u32)>) -> bool {
+            let mut z = ZipTree::new();
+            let mut t = Treap::new();
+
+            for (ins, k, v) in ops {
+                let k = k % 32;
+                if ins {
+                    z.ins(k, v);
+                    t.ins(k, v);
+                } else if z.del(&k) != t.del(&k) {
+                    return false;
+                }
+
+                if (0 .. 32).any(|k| z.find(&k) != t.find(&k)) {
+                    return false;
+                }
+            }
+
+            z.is_valid() && t.is_valid()
+        }
+
+        // Same seed, same operations, same shape.
+        fn seeded(seed: u64, ks: Vec<u16>) -> bool {
+            let mut t1 = ZipTree::with_seed(seed);
+            let mut t2 = ZipTree::with_seed(seed);
+            for &k in &ks {
+                t1.ins(k, ());
+                t2.ins(k, ());
+            }
+
+            format!("{:?}", t1.root) == format!("{:?}", t2.root)
+        }
+
+        // The expected height is about 1.5 log n; this leaves lots of slack.
+        fn height_sorted(n: u16, seed: u64) -> bool {
+            let mut t = ZipTree::with_seed(seed);
+            for i in 0 .. n {
+                t.ins(i, ());
+            }
+
+            t.height() as f64 <= 8.0 * ((n as f64) + 1.0).log2() + 1.0
+        }
+    }
+}
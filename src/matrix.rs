@@ -0,0 +1,375 @@
+//! Dense matrices and cache-aware algorithms on them.
+//!
+//! The textbook algorithms walk one of their arguments column by column. A matrix
+//! is stored row by row, so for big matrices every step of such a walk is a cache
+//! miss. The blocked variants split matrices into square blocks small enough to
+//! stay in the cache and finish working on a block before moving to the next one.
+//! Run `cargo bench --bench matrix` to see the difference.
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use rand;
+use rand::Rng;
+
+use std::ops::{Add, Index, IndexMut, Mul, Neg, Sub};
+
+/// The operations matrix multiplication needs. `Default` has to be zero.
+pub trait Scalar: Copy + Default + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {}
+
+impl<T> Scalar for T
+    where T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T> {}
+
+/// Scalars which can be negated, so that subtracting a bigger one doesn't
+/// overflow: signed integers, floats, and unsigned integers wrapped in
+/// `std::num::Wrapping`. Strassen's algorithm needs them, because it
+/// subtracts blocks which have nothing to do with the result.
+pub trait Ring: Scalar + Neg<Output = Self> {}
+
+impl<T> Ring for T where T: Scalar + Neg<Output = T> {}
+
+/// A matrix stored row by row in a single vector.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::matrix::Matrix;
+///
+/// let a = Matrix::from_vec(2, 3, vec![1, 2, 3, 4, 5, 6]);
+/// let b = a.transpose();
+/// assert_eq!(b[(2, 0)], 3);
+///
+/// let c = a.multiply(&b);
+/// assert_eq!(c, Matrix::from_vec(2, 2, vec![14, 32, 32, 77]));
+/// assert_eq!(a.multiply_blocked(&b, 32), c);
+/// assert_eq!(a.multiply_strassen(&b, 1), c);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>
+}
+
+impl<T> Matrix<T> {
+    /// Creates a matrix out of its entries, listed row by row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are not `rows * cols` entries.
+    pub fn from_vec(rows: usize, cols: usize, data: Vec<T>) -> Matrix<T> {
+        assert_eq!(data.len(), rows * cols, "Matrix: wrong number of entries");
+
+        Matrix {rows, cols, data}
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns row `i`.
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.data[i * self.cols .. (i + 1) * self.cols]
+    }
+}
+
+impl<T: Copy + Default> Matrix<T> {
+    /// Creates a matrix filled with `T::default()`.
+    pub fn new(rows: usize, cols: usize) -> Matrix<T> {
+        Matrix::from_vec(rows, cols, vec![T::default(); rows * cols])
+    }
+
+    /// Computes the transpose entry by entry. Writes go down the columns of the
+    /// result, so for big matrices almost every one of them misses the cache.
+    /// Time: O(rows * cols)
+    pub fn transpose(&self) -> Matrix<T> {
+        let mut t = Matrix::new(self.cols, self.rows);
+        for i in 0 .. self.rows {
+            for j in 0 .. self.cols {
+                t[(j, i)] = self[(i, j)];
+            }
+        }
+
+        t
+    }
+
+    /// Computes the transpose block by block, so that both the rows read and
+    /// the rows written stay in the cache while a block is being copied.
+    /// Time: O(rows * cols)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block` is 0.
+    pub fn transpose_blocked(&self, block: usize) -> Matrix<T> {
+        assert!(block > 0, "Matrix: blocks can't be empty");
+
+        let mut t = Matrix::new(self.cols, self.rows);
+        for i0 in (0 .. self.rows).step_by(block) {
+            for j0 in (0 .. self.cols).step_by(block) {
+                for i in i0 .. (i0 + block).min(self.rows) {
+                    for j in j0 .. (j0 + block).min(self.cols) {
+                        t[(j, i)] = self[(i, j)];
+                    }
+                }
+            }
+        }
+
+        t
+    }
+}
+
+impl<T: Scalar> Matrix<T> {
+    fn check_product(&self, other: &Matrix<T>) {
+        assert_eq!(self.cols, other.rows, "Matrix: can't multiply a {}x{} matrix by a {}x{} one",
+                   self.rows, self.cols, other.rows, other.cols);
+    }
+
+    /// Multiplies two matrices with the textbook algorithm: every entry of the
+    /// result is a row of `self` times a column of `other`.
+    /// Time: O(n * m * p) for an n x m matrix times an m x p one
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of columns of `self` isn't the number of rows of `other`.
+    pub fn multiply(&self, other: &Matrix<T>) -> Matrix<T> {
+        self.check_product(other);
+
+        let mut c = Matrix::new(self.rows, other.cols);
+        for i in 0 .. self.rows {
+            for j in 0 .. other.cols {
+                let mut sum = T::default();
+                for k in 0 .. self.cols {
+                    sum = sum + self[(i, k)] * other[(k, j)];
+                }
+                c[(i, j)] = sum;
+            }
+        }
+
+        c
+    }
+
+    /// Multiplies two matrices block by block. Within a pair of blocks, the loops
+    /// go along rows of both `other` and the result, never down columns.
+    /// Time: O(n * m * p), like `multiply`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block` is 0 or the matrices can't be multiplied.
+    pub fn multiply_blocked(&self, other: &Matrix<T>, block: usize) -> Matrix<T> {
+        self.check_product(other);
+        assert!(block > 0, "Matrix: blocks can't be empty");
+
+        let (n, m, p) = (self.rows, self.cols, other.cols);
+        let mut c = Matrix::new(n, p);
+        for i0 in (0 .. n).step_by(block) {
+            for k0 in (0 .. m).step_by(block) {
+                for j0 in (0 .. p).step_by(block) {
+                    let j1 = (j0 + block).min(p);
+                    for i in i0 .. (i0 + block).min(n) {
+                        for k in k0 .. (k0 + block).min(m) {
+                            let a = self[(i, k)];
+                            let row = &other.data[k * p + j0 .. k * p + j1];
+                            let out = &mut c.data[i * p + j0 .. i * p + j1];
+                            for (x, &y) in out.iter_mut().zip(row) {
+                                *x = *x + a * y;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        c
+    }
+
+    /// The matrix padded with zeros to `rows` x `cols`.
+    fn padded(&self, rows: usize, cols: usize) -> Matrix<T> {
+        let mut m = Matrix::new(rows, cols);
+        for i in 0 .. self.rows {
+            m.data[i * cols .. i * cols + self.cols].copy_from_slice(self.row(i));
+        }
+
+        m
+    }
+
+    /// The `rows` x `cols` block whose top left corner is at (i, j).
+    fn submatrix(&self, i: usize, j: usize, rows: usize, cols: usize) -> Matrix<T> {
+        let mut data = Vec::with_capacity(rows * cols);
+        for r in i .. i + rows {
+            data.extend_from_slice(&self.row(r)[j .. j + cols]);
+        }
+
+        Matrix::from_vec(rows, cols, data)
+    }
+
+    fn zip_with<F: Fn(T, T) -> T>(&self, other: &Matrix<T>, f: F) -> Matrix<T> {
+        let data = self.data.iter().zip(&other.data).map(|(&x, &y)| f(x, y)).collect();
+
+        Matrix::from_vec(self.rows, self.cols, data)
+    }
+}
+
+impl<T: Ring> Matrix<T> {
+    /// Multiplies two matrices with Strassen's algorithm, which multiplies 2x2
+    /// block matrices with 7 multiplications of blocks instead of 8. A matrix
+    /// with an odd number of rows or columns gets padded with a zero row or
+    /// column before being split. Blocks with a side of at most `threshold`
+    /// are multiplied by `multiply_blocked` instead, because for small
+    /// matrices the additions cost more than they save. So do thin matrices,
+    /// like a row times a column.
+    /// Time: O(n^2.81) for n x n matrices
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threshold` is 0 or the matrices can't be multiplied.
+    pub fn multiply_strassen(&self, other: &Matrix<T>, threshold: usize) -> Matrix<T> {
+        self.check_product(other);
+        assert!(threshold > 0, "Matrix: the threshold must be positive");
+
+        strassen(self, other, threshold)
+    }
+}
+
+/// Strassen's algorithm for an n x m matrix times an m x p one.
+fn strassen<T: Ring>(a: &Matrix<T>, b: &Matrix<T>, threshold: usize) -> Matrix<T> {
+    let (n, m, p) = (a.rows, a.cols, b.cols);
+    if n.min(m).min(p) <= threshold {
+        return a.multiply_blocked(b, threshold);
+    }
+
+    let (hn, hm, hp) = (n.div_ceil(2), m.div_ceil(2), p.div_ceil(2));
+    let (a, b) = (a.padded(2 * hn, 2 * hm), b.padded(2 * hm, 2 * hp));
+    let quarters = |x: &Matrix<T>, h: usize, w: usize| {
+        (x.submatrix(0, 0, h, w), x.submatrix(0, w, h, w), x.submatrix(h, 0, h, w), x.submatrix(h, w, h, w))
+    };
+    let (a11, a12, a21, a22) = quarters(&a, hn, hm);
+    let (b11, b12, b21, b22) = quarters(&b, hm, hp);
+    let add = |x: &Matrix<T>, y: &Matrix<T>| x.zip_with(y, |p, q| p + q);
+    let sub = |x: &Matrix<T>, y: &Matrix<T>| x.zip_with(y, |p, q| p - q);
+
+    let m1 = strassen(&add(&a11, &a22), &add(&b11, &b22), threshold);
+    let m2 = strassen(&add(&a21, &a22), &b11, threshold);
+    let m3 = strassen(&a11, &sub(&b12, &b22), threshold);
+    let m4 = strassen(&a22, &sub(&b21, &b11), threshold);
+    let m5 = strassen(&add(&a11, &a12), &b22, threshold);
+    let m6 = strassen(&sub(&a21, &a11), &add(&b11, &b12), threshold);
+    let m7 = strassen(&sub(&a12, &a22), &add(&b21, &b22), threshold);
+
+    let c11 = add(&sub(&add(&m1, &m4), &m5), &m7);
+    let c12 = add(&m3, &m5);
+    let c21 = add(&m2, &m4);
+    let c22 = add(&add(&sub(&m1, &m2), &m3), &m6);
+
+    // The top left n x p corner of the product of the padded matrices.
+    let mut c = Matrix::new(n, p);
+    for i in 0 .. n {
+        let (left, right) = if i < hn {(&c11, &c12)} else {(&c21, &c22)};
+        let row = c.data[i * p .. (i + 1) * p].iter_mut();
+        for (x, &y) in row.zip(left.row(i % hn).iter().chain(right.row(i % hn))) {
+            *x = y;
+        }
+    }
+
+    c
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Matrix<T> {
+    fn arbitrary<G: Gen>(g: &mut G) -> Matrix<T> {
+        let mut rng = rand::thread_rng();
+
+        let rows = rng.gen_range(0, g.size() + 1);
+        let cols = rng.gen_range(0, g.size() + 1);
+        let data = (0 .. rows * cols).map(|_| T::arbitrary(g)).collect();
+
+        Matrix::from_vec(rows, cols, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Matrix;
+
+    use std::num::Wrapping;
+
+    use rand;
+    use rand::Rng;
+
+    fn random(rows: usize, cols: usize) -> Matrix<i64> {
+        let mut rng = rand::thread_rng();
+        Matrix::from_vec(rows, cols, (0 .. rows * cols).map(|_| rng.gen_range(-100, 100)).collect())
+    }
+
+    quickcheck! {
+        fn transpose_blocked_transpose(m: Matrix<i32>, block: usize) -> bool {
+            m.transpose_blocked(1 + block % 8) == m.transpose()
+        }
+
+        fn transpose_involutive(m: Matrix<i32>) -> bool {
+            m.transpose().transpose() == m
+        }
+
+        fn multiply_blocked_multiply(n: u8, m: u8, p: u8, block: usize) -> bool {
+            let (a, b) = (random(n as usize % 30, m as usize % 30), random(m as usize % 30, p as usize % 30));
+
+            a.multiply_blocked(&b, 1 + block % 10) == a.multiply(&b)
+        }
+
+        fn multiply_strassen_multiply(n: u8, m: u8, p: u8, threshold: usize) -> bool {
+            let (a, b) = (random(n as usize % 30, m as usize % 30), random(m as usize % 30, p as usize % 30));
+
+            a.multiply_strassen(&b, 1 + threshold % 8) == a.multiply(&b)
+        }
+
+        // (AB)^T = B^T A^T
+        fn multiply_transpose(n: u8, m: u8, p: u8) -> bool {
+            let (a, b) = (random(n as usize % 20, m as usize % 20), random(m as usize % 20, p as usize % 20));
+
+            a.multiply(&b).transpose() == b.transpose().multiply(&a.transpose())
+        }
+    }
+
+    // Strassen's algorithm subtracts blocks of `b` which are bigger than those
+    // subtracted from them.
+    #[test]
+    fn multiply_strassen_unsigned() {
+        let b = Matrix::from_vec(2, 2, vec![1, 2, 3, 4].into_iter().map(Wrapping).collect());
+        let id = Matrix::from_vec(2, 2, vec![1, 0, 0, 1].into_iter().map(Wrapping).collect::<Vec<Wrapping<u32>>>());
+
+        assert_eq!(id.multiply_strassen(&b, 1), b);
+        assert_eq!(b.multiply_strassen(&id, 1), b);
+    }
+
+    #[test]
+    fn multiply_strassen_thin() {
+        let (a, b) = (random(1, 1000), random(1000, 1));
+        assert_eq!(a.multiply_strassen(&b, 1), a.multiply(&b));
+        assert_eq!(b.multiply_strassen(&a, 4), b.multiply(&a));
+    }
+
+    #[test]
+    #[should_panic]
+    fn multiply_wrong_sizes() {
+        random(2, 3).multiply(&random(2, 3));
+    }
+}
@@ -1,5 +1,6 @@
 //! Priority queues.
 
+use std::cmp::Reverse;
 use std::ops::Index;
 use std::f64;
 
@@ -32,12 +33,48 @@ pub trait PriorityQueue {
     fn del_min(&mut self) -> Option<Self::Item>;
 }
 
+/// How `Heap::heapify` builds a heap out of a vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// Floyd's algorithm: sift every parent down, starting from the last one.
+    /// Most nodes are near the bottom and don't go far, so it makes at most 2n
+    /// comparisons.
+    #[default]
+    Floyd,
+
+    /// Insert the elements one by one, sifting each up. Makes O(n log n)
+    /// comparisons in the worst case (for example, for decreasing input).
+    Insertion
+}
+
+/// What `Heap::from_iter_bounded` does with an element which comes when the heap
+/// is already full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Drop the new element, so the heap keeps the first elements that came.
+    Reject,
+
+    /// Drop the greatest of the elements in the heap and the new one, so the
+    /// heap keeps the least elements of the whole stream.
+    DropMax,
+
+    /// Like `DropMax`, but the greatest elements are kept in a vector instead of
+    /// being dropped.
+    SpillToVec
+}
+
 // A binary heap implemented implicitly using a Vec.
 #[derive(Debug)]
 pub struct Heap<T: PartialOrd> {
     array: Vec<T>
 }
 
+impl<T: PartialOrd> Default for Heap<T> {
+    fn default() -> Heap<T> {
+        Heap::new()
+    }
+}
+
 impl<T: PartialOrd> Heap<T> {
     /// Create an empty priority queue.
     /// Time: O(1)
@@ -65,8 +102,8 @@ impl<T: PartialOrd> Heap<T> {
 
     /// Check if a vector is a valid heap.
     /// Time:O(size of the heap)
-    pub fn is_heap_aux(v: &Vec<T>, cmp: fn(&T, &T) -> bool) -> bool {
-        if v.len() > 0 {
+    pub fn is_heap_aux(v: &[T], cmp: fn(&T, &T) -> bool) -> bool {
+        if !v.is_empty() {
             let last = v.len() - 1;
 
             // Iterate over all nodes that have children and check if their
@@ -79,10 +116,8 @@ impl<T: PartialOrd> Heap<T> {
                     if cmp(&v[i], &v[l]) || cmp(&v[i], &v[r]) {
                         return false;
                     }
-                } else if l <= last {
-                    if cmp(&v[i], &v[l]) {
-                        return false;
-                    }
+                } else if l <= last && cmp(&v[i], &v[l]) {
+                    return false;
                 }
             }
         }
@@ -90,7 +125,7 @@ impl<T: PartialOrd> Heap<T> {
         true
     }
 
-    pub fn is_heap(v: &Vec<T>) -> bool {
+    pub fn is_heap(v: &[T]) -> bool {
         Heap::is_heap_aux(v, PartialOrd::gt)
     }
 
@@ -142,51 +177,106 @@ impl<T: PartialOrd> Heap<T> {
         }
     }
 
-    /// Create a heap from a vector.
-    /// Time: O(size of the heap * height of the heap)
-    pub fn make_heap_bottom_up(v: Vec<T>) -> Heap<T> {
+    /// Create a heap from a vector using the given strategy.
+    /// Time: O(size of the heap) for `Strategy::Floyd`, O(size of the heap *
+    /// height of the heap) for `Strategy::Insertion`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate aisd;
+    /// use aisd::pq::{Heap, Strategy};
+    ///
+    /// let h = Heap::heapify(vec![5, 3, 8, 1], Strategy::default());
+    /// assert_eq!(h.arr()[0], 1);
+    ///
+    /// let h = Heap::heapify(vec![5, 3, 8, 1], Strategy::Insertion);
+    /// assert!(Heap::is_heap(h.arr()));
+    /// ```
+    pub fn heapify(v: Vec<T>, strategy: Strategy) -> Heap<T> {
         let mut h = Heap {array: v};
 
-        for i in 0 .. h.size() {
-            h.fix_heap_property_bottom_up(i);
+        match strategy {
+            Strategy::Floyd => {
+                // Leaves are heaps already, so start from the last parent.
+                for i in (0 .. h.size() / 2).rev() {
+                    h.fix_heap_property_top_down(i);
+                }
+            },
+            Strategy::Insertion => {
+                for i in 0 .. h.size() {
+                    h.fix_heap_property_bottom_up(i);
+                }
+            }
         }
 
         h
     }
 
-    /// Create a heap from a vector.
-    /// Time: O(size of the heap)
-    pub fn make_heap_top_down(v: Vec<T>) -> Heap<T> {
-        if v.len() == 0 {
-            Heap::new()
-        } else {
-            let mut h = Heap {array: v};
-
-            for i in (0 .. (h.size() - 1)).rev() {
-                h.fix_heap_property_top_down(i);
+    /// Create a heap of at most `max_size` elements from an iterator of unknown
+    /// (possibly huge) length, using O(`max_size`) memory for the heap. What
+    /// happens to elements which don't fit depends on `overflow`. Returns the
+    /// heap and the elements spilled by `Overflow::SpillToVec` (for the other
+    /// policies the vector is empty).
+    ///
+    /// Until the heap is full, elements are kept in a heap with the greatest
+    /// element on top, so that a new element only has to be compared with it to
+    /// know whether it replaces it. The result is heapified once at the end.
+    /// Time: O(length of the iterator * log(`max_size`))
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate aisd;
+    /// use aisd::pq::{Heap, Overflow};
+    ///
+    /// let (h, spilled) = Heap::from_iter_bounded(vec![5, 3, 8, 1, 9], 3, Overflow::SpillToVec);
+    /// assert_eq!(h.collect::<Vec<_>>(), vec![1, 3, 5]);
+    /// assert_eq!(spilled, vec![8, 9]);
+    ///
+    /// let (h, _) = Heap::from_iter_bounded(vec![5, 3, 8, 1, 9], 3, Overflow::Reject);
+    /// assert_eq!(h.collect::<Vec<_>>(), vec![3, 5, 8]);
+    /// ```
+    pub fn from_iter_bounded<I>(iter: I, max_size: usize, overflow: Overflow) -> (Heap<T>, Vec<T>)
+        where I: IntoIterator<Item = T>
+    {
+        let mut kept: Heap<Reverse<T>> = Heap::new();
+        let mut spilled = vec![];
+        for x in iter {
+            if kept.size() < max_size {
+                kept.insert(Reverse(x));
+                continue;
             }
 
-            h
+            let evicted = match overflow {
+                Overflow::Reject => continue,
+                _ if PriorityQueue::min(&kept).is_some_and(|max| x < max.0) => {
+                    let Reverse(max) = ::std::mem::replace(&mut kept.array[0], Reverse(x));
+                    kept.fix_heap_property_top_down(0);
+                    max
+                },
+                _ => x
+            };
+            if overflow == Overflow::SpillToVec {
+                spilled.push(evicted);
+            }
         }
+
+        let v = kept.array.into_iter().map(|Reverse(x)| x).collect();
+        (Heap::heapify(v, Strategy::Floyd), spilled)
     }
-    
+
     /// Destructive heapsort.
     /// Time: O(nlgn)
     pub fn sort(v: &mut Vec<T>) {
         let mut h = Heap::new();
         
-        loop {
-            match v.pop() {
-                Some(x) => h.insert(x),
-                None => break
-            }
+        while let Some(x) = v.pop() {
+            h.insert(x);
         }
 
-        loop {
-            match h.del_min() {
-                Some(x) => v.push(x),
-                None => break
-            }
+        while let Some(x) = h.del_min() {
+            v.push(x);
         }
     }
 
@@ -283,12 +373,365 @@ impl<T: PartialOrd + Arbitrary + Clone> Arbitrary for Heap<T> {
     }
 }
 
+/// A weak heap (Dutton): a binary tree in which every element is no smaller than
+/// its distinguished ancestor (the parent of the closest ancestor which is a
+/// right child), and the root has no left subtree. It's looser than a heap, so
+/// it's cheaper to repair: building takes n - 1 comparisons and `del_min` takes
+/// about log n, while a binary heap needs up to 2n and 2 log n.
+///
+/// The tree lives in an array with an extra bit per node which says whether its
+/// children are swapped, so that swapping subtrees costs O(1).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::pq::{PriorityQueue, WeakHeap};
+///
+/// let mut h = WeakHeap::heapify(vec![5, 3, 8, 1]);
+/// h.insert(2);
+/// assert_eq!(h.collect::<Vec<_>>(), vec![1, 2, 3, 5, 8]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct WeakHeap<T: PartialOrd> {
+    array: Vec<T>,
+    // Whether the children of a node are swapped: the left child of i is
+    // 2i + reverse[i] and the right one is 2i + 1 - reverse[i].
+    reverse: Vec<bool>
+}
+
+impl<T: PartialOrd> Default for WeakHeap<T> {
+    fn default() -> WeakHeap<T> {
+        WeakHeap::new()
+    }
+}
+
+impl<T: PartialOrd> WeakHeap<T> {
+    /// Create an empty priority queue.
+    /// Time: O(1)
+    pub fn new() -> WeakHeap<T> {
+        WeakHeap {array: vec![], reverse: vec![]}
+    }
+
+    /// Create a weak heap from a vector with n - 1 comparisons.
+    /// Time: O(size of the heap)
+    pub fn heapify(v: Vec<T>) -> WeakHeap<T> {
+        let n = v.len();
+        let mut h = WeakHeap {array: v, reverse: vec![false; n]};
+        for j in (1 .. n).rev() {
+            let i = h.ancestor(j);
+            h.join(i, j);
+        }
+
+        h
+    }
+
+    /// The distinguished ancestor of `j`, which has to be bigger than 0: the
+    /// parent of the closest ancestor of `j` (or `j` itself) which is a right
+    /// child.
+    fn ancestor(&self, mut j: usize) -> usize {
+        while (j & 1 == 1) == self.reverse[j / 2] {
+            j /= 2;
+        }
+
+        j / 2
+    }
+
+    /// Makes sure that `array[i] <= array[j]`, where `i` is the distinguished
+    /// ancestor of `j`, by swapping them along with the subtrees of `j` if
+    /// needed. Returns whether they were in order already.
+    fn join(&mut self, i: usize, j: usize) -> bool {
+        if self.array[j] < self.array[i] {
+            self.array.swap(i, j);
+            self.reverse[j] = !self.reverse[j];
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Check whether every element is no smaller than its distinguished ancestor.
+    /// Time: O(size of the heap)
+    #[cfg(test)]
+    fn is_heap(&self) -> bool {
+        (1 .. self.array.len()).all(|j| self.array[self.ancestor(j)] <= self.array[j])
+    }
+}
+
+impl<T: PartialOrd> PriorityQueue for WeakHeap<T> {
+    type Item = T;
+
+    /// Time: O(1)
+    fn is_empty(&self) -> bool {
+        self.array.is_empty()
+    }
+
+    /// Time: O(1)
+    fn size(&self) -> usize {
+        self.array.len()
+    }
+
+    /// Time: O(log(size of the heap))
+    fn insert(&mut self, elem: T) {
+        let n = self.size();
+        self.array.push(elem);
+        self.reverse.push(false);
+
+        // The parent is a leaf, so this doesn't move anything. It makes the
+        // new node a left child, which saves a comparison on the way up.
+        if n.is_multiple_of(2) && n > 0 {
+            self.reverse[n / 2] = false;
+        }
+
+        let mut j = n;
+        while j != 0 {
+            let i = self.ancestor(j);
+            if self.join(i, j) {
+                break;
+            }
+            j = i;
+        }
+    }
+
+    /// Time: O(1)
+    fn min(&self) -> Option<&T> {
+        self.array.first()
+    }
+
+    /// Time: O(log(size of the heap))
+    fn del_min(&mut self) -> Option<T> {
+        let n = self.size();
+        if n == 0 {
+            return None;
+        }
+
+        self.array.swap(0, n - 1);
+        self.reverse.pop();
+        let result = self.array.pop();
+
+        // Every node on the left spine of the right subtree of the root has the
+        // root as its distinguished ancestor. Join them with the root bottom-up.
+        let n = n - 1;
+        if n > 1 {
+            let mut j = 1;
+            while 2 * j + (self.reverse[j] as usize) < n {
+                j = 2 * j + self.reverse[j] as usize;
+            }
+            while j != 0 {
+                self.join(0, j);
+                j /= 2;
+            }
+        }
+
+        result
+    }
+}
+
+/// A weak heap is also an iterator (`next` is `del_min`).
+impl<T: PartialOrd> Iterator for WeakHeap<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.del_min()
+    }
+}
+
+impl<T: PartialOrd + Arbitrary> Arbitrary for WeakHeap<T> {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let v: Vec<T> = Arbitrary::arbitrary(g);
+
+        let mut h = WeakHeap::new();
+        for x in v {
+            h.insert(x);
+        }
+
+        h
+    }
+}
+
+/// A binary heap of the items {0, ..., n - 1}, each with a key, which knows
+/// where every item is, so that the key of an item already in the heap can be
+/// decreased. That's what Dijkstra's and Prim's algorithms need to keep one
+/// entry per node, instead of inserting a new one for every improvement and
+/// skipping the stale ones later.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::pq::IndexedHeap;
+///
+/// let mut h = IndexedHeap::new(4);
+/// h.insert(0, 7);
+/// h.insert(2, 5);
+/// h.insert(3, 9);
+/// h.decrease_key(3, 1);
+/// assert_eq!(h.key(3), Some(&1));
+/// assert_eq!(h.del_min(), Some((3, 1)));
+/// assert_eq!(h.del_min(), Some((2, 5)));
+/// assert!(!h.contains(2) && h.contains(0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct IndexedHeap<K: PartialOrd> {
+    // The items, arranged as a binary heap by their keys.
+    heap: Vec<usize>,
+    // The position of every item in `heap`, if it's there.
+    pos: Vec<Option<usize>>,
+    keys: Vec<Option<K>>
+}
+
+impl<K: PartialOrd> IndexedHeap<K> {
+    /// Creates an empty heap for the items {0, ..., n - 1}.
+    /// Time: O(n)
+    pub fn new(n: usize) -> IndexedHeap<K> {
+        IndexedHeap {heap: vec![], pos: vec![None; n], keys: (0 .. n).map(|_| None).collect()}
+    }
+
+    /// Checks whether the heap is empty.
+    /// Time: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Computes the number of items in the heap.
+    /// Time: O(1)
+    pub fn size(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Checks whether `i` is in the heap.
+    /// Time: O(1)
+    pub fn contains(&self, i: usize) -> bool {
+        self.pos[i].is_some()
+    }
+
+    /// Returns the key of `i`, if it's in the heap.
+    /// Time: O(1)
+    pub fn key(&self, i: usize) -> Option<&K> {
+        self.keys[i].as_ref()
+    }
+
+    /// Inserts `i` with the given key.
+    /// Time: O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is already in the heap.
+    pub fn insert(&mut self, i: usize, key: K) {
+        assert!(!self.contains(i), "IndexedHeap::insert: item {} is already in the heap", i);
+        self.keys[i] = Some(key);
+        self.pos[i] = Some(self.heap.len());
+        self.heap.push(i);
+        let last = self.heap.len() - 1;
+        self.sift_up(last);
+    }
+
+    /// Decreases the key of `i`, which is in the heap, to `key`.
+    /// Time: O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` isn't in the heap or `key` is greater than its key.
+    pub fn decrease_key(&mut self, i: usize, key: K) {
+        let p = self.pos[i].expect("IndexedHeap::decrease_key: item not in the heap");
+        assert!(self.keys[i].as_ref().is_some_and(|k| key <= *k),
+                "IndexedHeap::decrease_key: the key of item {} would increase", i);
+        self.keys[i] = Some(key);
+        self.sift_up(p);
+    }
+
+    /// Inserts `i` with the given key if it isn't in the heap, or decreases
+    /// its key if the given one is less. Returns whether anything changed.
+    /// Time: O(log n)
+    pub fn insert_or_decrease(&mut self, i: usize, key: K) -> bool {
+        match self.keys[i] {
+            Some(ref k) if key < *k => {
+                self.decrease_key(i, key);
+                true
+            },
+            Some(_) => false,
+            None => {
+                self.insert(i, key);
+                true
+            }
+        }
+    }
+
+    /// Returns the item with the least key, and the key.
+    /// Time: O(1)
+    pub fn min(&self) -> Option<(usize, &K)> {
+        self.heap.first().map(|&i| (i, self.keys[i].as_ref().unwrap()))
+    }
+
+    /// Removes the item with the least key from the heap and returns it with
+    /// its key.
+    /// Time: O(log n)
+    pub fn del_min(&mut self) -> Option<(usize, K)> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        let last = self.heap.len() - 1;
+        self.swap(0, last);
+        let i = self.heap.pop().unwrap();
+        self.pos[i] = None;
+        self.sift_down(0);
+
+        self.keys[i].take().map(|k| (i, k))
+    }
+
+    fn less(&self, a: usize, b: usize) -> bool {
+        self.keys[self.heap[a]] < self.keys[self.heap[b]]
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.pos[self.heap[a]] = Some(a);
+        self.pos[self.heap[b]] = Some(b);
+    }
+
+    // Moves the item at position `p` up while it's less than its parent.
+    fn sift_up(&mut self, mut p: usize) {
+        while p > 0 && self.less(p, (p - 1) / 2) {
+            self.swap(p, (p - 1) / 2);
+            p = (p - 1) / 2;
+        }
+    }
+
+    // Moves the item at position `p` down while a child is less than it.
+    fn sift_down(&mut self, mut p: usize) {
+        loop {
+            let (left, right) = (2 * p + 1, 2 * p + 2);
+            let mut least = p;
+            if left < self.heap.len() && self.less(left, least) {
+                least = left;
+            }
+            if right < self.heap.len() && self.less(right, least) {
+                least = right;
+            }
+            if least == p {
+                break;
+            }
+            self.swap(p, least);
+            p = least;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pq::Heap;
+    use pq::IndexedHeap;
+    use pq::Overflow;
     use pq::PriorityQueue;
+    use pq::Strategy;
+    use pq::WeakHeap;
+    use stats::{self, Counted};
 
-    fn is_sorted<T: PartialOrd>(v: &Vec<T>) -> bool {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    fn is_sorted<T: PartialOrd>(v: &[T]) -> bool {
         if v.len() >= 2 {
             for i in 0 .. v.len() - 2 {
                 if v[i] > v[i + 1] {return false;}
@@ -373,6 +816,84 @@ mod tests {
         }
     }
 
+    // The worst cases at a size quickcheck doesn't reach.
+    #[test]
+    fn heapify_comparisons_big() {
+        let n = 1 << 16;
+
+        let decreasing = (0 .. n).rev().map(Counted).collect();
+        let (_, c) = stats::count(|| Heap::heapify(decreasing, Strategy::Floyd));
+        assert!(c <= 2 * n);
+
+        // Insertion isn't linear: every element goes all the way up.
+        let decreasing = (0 .. n).rev().map(Counted).collect();
+        let (_, c) = stats::count(|| Heap::heapify(decreasing, Strategy::Insertion));
+        assert!(c > 10 * n);
+    }
+
+    quickcheck! {
+        fn is_heap_weak_heapify(v: Vec<u32>) -> bool {
+            WeakHeap::heapify(v).is_heap()
+        }
+
+        fn is_heap_weak_ins(h: WeakHeap<u32>, i: u32) -> bool {
+            h.clone().ins(i).is_heap()
+        }
+
+        fn is_heap_weak_del_min(h: WeakHeap<u32>) -> bool {
+            let mut h = h.clone();
+            h.del_min();
+            h.is_heap()
+        }
+
+        fn weak_heap_std(v: Vec<u32>, ops: Vec<Option<u32>>) -> bool {
+            let mut h = WeakHeap::heapify(v.clone());
+            let mut oracle: BinaryHeap<Reverse<u32>> = v.into_iter().map(Reverse).collect();
+
+            ops.into_iter().all(|op| {
+                match op {
+                    Some(x) => {
+                        h.insert(x);
+                        oracle.push(Reverse(x));
+                    },
+                    None => if h.del_min() != oracle.pop().map(|Reverse(x)| x) {
+                        return false;
+                    }
+                }
+
+                h.size() == oracle.len() && PriorityQueue::min(&h) == oracle.peek().map(|Reverse(x)| x)
+            })
+        }
+
+        // Building takes n - 1 comparisons and draining at most about n log n.
+        fn weak_heap_comparisons(v: Vec<u32>) -> bool {
+            let n = v.len() as u64;
+            let log = 64 - n.leading_zeros() as u64;
+            let v: Vec<_> = v.into_iter().map(Counted).collect();
+
+            let (h, build) = stats::count(|| WeakHeap::heapify(v));
+            let (_, drain) = stats::count(|| h.count());
+
+            build == n.saturating_sub(1) && drain <= n * log
+        }
+    }
+
+    // A weak heap makes fewer comparisons than a binary heap, both when building
+    // and when removing.
+    #[test]
+    fn weak_heap_fewer_comparisons() {
+        let n = 1 << 14;
+        let v: Vec<_> = (0 .. n).map(|i: u64| Counted(i.wrapping_mul(0x9e37_79b9_7f4a_7c15))).collect();
+
+        let (h, weak_build) = stats::count(|| WeakHeap::heapify(v.clone()));
+        let (_, weak_drain) = stats::count(|| h.count());
+        let (h, build) = stats::count(|| Heap::heapify(v, Strategy::Floyd));
+        let (_, drain) = stats::count(|| h.count());
+
+        assert!(weak_build < build, "{} >= {}", weak_build, build);
+        assert!(3 * weak_drain < 2 * drain, "{} vs {}", weak_drain, drain);
+    }
+
     // Implementation tests.
     quickcheck! {
         
@@ -392,37 +913,32 @@ mod tests {
             Heap::is_heap((Heap::new() as Heap<u32>).arr())
         }
 
-        fn is_empty_make_new_heap_bottom_up(v: Vec<u32>) -> bool {
+        fn is_empty_heapify(v: Vec<u32>) -> bool {
             let b = v.is_empty();
-            let h = Heap::make_heap_bottom_up(v);
-            h.is_empty() == b
+
+            Heap::heapify(v.clone(), Strategy::Floyd).is_empty() == b &&
+            Heap::heapify(v, Strategy::Insertion).is_empty() == b
         }
 
-        fn size_make_new_heap_bottom_up(v: Vec<u32>) -> bool {
+        fn size_heapify(v: Vec<u32>) -> bool {
             let len = v.len();
-            let h = Heap::make_heap_bottom_up(v);
-            h.size() == len
-        }
 
-        fn is_heap_make_new_heap_bottom_up(v: Vec<u32>) -> bool {
-            let h = Heap::make_heap_bottom_up(v);
-            Heap::is_heap(h.arr())
+            Heap::heapify(v.clone(), Strategy::Floyd).size() == len &&
+            Heap::heapify(v, Strategy::Insertion).size() == len
         }
 
-        fn is_empty_make_new_heap_top_down(v: Vec<u32>) -> bool {
-            let b = v.is_empty();
-            let h = Heap::make_heap_top_down(v);
-            h.is_empty() == b
-        }
-        fn size_make_new_heap_top_down(v: Vec<u32>) -> bool {
-            let len = v.len();
-            let h = Heap::make_heap_top_down(v);
-            h.size() == len
+        fn is_heap_heapify(v: Vec<u32>) -> bool {
+            Heap::is_heap(Heap::heapify(v.clone(), Strategy::Floyd).arr()) &&
+            Heap::is_heap(Heap::heapify(v, Strategy::Insertion).arr())
         }
 
-        fn is_heap_make_new_heap_top_down(v: Vec<u32>) -> bool {
-            let h = Heap::make_heap_top_down(v);
-            Heap::is_heap(h.arr())
+        // Floyd's algorithm is linear: at most 2 comparisons per element.
+        fn heapify_comparisons(v: Vec<u32>) -> bool {
+            let n = v.len() as u64;
+            let v = v.into_iter().map(Counted).collect();
+            let (_, c) = stats::count(|| Heap::heapify(v, Strategy::Floyd));
+
+            c <= 2 * n
         }
 
         fn is_heap_arbitrary(h: Heap<u32>) -> bool {
@@ -449,5 +965,68 @@ mod tests {
             let v = Heap::sort2(v);
             is_sorted(&v)
         }
+
+        fn from_iter_bounded_reject(v: Vec<u32>, max_size: usize) -> bool {
+            let max_size = max_size % 16;
+            let (h, spilled) = Heap::from_iter_bounded(v.clone(), max_size, Overflow::Reject);
+
+            let mut first: Vec<u32> = v.into_iter().take(max_size).collect();
+            first.sort();
+            Heap::is_heap(h.arr()) && spilled.is_empty() && h.collect::<Vec<_>>() == first
+        }
+
+        // The heap keeps the least elements, and the spilled ones are the rest.
+        fn from_iter_bounded_spill(v: Vec<u32>, max_size: usize) -> bool {
+            let max_size = max_size % 16;
+            let (h, mut spilled) = Heap::from_iter_bounded(v.clone(), max_size, Overflow::SpillToVec);
+            let (h2, nothing) = Heap::from_iter_bounded(v.clone(), max_size, Overflow::DropMax);
+
+            let mut sorted = v;
+            sorted.sort();
+            spilled.sort();
+            let least = h.collect::<Vec<_>>();
+            least.len() == max_size.min(sorted.len()) && nothing.is_empty() && h2.collect::<Vec<_>>() == least &&
+            least[..] == sorted[.. least.len()] && spilled[..] == sorted[least.len() ..]
+        }
+    }
+
+    quickcheck! {
+        // Against a vector of keys: Some(key) inserts or decreases the key of
+        // an item, None removes the minimum.
+        fn indexed_heap_naive(ops: Vec<Option<(u8, u32)>>) -> bool {
+            let n = 16;
+            let mut h = IndexedHeap::new(n);
+            let mut keys: Vec<Option<u32>> = vec![None; n];
+
+            ops.into_iter().all(|op| match op {
+                Some((i, key)) => {
+                    let i = i as usize % n;
+                    let changed = keys[i].is_none_or(|k| key < k);
+                    if changed {
+                        keys[i] = Some(key);
+                    }
+                    h.insert_or_decrease(i, key) == changed && h.key(i) == keys[i].as_ref()
+                },
+                None => {
+                    let least = keys.iter().filter_map(|&k| k).min();
+                    match h.del_min() {
+                        Some((i, key)) => {
+                            let ok = Some(key) == least && keys[i] == Some(key);
+                            keys[i] = None;
+                            ok
+                        },
+                        None => least.is_none()
+                    }
+                }
+            } && h.size() == keys.iter().filter(|k| k.is_some()).count())
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn indexed_heap_increase() {
+        let mut h = IndexedHeap::new(2);
+        h.insert(1, 5);
+        h.decrease_key(1, 6);
     }
 }
\ No newline at end of file
@@ -0,0 +1,192 @@
+//! Counting in a sliding window with the DGIM algorithm (Datar, Gionis, Indyk and
+//! Motwani).
+
+use std::collections::VecDeque;
+
+/// Approximates the number of 1s among the last `window` bits of a stream using
+/// O(r log window) memory, instead of the `window` bits an exact count needs.
+///
+/// The 1s are grouped into buckets whose sizes are powers of 2, newer buckets
+/// being no bigger than older ones. There are at most `r` buckets of every size:
+/// when there would be more, the two oldest ones are merged. A bucket remembers
+/// only when its newest 1 came and is thrown away once that falls out of the
+/// window. Only the oldest bucket may stick out of the window, so counting half of
+/// it gives a relative error of at most 1 / r.
+///
+/// Time: `push` is O(log window) amortized, `count` is O(r log window).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::probabilistic::DgimCounter;
+///
+/// let mut c = DgimCounter::with_error(100, 0.1);
+/// for i in 0 .. 1000 {
+///     c.push(i % 3 == 0);
+/// }
+///
+/// // 33 of the last 100 bits are 1s.
+/// let estimate = c.count() as f64;
+/// assert!((estimate - 33.0).abs() <= 3.3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DgimCounter {
+    window: u64,
+    r: usize,
+    // The number of bits pushed so far.
+    time: u64,
+    // The buckets as (time of the newest 1, size), newest first.
+    buckets: VecDeque<(u64, u64)>
+}
+
+impl DgimCounter {
+    /// Creates a counter for the last `window` bits with a relative error of at
+    /// most 1/2, which is what the original algorithm guarantees.
+    pub fn new(window: usize) -> DgimCounter {
+        DgimCounter::with_buckets(window, 2)
+    }
+
+    /// Creates a counter for the last `window` bits whose relative error is at
+    /// most `error`. Memory grows like 1 / `error`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `error` isn't positive.
+    pub fn with_error(window: usize, error: f64) -> DgimCounter {
+        assert!(error > 0.0, "DgimCounter: the error must be positive");
+
+        DgimCounter::with_buckets(window, ((1.0 / error).ceil() as usize).max(2))
+    }
+
+    /// Creates a counter for the last `window` bits which keeps at most `r`
+    /// buckets of every size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is 0 or `r` is less than 2.
+    pub fn with_buckets(window: usize, r: usize) -> DgimCounter {
+        assert!(window > 0, "DgimCounter: the window can't be empty");
+        assert!(r >= 2, "DgimCounter: there must be at least 2 buckets of every size");
+
+        DgimCounter {
+            window: window as u64,
+            r,
+            time: 0,
+            buckets: VecDeque::new()
+        }
+    }
+
+    /// Returns the length of the window.
+    pub fn window(&self) -> usize {
+        self.window as usize
+    }
+
+    /// Returns the number of buckets, which is how much memory the counter uses.
+    pub fn bucket_count(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Appends a bit to the stream.
+    pub fn push(&mut self, bit: bool) {
+        self.time += 1;
+        while self.buckets.back().is_some_and(|&(t, _)| t + self.window <= self.time) {
+            self.buckets.pop_back();
+        }
+
+        if !bit {
+            return;
+        }
+
+        self.buckets.push_front((self.time, 1));
+
+        // Sizes never decrease towards the back, so buckets of the same size are
+        // next to each other. Merging two of them may make one size too many of
+        // the next size, and so on.
+        let mut first = 0;
+        loop {
+            let size = self.buckets[first].1;
+            let same = self.buckets.iter().skip(first).take_while(|b| b.1 == size).count();
+            if same <= self.r {
+                break;
+            }
+
+            let oldest = first + same - 1;
+            self.buckets.remove(oldest);
+            self.buckets[oldest - 1].1 = 2 * size;
+            first = oldest - 1;
+        }
+    }
+
+    /// Estimates the number of 1s among the last `window` bits.
+    pub fn count(&self) -> usize {
+        match self.buckets.back() {
+            None => 0,
+            Some(&(_, oldest)) => {
+                let total: u64 = self.buckets.iter().map(|b| b.1).sum();
+                (total - oldest / 2) as usize
+            }
+        }
+    }
+}
+
+impl Extend<bool> for DgimCounter {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        for bit in iter {
+            self.push(bit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DgimCounter;
+
+    // The exact number of 1s among the last `window` bits.
+    fn exact(bits: &[bool], window: usize) -> usize {
+        bits.iter().rev().take(window).filter(|&&b| b).count()
+    }
+
+    quickcheck! {
+        fn count_error(bits: Vec<bool>, window: u8, r: u8) -> bool {
+            let window = 1 + window as usize;
+            let r = 2 + r as usize % 6;
+            let mut c = DgimCounter::with_buckets(window, r);
+
+            (0 .. bits.len()).all(|i| {
+                c.push(bits[i]);
+                let exact = exact(&bits[.. i + 1], window) as f64;
+
+                (c.count() as f64 - exact).abs() <= exact / r as f64
+            })
+        }
+
+        // At most r buckets of every size, and sizes don't go beyond the window.
+        fn bucket_count(bits: Vec<bool>, window: u8) -> bool {
+            let window = 1 + window as usize;
+            let mut c = DgimCounter::new(window);
+            c.extend(bits);
+
+            let sizes = 2 + (window as f64).log2() as usize;
+            c.bucket_count() <= 2 * sizes
+        }
+
+        fn exact_when_small(bits: Vec<bool>) -> bool {
+            let mut c = DgimCounter::with_buckets(1000, 2);
+            c.extend(bits.iter().cloned());
+
+            // At most one 1, so there's one bucket of size 1.
+            exact(&bits, 1000) > 1 || c.count() == exact(&bits, 1000)
+        }
+    }
+
+    #[test]
+    fn all_ones() {
+        let mut c = DgimCounter::new(1 << 16);
+        c.extend((0 .. 1 << 20).map(|_| true));
+
+        let count = c.count() as f64;
+        assert!((count - 65536.0).abs() <= 32768.0);
+        assert!(c.bucket_count() <= 2 * 18);
+    }
+}
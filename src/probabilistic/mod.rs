@@ -0,0 +1,5 @@
+//! Probabilistic and approximate data structures for streams.
+
+pub mod dgim;
+
+pub use self::dgim::DgimCounter;
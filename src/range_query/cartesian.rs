@@ -0,0 +1,303 @@
+//! Cartesian trees, and range minimum queries answered as lowest common
+//! ancestor queries in them.
+
+use map::bst::{Node, Tree};
+use map::bst::Node::{E, N};
+use range_query::bounds;
+
+use std::ops::RangeBounds;
+
+/// The shape of the Cartesian tree of `xs`, as arrays of children indexed by
+/// positions in `xs`, and its root.
+///
+/// The tree is built left to right. The stack holds the right spine of the tree
+/// of the elements so far; a new element pops the spine's elements bigger than
+/// itself, which become its left subtree, and then is pushed as the right child
+/// of what's left. Every element is pushed and popped once, so it takes O(n).
+/// Equal elements aren't popped, so of equal elements the leftmost one is the
+/// highest.
+fn shape<T: PartialOrd>(xs: &[T]) -> (Option<usize>, Vec<Option<usize>>, Vec<Option<usize>>) {
+    let n = xs.len();
+    let mut left = vec![None; n];
+    let mut right = vec![None; n];
+    let mut stack: Vec<usize> = vec![];
+
+    for i in 0 .. n {
+        let mut last = None;
+        while let Some(&top) = stack.last() {
+            if xs[top] > xs[i] {
+                last = stack.pop();
+            } else {
+                break;
+            }
+        }
+
+        left[i] = last;
+        if let Some(&top) = stack.last() {
+            right[top] = Some(i);
+        }
+        stack.push(i);
+    }
+
+    (stack.first().cloned(), left, right)
+}
+
+/// Visits the tree from `root` in preorder, without recursion.
+fn preorder(root: Option<usize>, left: &[Option<usize>], right: &[Option<usize>]) -> Vec<usize> {
+    let mut order = Vec::with_capacity(left.len());
+    let mut stack: Vec<usize> = root.into_iter().collect();
+    while let Some(u) = stack.pop() {
+        order.push(u);
+        stack.extend(right[u]);
+        stack.extend(left[u]);
+    }
+
+    order
+}
+
+/// Builds the Cartesian tree of `xs`: the binary tree whose root is the least
+/// element (the leftmost one, if there are many), whose left subtree is the
+/// Cartesian tree of the elements to its left and right subtree of those to its
+/// right. Keyed by positions, it's a binary search tree, and by values it's a
+/// heap, so the least element of `xs[l ..= r]` is the lowest common ancestor
+/// of `l` and `r`.
+/// Time: O(n)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::map::Map;
+/// use aisd::range_query::cartesian::cartesian_tree;
+///
+/// let t = cartesian_tree(&[3, 1, 4, 1, 5]);
+/// assert!(t.is_bst());
+/// assert_eq!(t.find(&3), Some(&1));
+/// assert_eq!(t.into_iter().map(|(_, x)| x).collect::<Vec<_>>(), vec![3, 1, 4, 1, 5]);
+/// ```
+pub fn cartesian_tree<T: PartialOrd + Clone>(xs: &[T]) -> Tree<usize, T> {
+    let (root, mut left, mut right) = shape(xs);
+
+    // Children come after their parents in preorder, so going backwards builds
+    // every subtree before it's needed.
+    let mut built: Vec<Node<usize, T>> = (0 .. xs.len()).map(|_| E).collect();
+    for u in preorder(root, &left, &right).into_iter().rev() {
+        let l = left[u].take().map_or(E, |c| ::std::mem::replace(&mut built[c], E));
+        let r = right[u].take().map_or(E, |c| ::std::mem::replace(&mut built[c], E));
+        built[u] = N(u, xs[u].clone(), Box::new(l), Box::new(r));
+    }
+
+    let root = match root {
+        Some(r) => ::std::mem::replace(&mut built[r], E),
+        None => E
+    };
+
+    Tree::from_parts(root, xs.len())
+}
+
+/// Range minimum queries on a fixed array, by reduction to lowest common
+/// ancestors in its Cartesian tree, which in turn reduce to range minimum
+/// queries on depths along an Euler tour of the tree. The tour's minima are
+/// kept in a sparse table: the positions of the minima of all the ranges whose
+/// lengths are powers of two, so that every range is covered by two of them.
+///
+/// Time: `new` is O(n log n), `min_index` and `lca` are O(1).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::range_query::cartesian::CartesianRmq;
+///
+/// let rmq = CartesianRmq::new(&[5, 2, 7, 1, 3, 1]);
+/// assert_eq!(rmq.min_index(0 .. 3), Some(1));
+/// assert_eq!(rmq.min_index(2 ..), Some(3));
+/// assert_eq!(rmq.min_index(4 .. 4), None);
+///
+/// // The ancestors of 2 are 1 and 3.
+/// assert_eq!(rmq.lca(0, 2), 1);
+/// assert_eq!(rmq.parent(2), Some(1));
+/// assert_eq!(rmq.root(), Some(3));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CartesianRmq {
+    root: Option<usize>,
+    parent: Vec<Option<usize>>,
+    // The nodes in the order the Euler tour visits them, with their depths.
+    tour: Vec<usize>,
+    depth: Vec<usize>,
+    // The position of the first visit of every node in the tour.
+    first: Vec<usize>,
+    // sparse[k][i] is the position in the tour of the least deep node among
+    // tour[i .. i + 2^k].
+    sparse: Vec<Vec<usize>>
+}
+
+impl CartesianRmq {
+    /// Preprocesses `xs`. Only the order of the elements is remembered.
+    /// Time: O(n log n)
+    pub fn new<T: PartialOrd>(xs: &[T]) -> CartesianRmq {
+        let n = xs.len();
+        let (root, left, right) = shape(xs);
+
+        let mut parent = vec![None; n];
+        for u in 0 .. n {
+            for c in left[u].into_iter().chain(right[u]) {
+                parent[c] = Some(u);
+            }
+        }
+
+        // The tour writes down a node when entering it and after returning from
+        // each of its children.
+        let mut tour = Vec::with_capacity(2 * n);
+        let mut depth = Vec::with_capacity(2 * n);
+        let mut first = vec![0; n];
+        let mut stack: Vec<(usize, usize, usize)> = root.into_iter().map(|r| (r, 0, 0)).collect();
+        while let Some((u, d, visited)) = stack.pop() {
+            if visited == 0 {
+                first[u] = tour.len();
+            }
+            tour.push(u);
+            depth.push(d);
+
+            let children = [left[u], right[u]];
+            if let Some(c) = children[visited ..].iter().flatten().next() {
+                let visited = if Some(*c) == left[u] {1} else {2};
+                stack.push((u, d, visited));
+                stack.push((*c, d + 1, 0));
+            }
+        }
+
+        let mut sparse = vec![(0 .. tour.len()).collect::<Vec<_>>()];
+        let mut k = 1;
+        while 2 * k <= tour.len() {
+            let prev = &sparse[sparse.len() - 1];
+            let level = (0 ..= tour.len() - 2 * k).map(|i| {
+                let (a, b) = (prev[i], prev[i + k]);
+                if depth[b] < depth[a] {b} else {a}
+            }).collect();
+            sparse.push(level);
+            k *= 2;
+        }
+
+        CartesianRmq {root, parent, tour, depth, first, sparse}
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    /// Checks whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// The position of the least element, the root of the Cartesian tree.
+    pub fn root(&self) -> Option<usize> {
+        self.root
+    }
+
+    /// The parent of position `i` in the Cartesian tree.
+    pub fn parent(&self, i: usize) -> Option<usize> {
+        self.parent[i]
+    }
+
+    /// The position in the tour of the least deep node among `tour[a ..= b]`.
+    fn shallowest(&self, a: usize, b: usize) -> usize {
+        let k = (b - a + 1).ilog2() as usize;
+        let (x, y) = (self.sparse[k][a], self.sparse[k][b + 1 - (1 << k)]);
+        if self.depth[y] < self.depth[x] {y} else {x}
+    }
+
+    /// The lowest common ancestor of positions `u` and `v` in the Cartesian tree,
+    /// which is the position of the least element between them.
+    /// Time: O(1)
+    pub fn lca(&self, u: usize, v: usize) -> usize {
+        let (a, b) = (self.first[u].min(self.first[v]), self.first[u].max(self.first[v]));
+        self.tour[self.shallowest(a, b)]
+    }
+
+    /// The position of the least element in `range` (the leftmost one, if there
+    /// are many), or `None` for an empty range.
+    /// Time: O(1)
+    pub fn min_index<R: RangeBounds<usize>>(&self, range: R) -> Option<usize> {
+        let (l, r) = bounds(range, self.len());
+        if l < r {
+            Some(self.lca(l, r - 1))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cartesian_tree, CartesianRmq};
+    use map::bst::Node;
+    use map::bst::Node::{E, N};
+
+    // Every node is at most its children.
+    fn is_heap(t: &Node<usize, u8>) -> bool {
+        match t {
+            E => true,
+            N(_, x, l, r) => [l, r].iter().all(|c| match &***c {
+                N(_, y, _, _) => x <= y && is_heap(c),
+                E => true
+            })
+        }
+    }
+
+    #[test]
+    fn sorted_big() {
+        let n = 100_000;
+        let xs: Vec<usize> = (0 .. n).collect();
+
+        let t = cartesian_tree(&xs);
+        assert_eq!(t.height(), n);
+        assert!(t.is_bst());
+
+        let rmq = CartesianRmq::new(&xs);
+        assert_eq!(rmq.min_index(10 ..), Some(10));
+        let rmq = CartesianRmq::new(&xs.iter().rev().collect::<Vec<_>>());
+        assert_eq!(rmq.min_index(.. n - 10), Some(n - 11));
+    }
+
+    quickcheck! {
+        fn cartesian_tree_shape(xs: Vec<u8>) -> bool {
+            let t = cartesian_tree(&xs);
+
+            t.is_bst() && is_heap(t.root()) && t.iter().map(|(&i, &x)| (i, x)).eq(xs.iter().cloned().enumerate())
+        }
+
+        fn min_index_naive(xs: Vec<u8>) -> bool {
+            let rmq = CartesianRmq::new(&xs);
+
+            (0 ..= xs.len()).all(|l| (l ..= xs.len()).all(|r| {
+                let naive = (l .. r).min_by_key(|&i| xs[i]);
+                rmq.min_index(l .. r) == naive
+            }))
+        }
+
+        fn lca_naive(xs: Vec<u8>, u: usize, v: usize) -> bool {
+            if xs.is_empty() {
+                return true;
+            }
+            let rmq = CartesianRmq::new(&xs);
+            let (u, v) = (u % xs.len(), v % xs.len());
+
+            let ancestors = |mut u: usize| {
+                let mut path = vec![u];
+                while let Some(p) = rmq.parent(u) {
+                    path.push(p);
+                    u = p;
+                }
+                path
+            };
+            let (pu, pv) = (ancestors(u), ancestors(v));
+            let naive = pu.iter().find(|a| pv.contains(a)).cloned();
+
+            Some(rmq.lca(u, v)) == naive && pu.last().cloned() == rmq.root()
+        }
+    }
+}
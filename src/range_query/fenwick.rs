@@ -0,0 +1,298 @@
+//! Fenwick trees, also known as binary indexed trees.
+
+use range_query::bounds;
+
+use std::ops::{Add, RangeBounds, Sub};
+
+/// The lowest set bit of `i`.
+fn lowbit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+/// A Fenwick tree over an array of n numbers, which finds sums of prefixes and
+/// ranges while the numbers change. It's an array of n partial sums: entry i
+/// (counting from 1) holds the sum of the `lowbit(i)` elements ending at i, so a
+/// prefix is the sum of the entries found by clearing the lowest bit over and
+/// over, and an element is part of the entries found by adding the lowest bit.
+///
+/// It does less than a segment tree (it needs subtraction to answer ranges), but
+/// it's half the size and simpler, so it's faster.
+///
+/// Time: `build` is O(n), the rest is O(log n).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::range_query::FenwickTree;
+///
+/// let mut t = FenwickTree::build(&[5, 2, 7, 1, 3]);
+/// assert_eq!(t.prefix_sum(3), 14);
+/// t.add(1, 10);
+/// assert_eq!(t.range_sum(1 ..= 2), 19);
+///
+/// // The smallest i such that the elements up to i add up to more than 20.
+/// assert_eq!(t.kth(20), Some(2));
+/// assert_eq!(t.kth(28), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FenwickTree<T> {
+    // tree[i - 1] is entry i.
+    tree: Vec<T>
+}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> FenwickTree<T> {
+    /// Creates a tree over n zeros.
+    pub fn new(n: usize) -> FenwickTree<T> {
+        FenwickTree {tree: vec![T::default(); n]}
+    }
+
+    /// Builds the tree over the given numbers by adding every entry to the next
+    /// one which covers it.
+    /// Time: O(n)
+    pub fn build(xs: &[T]) -> FenwickTree<T> {
+        let mut tree = xs.to_vec();
+        for i in 1 ..= tree.len() {
+            let parent = i + lowbit(i);
+            if parent <= tree.len() {
+                tree[parent - 1] = tree[parent - 1] + tree[i - 1];
+            }
+        }
+
+        FenwickTree {tree}
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Checks whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Adds `delta` to element `i`.
+    /// Time: O(log n)
+    pub fn add(&mut self, i: usize, delta: T) {
+        assert!(i < self.len(), "FenwickTree: index {} out of range for length {}", i, self.len());
+
+        let mut i = i + 1;
+        while i <= self.len() {
+            self.tree[i - 1] = self.tree[i - 1] + delta;
+            i += lowbit(i);
+        }
+    }
+
+    /// Returns the sum of the first `i` elements, `a[0 .. i]`.
+    /// Time: O(log n)
+    pub fn prefix_sum(&self, i: usize) -> T {
+        let mut i = i.min(self.len());
+        let mut sum = T::default();
+        while i > 0 {
+            sum = sum + self.tree[i - 1];
+            i -= lowbit(i);
+        }
+
+        sum
+    }
+
+    /// Returns the sum of the elements in `range`.
+    /// Time: O(log n)
+    pub fn range_sum<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (l, r) = bounds(range, self.len());
+        if l < r {
+            self.prefix_sum(r) - self.prefix_sum(l)
+        } else {
+            T::default()
+        }
+    }
+}
+
+impl<T: Copy + Default + PartialOrd + Add<Output = T> + Sub<Output = T>> FenwickTree<T> {
+    /// Finds the smallest `i` such that `a[0 ..= i]` adds up to more than `k`, or
+    /// `None` if all the elements don't. If the elements count occurrences of
+    /// values, this is the `k`-th smallest value (from 0). The elements must not
+    /// be negative.
+    ///
+    /// It descends the implicit tree instead of binary searching over prefix
+    /// sums, so it takes one pass.
+    /// Time: O(log n)
+    pub fn kth(&self, k: T) -> Option<usize> {
+        let mut pos = 0;
+        let mut rest = k;
+        let mut step = if self.is_empty() {0} else {1 << self.len().ilog2()};
+        while step > 0 {
+            if pos + step <= self.len() && self.tree[pos + step - 1] <= rest {
+                pos += step;
+                rest = rest - self.tree[pos - 1];
+            }
+            step /= 2;
+        }
+
+        if pos < self.len() {Some(pos)} else {None}
+    }
+}
+
+/// A Fenwick tree over a matrix of numbers, which finds sums of rectangles. It's
+/// a Fenwick tree over rows whose every entry is a Fenwick tree over columns.
+///
+/// Time: `add` and `sum` are O(log rows * log cols).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::range_query::FenwickTree2D;
+///
+/// let mut t = FenwickTree2D::new(3, 4);
+/// t.add(0, 0, 1);
+/// t.add(1, 2, 5);
+/// t.add(2, 3, 7);
+/// assert_eq!(t.prefix_sum(2, 3), 6);
+/// assert_eq!(t.sum(1 .., 2 ..), 12);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FenwickTree2D<T> {
+    rows: usize,
+    cols: usize,
+    // Entry (i, j), counting from 1, is tree[(i - 1) * cols + j - 1].
+    tree: Vec<T>
+}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> FenwickTree2D<T> {
+    /// Creates a tree over a `rows` x `cols` matrix of zeros.
+    pub fn new(rows: usize, cols: usize) -> FenwickTree2D<T> {
+        FenwickTree2D {rows, cols, tree: vec![T::default(); rows * cols]}
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Adds `delta` to the element in row `r` and column `c`.
+    /// Time: O(log rows * log cols)
+    pub fn add(&mut self, r: usize, c: usize, delta: T) {
+        assert!(r < self.rows && c < self.cols,
+                "FenwickTree2D: index ({}, {}) out of range for {} x {}", r, c, self.rows, self.cols);
+
+        let mut i = r + 1;
+        while i <= self.rows {
+            let mut j = c + 1;
+            while j <= self.cols {
+                let e = (i - 1) * self.cols + j - 1;
+                self.tree[e] = self.tree[e] + delta;
+                j += lowbit(j);
+            }
+            i += lowbit(i);
+        }
+    }
+
+    /// Returns the sum of the elements in the first `r` rows and `c` columns.
+    /// Time: O(log rows * log cols)
+    pub fn prefix_sum(&self, r: usize, c: usize) -> T {
+        let mut sum = T::default();
+        let mut i = r.min(self.rows);
+        while i > 0 {
+            let mut j = c.min(self.cols);
+            while j > 0 {
+                sum = sum + self.tree[(i - 1) * self.cols + j - 1];
+                j -= lowbit(j);
+            }
+            i -= lowbit(i);
+        }
+
+        sum
+    }
+
+    /// Returns the sum of the elements in the given rows and columns, by
+    /// inclusion-exclusion over four prefixes.
+    /// Time: O(log rows * log cols)
+    pub fn sum<R: RangeBounds<usize>, C: RangeBounds<usize>>(&self, rows: R, cols: C) -> T {
+        let (r1, r2) = bounds(rows, self.rows);
+        let (c1, c2) = bounds(cols, self.cols);
+        if r1 >= r2 || c1 >= c2 {
+            return T::default();
+        }
+
+        self.prefix_sum(r2, c2) - self.prefix_sum(r1, c2) - self.prefix_sum(r2, c1) + self.prefix_sum(r1, c1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FenwickTree, FenwickTree2D};
+
+    // Clamps both ends to [0, len] and orders them.
+    fn range(a: usize, b: usize, len: usize) -> (usize, usize) {
+        let (a, b) = (a % (len + 1), b % (len + 1));
+        (a.min(b), a.max(b))
+    }
+
+    quickcheck! {
+        fn build_is_adds(xs: Vec<i64>) -> bool {
+            let built = FenwickTree::build(&xs);
+            let mut added = FenwickTree::new(xs.len());
+            for (i, &x) in xs.iter().enumerate() {
+                added.add(i, x);
+            }
+
+            built.tree == added.tree
+        }
+
+        fn sums_naive(xs: Vec<i32>, ops: Vec<(bool, usize, usize, i32)>) -> bool {
+            let mut xs: Vec<i64> = xs.into_iter().map(i64::from).collect();
+            let mut t = FenwickTree::build(&xs);
+            let n = xs.len();
+
+            ops.into_iter().all(|(add, a, b, x)| {
+                if add && n > 0 {
+                    xs[a % n] += i64::from(x);
+                    t.add(a % n, i64::from(x));
+                }
+                let (l, r) = range(a, b, n);
+
+                t.prefix_sum(r) == xs[.. r].iter().sum::<i64>() &&
+                t.range_sum(l .. r) == xs[l .. r].iter().sum::<i64>()
+            })
+        }
+
+        fn kth_naive(xs: Vec<u8>, k: u16) -> bool {
+            let xs: Vec<u32> = xs.into_iter().map(u32::from).collect();
+            let t = FenwickTree::build(&xs);
+            let k = u32::from(k) % (xs.iter().sum::<u32>() + 2);
+
+            let mut sum = 0;
+            let naive = xs.iter().position(|&x| {
+                sum += x;
+                sum > k
+            });
+
+            t.kth(k) == naive
+        }
+
+        fn sum_2d_naive(rows: u8, cols: u8, adds: Vec<(usize, usize, i32)>, q: (usize, usize, usize, usize)) -> bool {
+            let (rows, cols) = (rows as usize % 8, cols as usize % 8);
+            let mut m = vec![vec![0i64; cols]; rows];
+            let mut t = FenwickTree2D::new(rows, cols);
+            if rows > 0 && cols > 0 {
+                for (r, c, x) in adds {
+                    m[r % rows][c % cols] += i64::from(x);
+                    t.add(r % rows, c % cols, i64::from(x));
+                }
+            }
+
+            let (r1, r2) = range(q.0, q.1, rows);
+            let (c1, c2) = range(q.2, q.3, cols);
+            let naive: i64 = m[r1 .. r2].iter().map(|row| row[c1 .. c2].iter().sum::<i64>()).sum();
+
+            t.sum(r1 .. r2, c1 .. c2) == naive
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Segment trees with lazy propagation, which update whole ranges at once.
+
+use range_query::{bounds, Max, Min, Monoid, Sum};
+
+use std::convert::TryFrom;
+use std::mem;
+use std::ops;
+use std::ops::RangeBounds;
+
+/// Updates of ranges, which act on the combinations of the monoid `Op`. An update
+/// has to distribute over the operation: updating each element of a range and
+/// then combining them gives the same as updating the combination of the range,
+/// given its length. That's what lets a node hold an update for its whole range
+/// without touching its children.
+pub trait Action<T, Op: Monoid<T>> {
+    /// The description of an update, like the number to add.
+    type Update: Clone;
+
+    /// The update which changes nothing.
+    fn identity() -> Self::Update;
+
+    /// The update which does `old` first and then `new`.
+    fn compose(new: &Self::Update, old: &Self::Update) -> Self::Update;
+
+    /// Applies `u` to `x`, the combination of `len` elements.
+    fn apply(u: &Self::Update, x: &T, len: usize) -> T;
+}
+
+/// Adding a number to every element of a range.
+#[derive(Debug, Clone, Copy)]
+pub struct Add;
+
+/// Setting every element of a range to a number.
+#[derive(Debug, Clone, Copy)]
+pub struct Assign;
+
+/// Turns the length of a range into a number which can multiply elements.
+fn times<T: TryFrom<usize>>(len: usize) -> T {
+    T::try_from(len).unwrap_or_else(|_| panic!("LazySegmentTree: length {} doesn't fit the element type", len))
+}
+
+impl<T> Action<T, Sum> for Add
+    where T: Copy + Default + ops::Add<Output = T> + ops::Mul<Output = T> + TryFrom<usize>
+{
+    type Update = T;
+
+    fn identity() -> T {
+        T::default()
+    }
+
+    fn compose(new: &T, old: &T) -> T {
+        *new + *old
+    }
+
+    fn apply(u: &T, x: &T, len: usize) -> T {
+        *x + *u * times(len)
+    }
+}
+
+impl<T> Action<T, Sum> for Assign
+    where T: Copy + Default + ops::Add<Output = T> + ops::Mul<Output = T> + TryFrom<usize>
+{
+    type Update = Option<T>;
+
+    fn identity() -> Option<T> {
+        None
+    }
+
+    fn compose(new: &Option<T>, old: &Option<T>) -> Option<T> {
+        new.or(*old)
+    }
+
+    fn apply(u: &Option<T>, x: &T, len: usize) -> T {
+        u.map_or(*x, |v| v * times(len))
+    }
+}
+
+// Adding to or setting all elements moves their minimum and maximum the same way.
+macro_rules! impl_extremum_actions {
+    ($($op:ident)*) => {
+        $(impl<T> Action<T, $op> for Add where $op: Monoid<T>, T: Copy + Default + ops::Add<Output = T> {
+            type Update = T;
+
+            fn identity() -> T {
+                T::default()
+            }
+
+            fn compose(new: &T, old: &T) -> T {
+                *new + *old
+            }
+
+            fn apply(u: &T, x: &T, _: usize) -> T {
+                *x + *u
+            }
+        }
+
+        impl<T> Action<T, $op> for Assign where $op: Monoid<T>, T: Copy {
+            type Update = Option<T>;
+
+            fn identity() -> Option<T> {
+                None
+            }
+
+            fn compose(new: &Option<T>, old: &Option<T>) -> Option<T> {
+                new.or(*old)
+            }
+
+            fn apply(u: &Option<T>, x: &T, _: usize) -> T {
+                u.unwrap_or(*x)
+            }
+        })*
+    };
+}
+
+impl_extremum_actions!(Min Max);
+
+/// A segment tree which, besides combining the elements of a range with the
+/// monoid `Op`, updates all the elements of a range with the action `Act`, like
+/// adding 5 to `a[l .. r]` and then asking for the minimum of `a[l' .. r']`.
+///
+/// Every node holds the combination of its range, already updated, and the
+/// update still owed to its children. An update stops at the O(log n) nodes
+/// making up its range and pushes the owed updates one level down on its way, so
+/// that the order of updates is kept. A query doesn't push anything: it applies
+/// the owed updates of the nodes it passes to the answers of their parts.
+///
+/// Time: `build` is O(n), `update` and `query` are O(log n).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::range_query::{Min, Sum};
+/// use aisd::range_query::lazy::{Add, Assign, LazySegmentTree};
+///
+/// let mut mins: LazySegmentTree<i64, Min, Add> = LazySegmentTree::build(&[5, 2, 7, 1, 3]);
+/// mins.update(2 .., 10);
+/// assert_eq!(mins.query(..), 2);
+/// assert_eq!(mins.query(2 ..), 11);
+///
+/// let mut sums: LazySegmentTree<i64, Sum, Assign> = LazySegmentTree::build(&[5, 2, 7, 1, 3]);
+/// sums.update(1 .. 4, Some(0));
+/// assert_eq!(sums.query(..), 8);
+/// ```
+pub struct LazySegmentTree<T, Op: Monoid<T>, Act: Action<T, Op>> {
+    n: usize,
+    // Node 1 is the root and the children of node i are 2i and 2i + 1.
+    tree: Vec<T>,
+    lazy: Vec<Act::Update>
+}
+
+impl<T: Clone, Op: Monoid<T>, Act: Action<T, Op>> Clone for LazySegmentTree<T, Op, Act> {
+    fn clone(&self) -> LazySegmentTree<T, Op, Act> {
+        LazySegmentTree {n: self.n, tree: self.tree.clone(), lazy: self.lazy.clone()}
+    }
+}
+
+impl<T: Clone, Op: Monoid<T>, Act: Action<T, Op>> LazySegmentTree<T, Op, Act> {
+    /// Builds the tree over the given elements.
+    /// Time: O(n)
+    pub fn build(xs: &[T]) -> LazySegmentTree<T, Op, Act> {
+        let n = xs.len();
+        let size = 2 * n.next_power_of_two();
+        let mut t = LazySegmentTree {
+            n,
+            tree: vec![Op::identity(); size],
+            lazy: vec![Act::identity(); size]
+        };
+        if n > 0 {
+            t.build_node(1, 0, n, xs);
+        }
+
+        t
+    }
+
+    fn build_node(&mut self, node: usize, l: usize, r: usize, xs: &[T]) {
+        if r - l == 1 {
+            self.tree[node] = xs[l].clone();
+        } else {
+            let mid = (l + r) / 2;
+            self.build_node(2 * node, l, mid, xs);
+            self.build_node(2 * node + 1, mid, r, xs);
+            self.tree[node] = Op::op(&self.tree[2 * node], &self.tree[2 * node + 1]);
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Checks whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Applies `u` to the node covering `len` elements.
+    fn apply(&mut self, node: usize, u: &Act::Update, len: usize) {
+        self.tree[node] = Act::apply(u, &self.tree[node], len);
+        self.lazy[node] = Act::compose(u, &self.lazy[node]);
+    }
+
+    /// Hands the update owed by `node` over to its children.
+    fn push(&mut self, node: usize, l: usize, mid: usize, r: usize) {
+        let u = mem::replace(&mut self.lazy[node], Act::identity());
+        self.apply(2 * node, &u, mid - l);
+        self.apply(2 * node + 1, &u, r - mid);
+    }
+
+    fn update_node(&mut self, node: usize, l: usize, r: usize, ql: usize, qr: usize, u: &Act::Update) {
+        if qr <= l || r <= ql {
+            return;
+        }
+        if ql <= l && r <= qr {
+            self.apply(node, u, r - l);
+            return;
+        }
+
+        let mid = (l + r) / 2;
+        self.push(node, l, mid, r);
+        self.update_node(2 * node, l, mid, ql, qr, u);
+        self.update_node(2 * node + 1, mid, r, ql, qr, u);
+        self.tree[node] = Op::op(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    /// Applies `u` to every element in `range`.
+    /// Time: O(log n)
+    pub fn update<R: RangeBounds<usize>>(&mut self, range: R, u: Act::Update) {
+        let (l, r) = bounds(range, self.n);
+        if l < r {
+            self.update_node(1, 0, self.n, l, r, &u);
+        }
+    }
+
+    fn query_node(&self, node: usize, l: usize, r: usize, ql: usize, qr: usize) -> T {
+        if ql <= l && r <= qr {
+            return self.tree[node].clone();
+        }
+
+        let mid = (l + r) / 2;
+        let answer = match (ql < mid, mid < qr) {
+            (true, true) => Op::op(&self.query_node(2 * node, l, mid, ql, qr),
+                                   &self.query_node(2 * node + 1, mid, r, ql, qr)),
+            (true, false) => self.query_node(2 * node, l, mid, ql, qr),
+            (false, _) => self.query_node(2 * node + 1, mid, r, ql, qr)
+        };
+
+        Act::apply(&self.lazy[node], &answer, qr.min(r) - ql.max(l))
+    }
+
+    /// Combines the elements in `range`, from left to right. The empty range gives
+    /// the identity.
+    /// Time: O(log n)
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (l, r) = bounds(range, self.n);
+        if l < r {
+            self.query_node(1, 0, self.n, l, r)
+        } else {
+            Op::identity()
+        }
+    }
+
+    /// Returns element `i`.
+    /// Time: O(log n)
+    pub fn get(&self, i: usize) -> T {
+        assert!(i < self.n, "LazySegmentTree: index {} out of range for length {}", i, self.n);
+
+        self.query(i ..= i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use range_query::{Max, Min, Monoid, Sum};
+    use super::{Action, Add, Assign, LazySegmentTree};
+
+    // Whether to update, the ends of the range and the argument of the update.
+    type Step = (bool, usize, usize, i64);
+
+    // Runs range updates and queries on the tree and on a plain array, where
+    // `naive` applies an update to a single element.
+    fn agrees<Op, Act, F>(xs: Vec<i64>, ops: Vec<Step>, update: F, naive: fn(i64, &mut i64)) -> bool
+        where Op: Monoid<i64>, Act: Action<i64, Op>, F: Fn(i64) -> Act::Update
+    {
+        let mut xs = xs;
+        let mut t: LazySegmentTree<i64, Op, Act> = LazySegmentTree::build(&xs);
+        let n = xs.len();
+
+        ops.into_iter().all(|(upd, a, b, x)| {
+            let (l, r) = (a % (n + 1), b % (n + 1));
+            let (l, r) = (l.min(r), l.max(r));
+            if upd {
+                xs[l .. r].iter_mut().for_each(|y| naive(x, y));
+                t.update(l .. r, update(x));
+            }
+            let expected = xs[l .. r].iter().fold(Op::identity(), |acc, x| Op::op(&acc, x));
+
+            t.query(l .. r) == expected && (0 .. n).all(|i| t.get(i) == xs[i])
+        })
+    }
+
+    fn small(xs: Vec<i64>, ops: Vec<Step>) -> (Vec<i64>, Vec<Step>) {
+        (xs.iter().map(|x| x % 1000).collect(), ops.into_iter().map(|(u, a, b, x)| (u, a, b, x % 1000)).collect())
+    }
+
+    quickcheck! {
+        fn add_sum_naive(xs: Vec<i64>, ops: Vec<Step>) -> bool {
+            let (xs, ops) = small(xs, ops);
+            agrees::<Sum, Add, _>(xs, ops, |x| x, |x, y| *y += x)
+        }
+
+        fn add_min_naive(xs: Vec<i64>, ops: Vec<Step>) -> bool {
+            let (xs, ops) = small(xs, ops);
+            agrees::<Min, Add, _>(xs, ops, |x| x, |x, y| *y += x)
+        }
+
+        fn add_max_naive(xs: Vec<i64>, ops: Vec<Step>) -> bool {
+            let (xs, ops) = small(xs, ops);
+            agrees::<Max, Add, _>(xs, ops, |x| x, |x, y| *y += x)
+        }
+
+        fn assign_sum_naive(xs: Vec<i64>, ops: Vec<Step>) -> bool {
+            let (xs, ops) = small(xs, ops);
+            agrees::<Sum, Assign, _>(xs, ops, Some, |x, y| *y = x)
+        }
+
+        fn assign_min_naive(xs: Vec<i64>, ops: Vec<Step>) -> bool {
+            agrees::<Min, Assign, _>(xs, ops, Some, |x, y| *y = x)
+        }
+
+        fn assign_max_naive(xs: Vec<i64>, ops: Vec<Step>) -> bool {
+            agrees::<Max, Assign, _>(xs, ops, Some, |x, y| *y = x)
+        }
+    }
+}
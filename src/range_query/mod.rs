@@ -0,0 +1,100 @@
+//! Structures answering queries about ranges of an array, like the sum or the
+//! minimum of `a[l .. r]`, while the array changes.
+
+use std::ops::{Add, Bound, RangeBounds};
+
+pub mod segment_tree;
+pub mod lazy;
+pub mod fenwick;
+pub mod cartesian;
+
+pub use self::segment_tree::SegmentTree;
+pub use self::fenwick::{FenwickTree, FenwickTree2D};
+pub use self::cartesian::CartesianRmq;
+
+/// An associative operation with an identity element, which is what a range
+/// query needs: the answer for a range can be put together from the answers for
+/// any split of it. The operation doesn't have to be commutative.
+pub trait Monoid<T> {
+    /// The identity: `op(identity(), x) == op(x, identity()) == x`.
+    fn identity() -> T;
+
+    /// The operation. It has to be associative.
+    fn op(a: &T, b: &T) -> T;
+}
+
+/// Addition, with 0 (`T::default()`) as the identity.
+#[derive(Debug, Clone, Copy)]
+pub struct Sum;
+
+impl<T: Copy + Default + Add<Output = T>> Monoid<T> for Sum {
+    fn identity() -> T {
+        T::default()
+    }
+
+    fn op(a: &T, b: &T) -> T {
+        *a + *b
+    }
+}
+
+/// Types with a least and a greatest value, which are the identities of `Max`
+/// and `Min`.
+pub trait Bounded {
+    const MIN: Self;
+    const MAX: Self;
+}
+
+macro_rules! impl_bounded {
+    ($($t:ty)*) => {
+        $(impl Bounded for $t {
+            const MIN: $t = <$t>::MIN;
+            const MAX: $t = <$t>::MAX;
+        })*
+    };
+}
+
+impl_bounded!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+/// The minimum.
+#[derive(Debug, Clone, Copy)]
+pub struct Min;
+
+impl<T: Copy + Ord + Bounded> Monoid<T> for Min {
+    fn identity() -> T {
+        T::MAX
+    }
+
+    fn op(a: &T, b: &T) -> T {
+        *a.min(b)
+    }
+}
+
+/// The maximum.
+#[derive(Debug, Clone, Copy)]
+pub struct Max;
+
+impl<T: Copy + Ord + Bounded> Monoid<T> for Max {
+    fn identity() -> T {
+        T::MIN
+    }
+
+    fn op(a: &T, b: &T) -> T {
+        *a.max(b)
+    }
+}
+
+/// Turns any range of indices into [start, end), clamped to `len`.
+fn bounds<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s + 1,
+        Bound::Unbounded => 0
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e + 1,
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len
+    };
+
+    (start.min(len), end.min(len))
+}
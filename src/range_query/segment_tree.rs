@@ -0,0 +1,182 @@
+//! Segment trees.
+
+use range_query::{bounds, Monoid};
+
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+
+/// A segment tree over an array of n elements: a complete binary tree whose
+/// leaves are the elements and whose every inner node holds the combination of
+/// its two children. Any range is made of O(log n) nodes, and changing an element
+/// changes only its O(log n) ancestors.
+///
+/// The tree is stored in an array of 2n nodes: the children of node i are 2i and
+/// 2i + 1 and the leaves are n, ..., 2n - 1. Queries go bottom-up, combining the
+/// nodes on the left and right border of the range separately, so that the
+/// operation doesn't have to be commutative.
+///
+/// Time: `build` is O(n), `update` and `query` are O(log n).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::range_query::{SegmentTree, Sum, Min};
+///
+/// let mut sums: SegmentTree<i64, Sum> = SegmentTree::build(&[5, 2, 7, 1, 3]);
+/// assert_eq!(sums.query(1 .. 4), 10);
+/// sums.update(2, 0);
+/// assert_eq!(sums.query(..), 11);
+///
+/// let mins: SegmentTree<i64, Min> = SegmentTree::build(&[5, 2, 7, 1, 3]);
+/// assert_eq!(mins.query(2 ..), 1);
+/// assert_eq!(mins.query(3 .. 3), i64::MAX);
+/// ```
+#[derive(Debug)]
+pub struct SegmentTree<T, Op> {
+    n: usize,
+    tree: Vec<T>,
+    op: PhantomData<Op>
+}
+
+impl<T: Clone, Op> Clone for SegmentTree<T, Op> {
+    fn clone(&self) -> SegmentTree<T, Op> {
+        SegmentTree {n: self.n, tree: self.tree.clone(), op: PhantomData}
+    }
+}
+
+impl<T: Clone, Op: Monoid<T>> SegmentTree<T, Op> {
+    /// Builds the tree over the given elements.
+    /// Time: O(n)
+    pub fn build(xs: &[T]) -> SegmentTree<T, Op> {
+        let n = xs.len();
+        let mut tree = vec![Op::identity(); n];
+        tree.extend_from_slice(xs);
+        for i in (1 .. n).rev() {
+            tree[i] = Op::op(&tree[2 * i], &tree[2 * i + 1]);
+        }
+
+        SegmentTree {n, tree, op: PhantomData}
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Checks whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Returns element `i`.
+    pub fn get(&self, i: usize) -> &T {
+        &self.tree[self.n + i]
+    }
+
+    /// Sets element `i` to `x`.
+    /// Time: O(log n)
+    pub fn update(&mut self, i: usize, x: T) {
+        assert!(i < self.n, "SegmentTree: index {} out of range for length {}", i, self.n);
+
+        let mut i = i + self.n;
+        self.tree[i] = x;
+        while i > 1 {
+            i /= 2;
+            self.tree[i] = Op::op(&self.tree[2 * i], &self.tree[2 * i + 1]);
+        }
+    }
+
+    /// Combines the elements in `range`, from left to right. The empty range gives
+    /// the identity.
+    /// Time: O(log n)
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (l, r) = bounds(range, self.n);
+        let (mut l, mut r) = (l + self.n, r.max(l) + self.n);
+        let mut left = Op::identity();
+        let mut right = Op::identity();
+
+        while l < r {
+            if l % 2 == 1 {
+                left = Op::op(&left, &self.tree[l]);
+                l += 1;
+            }
+            if r % 2 == 1 {
+                r -= 1;
+                right = Op::op(&self.tree[r], &right);
+            }
+            l /= 2;
+            r /= 2;
+        }
+
+        Op::op(&left, &right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use range_query::*;
+
+    // Concatenation, which isn't commutative.
+    struct Concat;
+
+    impl Monoid<String> for Concat {
+        fn identity() -> String {
+            String::new()
+        }
+
+        fn op(a: &String, b: &String) -> String {
+            format!("{}{}", a, b)
+        }
+    }
+
+    // Runs updates and queries on the tree and on a plain array.
+    fn agrees<Op: Monoid<i32>>(xs: Vec<i32>, ops: Vec<(bool, usize, usize, i32)>) -> bool {
+        let mut xs = xs;
+        let mut t: SegmentTree<i32, Op> = SegmentTree::build(&xs);
+
+        ops.into_iter().all(|(update, a, b, x)| {
+            if xs.is_empty() {
+                return t.query(..) == Op::identity();
+            }
+
+            if update {
+                let i = a % xs.len();
+                xs[i] = x;
+                t.update(i, x);
+                true
+            } else {
+                let (l, r) = (a % (xs.len() + 1), b % (xs.len() + 1));
+                let (l, r) = (l.min(r), l.max(r));
+                let naive = xs[l .. r].iter().fold(Op::identity(), |acc, x| Op::op(&acc, x));
+
+                t.query(l .. r) == naive && (l == r || t.query(l ..= r - 1) == naive)
+            }
+        })
+    }
+
+    quickcheck! {
+        fn sum_naive(xs: Vec<i32>, ops: Vec<(bool, usize, usize, i32)>) -> bool {
+            let xs = xs.iter().map(|x| x % 1000).collect();
+            let ops = ops.into_iter().map(|(u, a, b, x)| (u, a, b, x % 1000)).collect();
+            agrees::<Sum>(xs, ops)
+        }
+
+        fn min_naive(xs: Vec<i32>, ops: Vec<(bool, usize, usize, i32)>) -> bool {
+            agrees::<Min>(xs, ops)
+        }
+
+        fn max_naive(xs: Vec<i32>, ops: Vec<(bool, usize, usize, i32)>) -> bool {
+            agrees::<Max>(xs, ops)
+        }
+
+        fn concat_in_order(xs: Vec<u8>, l: usize, r: usize) -> bool {
+            let xs: Vec<String> = xs.iter().map(|x| ((b'a' + x % 26) as char).to_string()).collect();
+            let t: SegmentTree<String, Concat> = SegmentTree::build(&xs);
+            let (l, r) = (l % (xs.len() + 1), r % (xs.len() + 1));
+            let (l, r) = (l.min(r), l.max(r));
+
+            t.query(l .. r) == xs[l .. r].concat()
+        }
+    }
+}
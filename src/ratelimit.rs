@@ -0,0 +1,445 @@
+//! Rate limiters: the token bucket and the leaky bucket.
+//!
+//! Both take the time from a `Clock`, so that tests can move it by hand with a
+//! `ManualClock` instead of sleeping.
+
+use std::cell::Cell;
+use std::convert::TryFrom;
+use std::time::{Duration, Instant};
+
+/// A source of time. Only differences between readings matter.
+pub trait Clock {
+    /// Returns the time elapsed since some fixed moment.
+    fn now(&self) -> Duration;
+}
+
+impl<C: Clock> Clock for &C {
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+}
+
+/// The real time, measured from the creation of the clock.
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicClock {
+    start: Instant
+}
+
+impl MonotonicClock {
+    pub fn new() -> MonotonicClock {
+        MonotonicClock {start: Instant::now()}
+    }
+}
+
+impl Default for MonotonicClock {
+    fn default() -> MonotonicClock {
+        MonotonicClock::new()
+    }
+}
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// A clock which moves only when told to. Pass it by reference to a limiter
+/// and keep advancing it.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Cell<Duration>
+}
+
+impl ManualClock {
+    /// Creates a clock showing zero.
+    pub fn new() -> ManualClock {
+        ManualClock::default()
+    }
+
+    /// Moves the clock forward by `d`.
+    pub fn advance(&self, d: Duration) {
+        self.now.set(self.now.get() + d);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+/// A token bucket holds up to `burst` tokens and gains `rate` of them per second.
+/// Every request takes some tokens and is allowed only if there are enough of
+/// them, so in the long run requests get through at `rate` per second, but up
+/// to `burst` of them may come at once after a quiet period.
+///
+/// The bucket starts full. Time: all operations are O(1).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::ratelimit::{ManualClock, TokenBucket};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let mut b = TokenBucket::with_clock(2.0, 3.0, &clock);
+///
+/// // A burst of 3, then nothing.
+/// assert!(b.try_take(1.0) && b.try_take(1.0) && b.try_take(1.0));
+/// assert!(!b.try_take(1.0));
+/// assert_eq!(b.wait_time(1.0), Some(Duration::from_millis(500)));
+///
+/// clock.advance(Duration::from_millis(500));
+/// assert!(b.try_take(1.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TokenBucket<C = MonotonicClock> {
+    rate: f64,
+    burst: f64,
+    // The time it takes to refill an empty bucket.
+    burst_time: Duration,
+    // When the bucket is full again. Keeping this instead of the number of tokens
+    // makes all the comparisons exact.
+    full_at: Duration,
+    clock: C
+}
+
+impl TokenBucket<MonotonicClock> {
+    /// Creates a full bucket which refills at `rate` tokens per second and holds
+    /// at most `burst` tokens. Panics unless both are positive and finite, and
+    /// the time to refill the bucket, `burst / rate` seconds, fits in a
+    /// `Duration`.
+    pub fn new(rate: f64, burst: f64) -> TokenBucket<MonotonicClock> {
+        TokenBucket::with_clock(rate, burst, MonotonicClock::new())
+    }
+}
+
+impl<C: Clock> TokenBucket<C> {
+    /// Like `new`, but reads the time from `clock`.
+    pub fn with_clock(rate: f64, burst: f64, clock: C) -> TokenBucket<C> {
+        assert!(rate > 0.0 && rate.is_finite() && burst > 0.0 && burst.is_finite(),
+                "rate and burst must be positive and finite");
+
+        let burst_time = Duration::try_from_secs_f64(burst / rate)
+            .expect("the bucket must refill in a Duration");
+
+        let full_at = clock.now();
+        TokenBucket {rate, burst, burst_time, full_at, clock}
+    }
+
+    /// When the bucket would be full again if `n` more tokens were taken now,
+    /// together with the current time.
+    fn full_after(&self, n: f64) -> (Duration, Duration) {
+        let now = self.clock.now();
+        (self.full_at.max(now) + Duration::from_secs_f64(n / self.rate), now)
+    }
+
+    /// Returns the number of tokens available now.
+    pub fn tokens(&self) -> f64 {
+        let (full_at, now) = self.full_after(0.0);
+        self.burst - (full_at - now).as_secs_f64() * self.rate
+    }
+
+    /// Takes `n` tokens if there are enough of them. Returns whether it did.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is negative or NaN.
+    pub fn try_take(&mut self, n: f64) -> bool {
+        assert!(n >= 0.0, "TokenBucket: the number of tokens must be non-negative");
+        // More than a full bucket never fits, and the time to take it may not
+        // even be a `Duration`.
+        if n > self.burst {
+            return false;
+        }
+
+        let (full_at, now) = self.full_after(n);
+        if full_at - now <= self.burst_time {
+            self.full_at = full_at;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Computes how long to wait until `n` tokens are available. Returns `None`
+    /// if that never happens, because `n` is more than `burst`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is negative or NaN.
+    pub fn wait_time(&self, n: f64) -> Option<Duration> {
+        assert!(n >= 0.0, "TokenBucket: the number of tokens must be non-negative");
+        if n > self.burst {
+            return None;
+        }
+
+        let (full_at, now) = self.full_after(n);
+        Some((full_at - now).saturating_sub(self.burst_time))
+    }
+}
+
+/// A leaky bucket is a queue of at most `capacity` requests which leak out of it
+/// at `rate` per second. Unlike a token bucket, it never lets requests through in
+/// bursts: it spaces them out evenly, delaying those that come too early and
+/// rejecting those that don't fit in the queue.
+///
+/// The queue isn't stored: it's enough to remember when it empties.
+/// Time: all operations are O(1).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::ratelimit::{LeakyBucket, ManualClock};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let mut b = LeakyBucket::with_clock(4.0, 2, &clock);
+///
+/// // Two requests fit, 250 ms apart. The third one doesn't.
+/// assert_eq!(b.offer(), Some(Duration::from_millis(0)));
+/// assert_eq!(b.offer(), Some(Duration::from_millis(250)));
+/// assert_eq!(b.offer(), None);
+///
+/// clock.advance(Duration::from_millis(250));
+/// assert_eq!(b.offer(), Some(Duration::from_millis(250)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LeakyBucket<C = MonotonicClock> {
+    // The time between two requests leaving.
+    interval: Duration,
+    // The time the last of `capacity` requests waits in a full queue. A request
+    // which would wait that long doesn't fit.
+    max_wait: Duration,
+    // When the queue empties, that is when the last request in it has left.
+    empty_at: Duration,
+    clock: C
+}
+
+impl LeakyBucket<MonotonicClock> {
+    /// Creates an empty bucket which lets out `rate` requests per second and
+    /// queues at most `capacity` of them. Panics unless both are positive, `rate`
+    /// is finite and the time between two requests, `1 / rate` seconds, is a
+    /// `Duration` of at least a nanosecond.
+    pub fn new(rate: f64, capacity: usize) -> LeakyBucket<MonotonicClock> {
+        LeakyBucket::with_clock(rate, capacity, MonotonicClock::new())
+    }
+}
+
+impl<C: Clock> LeakyBucket<C> {
+    /// Like `new`, but reads the time from `clock`.
+    pub fn with_clock(rate: f64, capacity: usize, clock: C) -> LeakyBucket<C> {
+        assert!(rate > 0.0 && rate.is_finite() && capacity > 0,
+                "rate and capacity must be positive and the rate finite");
+        let interval = Duration::try_from_secs_f64(1.0 / rate)
+            .ok()
+            .filter(|i| !i.is_zero())
+            .expect("the time between requests must be a Duration of at least a nanosecond");
+
+        // interval * capacity, or Duration::MAX if that's longer.
+        let nanos = interval.as_nanos().saturating_mul(capacity as u128);
+        let max_wait = match u64::try_from(nanos / 1_000_000_000) {
+            Ok(s) => Duration::new(s, (nanos % 1_000_000_000) as u32),
+            Err(_) => Duration::MAX
+        };
+
+        let empty_at = clock.now();
+        LeakyBucket {interval, max_wait, empty_at, clock}
+    }
+
+    /// Returns the number of requests in the queue, counting the one leaving now
+    /// as partly gone.
+    pub fn level(&self) -> f64 {
+        let now = self.clock.now();
+        self.empty_at.saturating_sub(now).as_secs_f64() / self.interval.as_secs_f64()
+    }
+
+    /// Puts a request into the queue. Returns how long it has to wait before it
+    /// leaves, or `None` if the queue is full and the request is rejected.
+    pub fn offer(&mut self) -> Option<Duration> {
+        let now = self.clock.now();
+        let wait = self.empty_at.saturating_sub(now);
+        if wait >= self.max_wait {
+            return None;
+        }
+
+        self.empty_at = (now + wait).saturating_add(self.interval);
+        Some(wait)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    #[test]
+    fn token_bucket_refills_up_to_burst() {
+        let clock = ManualClock::new();
+        let mut b = TokenBucket::with_clock(10.0, 5.0, &clock);
+
+        assert!(b.try_take(5.0));
+        assert!(!b.try_take(0.5));
+        clock.advance(Duration::from_secs(100));
+        assert_eq!(b.tokens(), 5.0);
+        assert_eq!(b.wait_time(6.0), None);
+    }
+
+    #[test]
+    fn token_bucket_infinite() {
+        let clock = ManualClock::new();
+        let mut b = TokenBucket::with_clock(1.0, 1.0, &clock);
+
+        assert!(!b.try_take(f64::INFINITY));
+        assert_eq!(b.wait_time(f64::INFINITY), None);
+        assert!(b.try_take(1.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn token_bucket_negative() {
+        TokenBucket::new(1.0, 1.0).try_take(-1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn token_bucket_nan() {
+        TokenBucket::new(1.0, 1.0).try_take(f64::NAN);
+    }
+
+    #[test]
+    fn token_bucket_slow() {
+        let clock = ManualClock::new();
+        let mut b = TokenBucket::with_clock(1e-11, 1.0, &clock);
+
+        assert!(b.try_take(1.0));
+        assert_eq!(b.wait_time(1.0), Some(Duration::from_secs(100_000_000_000)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn token_bucket_tiny_rate() {
+        TokenBucket::new(1e-300, 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn token_bucket_infinite_burst() {
+        TokenBucket::new(1.0, f64::INFINITY);
+    }
+
+    #[test]
+    #[should_panic]
+    fn token_bucket_nan_rate() {
+        TokenBucket::new(f64::NAN, 1.0);
+    }
+
+    #[test]
+    fn leaky_bucket_drains() {
+        let clock = ManualClock::new();
+        let mut b = LeakyBucket::with_clock(2.0, 3, &clock);
+
+        for _ in 0 .. 3 {
+            assert!(b.offer().is_some());
+        }
+        assert_eq!(b.offer(), None);
+        assert_eq!(b.level(), 3.0);
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(b.level(), 0.0);
+        assert_eq!(b.offer(), Some(Duration::from_millis(0)));
+    }
+
+    #[test]
+    fn leaky_bucket_huge_capacity() {
+        let clock = ManualClock::new();
+        let mut b = LeakyBucket::with_clock(1.0, 1usize << 32, &clock);
+        assert_eq!(b.offer(), Some(Duration::from_secs(0)));
+        assert_eq!(b.offer(), Some(Duration::from_secs(1)));
+
+        let mut b = LeakyBucket::with_clock(1e-11, 3_000_000_000, &clock);
+        assert_eq!(b.offer(), Some(Duration::from_secs(0)));
+        assert_eq!(b.offer(), Some(Duration::from_secs(100_000_000_000)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn leaky_bucket_tiny_rate() {
+        LeakyBucket::new(1e-300, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn leaky_bucket_rate_too_high() {
+        LeakyBucket::new(1e10, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn leaky_bucket_nan_rate() {
+        LeakyBucket::new(f64::NAN, 1);
+    }
+
+    quickcheck! {
+        // During t seconds, at most burst + rate * t tokens can be taken.
+        fn token_bucket_bound(steps: Vec<(u16, u8)>) -> bool {
+            let (rate, burst) = (3.0, 4.0);
+            let clock = ManualClock::new();
+            let mut b = TokenBucket::with_clock(rate, burst, &clock);
+
+            let mut taken = 0.0;
+            steps.iter().all(|&(ms, n)| {
+                clock.advance(Duration::from_millis(ms as u64));
+                let n = (n % 3) as f64;
+                if b.try_take(n) {
+                    taken += n;
+                }
+
+                taken <= burst + rate * clock.now().as_secs_f64() + 1e-6
+            })
+        }
+
+        // A request that fails succeeds after waiting for `wait_time`.
+        fn token_bucket_wait_time(steps: Vec<(u16, u8)>) -> bool {
+            let clock = ManualClock::new();
+            let mut b = TokenBucket::with_clock(2.0, 4.0, &clock);
+
+            steps.iter().all(|&(ms, n)| {
+                clock.advance(Duration::from_millis(ms as u64 % 1000));
+                let n = (n % 4) as f64;
+                b.try_take(n) || {
+                    clock.advance(b.wait_time(n).unwrap());
+                    b.try_take(n)
+                }
+            })
+        }
+
+        // Accepted requests leave at least 1 / rate seconds apart, and never
+        // more than `capacity` of them wait at once.
+        fn leaky_bucket_spacing(steps: Vec<u16>) -> bool {
+            let capacity = 3;
+            let clock = ManualClock::new();
+            let mut b = LeakyBucket::with_clock(4.0, capacity, &clock);
+
+            let mut leaves: Vec<Duration> = vec![];
+            steps.iter().all(|&ms| {
+                clock.advance(Duration::from_millis(ms as u64 % 500));
+                let now = clock.now();
+                let waiting = leaves.iter().filter(|&&t| t > now).count();
+
+                match b.offer() {
+                    Some(d) => {
+                        let t = now + d;
+                        let ok = leaves.last().is_none_or(|&l| t - l >= Duration::from_millis(250));
+                        leaves.push(t);
+                        ok && waiting < capacity
+                    },
+                    None => waiting + 1 >= capacity
+                }
+            })
+        }
+    }
+}
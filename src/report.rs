@@ -0,0 +1,315 @@
+//! Writing results of algorithms as CSV or JSON, to analyze them elsewhere (say,
+//! in a notebook) without glue code.
+//!
+//! Everything is written as a table: named columns and rows of values. CSV gets
+//! a header line and a line per row, JSON gets an array with an object per row.
+//! Tables are provided for the common shapes of results: `Series` for arrays
+//! indexed by nodes or amounts (like distances), `EdgeList` for lists of
+//! weighted edges (like spanning trees), `Grid` for dynamic programming tables,
+//! and `Matrix` and `disjoint_set::Stats` are tables too.
+//!
+//! Only available with the `report` feature.
+//!
+//! # Example
+//!
+//! ```
+//! extern crate aisd;
+//! use aisd::report::{self, Series};
+//!
+//! let distances = vec![Some(0), Some(4), None];
+//! let mut out = vec![];
+//! report::write_csv(&mut out, &Series::new("distance", &distances)).unwrap();
+//! assert_eq!(String::from_utf8(out).unwrap(), "index,distance\n0,0\n1,4\n2,\n");
+//!
+//! let mut out = vec![];
+//! report::write_json(&mut out, &Series::new("distance", &distances)).unwrap();
+//! assert_eq!(String::from_utf8(out).unwrap(),
+//!            r#"[{"index":0,"distance":0},{"index":1,"distance":4},{"index":2,"distance":null}]"#);
+//! ```
+
+use disjoint_set::Stats;
+use matrix::Matrix;
+
+use std::io;
+use std::io::Write;
+
+/// A single value in a table.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A missing value, like the distance to an unreachable node. Written as an
+    /// empty field in CSV and as `null` in JSON.
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    /// NaN and infinities are written like `Null` in JSON, which has no way to
+    /// write them.
+    Float(f64),
+    Str(String)
+}
+
+macro_rules! impl_from {
+    ($variant:ident, $target:ty: $($t:ty)*) => {
+        $(impl From<$t> for Value {
+            fn from(x: $t) -> Value {
+                Value::$variant(x as $target)
+            }
+        })*
+    };
+}
+
+impl_from!(Int, i64: i8 i16 i32 i64 isize);
+impl_from!(UInt, u64: u8 u16 u32 u64 usize);
+impl_from!(Float, f64: f32 f64);
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(s: &'a str) -> Value {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::Str(s)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(x: Option<T>) -> Value {
+        x.map_or(Value::Null, Into::into)
+    }
+}
+
+/// Something which can be written as a table.
+pub trait Table {
+    /// The names of the columns.
+    fn columns(&self) -> Vec<String>;
+
+    /// The rows, each with a value for every column.
+    fn rows(&self) -> Vec<Vec<Value>>;
+}
+
+/// An array with one value per index, like distances from a node or the numbers
+/// of ways to make every amount. Its columns are `index` and the given name.
+#[derive(Debug, Clone, Copy)]
+pub struct Series<'a, T: 'a> {
+    name: &'a str,
+    values: &'a [T]
+}
+
+impl<'a, T> Series<'a, T> {
+    /// Makes a table of `values` whose column of values is called `name`.
+    pub fn new(name: &'a str, values: &'a [T]) -> Series<'a, T> {
+        Series {name, values}
+    }
+}
+
+impl<'a, T: Clone + Into<Value>> Table for Series<'a, T> {
+    fn columns(&self) -> Vec<String> {
+        vec!["index".to_string(), self.name.to_string()]
+    }
+
+    fn rows(&self) -> Vec<Vec<Value>> {
+        self.values.iter().enumerate().map(|(i, x)| vec![i.into(), x.clone().into()]).collect()
+    }
+}
+
+/// A list of weighted edges, like a minimum spanning tree. Its columns are
+/// `from`, `to` and `weight`.
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeList<'a, W: 'a>(pub &'a [(usize, usize, W)]);
+
+impl<'a, W: Clone + Into<Value>> Table for EdgeList<'a, W> {
+    fn columns(&self) -> Vec<String> {
+        vec!["from".to_string(), "to".to_string(), "weight".to_string()]
+    }
+
+    fn rows(&self) -> Vec<Vec<Value>> {
+        self.0.iter().map(|&(u, v, ref w)| vec![u.into(), v.into(), w.clone().into()]).collect()
+    }
+}
+
+/// A table of rows which may differ in length, like the table of a dynamic
+/// programming algorithm. Its columns are `row` and then the numbers of the
+/// columns. Short rows are padded with `Value::Null`.
+#[derive(Debug, Clone, Copy)]
+pub struct Grid<'a, T: 'a>(pub &'a [Vec<T>]);
+
+impl<'a, T: Clone + Into<Value>> Table for Grid<'a, T> {
+    fn columns(&self) -> Vec<String> {
+        let width = self.0.iter().map(|r| r.len()).max().unwrap_or(0);
+        Some("row".to_string()).into_iter().chain((0 .. width).map(|j| j.to_string())).collect()
+    }
+
+    fn rows(&self) -> Vec<Vec<Value>> {
+        let width = self.0.iter().map(|r| r.len()).max().unwrap_or(0);
+        self.0.iter().enumerate().map(|(i, r)| {
+            let cells = (0 .. width).map(|j| r.get(j).cloned().map_or(Value::Null, Into::into));
+            Some(i.into()).into_iter().chain(cells).collect()
+        }).collect()
+    }
+}
+
+/// A matrix has a row per row. Its columns are named by their numbers.
+impl<T: Clone + Into<Value>> Table for Matrix<T> {
+    fn columns(&self) -> Vec<String> {
+        (0 .. self.cols()).map(|j| j.to_string()).collect()
+    }
+
+    fn rows(&self) -> Vec<Vec<Value>> {
+        (0 .. self.rows()).map(|i| self.row(i).iter().cloned().map(Into::into).collect()).collect()
+    }
+}
+
+/// The counters are a single row, with a column per counter.
+impl Table for Stats {
+    fn columns(&self) -> Vec<String> {
+        ["finds", "unions", "links", "steps", "compressions"].iter().map(|s| s.to_string()).collect()
+    }
+
+    fn rows(&self) -> Vec<Vec<Value>> {
+        vec![vec![self.finds.into(), self.unions.into(), self.links.into(), self.steps.into(),
+                  self.compressions.into()]]
+    }
+}
+
+/// Writes a CSV field, quoted if it has to be (RFC 4180).
+fn csv_field<W: Write>(out: &mut W, s: &str) -> io::Result<()> {
+    if s.contains([',', '"', '\n', '\r']) {
+        write!(out, "\"{}\"", s.replace('"', "\"\""))
+    } else {
+        write!(out, "{}", s)
+    }
+}
+
+/// Writes `table` as CSV: a header with the names of the columns, then a line
+/// per row. Fields with commas, quotes or line breaks are quoted.
+pub fn write_csv<W: Write, T: Table + ?Sized>(out: &mut W, table: &T) -> io::Result<()> {
+    let lines = Some(table.columns().into_iter().map(Value::Str).collect()).into_iter().chain(table.rows());
+    for line in lines {
+        for (j, x) in line.iter().enumerate() {
+            if j > 0 {
+                write!(out, ",")?;
+            }
+            match x {
+                Value::Null => Ok(()),
+                Value::Bool(b) => write!(out, "{}", b),
+                Value::Int(i) => write!(out, "{}", i),
+                Value::UInt(u) => write!(out, "{}", u),
+                Value::Float(f) => write!(out, "{}", f),
+                Value::Str(s) => csv_field(out, s)
+            }?;
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a JSON string literal.
+fn json_string<W: Write>(out: &mut W, s: &str) -> io::Result<()> {
+    write!(out, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(out, "\\\""),
+            '\\' => write!(out, "\\\\"),
+            '\n' => write!(out, "\\n"),
+            '\r' => write!(out, "\\r"),
+            '\t' => write!(out, "\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32),
+            c => write!(out, "{}", c)
+        }?;
+    }
+    write!(out, "\"")
+}
+
+/// Writes `table` as a JSON array with an object per row, whose keys are the
+/// names of the columns.
+pub fn write_json<W: Write, T: Table + ?Sized>(out: &mut W, table: &T) -> io::Result<()> {
+    let columns = table.columns();
+    write!(out, "[")?;
+    for (i, row) in table.rows().iter().enumerate() {
+        write!(out, "{}{{", if i > 0 {","} else {""})?;
+        for (j, (name, x)) in columns.iter().zip(row).enumerate() {
+            if j > 0 {
+                write!(out, ",")?;
+            }
+            json_string(out, name)?;
+            write!(out, ":")?;
+            match x {
+                Value::Null => write!(out, "null"),
+                Value::Bool(b) => write!(out, "{}", b),
+                Value::Int(i) => write!(out, "{}", i),
+                Value::UInt(u) => write!(out, "{}", u),
+                Value::Float(f) if f.is_finite() => write!(out, "{}", f),
+                Value::Float(_) => write!(out, "null"),
+                Value::Str(s) => json_string(out, s)
+            }?;
+        }
+        write!(out, "}}")?;
+    }
+    write!(out, "]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use disjoint_set::{Auto, DisjointSet};
+
+    fn csv<T: Table>(t: &T) -> String {
+        let mut out = vec![];
+        write_csv(&mut out, t).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn json<T: Table>(t: &T) -> String {
+        let mut out = vec![];
+        write_json(&mut out, t).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn edge_list() {
+        let edges = [(0, 1, 2.5), (1, 2, -1.0)];
+
+        assert_eq!(csv(&EdgeList(&edges)), "from,to,weight\n0,1,2.5\n1,2,-1\n");
+        assert_eq!(json(&EdgeList(&edges)), r#"[{"from":0,"to":1,"weight":2.5},{"from":1,"to":2,"weight":-1}]"#);
+    }
+
+    #[test]
+    fn grid() {
+        let table = vec![vec![1], vec![1, 2]];
+
+        assert_eq!(csv(&Grid(&table)), "row,0,1\n0,1,\n1,1,2\n");
+        assert_eq!(json(&Grid(&table)), r#"[{"row":0,"0":1,"1":null},{"row":1,"0":1,"1":2}]"#);
+        assert_eq!(json(&Grid::<u8>(&[])), "[]");
+    }
+
+    #[test]
+    fn matrix_and_stats() {
+        let m = Matrix::from_vec(2, 2, vec![1, -2, 3, -4]);
+        assert_eq!(csv(&m), "0,1\n1,-2\n3,-4\n");
+
+        let mut uf = Auto::new(3);
+        uf.union(0, 1);
+        assert_eq!(csv(uf.stats()), "finds,unions,links,steps,compressions\n2,1,1,0,0\n");
+    }
+
+    #[test]
+    fn escaping() {
+        let names = ["plain", "a,b", "say \"hi\"", "two\nlines"];
+
+        assert_eq!(csv(&Series::new("name", &names)),
+                   "index,name\n0,plain\n1,\"a,b\"\n2,\"say \"\"hi\"\"\"\n3,\"two\nlines\"\n");
+        assert_eq!(json(&Series::new("name", &names[2 ..])),
+                   r#"[{"index":0,"name":"say \"hi\""},{"index":1,"name":"two\nlines"}]"#);
+        assert_eq!(json(&Series::new("x", &[f64::NAN, 0.5])), r#"[{"index":0,"x":null},{"index":1,"x":0.5}]"#);
+    }
+}
@@ -0,0 +1,210 @@
+//! Seedable random number generators, to make randomized algorithms reproducible.
+//!
+//! Every randomized algorithm in the crate takes its generator as an argument
+//! (`&mut R` where `R: Rng`) or, if it keeps one (like the treaps in `map`), can
+//! be created from a seed. Passing `default_rng()`, or an `Xoshiro256` seeded
+//! with a chosen number, makes a run repeatable; passing `rand::thread_rng()`
+//! makes it different every time.
+//!
+//! # Example
+//!
+//! ```
+//! extern crate aisd;
+//! extern crate rand;
+//! use aisd::rng::{self, Xoshiro256};
+//! use rand::Rng;
+//!
+//! let a: Vec<u32> = (0 .. 5).map(|_| rng::default_rng().gen()).collect();
+//! let b: Vec<u32> = (0 .. 5).map(|_| rng::default_rng().gen()).collect();
+//! assert_eq!(a, b);
+//!
+//! let mut r1 = Xoshiro256::seed_from_u64(42);
+//! let mut r2 = Xoshiro256::seed_from_u64(42);
+//! assert_eq!(r1.gen_range(0, 1000), r2.gen_range(0, 1000));
+//! ```
+
+use rand::{Error, RngCore, SeedableRng};
+
+/// The seed of `default_rng`.
+pub const DEFAULT_SEED: u64 = 0x5eed;
+
+/// A generator seeded with `DEFAULT_SEED`, for runs which should be the same
+/// every time.
+pub fn default_rng() -> Xoshiro256 {
+    Xoshiro256::seed_from_u64(DEFAULT_SEED)
+}
+
+/// Fills `dest` with the little-endian bytes of consecutive `next_u64` numbers.
+fn fill_via_u64<R: RngCore>(rng: &mut R, dest: &mut [u8]) {
+    for chunk in dest.chunks_mut(8) {
+        let bytes = rng.next_u64().to_le_bytes();
+        chunk.copy_from_slice(&bytes[.. chunk.len()]);
+    }
+}
+
+/// SplitMix64 (Steele, Lea and Flood): a 64-bit counter passed through a
+/// mixing function. It's fast and any seed is good, but its state is small, so
+/// it's mostly used to expand a single number into seeds of other generators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitMix64 {
+    state: u64
+}
+
+impl SplitMix64 {
+    /// Creates a generator starting at `seed`.
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 {state: seed}
+    }
+}
+
+impl RngCore for SplitMix64 {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_via_u64(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for SplitMix64 {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: [u8; 8]) -> SplitMix64 {
+        SplitMix64::new(u64::from_le_bytes(seed))
+    }
+}
+
+/// xoshiro256** (Blackman and Vigna): a fast general purpose generator with
+/// 256 bits of state and a period of 2^256 - 1. Not suitable for cryptography.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xoshiro256 {
+    s: [u64; 4]
+}
+
+impl Xoshiro256 {
+    /// Creates a generator whose state is expanded from `seed` with
+    /// `SplitMix64`, so that similar seeds give unrelated sequences.
+    pub fn seed_from_u64(seed: u64) -> Xoshiro256 {
+        let mut sm = SplitMix64::new(seed);
+        let mut s = [0; 4];
+        for x in &mut s {
+            *x = sm.next_u64();
+        }
+
+        Xoshiro256 {s}
+    }
+}
+
+impl RngCore for Xoshiro256 {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+
+        result
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_via_u64(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// The seed is read as four little-endian numbers. The state must not be all
+/// zeros, so the zero seed is replaced with `seed_from_u64(0)`.
+impl SeedableRng for Xoshiro256 {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: [u8; 32]) -> Xoshiro256 {
+        let mut s = [0; 4];
+        for (x, chunk) in s.iter_mut().zip(seed.chunks(8)) {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(chunk);
+            *x = u64::from_le_bytes(bytes);
+        }
+
+        if s == [0; 4] {
+            Xoshiro256::seed_from_u64(0)
+        } else {
+            Xoshiro256 {s}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SplitMix64, Xoshiro256};
+
+    use rand::{RngCore, SeedableRng};
+
+    // The outputs of the reference implementations.
+    #[test]
+    fn splitmix64_reference() {
+        let mut r = SplitMix64::new(0);
+        let out: Vec<u64> = (0 .. 3).map(|_| r.next_u64()).collect();
+
+        assert_eq!(out, vec![0xe220_a839_7b1d_cdaf, 0x6e78_9e6a_a1b9_65f4, 0x06c4_5d18_8009_454f]);
+    }
+
+    #[test]
+    fn xoshiro256_reference() {
+        let mut r = Xoshiro256 {s: [1, 2, 3, 4]};
+        let out: Vec<u64> = (0 .. 4).map(|_| r.next_u64()).collect();
+
+        assert_eq!(out, vec![11520, 0, 1509978240, 1215971899390074240]);
+    }
+
+    #[test]
+    fn zero_seed() {
+        let mut r = Xoshiro256::from_seed([0; 32]);
+
+        assert_eq!(r, Xoshiro256::seed_from_u64(0));
+        assert!((0 .. 10).any(|_| r.next_u64() != 0));
+    }
+
+    quickcheck! {
+        fn same_seed_same_sequence(seed: u64) -> bool {
+            let mut r1 = Xoshiro256::seed_from_u64(seed);
+            let mut r2 = Xoshiro256::seed_from_u64(seed);
+
+            (0 .. 100).all(|_| r1.next_u64() == r2.next_u64())
+        }
+
+        fn fill_bytes_is_next_u64(seed: u64) -> bool {
+            let mut r1 = Xoshiro256::seed_from_u64(seed);
+            let mut r2 = r1.clone();
+            let mut bytes = [0; 16];
+            r1.fill_bytes(&mut bytes);
+
+            bytes[.. 8] == r2.next_u64().to_le_bytes() && bytes[8 ..] == r2.next_u64().to_le_bytes()
+        }
+    }
+}
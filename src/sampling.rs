@@ -0,0 +1,214 @@
+//! Random sampling.
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use rand;
+use rand::Rng;
+
+use range_query::FenwickTree;
+
+/// Picks indices at random with probabilities proportional to their weights, like
+/// roulette-wheel selection, while allowing the weights to change.
+///
+/// The weights are kept in a `FenwickTree`, so prefix sums of weights can be
+/// found and updated in O(log n). Sampling draws a number below the total weight
+/// and finds the index whose prefix sum range contains it with `kth`.
+///
+/// Time: `set_weight`, `sample` and `total_weight` are O(log n).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::rng;
+/// use aisd::sampling::DynamicWeightedIndex;
+///
+/// let mut w = DynamicWeightedIndex::from_weights(&[1.0, 0.0, 3.0]);
+/// assert_eq!(w.total_weight(), 4.0);
+///
+/// let mut rng = rng::default_rng();
+/// assert_ne!(w.sample(&mut rng), Some(1));
+///
+/// w.set_weight(0, 0.0);
+/// w.set_weight(2, 0.0);
+/// w.set_weight(1, 0.5);
+/// assert_eq!(w.sample(&mut rng), Some(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DynamicWeightedIndex {
+    weights: Vec<f64>,
+    tree: FenwickTree<f64>,
+    // Updates since the tree was last rebuilt from `weights`.
+    updates: usize
+}
+
+fn check_weight(w: f64) {
+    assert!(w >= 0.0 && w.is_finite(), "DynamicWeightedIndex: weights must be finite and nonnegative");
+}
+
+impl DynamicWeightedIndex {
+    /// Creates `n` indices with weight 0.
+    pub fn new(n: usize) -> DynamicWeightedIndex {
+        DynamicWeightedIndex::from_weights(&vec![0.0; n])
+    }
+
+    /// Creates indices with the given weights.
+    /// Time: O(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if a weight is negative, infinite or NaN.
+    pub fn from_weights(weights: &[f64]) -> DynamicWeightedIndex {
+        weights.iter().for_each(|&w| check_weight(w));
+
+        DynamicWeightedIndex {
+            weights: weights.to_vec(),
+            tree: FenwickTree::build(weights),
+            updates: 0
+        }
+    }
+
+    /// Computes the tree from scratch.
+    fn rebuild(&mut self) {
+        self.tree = FenwickTree::build(&self.weights);
+        self.updates = 0;
+    }
+
+    /// Returns the number of indices.
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Checks whether there are no indices.
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    /// Returns the weight of index `i`.
+    pub fn weight(&self, i: usize) -> f64 {
+        self.weights[i]
+    }
+
+    /// Changes the weight of index `i` to `w`.
+    /// Time: O(log n) amortized
+    ///
+    /// # Panics
+    ///
+    /// Panics if `w` is negative, infinite or NaN.
+    pub fn set_weight(&mut self, i: usize, w: f64) {
+        check_weight(w);
+
+        let delta = w - self.weights[i];
+        self.weights[i] = w;
+
+        // Adding differences accumulates rounding errors, so every n updates the
+        // tree gets rebuilt, which keeps the errors small at O(1) amortized cost.
+        self.updates += 1;
+        if self.updates > self.len() {
+            self.rebuild();
+            return;
+        }
+
+        self.tree.add(i, delta);
+    }
+
+    /// Returns the sum of all the weights.
+    pub fn total_weight(&self) -> f64 {
+        self.tree.prefix_sum(self.len())
+    }
+
+    /// Picks an index with probability proportional to its weight. Returns `None`
+    /// if all weights are 0. Indices of weight 0 are never picked.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Option<usize> {
+        let total = self.total_weight();
+        if total <= 0.0 {
+            return None;
+        }
+
+        // The least index whose prefix sum is bigger than x, which is never one
+        // of weight 0.
+        let x = rng.gen::<f64>() * total;
+        match self.tree.kth(x) {
+            Some(i) if self.weights[i] > 0.0 => Some(i),
+            // Rounding may push x past the last positive weight.
+            _ => self.weights.iter().rposition(|&w| w > 0.0)
+        }
+    }
+}
+
+impl Arbitrary for DynamicWeightedIndex {
+    fn arbitrary<G: Gen>(g: &mut G) -> DynamicWeightedIndex {
+        let mut rng = rand::thread_rng();
+
+        let n = rng.gen_range(0, g.size() + 1);
+        let weights: Vec<f64> = (0 .. n).map(|_| {
+            if rng.gen_bool(0.3) {0.0} else {rng.gen_range(0.0, 10.0)}
+        }).collect();
+
+        DynamicWeightedIndex::from_weights(&weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DynamicWeightedIndex;
+
+    use rand::SeedableRng;
+    use rand::prng::XorShiftRng;
+
+    fn close(x: f64, y: f64) -> bool {
+        (x - y).abs() <= 1e-9 * (1.0 + x.abs().max(y.abs()))
+    }
+
+    quickcheck! {
+        fn total_weight_sum(w: DynamicWeightedIndex) -> bool {
+            let sum: f64 = (0 .. w.len()).map(|i| w.weight(i)).sum();
+            close(w.total_weight(), sum)
+        }
+
+        fn set_weight_sum(w: DynamicWeightedIndex, updates: Vec<(usize, u8)>) -> bool {
+            let mut w = w;
+            if w.is_empty() {
+                return true;
+            }
+
+            updates.iter().all(|&(i, x)| {
+                w.set_weight(i % w.len(), x as f64 / 7.0);
+                let sum: f64 = (0 .. w.len()).map(|i| w.weight(i)).sum();
+                close(w.total_weight(), sum)
+            })
+        }
+
+        fn sample_positive(w: DynamicWeightedIndex) -> bool {
+            let mut rng = ::rand::thread_rng();
+            let positive = (0 .. w.len()).any(|i| w.weight(i) > 0.0);
+
+            (0 .. 100).all(|_| match w.sample(&mut rng) {
+                Some(i) => w.weight(i) > 0.0,
+                None => !positive
+            })
+        }
+    }
+
+    #[test]
+    fn frequencies() {
+        let mut rng = XorShiftRng::from_seed([7; 16]);
+        let mut w = DynamicWeightedIndex::new(5);
+        for i in 0 .. 5 {
+            w.set_weight(i, i as f64);
+        }
+
+        let n = 100_000;
+        let mut counts = [0; 5];
+        for _ in 0 .. n {
+            counts[w.sample(&mut rng).unwrap()] += 1;
+        }
+
+        assert_eq!(counts[0], 0);
+        for (i, &c) in counts.iter().enumerate().skip(1) {
+            let expected = n as f64 * i as f64 / 10.0;
+            assert!((c as f64 - expected).abs() < 0.05 * expected);
+        }
+    }
+}
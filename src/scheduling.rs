@@ -0,0 +1,90 @@
+//! Scheduling intervals on a single resource.
+
+/// Chooses intervals of the greatest total weight which don't overlap, from
+/// intervals `(start, end, weight)`. Intervals are half-open, so one may start
+/// when another ends. Returns the weight and the indices of the chosen
+/// intervals, in increasing order.
+/// Time: O(n log n)
+/// Memory: O(n)
+///
+/// The intervals are sorted by their ends. `best[j]` is the greatest weight
+/// using the first `j` of them: either the `j`-th one isn't used, or it is,
+/// together with the best choice among those ending before it starts, which
+/// are a prefix found by binary search.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::scheduling::weighted_interval_scheduling;
+///
+/// let intervals = [(0, 3, 5), (2, 5, 6), (4, 7, 5), (6, 9, 4), (3, 4, 1)];
+/// assert_eq!(weighted_interval_scheduling(&intervals), (11, vec![0, 2, 4]));
+///
+/// // A heavy interval beats many light ones.
+/// let intervals = [(0, 10, 10), (0, 1, 2), (1, 2, 2), (2, 3, 2)];
+/// assert_eq!(weighted_interval_scheduling(&intervals), (10, vec![0]));
+/// ```
+pub fn weighted_interval_scheduling<T: Ord>(intervals: &[(T, T, u64)]) -> (u64, Vec<usize>) {
+    let n = intervals.len();
+    let mut order: Vec<usize> = (0 .. n).collect();
+    order.sort_by(|&a, &b| (&intervals[a].1, &intervals[a].0).cmp(&(&intervals[b].1, &intervals[b].0)));
+
+    // pred[j] is the number of intervals, in the order of ends, which end
+    // before the j-th one starts. Only an empty interval can end where it
+    // starts, so that one mustn't count itself or the ones after it.
+    let pred: Vec<usize> = order.iter().enumerate()
+        .map(|(j, &i)| order.partition_point(|&k| intervals[k].1 <= intervals[i].0).min(j))
+        .collect();
+
+    let mut best = vec![0; n + 1];
+    for j in 0 .. n {
+        best[j + 1] = best[j].max(intervals[order[j]].2 + best[pred[j]]);
+    }
+
+    let mut chosen = vec![];
+    let mut j = n;
+    while j > 0 {
+        if best[j] == best[j - 1] {
+            j -= 1;
+        } else {
+            chosen.push(order[j - 1]);
+            j = pred[j - 1];
+        }
+    }
+    chosen.sort();
+
+    (best[n], chosen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::weighted_interval_scheduling;
+
+    quickcheck! {
+        fn brute_force(intervals: Vec<(u8, u8, u8)>) -> bool {
+            let intervals: Vec<(u8, u8, u64)> = intervals.into_iter().take(12)
+                .map(|(s, l, w)| (s % 20, s % 20 + l % 8, w as u64)).collect();
+            let overlap = |a: usize, b: usize| {
+                intervals[a].0 < intervals[b].1 && intervals[b].0 < intervals[a].1
+            };
+            let disjoint = |chosen: &[usize]| {
+                chosen.iter().enumerate().all(|(x, &a)| chosen[.. x].iter().all(|&b| !overlap(a, b)))
+            };
+
+            let brute = (0 .. 1usize << intervals.len()).filter_map(|mask| {
+                let chosen: Vec<usize> = (0 .. intervals.len()).filter(|i| mask >> i & 1 == 1).collect();
+                if disjoint(&chosen) {
+                    Some(chosen.iter().map(|&i| intervals[i].2).sum::<u64>())
+                } else {
+                    None
+                }
+            }).max();
+
+            let (weight, chosen) = weighted_interval_scheduling(&intervals);
+            Some(weight) == brute && disjoint(&chosen) &&
+            chosen.iter().map(|&i| intervals[i].2).sum::<u64>() == weight &&
+            chosen.windows(2).all(|w| w[0] < w[1])
+        }
+    }
+}
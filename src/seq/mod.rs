@@ -0,0 +1,9 @@
+//! Sequences.
+
+pub mod order_maintenance;
+pub mod seg_vec;
+pub mod steady_vec;
+
+pub use self::order_maintenance::OrderMaintenance;
+pub use self::seg_vec::SegVec;
+pub use self::steady_vec::SteadyVec;
@@ -0,0 +1,292 @@
+//! The order-maintenance problem.
+
+use std::cmp::Ordering;
+
+/// Tags live in [0, TAG_SPACE). The new tags are put between old ones, so leaving
+/// lots of space makes relabeling rare.
+const TAG_SPACE: u64 = 1 << 62;
+const TAG_BITS: i32 = 62;
+
+/// A range of 2^i tags may be relabeled if it holds at most 2^i / T^i elements.
+/// The bigger ranges have to be sparser, which makes relabeling amortized cheap.
+const T: f64 = 1.5;
+
+/// A reference to an element of an `OrderMaintenance` list. Handles of deleted
+/// elements may be reused by later insertions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle(usize);
+
+#[derive(Debug, Clone)]
+struct Node {
+    tag: u64,
+    prev: Option<usize>,
+    next: Option<usize>
+}
+
+/// A list that answers "is a before b?" in O(1). Every element carries an integer
+/// tag and tags increase along the list. Inserting takes the middle of the gap
+/// between two tags; if there's no gap, the smallest aligned range of tags around
+/// the element which is sparse enough gets its tags spread evenly, as in Bender et
+/// al., "Two simplified algorithms for maintaining order in a list".
+///
+/// Time: `order` is O(1), `insert_after` is O(log n) amortized, `delete` is O(1).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::seq::OrderMaintenance;
+///
+/// use std::cmp::Ordering::*;
+///
+/// // A new list has one element which can't be deleted.
+/// let mut l = OrderMaintenance::new();
+/// let a = l.base();
+///
+/// let c = l.insert_after(a);
+/// let b = l.insert_after(a);
+///
+/// // Now the list is a, b, c.
+/// assert_eq!(l.order(a, b), Less);
+/// assert_eq!(l.order(c, b), Greater);
+///
+/// l.delete(b);
+/// assert_eq!(l.len(), 2);
+/// assert_eq!(l.order(a, c), Less);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrderMaintenance {
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    len: usize
+}
+
+impl Default for OrderMaintenance {
+    fn default() -> OrderMaintenance {
+        OrderMaintenance::new()
+    }
+}
+
+impl OrderMaintenance {
+    /// Creates a list with a single element, the base.
+    pub fn new() -> OrderMaintenance {
+        OrderMaintenance {
+            nodes: vec![Node {tag: 0, prev: None, next: None}],
+            free: vec![],
+            len: 1
+        }
+    }
+
+    /// Returns the first element of the list, which can't be deleted.
+    pub fn base(&self) -> Handle {
+        Handle(0)
+    }
+
+    /// Returns the number of elements (including the base).
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Always false: there's at least the base.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Compares the positions of `a` and `b` in the list.
+    /// Time: O(1)
+    pub fn order(&self, a: Handle, b: Handle) -> Ordering {
+        self.nodes[a.0].tag.cmp(&self.nodes[b.0].tag)
+    }
+
+    /// Returns the element after `h`.
+    pub fn next(&self, h: Handle) -> Option<Handle> {
+        self.nodes[h.0].next.map(Handle)
+    }
+
+    /// Returns the element before `h`.
+    pub fn prev(&self, h: Handle) -> Option<Handle> {
+        self.nodes[h.0].prev.map(Handle)
+    }
+
+    /// The tag of `i`, or the end of the tag space for no element.
+    fn tag(&self, i: Option<usize>) -> u64 {
+        match i {
+            Some(i) => self.nodes[i].tag,
+            None => TAG_SPACE
+        }
+    }
+
+    /// Makes sure that there's a free tag right after `x`.
+    fn relabel(&mut self, x: usize) {
+        // Grow the range of 2^i tags containing x's tag, together with the
+        // elements in it, until it's sparse enough, then spread them evenly.
+        let tag = self.nodes[x].tag;
+        let (mut first, mut last, mut count) = (x, x, 1u64);
+
+        for i in 1 ..= TAG_BITS {
+            let size = 1u64 << i;
+            let start = tag & !(size - 1);
+
+            while let Some(p) = self.nodes[first].prev.filter(|&p| self.nodes[p].tag >= start) {
+                first = p;
+                count += 1;
+            }
+            while let Some(n) = self.nodes[last].next.filter(|&n| self.nodes[n].tag < start + size) {
+                last = n;
+                count += 1;
+            }
+
+            // The whole tag space is always fine: it holds far fewer elements
+            // than there is memory for.
+            if i == TAG_BITS || (2 * count <= size && count as f64 <= size as f64 / T.powi(i)) {
+                let gap = size / count;
+                let mut current = Some(first);
+                for k in 0 .. count {
+                    let c = current.unwrap();
+                    self.nodes[c].tag = start + k * gap;
+                    current = self.nodes[c].next;
+                }
+
+                return;
+            }
+        }
+    }
+
+    /// Inserts a new element right after `h` and returns it.
+    /// Time: O(log n) amortized
+    pub fn insert_after(&mut self, h: Handle) -> Handle {
+        let x = h.0;
+        if self.tag(self.nodes[x].next) - self.nodes[x].tag < 2 {
+            self.relabel(x);
+        }
+
+        let next = self.nodes[x].next;
+        let tag = self.nodes[x].tag + (self.tag(next) - self.nodes[x].tag) / 2;
+        let node = Node {tag, prev: Some(x), next};
+
+        let i = match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = node;
+                i
+            },
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+
+        self.nodes[x].next = Some(i);
+        if let Some(n) = next {
+            self.nodes[n].prev = Some(i);
+        }
+        self.len += 1;
+
+        Handle(i)
+    }
+
+    /// Removes `h` from the list. Its handle may be reused.
+    /// Time: O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics when trying to delete the base.
+    pub fn delete(&mut self, h: Handle) {
+        assert!(h != self.base(), "OrderMaintenance: can't delete the base");
+
+        let (prev, next) = (self.nodes[h.0].prev, self.nodes[h.0].next);
+        if let Some(p) = prev {
+            self.nodes[p].next = next;
+        }
+        if let Some(n) = next {
+            self.nodes[n].prev = prev;
+        }
+
+        self.free.push(h.0);
+        self.len -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use seq::order_maintenance::*;
+
+    // Checks the list against `model`, which holds the handles in order.
+    fn agrees(l: &OrderMaintenance, model: &[Handle]) -> bool {
+        let mut walk = vec![l.base()];
+        while let Some(h) = l.next(*walk.last().unwrap()) {
+            walk.push(h);
+        }
+
+        walk == model && l.len() == model.len() &&
+        (0 .. model.len()).all(|i| (0 .. model.len()).all(|j| {
+            l.order(model[i], model[j]) == i.cmp(&j)
+        }))
+    }
+
+    quickcheck! {
+        // Insert after (or delete) the element at a random position.
+        fn same_as_vec(ops: Vec<(bool, usize)>) -> bool {
+            let mut l = OrderMaintenance::new();
+            let mut model = vec![l.base()];
+
+            for (ins, i) in ops {
+                if ins {
+                    let i = i % model.len();
+                    let h = l.insert_after(model[i]);
+                    model.insert(i + 1, h);
+                } else if model.len() > 1 {
+                    let i = 1 + i % (model.len() - 1);
+                    l.delete(model.remove(i));
+                }
+
+                if !agrees(&l, &model) {
+                    return false;
+                }
+            }
+
+            true
+        }
+    }
+
+    // Inserting at the same place over and over runs out of gaps quickly.
+    #[test]
+    fn relabel_same_place() {
+        let mut l = OrderMaintenance::new();
+        let mut model = vec![l.base()];
+
+        for _ in 0 .. 200 {
+            let h = l.insert_after(l.base());
+            model.insert(1, h);
+        }
+        for _ in 0 .. 200 {
+            let h = l.insert_after(model[100]);
+            model.insert(101, h);
+        }
+
+        assert!(agrees(&l, &model));
+    }
+
+    // Appending at the tail leaves no elements after the last one to spread, so
+    // relabeling has to reach to the left too.
+    #[test]
+    fn relabel_tail() {
+        let mut l = OrderMaintenance::new();
+        let mut model = vec![l.base()];
+
+        for _ in 0 .. 100_000 {
+            let h = l.insert_after(*model.last().unwrap());
+            model.push(h);
+        }
+
+        assert_eq!(l.len(), model.len());
+        assert!(model.windows(2).all(|w| l.next(w[0]) == Some(w[1]) && l.order(w[0], w[1]) == Ordering::Less));
+    }
+
+    #[test]
+    #[should_panic]
+    fn delete_base() {
+        let mut l = OrderMaintenance::new();
+        let b = l.base();
+        l.delete(b);
+    }
+}
@@ -0,0 +1,242 @@
+//! Segmented vectors.
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use std::ops::{Index, IndexMut};
+
+/// The number of elements per chunk used by `SegVec::new`.
+const DEFAULT_CHUNK: usize = 1024;
+
+/// A growable array stored in fixed-size chunks. When the last chunk is full, a
+/// new one is allocated, so growing never moves the elements already stored (only
+/// the vector of chunk pointers grows the way `Vec` does) and never needs one huge
+/// block of memory. Within a chunk the elements are contiguous, so iterating chunk
+/// by chunk is as cache-friendly as iterating a `Vec`.
+///
+/// Time: `push` and `pop` are O(1) amortized, indexing is O(1).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::seq::SegVec;
+///
+/// let mut v = SegVec::with_chunk_size(4);
+/// for i in 0 .. 10 {
+///     v.push(i);
+/// }
+///
+/// assert_eq!(v[7], 7);
+/// assert_eq!(v.chunks().map(|c| c.len()).collect::<Vec<_>>(), vec![4, 4, 2]);
+///
+/// assert_eq!(v.pop(), Some(9));
+/// assert_eq!(v.iter().sum::<i32>(), 36);
+/// ```
+#[derive(Debug)]
+pub struct SegVec<T> {
+    chunks: Vec<Vec<T>>,
+    chunk: usize,
+    len: usize
+}
+
+// A derived `Clone` would shrink the last chunk to fit, and then pushing into it
+// would move its elements.
+impl<T: Clone> Clone for SegVec<T> {
+    fn clone(&self) -> SegVec<T> {
+        let chunks = self.chunks.iter().map(|c| {
+            let mut copy = Vec::with_capacity(self.chunk);
+            copy.extend_from_slice(c);
+            copy
+        }).collect();
+
+        SegVec {
+            chunks,
+            chunk: self.chunk,
+            len: self.len
+        }
+    }
+}
+
+impl<T> Default for SegVec<T> {
+    fn default() -> SegVec<T> {
+        SegVec::new()
+    }
+}
+
+impl<T> SegVec<T> {
+    /// Creates an empty vector with chunks of 1024 elements.
+    pub fn new() -> SegVec<T> {
+        SegVec::with_chunk_size(DEFAULT_CHUNK)
+    }
+
+    /// Creates an empty vector with chunks of the given size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk` is 0.
+    pub fn with_chunk_size(chunk: usize) -> SegVec<T> {
+        assert!(chunk > 0, "SegVec: chunks can't be empty");
+
+        SegVec {
+            chunks: vec![],
+            chunk,
+            len: 0
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the i-th element.
+    /// Time: O(1)
+    pub fn get(&self, i: usize) -> Option<&T> {
+        self.chunks.get(i / self.chunk)?.get(i % self.chunk)
+    }
+
+    /// Returns the i-th element for modification.
+    /// Time: O(1)
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+        self.chunks.get_mut(i / self.chunk)?.get_mut(i % self.chunk)
+    }
+
+    /// Appends an element. Allows chaining calls.
+    /// Time: O(1) amortized
+    pub fn push(&mut self, x: T) -> &mut Self {
+        if self.len.is_multiple_of(self.chunk) {
+            self.chunks.push(Vec::with_capacity(self.chunk));
+        }
+
+        // The last chunk has room, so this never reallocates.
+        let last = self.chunks.len() - 1;
+        self.chunks[last].push(x);
+        self.len += 1;
+
+        self
+    }
+
+    /// Removes the last element and returns it. Empty chunks are freed.
+    /// Time: O(1) amortized
+    pub fn pop(&mut self) -> Option<T> {
+        let x = self.chunks.last_mut()?.pop();
+        if self.chunks.last().is_some_and(|c| c.is_empty()) {
+            self.chunks.pop();
+        }
+        self.len -= 1;
+
+        x
+    }
+
+    /// Iterates over the chunks, in order. Every chunk but the last one is full.
+    pub fn chunks(&self) -> impl Iterator<Item = &[T]> {
+        self.chunks.iter().map(|c| &c[..])
+    }
+
+    /// Iterates over the elements, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.chunks.iter().flat_map(|c| c.iter())
+    }
+}
+
+impl<T> Index<usize> for SegVec<T> {
+    type Output = T;
+
+    fn index(&self, i: usize) -> &T {
+        match self.get(i) {
+            Some(x) => x,
+            None => panic!("SegVec: index {} out of bounds (len {})", i, self.len)
+        }
+    }
+}
+
+impl<T> IndexMut<usize> for SegVec<T> {
+    fn index_mut(&mut self, i: usize) -> &mut T {
+        let len = self.len;
+        match self.get_mut(i) {
+            Some(x) => x,
+            None => panic!("SegVec: index {} out of bounds (len {})", i, len)
+        }
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for SegVec<T> {
+    fn arbitrary<G: Gen>(g: &mut G) -> SegVec<T> {
+        let data: Vec<T> = Arbitrary::arbitrary(g);
+        let chunk = 1 + g.size() % 8;
+
+        let mut v = SegVec::with_chunk_size(chunk);
+        for x in data {
+            v.push(x);
+        }
+
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use seq::seg_vec::*;
+
+    quickcheck! {
+        // Push (Some) or pop (None) and compare with a `Vec`.
+        fn same_as_vec(ops: Vec<Option<usize>>, chunk: usize) -> bool {
+            let mut v = SegVec::with_chunk_size(1 + chunk % 8);
+            let mut model = vec![];
+
+            for op in ops {
+                match op {
+                    Some(x) => {
+                        v.push(x);
+                        model.push(x);
+                    },
+                    None => if v.pop() != model.pop() {
+                        return false;
+                    }
+                }
+
+                if !v.iter().eq(model.iter()) || v.len() != model.len() {
+                    return false;
+                }
+            }
+
+            (0 .. model.len()).all(|i| v[i] == model[i])
+        }
+
+        // All chunks are full except the last one, which isn't empty.
+        fn chunks_full(v: SegVec<usize>) -> bool {
+            let sizes: Vec<usize> = v.chunks().map(|c| c.len()).collect();
+
+            match sizes.split_last() {
+                None => v.is_empty(),
+                Some((&last, rest)) =>
+                    rest.iter().all(|&s| s == v.chunk) && last > 0 && last <= v.chunk
+            }
+        }
+
+        // Pushing never moves the elements already stored.
+        fn push_no_move(v: SegVec<usize>, x: usize) -> bool {
+            let mut v = v.clone();
+            let before: Vec<*const usize> = (0 .. v.len()).map(|i| &v[i] as *const _).collect();
+            v.push(x);
+
+            (0 .. before.len()).all(|i| ::std::ptr::eq(&v[i], before[i]))
+        }
+
+        fn get_past_end(v: SegVec<usize>, i: usize) -> bool {
+            v.get(v.len() + i % 100) == None
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_chunks() {
+        SegVec::<usize>::with_chunk_size(0);
+    }
+}
@@ -0,0 +1,293 @@
+//! Dynamic arrays with worst-case constant time `push`.
+
+use quickcheck::Arbitrary;
+use quickcheck::Gen;
+
+use std::mem;
+use std::ops::{Index, IndexMut};
+
+/// A growable array which never copies all of its elements at once. `Vec` doubles
+/// its buffer when it's full, which makes `push` O(1) amortized, but the unlucky
+/// `push` that triggers the copy takes O(n). `SteadyVec` instead allocates the next,
+/// bigger buffer in advance and every `push` moves two elements from the current
+/// buffer to the next one. By the time the current buffer could fill up, the next
+/// one holds everything and takes over.
+///
+/// The elements are stored as `Option<T>`, so that moving them out leaves a `None`
+/// behind, and the retired buffer is emptied one slot per operation too. All of
+/// this assumes that allocating (but not initializing) a buffer takes O(1), which
+/// `Vec::with_capacity` does.
+///
+/// Time: `push`, `pop`, `get` and indexing are O(1) worst-case.
+/// Space: at most about 3 times as much as `Vec`.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::seq::SteadyVec;
+///
+/// let mut v = SteadyVec::new();
+/// for i in 0 .. 100 {
+///     v.push(i);
+/// }
+///
+/// assert_eq!(v.len(), 100);
+/// assert_eq!(v[42], 42);
+///
+/// v[42] = 0;
+/// assert_eq!(v.get(42), Some(&0));
+///
+/// assert_eq!(v.pop(), Some(99));
+/// assert_eq!(v.iter().sum::<i32>(), 99 * 98 / 2 - 42);
+/// ```
+#[derive(Debug)]
+pub struct SteadyVec<T> {
+    /// Holds all the elements, except that the first `new.len()` ones were moved out.
+    old: Vec<Option<T>>,
+
+    /// The next buffer, holding the first `new.len()` elements.
+    new: Vec<Option<T>>,
+
+    /// The previous buffer, full of `None`s, waiting to be dropped piece by piece.
+    retired: Vec<Option<T>>
+}
+
+/// Copies a buffer, keeping its capacity.
+fn copy<T: Clone>(buffer: &[Option<T>], capacity: usize) -> Vec<Option<T>> {
+    let mut copy = Vec::with_capacity(capacity);
+    copy.extend_from_slice(buffer);
+    copy
+}
+
+// A derived `Clone` would shrink the buffers to fit, and then `push` would have to
+// grow them.
+impl<T: Clone> Clone for SteadyVec<T> {
+    fn clone(&self) -> SteadyVec<T> {
+        SteadyVec {
+            old: copy(&self.old, self.old.capacity()),
+            new: copy(&self.new, self.new.capacity()),
+            retired: vec![]
+        }
+    }
+}
+
+impl<T> Default for SteadyVec<T> {
+    fn default() -> SteadyVec<T> {
+        SteadyVec::new()
+    }
+}
+
+impl<T> SteadyVec<T> {
+    /// Creates an empty array.
+    pub fn new() -> SteadyVec<T> {
+        SteadyVec {
+            old: Vec::with_capacity(1),
+            new: Vec::with_capacity(2),
+            retired: vec![]
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.old.len()
+    }
+
+    /// Checks whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.old.is_empty()
+    }
+
+    /// Returns the i-th element.
+    /// Time: O(1)
+    pub fn get(&self, i: usize) -> Option<&T> {
+        if i < self.new.len() {
+            self.new[i].as_ref()
+        } else {
+            self.old.get(i)?.as_ref()
+        }
+    }
+
+    /// Returns the i-th element for modification.
+    /// Time: O(1)
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+        if i < self.new.len() {
+            self.new[i].as_mut()
+        } else {
+            self.old.get_mut(i)?.as_mut()
+        }
+    }
+
+    /// Iterates over the elements in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        (0 .. self.len()).map(move |i| &self[i])
+    }
+
+    /// Appends an element. Allows chaining calls.
+    /// Time: O(1) worst-case
+    pub fn push(&mut self, x: T) -> &mut Self {
+        self.retired.pop();
+
+        // If a phase starts with L elements, it ends after at most L pushes, so
+        // the buffers (allocated with room for 4L) never have to grow.
+        debug_assert!(self.old.len() < self.old.capacity());
+        self.old.push(Some(x));
+
+        for _ in 0 .. 2 {
+            let i = self.new.len();
+            if i < self.old.len() {
+                let y = self.old[i].take();
+                self.new.push(y);
+            }
+        }
+
+        if self.new.len() == self.old.len() {
+            debug_assert!(self.retired.is_empty());
+            let next = Vec::with_capacity(4 * self.new.len());
+            let full = mem::replace(&mut self.new, next);
+            self.retired = mem::replace(&mut self.old, full);
+        }
+
+        self
+    }
+
+    /// Removes the last element and returns it.
+    /// Time: O(1) worst-case
+    pub fn pop(&mut self) -> Option<T> {
+        self.retired.pop();
+
+        let x = self.old.pop()?;
+        if self.new.len() > self.old.len() {
+            self.new.pop()?
+        } else {
+            x
+        }
+    }
+}
+
+impl<T> Index<usize> for SteadyVec<T> {
+    type Output = T;
+
+    fn index(&self, i: usize) -> &T {
+        match self.get(i) {
+            Some(x) => x,
+            None => panic!("SteadyVec: index {} out of bounds (len {})", i, self.len())
+        }
+    }
+}
+
+impl<T> IndexMut<usize> for SteadyVec<T> {
+    fn index_mut(&mut self, i: usize) -> &mut T {
+        let len = self.len();
+        match self.get_mut(i) {
+            Some(x) => x,
+            None => panic!("SteadyVec: index {} out of bounds (len {})", i, len)
+        }
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for SteadyVec<T> {
+    fn arbitrary<G: Gen>(g: &mut G) -> SteadyVec<T> {
+        let data: Vec<T> = Arbitrary::arbitrary(g);
+
+        let mut v = SteadyVec::new();
+        for x in data {
+            v.push(x);
+        }
+
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use seq::steady_vec::*;
+
+    fn to_vec(v: &SteadyVec<usize>) -> Vec<usize> {
+        v.iter().cloned().collect()
+    }
+
+    quickcheck! {
+        // Push (Some) or pop (None) and compare with a `Vec`.
+        fn same_as_vec(ops: Vec<Option<usize>>) -> bool {
+            let mut v = SteadyVec::new();
+            let mut model = vec![];
+
+            for op in ops {
+                match op {
+                    Some(x) => {
+                        v.push(x);
+                        model.push(x);
+                    },
+                    None => if v.pop() != model.pop() {
+                        return false;
+                    }
+                }
+
+                if to_vec(&v) != model || v.len() != model.len() {
+                    return false;
+                }
+            }
+
+            true
+        }
+
+        fn clone_push(v: SteadyVec<usize>, xs: Vec<usize>) -> bool {
+            let mut w = v.clone();
+            for &x in &xs {
+                w.push(x);
+            }
+
+            w.iter().eq(v.iter().chain(xs.iter()))
+        }
+
+        fn get_past_end(v: SteadyVec<usize>, i: usize) -> bool {
+            v.get(v.len() + i % 100) == None
+        }
+
+        fn get_mut_get(v: SteadyVec<usize>, i: usize, x: usize) -> bool {
+            let mut v = v.clone();
+
+            if v.is_empty() {
+                true
+            } else {
+                let i = i % v.len();
+                *v.get_mut(i).unwrap() = x;
+
+                v[i] == x
+            }
+        }
+
+        // Buffers have to stay small no matter how pushes and pops interleave.
+        fn space(ops: Vec<bool>) -> bool {
+            let mut v = SteadyVec::new();
+            let mut max_len = 0;
+
+            for push in ops {
+                if push {
+                    v.push(0);
+                } else {
+                    v.pop();
+                }
+                max_len = max_len.max(v.len());
+
+                if v.old.capacity() + v.new.capacity() > 8 * max_len + 8 {
+                    return false;
+                }
+            }
+
+            true
+        }
+    }
+
+    // Every `push` would trip the assertion if a buffer had to grow.
+    #[test]
+    fn many_pushes() {
+        let mut v = SteadyVec::new();
+        for i in 0 .. 100_000 {
+            v.push(i);
+        }
+
+        assert!((0 .. 100_000).all(|i| v[i] == i));
+    }
+}
@@ -0,0 +1,138 @@
+//! Bucket sort of floating point numbers spread uniformly over [0, 1).
+//!
+//! `n` numbers go to `n` buckets of equal widths, every bucket is sorted with
+//! insertion sort, and the buckets are concatenated. When the numbers are
+//! uniform, a bucket gets O(1) of them on average, so the whole sort takes O(n)
+//! expected time. Floats aren't `Ord` because of NaN, so the sort first turns
+//! them into `Total`s, which can't be NaN.
+
+use super::elementary::insertion_sort_by;
+
+use std::cmp::Ordering;
+
+/// A float which isn't NaN, and so is totally ordered. `-0.0` and `0.0` are
+/// equal.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Total(f64);
+
+impl Total {
+    /// Returns `None` if `x` is NaN.
+    pub fn new(x: f64) -> Option<Total> {
+        if x.is_nan() {
+            None
+        } else {
+            Some(Total(x))
+        }
+    }
+
+    pub fn get(self) -> f64 {
+        self.0
+    }
+}
+
+impl Eq for Total {}
+
+impl Ord for Total {
+    fn cmp(&self, other: &Total) -> Ordering {
+        // Neither is NaN.
+        self.0.partial_cmp(&other.0).unwrap()
+    }
+}
+
+impl PartialOrd for Total {
+    fn partial_cmp(&self, other: &Total) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Bucket sort of numbers from [0, 1). Numbers outside of it are still sorted,
+/// in the first or the last bucket, but many of them make the sort quadratic.
+/// Time: O(n) expected for uniformly spread numbers, O(n^2) in the worst case
+/// Memory: O(n)
+///
+/// # Panics
+///
+/// Panics if any of the numbers is NaN, before changing `v`.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec![0.78, 0.17, 0.39, 0.26, 0.72, 0.94, 0.21, 0.12, 0.23, 0.68];
+/// sort::bucket_sort(&mut v);
+/// assert_eq!(v, vec![0.12, 0.17, 0.21, 0.23, 0.26, 0.39, 0.68, 0.72, 0.78, 0.94]);
+/// ```
+pub fn bucket_sort(v: &mut [f64]) {
+    let xs: Vec<Total> = v.iter()
+        .map(|&x| Total::new(x).unwrap_or_else(|| panic!("bucket_sort: NaN")))
+        .collect();
+
+    let n = v.len();
+    let mut buckets: Vec<Vec<Total>> = vec![vec![]; n];
+    for x in xs {
+        // The cast saturates, so negative numbers go to the first bucket.
+        let b = ((x.get() * n as f64) as usize).min(n - 1);
+        buckets[b].push(x);
+    }
+
+    let sorted = buckets.iter_mut().flat_map(|bucket| {
+        insertion_sort_by(bucket, &mut Total::lt);
+        bucket.iter().map(|x| x.get())
+    });
+    for (y, x) in v.iter_mut().zip(sorted) {
+        *y = x;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f64;
+    use std::panic;
+
+    fn std_sorted(v: &[f64]) -> Vec<f64> {
+        let mut v = v.to_vec();
+        v.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        v
+    }
+
+    quickcheck! {
+        fn bucket_sort_std(v: Vec<u32>) -> bool {
+            let mut v: Vec<f64> = v.into_iter().map(|x| x as f64 / (1u64 << 32) as f64).collect();
+            let sorted = std_sorted(&v);
+            bucket_sort(&mut v);
+
+            v == sorted
+        }
+
+        // Skewed and out of range numbers are still sorted.
+        fn bucket_sort_any(v: Vec<i16>) -> bool {
+            let mut v: Vec<f64> = v.into_iter().map(|x| x as f64 / 1000.0).collect();
+            let sorted = std_sorted(&v);
+            bucket_sort(&mut v);
+
+            v == sorted
+        }
+    }
+
+    #[test]
+    fn infinities() {
+        let mut v = vec![0.5, f64::INFINITY, 0.0, f64::NEG_INFINITY, 0.25];
+        bucket_sort(&mut v);
+        assert_eq!(v, vec![f64::NEG_INFINITY, 0.0, 0.25, 0.5, f64::INFINITY]);
+    }
+
+    #[test]
+    fn nan() {
+        assert_eq!(Total::new(f64::NAN), None);
+
+        let mut v = vec![0.5, 0.25, f64::NAN, 0.0];
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| bucket_sort(&mut v)));
+        assert!(result.is_err());
+        assert_eq!(&v[.. 2], &[0.5, 0.25]);
+        assert!(v[2].is_nan());
+    }
+}
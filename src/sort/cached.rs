@@ -0,0 +1,80 @@
+//! Sorting by keys which are expensive to compute, computing each key once.
+
+use super::quicksort::quicksort;
+
+/// Moves to every position `k` of `v` the element from position `source(k)`,
+/// where `source` is a permutation, following its cycles.
+/// Time: O(n)
+/// Memory: O(n) bits
+pub(crate) fn permute<T, F: Fn(usize) -> usize>(v: &mut [T], source: F) {
+    let mut done = vec![false; v.len()];
+    for j in 0 .. v.len() {
+        let mut k = j;
+        while !done[k] {
+            done[k] = true;
+            let next = source(k);
+            if next == j {
+                break;
+            }
+            v.swap(k, next);
+            k = next;
+        }
+    }
+}
+
+/// Sorts `v` stably by the keys computed by `key`, which is called exactly
+/// once per element, so it pays off when computing a key costs more than
+/// comparing two (normalizing strings, hashing and so on). The keys are stored
+/// together with the positions of their elements, the pairs are sorted with
+/// `quicksort`, the positions breaking ties, and then the elements are moved
+/// to their places.
+/// Time: O(n log n) comparisons of keys and n calls to `key`
+/// Memory: O(n) keys
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec!["b", "A", "c", "a", "B"];
+/// sort::sort_by_cached_key(&mut v, |s| s.to_lowercase());
+/// assert_eq!(v, vec!["A", "a", "b", "B", "c"]);
+/// ```
+pub fn sort_by_cached_key<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], key: F) {
+    let mut keys: Vec<(K, usize)> = v.iter().map(key).zip(0 ..).collect();
+    quicksort(&mut keys);
+    permute(v, |k| keys[k].1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+
+    quickcheck! {
+        // Few distinct keys, so that stability matters.
+        fn sort_by_cached_key_stable(v: Vec<(u8, String)>) -> bool {
+            let calls = Cell::new(0);
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            sort_by_cached_key(&mut v1, |p| {
+                calls.set(calls.get() + 1);
+                p.0 % 4
+            });
+            v2.sort_by_key(|p| p.0 % 4);
+
+            v1 == v2 && calls.get() == v1.len()
+        }
+
+        fn permute_inverse(v: Vec<u16>) -> bool {
+            let mut order: Vec<usize> = (0 .. v.len()).collect();
+            order.sort_by_key(|&i| v[i]);
+            let mut w = v.clone();
+            permute(&mut w, |k| order[k]);
+
+            w.iter().enumerate().all(|(k, x)| *x == v[order[k]])
+        }
+    }
+}
@@ -0,0 +1,393 @@
+//! The elementary quadratic sorts: insertion, selection, bubble and cycle sort.
+//!
+//! Every sort comes in two versions: the plain one, and one whose name ends with
+//! `_stats`, which also returns the numbers of comparisons, swaps and writes it
+//! made, to compare the costs of the algorithms on the same input. Both run the
+//! same code.
+//!
+//! # Example
+//!
+//! ```
+//! extern crate aisd;
+//! use aisd::sort::elementary;
+//!
+//! let v: Vec<u32> = (0 .. 100).rev().collect();
+//!
+//! // Reversed input is the worst case for insertion sort...
+//! let s = elementary::insertion_sort_stats(&mut v.clone());
+//! assert_eq!((s.comparisons, s.swaps), (4950, 4950));
+//!
+//! // ...while selection sort always compares as much, but swaps little.
+//! let s = elementary::selection_sort_stats(&mut v.clone());
+//! assert_eq!((s.comparisons, s.swaps), (4950, 50));
+//! ```
+
+use std::cmp::Ordering;
+use std::mem::ManuallyDrop;
+use std::ptr;
+
+/// The costs of a sort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SortStats {
+    /// The number of comparisons of elements.
+    pub comparisons: u64,
+
+    /// The number of swaps of two elements.
+    pub swaps: u64,
+
+    /// The number of elements written to the slice. A swap writes two.
+    pub writes: u64
+}
+
+impl SortStats {
+    pub(super) fn less<T, F: FnMut(&T, &T) -> bool>(&mut self, is_less: &mut F, a: &T, b: &T) -> bool {
+        self.comparisons += 1;
+        is_less(a, b)
+    }
+
+    pub(super) fn swap<T>(&mut self, v: &mut [T], i: usize, j: usize) {
+        self.swaps += 1;
+        self.writes += 2;
+        v.swap(i, j);
+    }
+
+    fn cmp<T: Ord>(&mut self, a: &T, b: &T) -> Ordering {
+        self.comparisons += 1;
+        a.cmp(b)
+    }
+}
+
+fn insertion<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], is_less: &mut F, s: &mut SortStats) {
+    for i in 1 .. v.len() {
+        let mut j = i;
+        while j > 0 && s.less(is_less, &v[j], &v[j - 1]) {
+            s.swap(v, j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+/// Insertion sort by `is_less`, for the other sorts to finish short runs.
+pub(crate) fn insertion_sort_by<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], is_less: &mut F) {
+    insertion(v, is_less, &mut SortStats::default());
+}
+
+fn selection<T: Ord>(v: &mut [T], s: &mut SortStats) {
+    let n = v.len();
+    for i in 0 .. n {
+        let mut min = i;
+        for j in i + 1 .. n {
+            if s.less(&mut T::lt, &v[j], &v[min]) {
+                min = j;
+            }
+        }
+        if min != i {
+            s.swap(v, i, min);
+        }
+    }
+}
+
+fn bubble<T: Ord>(v: &mut [T], s: &mut SortStats) {
+    let mut end = v.len();
+    while end > 1 {
+        // Everything after the last swap is in place already.
+        let mut last_swap = 0;
+        for j in 1 .. end {
+            if s.less(&mut T::lt, &v[j], &v[j - 1]) {
+                s.swap(v, j, j - 1);
+                last_swap = j;
+            }
+        }
+        end = last_swap;
+    }
+}
+
+/// An element taken out of the slice, and the hole it left. When dropped, even
+/// by a panic in the comparison, it writes the element it holds to the hole.
+struct Hand<T> {
+    item: ManuallyDrop<T>,
+    hole: *mut T
+}
+
+impl<T> Drop for Hand<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::write(self.hole, ManuallyDrop::take(&mut self.item));
+        }
+    }
+}
+
+fn cycle<T: Ord>(v: &mut [T], s: &mut SortStats) {
+    let n = v.len();
+    for start in 0 .. n.saturating_sub(1) {
+        // Where the element goes: after all the smaller ones, and the equal
+        // ones which are already in place.
+        let place = |v: &[T], item: &T, s: &mut SortStats| {
+            let mut pos = start;
+            for x in &v[start + 1 ..] {
+                if s.less(&mut T::lt, x, item) {
+                    pos += 1;
+                }
+            }
+            while pos != start && s.cmp(&v[pos], item) == Ordering::Equal {
+                pos += 1;
+            }
+            pos
+        };
+
+        if place(v, &v[start], s) == start {
+            continue;
+        }
+
+        // Rotate the cycle starting here: put the element in hand in its place
+        // and take the one which was there, until the hole at the start is the
+        // place. The hand never looks at the hole.
+        // The slice is only read through `v` meanwhile, and written through
+        // `base`, which the hand's hole comes from.
+        let base = v.as_mut_ptr();
+        let mut hand = unsafe {
+            let hole = base.add(start);
+            Hand {item: ManuallyDrop::new(ptr::read(hole)), hole}
+        };
+        loop {
+            let pos = place(v, &hand.item, s);
+            s.writes += 1;
+            if pos == start {
+                break;
+            }
+            unsafe {
+                ptr::swap(&mut *hand.item, base.add(pos));
+            }
+        }
+    }
+}
+
+/// Insertion sort: moves every element left, swapping it with its neighbour,
+/// until it's in place among the elements before it. It makes one swap per
+/// inversion, so it's fast on nearly sorted input. Stable.
+/// Time: O(n + number of inversions), O(n^2) in the worst case
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec![5, 3, 8, 1];
+/// sort::insertion_sort(&mut v);
+/// assert_eq!(v, vec![1, 3, 5, 8]);
+/// ```
+pub fn insertion_sort<T: Ord>(v: &mut [T]) {
+    insertion_sort_stats(v);
+}
+
+/// Like `insertion_sort`, but returns the numbers of comparisons and swaps.
+pub fn insertion_sort_stats<T: Ord>(v: &mut [T]) -> SortStats {
+    let mut s = SortStats::default();
+    insertion(v, &mut T::lt, &mut s);
+
+    s
+}
+
+/// Selection sort: puts the least of the remaining elements at the front of
+/// them. It always makes n(n - 1)/2 comparisons, but at most n - 1 swaps. Not
+/// stable.
+/// Time: O(n^2)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec![5, 3, 8, 1];
+/// sort::selection_sort(&mut v);
+/// assert_eq!(v, vec![1, 3, 5, 8]);
+/// ```
+pub fn selection_sort<T: Ord>(v: &mut [T]) {
+    selection_sort_stats(v);
+}
+
+/// Like `selection_sort`, but returns the numbers of comparisons and swaps.
+pub fn selection_sort_stats<T: Ord>(v: &mut [T]) -> SortStats {
+    let mut s = SortStats::default();
+    selection(v, &mut s);
+
+    s
+}
+
+/// Bubble sort: passes over the elements swapping neighbours which are out of
+/// order, until a pass makes no swaps. Every pass ends after its last swap of
+/// the previous one, so sorted input takes a single pass. Stable.
+/// Time: O(n^2), O(n) on sorted input
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec![5, 3, 8, 1];
+/// sort::bubble_sort(&mut v);
+/// assert_eq!(v, vec![1, 3, 5, 8]);
+/// ```
+pub fn bubble_sort<T: Ord>(v: &mut [T]) {
+    bubble_sort_stats(v);
+}
+
+/// Like `bubble_sort`, but returns the numbers of comparisons and swaps.
+pub fn bubble_sort_stats<T: Ord>(v: &mut [T]) -> SortStats {
+    let mut s = SortStats::default();
+    bubble(v, &mut s);
+
+    s
+}
+
+/// Cycle sort: for every cycle of the permutation which sorts the slice,
+/// takes one element out and puts it in its place, then the element from
+/// there, and so on, until the cycle is closed. Every element is written at
+/// most once, and only if it's not in place, which is the least possible
+/// number of writes, at the price of always making O(n^2) comparisons. Not
+/// stable.
+/// Time: O(n^2)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort::{self, elementary};
+///
+/// let mut v = vec![5, 3, 8, 1];
+/// sort::cycle_sort(&mut v);
+/// assert_eq!(v, vec![1, 3, 5, 8]);
+///
+/// // 1 and 2 are in place, the others form a single cycle.
+/// let s = elementary::cycle_sort_stats(&mut vec![1, 2, 4, 5, 3]);
+/// assert_eq!((s.writes, s.swaps), (3, 0));
+/// ```
+pub fn cycle_sort<T: Ord>(v: &mut [T]) {
+    cycle_sort_stats(v);
+}
+
+/// Like `cycle_sort`, but returns the numbers of comparisons and writes.
+pub fn cycle_sort_stats<T: Ord>(v: &mut [T]) -> SortStats {
+    let mut s = SortStats::default();
+    cycle(v, &mut s);
+
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+    use std::panic;
+
+    fn inversions(v: &[u8]) -> u64 {
+        let mut n = 0;
+        for i in 0 .. v.len() {
+            for j in i + 1 .. v.len() {
+                if v[j] < v[i] {
+                    n += 1;
+                }
+            }
+        }
+
+        n
+    }
+
+    quickcheck! {
+        // Insertion sort and bubble sort swap every inversion once.
+        fn sorts_std(v: Vec<u8>) -> bool {
+            let mut sorted = v.clone();
+            sorted.sort();
+            let inv = inversions(&v);
+            let n = v.len() as u64;
+
+            let mut v1 = v.clone();
+            let s1 = insertion_sort_stats(&mut v1);
+            let mut v2 = v.clone();
+            let s2 = selection_sort_stats(&mut v2);
+            let mut v3 = v;
+            let s3 = bubble_sort_stats(&mut v3);
+
+            v1 == sorted && v2 == sorted && v3 == sorted &&
+            s1.swaps == inv && s3.swaps == inv &&
+            s2.comparisons == n * n.saturating_sub(1) / 2 && s2.swaps < n.max(1)
+        }
+
+        // Cycle sort writes exactly the elements which aren't in place.
+        fn cycle_sort_writes(v: Vec<u8>) -> bool {
+            let mut sorted = v.clone();
+            sorted.sort();
+            let misplaced = v.iter().zip(&sorted).filter(|p| p.0 != p.1).count() as u64;
+            let mut v = v;
+            let s = cycle_sort_stats(&mut v);
+
+            v == sorted && s.writes == misplaced && s.swaps == 0
+        }
+
+        fn insertion_sort_stable(v: Vec<(u8, u8)>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            insertion_sort_by(&mut v1, &mut |a, b| a.0 % 4 < b.0 % 4);
+            v2.sort_by_key(|p| p.0 % 4);
+
+            v1 == v2
+        }
+    }
+
+    thread_local! {
+        static BUDGET: Cell<u32> = const { Cell::new(0) };
+    }
+
+    // Strings whose comparisons panic when the budget runs out.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Bomb(String);
+
+    impl Ord for Bomb {
+        fn cmp(&self, other: &Bomb) -> Ordering {
+            BUDGET.with(|b| {
+                assert!(b.get() > 0, "out of comparisons");
+                b.set(b.get() - 1);
+            });
+            self.0.cmp(&other.0)
+        }
+    }
+
+    impl PartialOrd for Bomb {
+        fn partial_cmp(&self, other: &Bomb) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    // A panicking comparison leaves every element in the slice exactly once,
+    // and nothing is dropped twice.
+    #[test]
+    fn cycle_sort_panic_safe() {
+        let original: Vec<Bomb> = (0 .. 50).map(|i| Bomb((i * 7919 % 50).to_string())).collect();
+        let mut sorted = original.clone();
+        sorted.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for &budget in &[5, 60, 500] {
+            let mut v = original.clone();
+            BUDGET.with(|b| b.set(budget));
+            let result = panic::catch_unwind(panic::AssertUnwindSafe(|| cycle_sort(&mut v)));
+
+            assert!(result.is_err());
+            v.sort_by(|a, b| a.0.cmp(&b.0));
+            assert_eq!(v, sorted);
+        }
+    }
+
+    #[test]
+    fn sorted_input() {
+        let mut v: Vec<u32> = (0 .. 1000).collect();
+
+        assert_eq!(insertion_sort_stats(&mut v), SortStats {comparisons: 999, swaps: 0, writes: 0});
+        assert_eq!(bubble_sort_stats(&mut v), SortStats {comparisons: 999, swaps: 0, writes: 0});
+        assert_eq!(selection_sort_stats(&mut v), SortStats {comparisons: 499_500, swaps: 0, writes: 0});
+        assert_eq!(cycle_sort_stats(&mut v).writes, 0);
+    }
+}
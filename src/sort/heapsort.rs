@@ -0,0 +1,156 @@
+//! Heapsorts working in place on a max-heap.
+//!
+//! Both build the heap with Floyd's algorithm and then repeatedly swap the
+//! maximum with the last element of the heap and sift the new root down. They
+//! differ in how they sift down.
+
+/// Sifts `v[i]` down the max-heap `v[.. end]` the usual way: at every level,
+/// one comparison picks the bigger child and another checks whether it's bigger
+/// than the sifted element.
+fn sift_down<T: Ord>(v: &mut [T], mut i: usize, end: usize) {
+    loop {
+        let l = 2 * i + 1;
+        if l >= end {
+            return;
+        }
+
+        let child = if l + 1 < end && v[l] < v[l + 1] {l + 1} else {l};
+        if v[i] < v[child] {
+            v.swap(i, child);
+            i = child;
+        } else {
+            return;
+        }
+    }
+}
+
+/// Sifts `v[i]` down the max-heap `v[.. end]` bottom-up: first follow the bigger
+/// children all the way to a leaf, one comparison per level, then climb back up
+/// to the place of the sifted element. After a swap with the last element, the
+/// root is small and belongs near the bottom, so the climb is short.
+fn sift_down_bottom_up<T: Ord>(v: &mut [T], i: usize, end: usize) {
+    let mut j = i;
+    loop {
+        let l = 2 * j + 1;
+        if l + 1 < end {
+            j = if v[l] < v[l + 1] {l + 1} else {l};
+        } else {
+            if l < end {
+                j = l;
+            }
+            break;
+        }
+    }
+
+    while v[j] < v[i] {
+        j = (j - 1) / 2;
+    }
+
+    // Move the elements on the path from i to j one level up and put v[i] at j.
+    while j != i {
+        v.swap(i, j);
+        j = (j - 1) / 2;
+    }
+}
+
+fn sort_with<T: Ord>(v: &mut [T], sift: fn(&mut [T], usize, usize)) {
+    let n = v.len();
+    for i in (0 .. n / 2).rev() {
+        sift(v, i, n);
+    }
+
+    for end in (1 .. n).rev() {
+        v.swap(0, end);
+        sift(v, 0, end);
+    }
+}
+
+/// The usual heapsort. Makes about 2n log n comparisons.
+/// Time: O(n log n)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec![5, 3, 8, 1, 9, 2];
+/// sort::heapsort(&mut v);
+/// assert_eq!(v, vec![1, 2, 3, 5, 8, 9]);
+/// ```
+pub fn heapsort<T: Ord>(v: &mut [T]) {
+    sort_with(v, sift_down);
+}
+
+/// Bottom-up heapsort (Wegener). Sifts down by first finding the leaf at the end
+/// of the path of bigger children and then climbing up from it, which makes
+/// about n log n comparisons, half as many as `heapsort`. Worth it when
+/// comparisons are expensive.
+/// Time: O(n log n)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec!["pear", "apple", "fig", "kiwi"];
+/// sort::bottom_up_heapsort(&mut v);
+/// assert_eq!(v, vec!["apple", "fig", "kiwi", "pear"]);
+/// ```
+pub fn bottom_up_heapsort<T: Ord>(v: &mut [T]) {
+    sort_with(v, sift_down_bottom_up);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stats::{self, Counted};
+
+    use rand::{Rng, SeedableRng};
+    use rand::prng::XorShiftRng;
+
+    quickcheck! {
+        fn heapsort_std(v: Vec<i32>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            heapsort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+
+        fn bottom_up_heapsort_std(v: Vec<i32>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            bottom_up_heapsort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+
+        // Few distinct values, so that there are many ties.
+        fn bottom_up_heapsort_ties(v: Vec<u8>) -> bool {
+            let mut v1: Vec<u8> = v.iter().map(|x| x % 4).collect();
+            let mut v2 = v1.clone();
+            bottom_up_heapsort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+    }
+
+    #[test]
+    fn bottom_up_comparisons() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let n = 1 << 14;
+        let v: Vec<Counted<u32>> = (0 .. n).map(|_| Counted(rng.gen())).collect();
+
+        let (_, plain) = stats::count(|| heapsort(&mut v.clone()));
+        let (_, bottom_up) = stats::count(|| bottom_up_heapsort(&mut v.clone()));
+
+        // n log n is about 229 000.
+        assert!(plain > 400_000, "{}", plain);
+        assert!(bottom_up < 260_000, "{}", bottom_up);
+    }
+}
@@ -0,0 +1,408 @@
+//! Stable mergesorts: top-down, bottom-up and natural.
+//!
+//! All of them sort short runs with insertion sort and then merge them. A merge
+//! moves the left run to a buffer and merges it with the right run back into
+//! the slice. The buffer is allocated once and reused by all the merges.
+
+use super::elementary::insertion_sort_by;
+
+use std::cmp::Ordering;
+use std::ptr;
+
+/// Runs at most this long are sorted with insertion sort.
+const RUN: usize = 16;
+
+/// The part of the left run which is still in the buffer, and the place in the
+/// slice where it goes next. When dropped, even by a panic in the comparison,
+/// it moves the rest of the run to its place, so that the slice again holds
+/// every element exactly once.
+struct Hole<T> {
+    start: *mut T,
+    end: *mut T,
+    dest: *mut T
+}
+
+impl<T> Drop for Hole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let n = self.end.offset_from(self.start) as usize;
+            ptr::copy_nonoverlapping(self.start, self.dest, n);
+        }
+    }
+}
+
+/// Merges the sorted runs `v[.. mid]` and `v[mid ..]`. Of equal elements, those
+/// of the left run go first. `buf` has to be empty, and is left empty; it only
+/// grows if it has no room for the left run.
+fn merge<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], mid: usize, buf: &mut Vec<T>, is_less: &mut F) {
+    let len = v.len();
+    if mid == 0 || mid == len || !is_less(&v[mid], &v[mid - 1]) {
+        return;
+    }
+    debug_assert!(buf.is_empty());
+    buf.reserve(mid);
+
+    // The buffer's length stays 0, so it never drops the elements copied to it.
+    unsafe {
+        let v = v.as_mut_ptr();
+        let b = buf.as_mut_ptr();
+        ptr::copy_nonoverlapping(v, b, mid);
+
+        let mut hole = Hole {start: b, end: b.add(mid), dest: v};
+        let mut right = v.add(mid);
+        let end = v.add(len);
+
+        // The destination is behind the right run's next element until the left
+        // run is used up, so nothing is overwritten before it's moved.
+        while hole.start < hole.end && right < end {
+            let src = if is_less(&*right, &*hole.start) {
+                right = right.add(1);
+                right.sub(1)
+            } else {
+                hole.start = hole.start.add(1);
+                hole.start.sub(1)
+            };
+            ptr::copy_nonoverlapping(src, hole.dest, 1);
+            hole.dest = hole.dest.add(1);
+        }
+        // Dropping the hole moves what's left of the left run; what's left of
+        // the right run is already in place.
+    }
+}
+
+fn top_down<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], buf: &mut Vec<T>, is_less: &mut F) {
+    let len = v.len();
+    if len <= RUN {
+        insertion_sort_by(v, is_less);
+        return;
+    }
+
+    let mid = len / 2;
+    top_down(&mut v[.. mid], buf, is_less);
+    top_down(&mut v[mid ..], buf, is_less);
+    merge(v, mid, buf, is_less);
+}
+
+fn bottom_up<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], is_less: &mut F) {
+    let len = v.len();
+    for run in v.chunks_mut(RUN) {
+        insertion_sort_by(run, is_less);
+    }
+
+    let mut buf = vec![];
+    let mut width = RUN;
+    while width < len {
+        for runs in v.chunks_mut(2 * width) {
+            let mid = width.min(runs.len());
+            merge(runs, mid, &mut buf, is_less);
+        }
+        width *= 2;
+    }
+}
+
+/// Finds the run at the start of `v`, which is its longest non-decreasing or
+/// strictly decreasing prefix, and returns its length. A decreasing run is
+/// reversed; it has no equal elements, so that keeps the sort stable. Runs
+/// shorter than `RUN` are extended with insertion sort.
+fn find_run<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], is_less: &mut F) -> usize {
+    let len = v.len();
+    let mut end = len.min(2);
+    if len >= 2 && is_less(&v[1], &v[0]) {
+        while end < len && is_less(&v[end], &v[end - 1]) {
+            end += 1;
+        }
+        v[.. end].reverse();
+    } else {
+        while end < len && !is_less(&v[end], &v[end - 1]) {
+            end += 1;
+        }
+    }
+
+    if end < RUN.min(len) {
+        end = RUN.min(len);
+        insertion_sort_by(&mut v[.. end], is_less);
+    }
+
+    end
+}
+
+/// Like `merge`, but first leaves out the start of the left run which isn't
+/// greater than the first element of the right run, and the end of the right
+/// run which isn't less than the last element of the left run, as they're in
+/// place already. Binary search finds them, so merging a long run with a short
+/// one doesn't copy all of the long one.
+fn merge_trimmed<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], mid: usize, buf: &mut Vec<T>, is_less: &mut F) {
+    if mid == 0 || mid == v.len() {
+        return;
+    }
+    let start = v[.. mid].partition_point(|x| !is_less(&v[mid], x));
+    let end = mid + v[mid ..].partition_point(|x| is_less(x, &v[mid - 1]));
+    merge(&mut v[start .. end], mid - start, buf, is_less);
+}
+
+fn natural<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], is_less: &mut F) {
+    let len = v.len();
+    let mut buf = vec![];
+
+    // The runs which aren't merged yet, as (start, length). As in Timsort,
+    // neighbouring runs are merged until every run on the stack is longer than
+    // the next two together, so that merged runs have similar lengths, and a
+    // long run isn't merged over and over with short ones.
+    let mut runs: Vec<(usize, usize)> = vec![];
+    let mut start = 0;
+    while start < len {
+        let n = find_run(&mut v[start ..], is_less);
+        runs.push((start, n));
+        start += n;
+
+        while runs.len() >= 2 {
+            let k = runs.len();
+            let (a, b) = (runs[k - 2].1, runs[k - 1].1);
+            let i = if k >= 3 && runs[k - 3].1 <= a + b {
+                if runs[k - 3].1 < b {k - 3} else {k - 2}
+            } else if a <= b || start == len {
+                k - 2
+            } else {
+                break;
+            };
+
+            let ((s, l), (_, r)) = (runs[i], runs[i + 1]);
+            merge_trimmed(&mut v[s .. s + l + r], l, &mut buf, is_less);
+            runs[i] = (s, l + r);
+            runs.remove(i + 1);
+        }
+    }
+}
+
+/// Top-down mergesort: sorts the halves recursively and merges them. It's
+/// stable, so equal elements keep their order.
+/// Time: O(n log n)
+/// Memory: n / 2 elements for the buffer
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec![5, 3, 8, 1, 9, 2];
+/// sort::mergesort(&mut v);
+/// assert_eq!(v, vec![1, 2, 3, 5, 8, 9]);
+/// ```
+pub fn mergesort<T: Ord>(v: &mut [T]) {
+    mergesort_by(v, T::cmp);
+}
+
+/// Like `mergesort`, but sorts by the order given by `compare`.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec![1, 4, 2, 3];
+/// sort::mergesort_by(&mut v, |a, b| b.cmp(a));
+/// assert_eq!(v, vec![4, 3, 2, 1]);
+/// ```
+pub fn mergesort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut compare: F) {
+    let mut buf = Vec::with_capacity(v.len() / 2);
+    top_down(v, &mut buf, &mut |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Like `mergesort`, but sorts by the keys computed by `key`. Elements with
+/// equal keys keep their order.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec![("b", 2), ("a", 1), ("c", 2), ("d", 1)];
+/// sort::mergesort_by_key(&mut v, |p| p.1);
+/// assert_eq!(v, vec![("a", 1), ("d", 1), ("b", 2), ("c", 2)]);
+/// ```
+pub fn mergesort_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    mergesort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Bottom-up mergesort: sorts short runs and then merges neighbouring runs
+/// of doubling widths, without recursion. Stable.
+/// Time: O(n log n)
+/// Memory: at most n elements for the buffer
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec!["pear", "apple", "fig", "kiwi"];
+/// sort::bottom_up_mergesort(&mut v);
+/// assert_eq!(v, vec!["apple", "fig", "kiwi", "pear"]);
+/// ```
+pub fn bottom_up_mergesort<T: Ord>(v: &mut [T]) {
+    bottom_up_mergesort_by(v, T::cmp);
+}
+
+/// Like `bottom_up_mergesort`, but sorts by the order given by `compare`.
+pub fn bottom_up_mergesort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut compare: F) {
+    bottom_up(v, &mut |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Like `bottom_up_mergesort`, but sorts by the keys computed by `key`.
+pub fn bottom_up_mergesort_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    bottom_up_mergesort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Natural mergesort: splits the slice into the runs which are already sorted,
+/// either way, and merges neighbouring runs until only one is left. Sorted or
+/// reversed input takes a single pass, and input made of k runs is sorted in
+/// O(n log k). Stable.
+/// Time: O(n log n), O(n) on sorted input
+/// Memory: at most n elements for the buffer
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v: Vec<u32> = (0 .. 1000).chain((1000 .. 2000).rev()).collect();
+/// sort::natural_mergesort(&mut v);
+/// assert!(v.iter().cloned().eq(0 .. 2000));
+/// ```
+pub fn natural_mergesort<T: Ord>(v: &mut [T]) {
+    natural_mergesort_by(v, T::cmp);
+}
+
+/// Like `natural_mergesort`, but sorts by the order given by `compare`.
+pub fn natural_mergesort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut compare: F) {
+    natural(v, &mut |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Like `natural_mergesort`, but sorts by the keys computed by `key`.
+pub fn natural_mergesort_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    natural_mergesort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use stats::{self, Counted};
+
+    use std::panic;
+
+    quickcheck! {
+        fn mergesort_std(v: Vec<i32>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            mergesort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+
+        fn bottom_up_mergesort_std(v: Vec<i32>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            bottom_up_mergesort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+
+        // Long runs both ways, with ties.
+        fn natural_mergesort_runs(runs: Vec<(u8, bool)>) -> bool {
+            let mut v1: Vec<(u8, usize)> = vec![];
+            for (len, up) in runs {
+                let base = v1.len();
+                let run = (0 .. len / 4).map(|x| (x / 3, base + x as usize));
+                if up {
+                    v1.extend(run);
+                } else {
+                    v1.extend(run.rev());
+                }
+            }
+            let mut v2 = v1.clone();
+            natural_mergesort_by_key(&mut v1, |p| p.0);
+            v2.sort_by_key(|p| p.0);
+
+            v1 == v2
+        }
+
+        // Few distinct keys, so that stability matters.
+        fn mergesort_by_key_stable(v: Vec<(u8, u32)>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v.clone();
+            let mut v3 = v.clone();
+            let mut v4 = v;
+            mergesort_by_key(&mut v1, |p| p.0 % 4);
+            bottom_up_mergesort_by_key(&mut v2, |p| p.0 % 4);
+            natural_mergesort_by_key(&mut v3, |p| p.0 % 4);
+            v4.sort_by_key(|p| p.0 % 4);
+
+            v1 == v4 && v2 == v4 && v3 == v4
+        }
+    }
+
+    // A panicking comparison leaves every element in the slice exactly once,
+    // and nothing is dropped twice.
+    #[test]
+    fn panic_safe() {
+        let original: Vec<String> = (0 .. 200).map(|i| (i * 7919 % 200).to_string()).collect();
+        let mut sorted = original.clone();
+        sorted.sort();
+
+        for &after in &[0, 10, 300, 1000] {
+            let mut v = original.clone();
+            let mut count = 0;
+            let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                mergesort_by(&mut v, |a, b| {
+                    count += 1;
+                    if count > after {
+                        panic!("comparison {}", count);
+                    }
+                    a.cmp(b)
+                })
+            }));
+
+            assert!(result.is_err());
+            v.sort();
+            assert_eq!(v, sorted);
+        }
+    }
+
+    #[test]
+    fn sorted_and_reversed() {
+        let n = 100_000;
+        let mut v: Vec<usize> = (0 .. n).rev().collect();
+        mergesort(&mut v);
+        assert!(v.iter().cloned().eq(0 .. n));
+
+        bottom_up_mergesort_by(&mut v, |a, b| b.cmp(a));
+        assert!(v.iter().cloned().eq((0 .. n).rev()));
+    }
+
+    #[test]
+    fn natural_mergesort_comparisons() {
+        let n = 100_000;
+        let mut v: Vec<Counted<usize>> = (0 .. n).map(Counted).collect();
+        let (_, c) = stats::count(|| natural_mergesort(&mut v));
+        assert_eq!(c, n as u64 - 1);
+
+        v.reverse();
+        let (_, c) = stats::count(|| natural_mergesort(&mut v));
+        assert_eq!(c, n as u64 - 1);
+        assert!(v.iter().map(|x| x.0).eq(0 .. n));
+
+        // Four interleaved runs: n - 1 comparisons find them, and merging them
+        // takes two rounds of about n each, and some binary searches.
+        let mut v: Vec<Counted<usize>> = (0 .. n).map(|i| Counted(i * 4 % n + i * 4 / n)).collect();
+        let (_, c) = stats::count(|| natural_mergesort(&mut v));
+        assert!(v.iter().map(|x| x.0).eq(0 .. n));
+        assert!(c <= 3 * n as u64 + 100, "{}", c);
+    }
+}
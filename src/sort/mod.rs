@@ -0,0 +1,31 @@
+//! Sorting algorithms. All of them sort slices in place, in increasing order.
+
+pub mod bucket;
+pub mod cached;
+pub mod elementary;
+pub mod heapsort;
+pub mod mergesort;
+pub mod network;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod quicksort;
+pub mod radix;
+pub mod select;
+pub mod shell;
+pub mod strings;
+
+pub use self::bucket::{bucket_sort, Total};
+pub use self::cached::sort_by_cached_key;
+pub use self::elementary::{bubble_sort, cycle_sort, insertion_sort, selection_sort, SortStats};
+pub use self::heapsort::{bottom_up_heapsort, heapsort};
+pub use self::mergesort::{bottom_up_mergesort, bottom_up_mergesort_by, bottom_up_mergesort_by_key,
+                          mergesort, mergesort_by, mergesort_by_key, natural_mergesort,
+                          natural_mergesort_by, natural_mergesort_by_key};
+pub use self::network::network_sort;
+#[cfg(feature = "parallel")]
+pub use self::parallel::{par_sort, par_sort_by};
+pub use self::quicksort::{dual_pivot_quicksort, quicksort};
+pub use self::radix::{radix_sort, radix_sort_by_key, RadixKey};
+pub use self::select::{select_nth, select_nth_deterministic};
+pub use self::shell::{shellsort, Gaps};
+pub use self::strings::{msd_radix_sort, string_quicksort};
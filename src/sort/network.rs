@@ -0,0 +1,219 @@
+//! Sorting networks for arrays of up to 16 elements, built at compile time.
+//!
+//! A sorting network is a fixed sequence of comparators, each of which swaps
+//! two positions if they're out of order. The sequence doesn't depend on the
+//! data, so it has no unpredictable branches, and with the length of the
+//! array known at compile time the whole network can be unrolled. That makes
+//! it a good fit for sorting tiny buffers in hot loops.
+//!
+//! Every network has the fewest comparators known for its length (The Art of
+//! Computer Programming, 5.3.4): 0, 0, 1, 3, 5, 9, 12, 16, 19, 25, 29, 35, 39,
+//! 45, 51, 56 and 60 for 0 to 16 elements. Up to 8 elements, Batcher's odd-even
+//! merge sorts, in Knuth's merge exchange form (5.2.2, Algorithm M), are that
+//! small, so they're built by the algorithm. The networks for 9 to 13 elements
+//! are tables, and those for 14 and 15 are Green's network for 16 without the
+//! comparators that touch the last inputs.
+
+/// The greatest supported length.
+const MAX: usize = 16;
+
+/// Comparators of a network, and how many of them are used. The network for 16
+/// elements, the biggest one, has 60.
+type Comparators = ([(u8, u8); 60], usize);
+
+/// The smallest networks known for 9 to 13 elements.
+const SMALL: [&[(u8, u8)]; 5] = [
+    // 9 elements
+    &[
+        (0, 3), (1, 7), (2, 5), (4, 8), (0, 7), (2, 4), (3, 8), (5, 6), (0, 2), (1, 3),
+        (4, 5), (7, 8), (1, 4), (3, 6), (5, 7), (0, 1), (2, 4), (3, 5), (6, 8), (2, 3),
+        (4, 5), (6, 7), (1, 2), (3, 4), (5, 6)
+    ],
+    // 10 elements
+    &[
+        (0, 8), (1, 9), (2, 7), (3, 5), (4, 6), (0, 2), (1, 4), (5, 8), (7, 9), (0, 3),
+        (2, 4), (5, 7), (6, 9), (0, 1), (3, 6), (8, 9), (1, 5), (2, 3), (4, 8), (6, 7),
+        (1, 2), (3, 5), (4, 6), (7, 8), (2, 3), (4, 5), (6, 7), (3, 4), (5, 6)
+    ],
+    // 11 elements
+    &[
+        (0, 9), (1, 6), (2, 4), (3, 7), (5, 8), (0, 1), (3, 5), (4, 10), (6, 9), (7, 8),
+        (1, 3), (2, 5), (4, 7), (8, 10), (0, 4), (1, 2), (3, 7), (5, 9), (6, 8), (0, 1),
+        (2, 6), (4, 5), (7, 8), (9, 10), (2, 4), (3, 6), (5, 7), (8, 9), (1, 2), (3, 4),
+        (5, 6), (7, 8), (2, 3), (4, 5), (6, 7)
+    ],
+    // 12 elements
+    &[
+        (0, 8), (1, 7), (2, 6), (3, 11), (4, 10), (5, 9), (0, 1), (2, 5), (3, 4), (6, 9),
+        (7, 8), (10, 11), (0, 2), (1, 6), (5, 10), (9, 11), (0, 3), (1, 2), (4, 6), (5, 7),
+        (8, 11), (9, 10), (1, 4), (3, 5), (6, 8), (7, 10), (1, 3), (2, 5), (6, 9), (8, 10),
+        (2, 3), (4, 5), (6, 7), (8, 9), (4, 6), (5, 7), (3, 4), (5, 6), (7, 8)
+    ],
+    // 13 elements
+    &[
+        (0, 12), (1, 10), (2, 9), (3, 7), (5, 11), (6, 8), (1, 6), (2, 3), (4, 11), (7, 9),
+        (8, 10), (0, 4), (1, 2), (3, 6), (7, 8), (9, 10), (11, 12), (4, 6), (5, 9), (8, 11),
+        (10, 12), (0, 5), (3, 8), (4, 7), (6, 11), (9, 10), (0, 1), (2, 5), (6, 9), (7, 8),
+        (10, 11), (1, 3), (2, 4), (5, 6), (9, 10), (1, 2), (3, 4), (5, 7), (6, 8), (2, 3),
+        (4, 5), (6, 7), (8, 9), (3, 4), (5, 6)
+    ]
+];
+
+/// Green's network for 16 elements.
+const GREEN: [(u8, u8); 60] = [
+    (0, 13), (1, 12), (2, 15), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10), (0, 5), (1, 7),
+    (2, 9), (3, 4), (6, 13), (8, 14), (10, 15), (11, 12), (0, 1), (2, 3), (4, 5), (6, 8),
+    (7, 9), (10, 11), (12, 13), (14, 15), (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9),
+    (12, 14), (13, 15), (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14), (1, 4),
+    (2, 6), (5, 8), (7, 10), (9, 13), (11, 14), (2, 4), (3, 6), (9, 12), (11, 13), (3, 5),
+    (6, 8), (7, 9), (10, 12), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (6, 7), (8, 9)
+];
+
+/// The network for `n` elements.
+const fn network(n: usize) -> Comparators {
+    assert!(n <= MAX, "network_sort: arrays longer than 16 aren't supported");
+    if n <= 8 {
+        return merge_exchange(n);
+    }
+
+    let mut net = ([(0, 0); 60], 0);
+    if n <= 13 {
+        let small = SMALL[n - 9];
+        while net.1 < small.len() {
+            net.0[net.1] = small[net.1];
+            net.1 += 1;
+        }
+    } else {
+        // Inputs from n on can be thought of as infinite, so the comparators
+        // touching them never swap anything.
+        let mut i = 0;
+        while i < GREEN.len() {
+            if (GREEN[i].1 as usize) < n {
+                net.0[net.1] = GREEN[i];
+                net.1 += 1;
+            }
+            i += 1;
+        }
+    }
+
+    net
+}
+
+/// Batcher's merge exchange network for `n` elements, at most 8 of them.
+const fn merge_exchange(n: usize) -> Comparators {
+    let mut net = ([(0, 0); 60], 0);
+    if n < 2 {
+        return net;
+    }
+
+    // t = ceil(log2 n)
+    let mut t = 0;
+    while 1 << t < n {
+        t += 1;
+    }
+
+    let mut p = 1 << (t - 1);
+    while p > 0 {
+        let (mut q, mut r, mut d) = (1 << (t - 1), 0, p);
+        loop {
+            let mut i = 0;
+            while i + d < n {
+                if i & p == r {
+                    net.0[net.1] = (i as u8, (i + d) as u8);
+                    net.1 += 1;
+                }
+                i += 1;
+            }
+            if q == p {
+                break;
+            }
+            d = q - p;
+            q /= 2;
+            r = p;
+        }
+        p /= 2;
+    }
+
+    net
+}
+
+struct Network<const N: usize>;
+
+impl<const N: usize> Network<N> {
+    // Evaluated once per length, at compile time, which also rejects lengths
+    // over 16.
+    const COMPARATORS: Comparators = network(N);
+}
+
+/// Sorts an array of at most 16 elements with a sorting network. Longer arrays
+/// don't compile. Not stable.
+/// Time: O(1) for a given `N`, at most 60 comparisons
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = [5, 3, 8, 1, 9, 2, 7];
+/// sort::network_sort(&mut v);
+/// assert_eq!(v, [1, 2, 3, 5, 7, 8, 9]);
+/// ```
+pub fn network_sort<T: Ord, const N: usize>(v: &mut [T; N]) {
+    let (comparators, len) = &Network::<N>::COMPARATORS;
+    for &(i, j) in &comparators[.. *len] {
+        let (i, j) = (i as usize, j as usize);
+        if v[j] < v[i] {
+            v.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // By the 0-1 principle, a network which sorts all arrays of zeros and ones
+    // sorts everything.
+    fn sorts_all_bits<const N: usize>() -> bool {
+        (0 .. 1u32 << N).all(|bits| {
+            let mut v = [0u8; N];
+            for (i, x) in v.iter_mut().enumerate() {
+                *x = (bits >> i & 1) as u8;
+            }
+            network_sort(&mut v);
+            v.windows(2).all(|w| w[0] <= w[1])
+        })
+    }
+
+    macro_rules! assert_sorts_all_bits {
+        ($($n:expr)*) => {
+            $(assert!(sorts_all_bits::<$n>(), "length {}", $n);)*
+        };
+    }
+
+    #[test]
+    fn zero_one_principle() {
+        assert_sorts_all_bits!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16);
+    }
+
+    #[test]
+    fn sizes() {
+        let sizes: Vec<usize> = (0 ..= MAX).map(|n| network(n).1).collect();
+        assert_eq!(sizes, vec![0, 0, 1, 3, 5, 9, 12, 16, 19, 25, 29, 35, 39, 45, 51, 56, 60]);
+    }
+
+    quickcheck! {
+        fn network_sort_std(v: Vec<i32>) -> bool {
+            let mut a = [0; 16];
+            for (x, y) in a.iter_mut().zip(v) {
+                *x = y;
+            }
+            let mut b = a;
+            network_sort(&mut a);
+            b.sort();
+
+            a == b
+        }
+    }
+}
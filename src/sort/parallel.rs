@@ -0,0 +1,213 @@
+//! Parallel mergesort on rayon's thread pool.
+//!
+//! The halves of the slice are sorted in parallel, recursively, and then merged
+//! in parallel too: the middle element of the longer run splits it, binary
+//! search splits the other run at the same place, and the two pairs of halves
+//! are merged into their parts of the output at the same time. Short slices are
+//! sorted and merged sequentially.
+
+use super::mergesort::mergesort_by;
+
+use rayon;
+
+use std::cmp::Ordering;
+use std::{mem, ptr, slice};
+
+/// Slices at most this long are sorted sequentially.
+const SEQUENTIAL_SORT: usize = 1 << 12;
+
+/// Runs at most this long in total are merged sequentially.
+const SEQUENTIAL_MERGE: usize = 1 << 13;
+
+/// A pointer which may be sent to another thread. The parts of the slice and
+/// the buffer which different threads use never overlap.
+struct Ptr<T>(*mut T);
+
+impl<T> Clone for Ptr<T> {
+    fn clone(&self) -> Ptr<T> {
+        *self
+    }
+}
+
+impl<T> Copy for Ptr<T> {}
+
+unsafe impl<T: Send> Send for Ptr<T> {}
+unsafe impl<T: Send> Sync for Ptr<T> {}
+
+impl<T> Ptr<T> {
+    unsafe fn add(self, n: usize) -> Ptr<T> {
+        Ptr(self.0.add(n))
+    }
+}
+
+/// Copies `len` elements from `src` back to `dst` when dropped by a panic in
+/// the comparison during a merge. The buffer `src` holds every element exactly
+/// once, while `dst` holds a mix of copies.
+struct CopyBack<T> {
+    src: *const T,
+    dst: *mut T,
+    len: usize
+}
+
+impl<T> Drop for CopyBack<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::copy_nonoverlapping(self.src, self.dst, self.len);
+        }
+    }
+}
+
+/// Merges the sorted runs `a[.. na]` and `b[.. nb]` into `dst`, which has room
+/// for both. Of equal elements, those from `a` go first.
+unsafe fn merge<T, F>(a: Ptr<T>, na: usize, b: Ptr<T>, nb: usize, dst: Ptr<T>, compare: &F)
+    where T: Send, F: Fn(&T, &T) -> Ordering + Sync
+{
+    if na + nb <= SEQUENTIAL_MERGE {
+        let (mut i, mut j, mut k) = (0, 0, 0);
+        while i < na && j < nb {
+            if compare(&*b.0.add(j), &*a.0.add(i)) == Ordering::Less {
+                ptr::copy_nonoverlapping(b.0.add(j), dst.0.add(k), 1);
+                j += 1;
+            } else {
+                ptr::copy_nonoverlapping(a.0.add(i), dst.0.add(k), 1);
+                i += 1;
+            }
+            k += 1;
+        }
+        ptr::copy_nonoverlapping(a.0.add(i), dst.0.add(k), na - i);
+        ptr::copy_nonoverlapping(b.0.add(j), dst.0.add(k + na - i), nb - j);
+        return;
+    }
+
+    // a[.. i] and b[.. j] go before a[i ..] and b[j ..].
+    let (i, j) = if na >= nb {
+        let i = na / 2;
+        let b_run = slice::from_raw_parts(b.0, nb);
+        (i, b_run.partition_point(|x| compare(x, &*a.0.add(i)) == Ordering::Less))
+    } else {
+        let j = nb / 2;
+        let a_run = slice::from_raw_parts(a.0, na);
+        (a_run.partition_point(|x| compare(x, &*b.0.add(j)) != Ordering::Greater), j)
+    };
+
+    rayon::join(
+        || merge(a, i, b, j, dst, compare),
+        || merge(a.add(i), na - i, b.add(j), nb - j, dst.add(i + j), compare)
+    );
+}
+
+/// Sorts `v` with the buffer `buf`, which has room for `v.len()` elements.
+fn sort<T, F>(v: &mut [T], buf: Ptr<T>, compare: &F)
+    where T: Send, F: Fn(&T, &T) -> Ordering + Sync
+{
+    let len = v.len();
+    if len <= SEQUENTIAL_SORT {
+        mergesort_by(v, compare);
+        return;
+    }
+
+    let mid = len / 2;
+    {
+        let (left, right) = v.split_at_mut(mid);
+        let buf_right = unsafe { buf.add(mid) };
+        rayon::join(|| sort(left, buf, compare), || sort(right, buf_right, compare));
+    }
+
+    // The halves are moved to the buffer and merged back. Nothing is dropped
+    // in between, so all it takes to undo a panic is to move them back.
+    unsafe {
+        let v = Ptr(v.as_mut_ptr());
+        ptr::copy_nonoverlapping(v.0, buf.0, len);
+        let guard = CopyBack {src: buf.0, dst: v.0, len};
+        merge(buf, mid, buf.add(mid), len - mid, v, compare);
+        mem::forget(guard);
+    }
+}
+
+/// Parallel mergesort. Stable.
+/// Time: O(n log n) work, O(log^3 n) span
+/// Memory: n elements for the buffer
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v: Vec<u64> = (0 .. 100_000).map(|i| i * 7919 % 100_000).collect();
+/// sort::par_sort(&mut v);
+/// assert!(v.iter().cloned().eq(0 .. 100_000));
+/// ```
+pub fn par_sort<T: Ord + Send>(v: &mut [T]) {
+    par_sort_by(v, T::cmp);
+}
+
+/// Like `par_sort`, but sorts by the order given by `compare`.
+pub fn par_sort_by<T, F>(v: &mut [T], compare: F)
+    where T: Send, F: Fn(&T, &T) -> Ordering + Sync
+{
+    // The buffer's length stays 0, so it never drops the elements copied to it.
+    let mut buf: Vec<T> = Vec::with_capacity(v.len());
+    sort(v, Ptr(buf.as_mut_ptr()), &compare);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng};
+    use rand::prng::XorShiftRng;
+
+    use std::panic;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+    quickcheck! {
+        fn par_sort_std(v: Vec<i32>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            par_sort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+    }
+
+    // Long enough to be sorted and merged in parallel, with few distinct keys,
+    // so that stability matters.
+    #[test]
+    fn par_sort_stable() {
+        let mut rng = XorShiftRng::from_seed([7; 16]);
+        let mut v1: Vec<(u8, usize)> = (0 .. 100_000).map(|i| (rng.gen_range(0, 16), i)).collect();
+        let mut v2 = v1.clone();
+        par_sort_by(&mut v1, |a, b| a.0.cmp(&b.0));
+        v2.sort_by_key(|p| p.0);
+
+        assert_eq!(v1, v2);
+    }
+
+    // A panicking comparison leaves every element in the slice exactly once.
+    #[test]
+    fn panic_safe() {
+        let n = 50_000;
+        let original: Vec<String> = (0 .. n).map(|i| (i * 7919 % n).to_string()).collect();
+        let mut sorted = original.clone();
+        sorted.sort();
+
+        for &after in &[100, 200_000, 500_000] {
+            let mut v = original.clone();
+            let count = AtomicUsize::new(0);
+            let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                par_sort_by(&mut v, |a, b| {
+                    if count.fetch_add(1, AtomicOrdering::Relaxed) >= after {
+                        panic!("comparison {}", after);
+                    }
+                    a.cmp(b)
+                })
+            }));
+
+            assert!(result.is_err());
+            v.sort();
+            assert_eq!(v, sorted);
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Quicksort, made safe from quadratic time as introsort (Musser), in two
+//! flavours: the classic one with a single pivot, and Yaroslavskiy's dual-pivot
+//! one, which splits the elements into three parts.
+//!
+//! The classic pivot is the median of the first, middle and last elements,
+//! which makes sorted and reversed inputs fast. In both, partitions shorter
+//! than `CUTOFF` are left to insertion sort. Some inputs still make every pivot
+//! bad, so the depth of recursion is limited to about 2 log n; a partition
+//! reaching the limit is sorted with heapsort instead, which keeps the worst
+//! case O(n log n).
+
+use super::elementary::insertion_sort_by;
+use super::heapsort::heapsort;
+
+/// Partitions at most this long are sorted with insertion sort.
+const CUTOFF: usize = 16;
+
+/// Moves the median of `v[0]`, `v[len / 2]` and `v[len - 1]` to `v[0]`, the
+/// least of them to the middle and the greatest to the end.
+fn median_of_three<T: Ord>(v: &mut [T]) {
+    let (mid, last) = (v.len() / 2, v.len() - 1);
+    if v[mid] < v[0] {
+        v.swap(0, mid);
+    }
+    if v[last] < v[0] {
+        v.swap(0, last);
+    }
+    if v[last] < v[mid] {
+        v.swap(mid, last);
+    }
+    v.swap(0, mid);
+}
+
+/// Partitions `v` around the pivot `v[0]` and returns its final position: the
+/// elements before it are at most the pivot, and those after it at least.
+/// Elements equal to the pivot stop both scans and are swapped, so that many
+/// equal elements end up on both sides and don't make the partition lopsided.
+fn partition<T: Ord>(v: &mut [T]) -> usize {
+    let (mut i, mut j) = (1, v.len() - 1);
+    loop {
+        while i <= j && v[i] < v[0] {
+            i += 1;
+        }
+        // Stops at the pivot at the latest.
+        while v[0] < v[j] {
+            j -= 1;
+        }
+        if i >= j {
+            break;
+        }
+        v.swap(i, j);
+        i += 1;
+        j -= 1;
+    }
+    v.swap(0, j);
+
+    j
+}
+
+fn introsort<T: Ord>(mut v: &mut [T], mut limit: u32) {
+    loop {
+        if v.len() <= CUTOFF {
+            insertion_sort_by(v, &mut T::lt);
+            return;
+        }
+        if limit == 0 {
+            heapsort(v);
+            return;
+        }
+        limit -= 1;
+
+        median_of_three(v);
+        let p = partition(v);
+        let (left, right) = v.split_at_mut(p);
+        let right = &mut right[1 ..];
+
+        // Recursion on the shorter part and a loop on the longer one keep the
+        // stack O(log n) deep.
+        if left.len() < right.len() {
+            introsort(left, limit);
+            v = right;
+        } else {
+            introsort(right, limit);
+            v = left;
+        }
+    }
+}
+
+/// Partitions `v` around the pivots `p = v[0]` and `q = v[len - 1]`, where
+/// `p <= q`, into the elements less than `p`, those between the pivots and
+/// those greater than `q` (Yaroslavskiy), and returns the final positions of
+/// the pivots.
+fn partition_dual<T: Ord>(v: &mut [T]) -> (usize, usize) {
+    let last = v.len() - 1;
+    // v[1 .. lt] < p, p <= v[lt .. i] <= q, v[gt + 1 .. last] > q.
+    let (mut lt, mut i, mut gt) = (1, 1, last - 1);
+    while i <= gt {
+        if v[i] < v[0] {
+            v.swap(i, lt);
+            lt += 1;
+        } else if v[last] < v[i] {
+            while v[last] < v[gt] && i < gt {
+                gt -= 1;
+            }
+            v.swap(i, gt);
+            gt -= 1;
+            if v[i] < v[0] {
+                v.swap(i, lt);
+                lt += 1;
+            }
+        }
+        i += 1;
+    }
+    v.swap(0, lt - 1);
+    v.swap(last, gt + 1);
+
+    (lt - 1, gt + 1)
+}
+
+fn dual_pivot<T: Ord>(v: &mut [T], mut limit: u32) {
+    let len = v.len();
+    if len <= CUTOFF {
+        insertion_sort_by(v, &mut T::lt);
+        return;
+    }
+    if limit == 0 {
+        heapsort(v);
+        return;
+    }
+    limit -= 1;
+
+    // The pivots are the elements at a third and two thirds of the way.
+    v.swap(0, len / 3);
+    v.swap(len - 1, 2 * len / 3);
+    if v[len - 1] < v[0] {
+        v.swap(0, len - 1);
+    }
+
+    let (p, q) = partition_dual(v);
+    dual_pivot(&mut v[.. p], limit);
+    dual_pivot(&mut v[q + 1 ..], limit);
+    // When the pivots are equal, so is everything between them.
+    if v[p] < v[q] {
+        dual_pivot(&mut v[p + 1 .. q], limit);
+    }
+}
+
+/// Introsort: quicksort with median-of-three pivots, insertion sort for short
+/// partitions and heapsort for partitions which went too deep. Not stable.
+/// Time: O(n log n) in the worst case
+/// Memory: O(log n)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec![5, 3, 8, 1, 9, 2, 7];
+/// sort::quicksort(&mut v);
+/// assert_eq!(v, vec![1, 2, 3, 5, 7, 8, 9]);
+/// ```
+pub fn quicksort<T: Ord>(v: &mut [T]) {
+    let limit = 2 * (v.len() | 1).ilog2();
+    introsort(v, limit);
+}
+
+/// Dual-pivot introsort: like `quicksort`, but with two pivots, which split
+/// the elements into those less than the first, those between them and those
+/// greater than the second (Yaroslavskiy, as in Java's sort of primitives).
+/// It makes more comparisons than single pivot quicksort, but fewer passes
+/// over the memory. Not stable.
+/// Time: O(n log n) in the worst case
+/// Memory: O(log n)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec![5, 3, 8, 1, 9, 2, 7];
+/// sort::dual_pivot_quicksort(&mut v);
+/// assert_eq!(v, vec![1, 2, 3, 5, 7, 8, 9]);
+/// ```
+pub fn dual_pivot_quicksort<T: Ord>(v: &mut [T]) {
+    let limit = 2 * (v.len() | 1).ilog2();
+    dual_pivot(v, limit);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stats::{self, Counted};
+
+    quickcheck! {
+        fn quicksort_std(v: Vec<i32>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            quicksort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+
+        fn quicksort_ties(v: Vec<u8>) -> bool {
+            let mut v1: Vec<u8> = v.iter().map(|x| x % 3).collect();
+            let mut v2 = v1.clone();
+            quicksort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+
+        // Only heapsort, and only quicksort, still sort.
+        fn introsort_limits(v: Vec<i32>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v.clone();
+            let mut v3 = v;
+            introsort(&mut v1, 0);
+            introsort(&mut v2, u32::MAX);
+            v3.sort();
+
+            v1 == v3 && v2 == v3
+        }
+
+        fn dual_pivot_quicksort_std(v: Vec<i32>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v.clone();
+            let mut v3 = v;
+            dual_pivot_quicksort(&mut v1);
+            dual_pivot(&mut v2, u32::MAX);
+            v3.sort();
+
+            v1 == v3 && v2 == v3
+        }
+
+        fn dual_pivot_quicksort_ties(v: Vec<u8>) -> bool {
+            let mut v1: Vec<u8> = v.iter().map(|x| x % 3).collect();
+            let mut v2 = v1.clone();
+            dual_pivot_quicksort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+    }
+
+    // Inputs which are bad for simpler pivot choices.
+    #[test]
+    fn patterns_comparisons() {
+        let n = 1 << 14;
+        let patterns: Vec<(&str, Vec<u32>)> = vec![
+            ("sorted", (0 .. n).collect()),
+            ("reversed", (0 .. n).rev().collect()),
+            ("equal", vec![7; n as usize]),
+            ("organ pipe", (0 .. n / 2).chain((0 .. n / 2).rev()).collect()),
+            ("sawtooth", (0 .. n).map(|i| i % 64).collect()),
+            ("two values", (0 .. n).map(|i| i % 2).collect())
+        ];
+
+        // n log n is about 229 000.
+        for (name, v) in patterns {
+            for &(sort, f) in &[("quicksort", quicksort as fn(&mut [Counted<u32>])),
+                                ("dual pivot", dual_pivot_quicksort)] {
+                let mut v: Vec<Counted<u32>> = v.iter().cloned().map(Counted).collect();
+                let (_, c) = stats::count(|| f(&mut v));
+
+                assert!(v.windows(2).all(|w| w[0] <= w[1]), "{} {}", sort, name);
+                assert!(c < 700_000, "{} {}: {}", sort, name, c);
+            }
+        }
+    }
+}
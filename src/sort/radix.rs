@@ -0,0 +1,177 @@
+//! Least significant digit radix sort of integers, a byte at a time.
+//!
+//! Every pass is a stable counting sort by one byte of the keys, from the least
+//! significant to the most, so after the last pass the keys are sorted. A pass
+//! takes O(n + 256) and there's one per byte of the key type, without any
+//! comparisons. On big arrays it's a few times faster than the comparison
+//! sorts of this module (see `cargo bench --bench sort`). Passes in which all
+//! keys have the same byte are skipped.
+
+use super::cached::permute;
+
+use std::mem;
+
+/// Integer keys which radix sort can split into bytes.
+pub trait RadixKey: Copy {
+    /// The number of bytes.
+    const BYTES: usize;
+
+    /// The `i`-th byte (from the least significant one) of an unsigned number
+    /// whose order is that of the keys.
+    fn byte(self, i: usize) -> u8;
+}
+
+macro_rules! impl_radix_key {
+    ($($t:ty)*) => {
+        $(impl RadixKey for $t {
+            const BYTES: usize = mem::size_of::<$t>();
+
+            fn byte(self, i: usize) -> u8 {
+                (self >> (8 * i)) as u8
+            }
+        })*
+    };
+}
+
+// Flipping the sign bit orders negative numbers before the others.
+macro_rules! impl_radix_key_signed {
+    ($($t:ty, $u:ty);*) => {
+        $(impl RadixKey for $t {
+            const BYTES: usize = mem::size_of::<$t>();
+
+            fn byte(self, i: usize) -> u8 {
+                ((self as $u ^ (1 << (8 * Self::BYTES - 1))) >> (8 * i)) as u8
+            }
+        })*
+    };
+}
+
+impl_radix_key!(u8 u16 u32 u64 u128 usize);
+impl_radix_key_signed!(i8, u8; i16, u16; i32, u32; i64, u64; i128, u128; isize, usize);
+
+/// Sorts `v` stably by `key`, with a buffer for a copy of `v`.
+fn lsd<T: Copy, K: RadixKey, F: Fn(&T) -> K>(v: &mut [T], key: F) {
+    let n = v.len();
+    let mut src = v.to_vec();
+    let mut dst = v.to_vec();
+
+    for i in 0 .. K::BYTES {
+        let mut counts = [0; 256];
+        for x in &src {
+            counts[key(x).byte(i) as usize] += 1;
+        }
+        if counts.contains(&n) {
+            continue;
+        }
+
+        // Where the elements with every byte go.
+        let mut next = [0; 256];
+        for b in 1 .. 256 {
+            next[b] = next[b - 1] + counts[b - 1];
+        }
+        for x in &src {
+            let b = key(x).byte(i) as usize;
+            dst[next[b]] = *x;
+            next[b] += 1;
+        }
+        mem::swap(&mut src, &mut dst);
+    }
+
+    v.copy_from_slice(&src);
+}
+
+/// Radix sort of integers.
+/// Time: O(n) per byte of `T`
+/// Memory: O(n)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v: Vec<i32> = vec![170, -45, 75, -90, 802, 24, 2, 66];
+/// sort::radix_sort(&mut v);
+/// assert_eq!(v, vec![-90, -45, 2, 24, 66, 75, 170, 802]);
+/// ```
+pub fn radix_sort<T: RadixKey>(v: &mut [T]) {
+    lsd(v, |&x| x);
+}
+
+/// Sorts `v` stably by the integer keys computed by `key`, which is called once
+/// per element. The keys are sorted together with the positions of their
+/// elements, and then the elements are moved to their places, so they don't
+/// have to be `Copy` or `Clone`.
+/// Time: O(n) per byte of `K`
+/// Memory: O(n)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec!["ccc", "a", "bb", "d", "ee"];
+/// sort::radix_sort_by_key(&mut v, |s| s.len() as u32);
+/// assert_eq!(v, vec!["a", "d", "bb", "ee", "ccc"]);
+/// ```
+pub fn radix_sort_by_key<T, K: RadixKey, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    let mut keys: Vec<(K, usize)> = v.iter().map(&mut key).zip(0 ..).collect();
+    lsd(&mut keys, |p| p.0);
+
+    permute(v, |k| keys[k].1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    quickcheck! {
+        fn radix_sort_u32(v: Vec<u32>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            radix_sort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+
+        fn radix_sort_u64(v: Vec<u64>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            radix_sort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+
+        fn radix_sort_i64(v: Vec<i64>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            radix_sort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+
+        fn radix_sort_by_key_stable(v: Vec<(i8, String)>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            radix_sort_by_key(&mut v1, |p| p.0);
+            v2.sort_by_key(|p| p.0);
+
+            v1 == v2
+        }
+    }
+
+    #[test]
+    fn extremes() {
+        let mut v = vec![i32::MAX, 0, i32::MIN, -1, 1, i32::MIN + 1];
+        radix_sort(&mut v);
+        assert_eq!(v, vec![i32::MIN, i32::MIN + 1, -1, 0, 1, i32::MAX]);
+
+        let mut v = vec![u128::MAX, 1 << 100, 0, 1 << 64];
+        radix_sort(&mut v);
+        assert_eq!(v, vec![0, 1 << 64, 1 << 100, u128::MAX]);
+    }
+}
@@ -0,0 +1,193 @@
+//! Selection of the k-th smallest element without sorting: quickselect and
+//! median of medians (Blum, Floyd, Pratt, Rivest and Tarjan).
+//!
+//! Both partition the slice around a pivot, like quicksort, but then continue
+//! only with the part which holds the k-th position. Quickselect picks the
+//! pivot at random, which takes linear time on average. Median of medians
+//! picks the median of the medians of groups of five, found recursively, which
+//! is greater than about 3/10 of the elements and less than another 3/10, and
+//! so takes linear time in the worst case, if with a bigger constant.
+
+use super::elementary::insertion_sort_by;
+
+use rand::Rng;
+
+use std::cmp::Ordering;
+
+/// Partitions `v` around the pivot `v[0]` into the elements less than it,
+/// those equal to it, and those greater, and returns where the equal ones
+/// start and end.
+fn partition<T: Ord>(v: &mut [T]) -> (usize, usize) {
+    // v[.. lt] < pivot, v[lt .. i] == pivot, v[gt ..] > pivot.
+    let (mut lt, mut i, mut gt) = (0, 1, v.len());
+    while i < gt {
+        match v[i].cmp(&v[lt]) {
+            Ordering::Less => {
+                v.swap(lt, i);
+                lt += 1;
+                i += 1;
+            },
+            Ordering::Greater => {
+                gt -= 1;
+                v.swap(i, gt);
+            },
+            Ordering::Equal => i += 1
+        }
+    }
+
+    (lt, gt)
+}
+
+/// Moves the k-th smallest element of `v` to `v[k]`, the smaller ones before
+/// it and the greater ones after it, with the pivots chosen by `pivot`.
+fn select<T: Ord, P: FnMut(&mut [T]) -> usize>(v: &mut [T], k: usize, pivot: &mut P) {
+    let (mut lo, mut hi) = (0, v.len());
+    loop {
+        let w = &mut v[lo .. hi];
+        let p = pivot(w);
+        w.swap(0, p);
+
+        // The equal elements aren't empty, so the window always shrinks.
+        let (lt, gt) = partition(w);
+        if k < lo + lt {
+            hi = lo + lt;
+        } else if k >= lo + gt {
+            lo += gt;
+        } else {
+            return;
+        }
+    }
+}
+
+/// Returns the position of the median of the medians of groups of five, after
+/// moving the medians to the front of `v`.
+fn median_of_medians<T: Ord>(v: &mut [T]) -> usize {
+    if v.len() <= 5 {
+        insertion_sort_by(v, &mut T::lt);
+        return v.len() / 2;
+    }
+
+    // The elements after the last full group are left out.
+    let groups = v.len() / 5;
+    for g in 0 .. groups {
+        insertion_sort_by(&mut v[5 * g .. 5 * g + 5], &mut T::lt);
+        v.swap(g, 5 * g + 2);
+    }
+    select(&mut v[.. groups], groups / 2, &mut median_of_medians);
+
+    groups / 2
+}
+
+/// Quickselect: moves the `k`-th smallest element of `v`, counting from 0, to
+/// `v[k]` and returns it. The elements before it are at most it, and those
+/// after it are at least it. Pivots are chosen with `rng`.
+/// Time: O(n) expected, O(n^2) in the worst case
+///
+/// # Panics
+///
+/// Panics if `k >= v.len()`.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// extern crate rand;
+/// use aisd::sort;
+///
+/// let mut v = vec![9, 1, 8, 2, 7, 3, 6, 4, 5];
+/// assert_eq!(*sort::select_nth(&mut v, 4, &mut rand::thread_rng()), 5);
+/// assert!(v[.. 4].iter().all(|&x| x < 5) && v[5 ..].iter().all(|&x| x > 5));
+/// ```
+pub fn select_nth<'a, T: Ord, R: Rng>(v: &'a mut [T], k: usize, rng: &mut R) -> &'a T {
+    assert!(k < v.len(), "select_nth: index {} out of bounds (len {})", k, v.len());
+    select(v, k, &mut |w: &mut [T]| rng.gen_range(0, w.len()));
+
+    &v[k]
+}
+
+/// Like `select_nth`, but with median of medians pivots, so it's deterministic
+/// and linear in the worst case.
+/// Time: O(n)
+///
+/// # Panics
+///
+/// Panics if `k >= v.len()`.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v: Vec<u32> = (0 .. 1000).rev().collect();
+/// assert_eq!(*sort::select_nth_deterministic(&mut v, 250), 250);
+/// assert!(v[.. 250].iter().all(|&x| x < 250));
+/// ```
+pub fn select_nth_deterministic<T: Ord>(v: &mut [T], k: usize) -> &T {
+    assert!(k < v.len(), "select_nth_deterministic: index {} out of bounds (len {})", k, v.len());
+    select(v, k, &mut median_of_medians);
+
+    &v[k]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stats::{self, Counted};
+
+    use rand::{self, Rng};
+
+    fn partitioned(v: &[u8], k: usize) -> bool {
+        v[.. k].iter().all(|x| *x <= v[k]) && v[k ..].iter().all(|x| *x >= v[k])
+    }
+
+    quickcheck! {
+        fn select_nth_sorted(v: Vec<u8>, k: usize) -> bool {
+            if v.is_empty() {
+                return true;
+            }
+            let k = k % v.len();
+            let mut sorted = v.clone();
+            sorted.sort();
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            let x1 = *select_nth(&mut v1, k, &mut rand::thread_rng());
+            let x2 = *select_nth_deterministic(&mut v2, k);
+
+            x1 == sorted[k] && x2 == sorted[k] && partitioned(&v1, k) && partitioned(&v2, k)
+        }
+    }
+
+    // Median of medians is linear even for inputs which are bad for quicksort
+    // pivots.
+    #[test]
+    fn deterministic_comparisons() {
+        let n = 1 << 16;
+        let mut rng = rand::thread_rng();
+        let patterns: Vec<(&str, Vec<u32>)> = vec![
+            ("sorted", (0 .. n).collect()),
+            ("reversed", (0 .. n).rev().collect()),
+            ("equal", vec![7; n as usize]),
+            ("organ pipe", (0 .. n / 2).chain((0 .. n / 2).rev()).collect()),
+            ("random", (0 .. n).map(|_| rng.gen()).collect())
+        ];
+
+        for (name, v) in patterns {
+            for &k in &[0, n as usize / 2, n as usize - 1] {
+                let mut v: Vec<Counted<u32>> = v.iter().cloned().map(Counted).collect();
+                let mut sorted = v.clone();
+                sorted.sort();
+                let (x, c) = stats::count(|| *select_nth_deterministic(&mut v, k));
+
+                assert_eq!(x, sorted[k], "{}", name);
+                assert!(c < 20 * n as u64, "{}: {}", name, c);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_bounds() {
+        select_nth_deterministic(&mut [1, 2, 3], 3);
+    }
+}
@@ -0,0 +1,144 @@
+//! Shellsort: insertion sort of elements a gap apart, for shrinking gaps.
+//!
+//! After sorting with gap h, every h-th element is in order, and later passes
+//! with smaller gaps keep it that way, while the last pass, with gap 1, is a
+//! plain insertion sort of nearly sorted elements. How fast it is depends
+//! entirely on the gaps; `shellsort_stats` counts comparisons and swaps to
+//! compare sequences on the same input.
+//!
+//! # Example
+//!
+//! ```
+//! extern crate aisd;
+//! use aisd::sort::shell::{self, Gaps};
+//!
+//! let v: Vec<u32> = (0 .. 1000).map(|i| i * 7919 % 1000).collect();
+//! let ciura = shell::shellsort_stats(&mut v.clone(), Gaps::Ciura);
+//! let knuth = shell::shellsort_stats(&mut v.clone(), Gaps::Knuth);
+//! assert!(ciura.comparisons < 1000 * 1000 / 20 && knuth.comparisons < 1000 * 1000 / 20);
+//! ```
+
+use super::elementary::SortStats;
+
+/// Sequences of gaps for shellsort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gaps {
+    /// 1, 4, 10, 23, 57, 132, 301, 701, found experimentally by Ciura, and then
+    /// 2.25 times the previous gap. The fastest known in practice.
+    Ciura,
+
+    /// (3^k - 1) / 2: 1, 4, 13, 40, 121, ..., up to n / 3. O(n^(3/2)).
+    Knuth,
+
+    /// 4^k + 3 * 2^(k - 1) + 1: 1, 8, 23, 77, 281, ... (Sedgewick, 1982).
+    /// O(n^(4/3)).
+    Sedgewick
+}
+
+impl Gaps {
+    /// The gaps less than `n`, in increasing order.
+    pub fn gaps(self, n: usize) -> Vec<usize> {
+        let mut gaps = vec![1];
+        loop {
+            let last = gaps[gaps.len() - 1];
+            let k = gaps.len();
+            let next = match self {
+                Gaps::Ciura => [1, 4, 10, 23, 57, 132, 301, 701].get(k).cloned()
+                    .unwrap_or(last * 9 / 4),
+                Gaps::Knuth => 3 * last + 1,
+                Gaps::Sedgewick => (1 << (2 * k)) + 3 * (1 << (k - 1)) + 1
+            };
+            let bound = if self == Gaps::Knuth {n / 3} else {n};
+            if next >= bound.max(2) {
+                return gaps;
+            }
+            gaps.push(next);
+        }
+    }
+}
+
+fn shell<T: Ord>(v: &mut [T], gaps: Gaps, s: &mut SortStats) {
+    for &h in gaps.gaps(v.len()).iter().rev() {
+        for i in h .. v.len() {
+            let mut j = i;
+            while j >= h && s.less(&mut T::lt, &v[j], &v[j - h]) {
+                s.swap(v, j, j - h);
+                j -= h;
+            }
+        }
+    }
+}
+
+/// Shellsort with the gaps `gaps`. Not stable.
+/// Time: depends on the gaps, see `Gaps`
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort::{self, Gaps};
+///
+/// let mut v = vec![5, 3, 8, 1, 9, 2, 7];
+/// sort::shellsort(&mut v, Gaps::Ciura);
+/// assert_eq!(v, vec![1, 2, 3, 5, 7, 8, 9]);
+/// ```
+pub fn shellsort<T: Ord>(v: &mut [T], gaps: Gaps) {
+    shellsort_stats(v, gaps);
+}
+
+/// Like `shellsort`, but returns the numbers of comparisons and swaps.
+pub fn shellsort_stats<T: Ord>(v: &mut [T], gaps: Gaps) -> SortStats {
+    let mut s = SortStats::default();
+    shell(v, gaps, &mut s);
+
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng};
+    use rand::prng::XorShiftRng;
+
+    const ALL: [Gaps; 3] = [Gaps::Ciura, Gaps::Knuth, Gaps::Sedgewick];
+
+    quickcheck! {
+        fn shellsort_std(v: Vec<i32>) -> bool {
+            let mut sorted = v.clone();
+            sorted.sort();
+
+            ALL.iter().all(|&gaps| {
+                let mut v = v.clone();
+                shellsort(&mut v, gaps);
+                v == sorted
+            })
+        }
+    }
+
+    #[test]
+    fn sequences() {
+        assert_eq!(Gaps::Ciura.gaps(2000), vec![1, 4, 10, 23, 57, 132, 301, 701, 1577]);
+        assert_eq!(Gaps::Knuth.gaps(2000), vec![1, 4, 13, 40, 121, 364]);
+        assert_eq!(Gaps::Sedgewick.gaps(2000), vec![1, 8, 23, 77, 281, 1073]);
+        for &gaps in ALL.iter() {
+            assert_eq!(gaps.gaps(0), vec![1]);
+            assert_eq!(gaps.gaps(1), vec![1]);
+        }
+    }
+
+    // All the sequences are far from quadratic on random input, and Ciura's
+    // is the best of them.
+    #[test]
+    fn comparisons() {
+        let mut rng = XorShiftRng::from_seed([7; 16]);
+        let n = 1 << 14;
+        let v: Vec<u32> = (0 .. n).map(|_| rng.gen()).collect();
+
+        let counts: Vec<u64> = ALL.iter()
+            .map(|&gaps| shellsort_stats(&mut v.clone(), gaps).comparisons)
+            .collect();
+        assert!(counts.iter().all(|&c| c < 40 * n as u64), "{:?}", counts);
+        assert!(counts[0] < counts[1] && counts[0] < counts[2], "{:?}", counts);
+    }
+}
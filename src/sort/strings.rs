@@ -0,0 +1,221 @@
+//! Sorts of strings which look at every byte a bounded number of times, instead
+//! of comparing whole strings: MSD radix sort and three-way string quicksort.
+//!
+//! A comparison sort compares the common prefixes of neighbouring strings over
+//! and over, about log n times per string. Both sorts here split the strings
+//! by their byte at some depth, and then go on only with the strings sharing
+//! that byte, at the next depth, so a common prefix is read once per group.
+//! Before splitting a group, both skip the prefix which all its strings share.
+//! The order is that of the bytes, which for UTF-8 strings is the order of
+//! `str`. Both keep their own stack of groups, so long common prefixes don't
+//! overflow the call stack.
+
+use super::elementary::insertion_sort_by;
+
+/// Groups at most this long are sorted with insertion sort.
+const CUTOFF: usize = 16;
+
+/// The byte of `s` at depth `d`, plus one, or 0 if `s` is shorter, so that
+/// shorter strings go first.
+fn byte<T: AsRef<[u8]>>(s: &T, d: usize) -> usize {
+    s.as_ref().get(d).map_or(0, |&b| b as usize + 1)
+}
+
+/// The length of the longest common prefix of the strings, after their first
+/// `d` bytes, which they all have.
+fn common_prefix<T: AsRef<[u8]>>(v: &[T], d: usize) -> usize {
+    let first = &v[0].as_ref()[d ..];
+    v[1 ..].iter().fold(first.len(), |common, s| {
+        let s = &s.as_ref()[d ..];
+        first[.. common].iter().zip(s).take_while(|p| p.0 == p.1).count()
+    })
+}
+
+/// Sorts strings with the same first `d` bytes.
+fn insertion_from<T: AsRef<[u8]>>(v: &mut [T], d: usize) {
+    insertion_sort_by(v, &mut |a: &T, b: &T| a.as_ref()[d ..] < b.as_ref()[d ..]);
+}
+
+/// MSD (most significant digit first) radix sort of strings: distributes the
+/// strings into 257 buckets by their first byte, or its absence, and sorts
+/// every bucket by the next byte, and so on. The buckets are formed in place,
+/// with swaps (American flag sort), so no buffer is needed. Not stable.
+/// Time: O(total length of the distinguishing prefixes + 257 per group)
+/// Memory: O(n) for the stack of groups
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v = vec!["she", "sells", "seashells", "by", "the", "sea", "shore"];
+/// sort::msd_radix_sort(&mut v);
+/// assert_eq!(v, vec!["by", "sea", "seashells", "sells", "she", "shore", "the"]);
+/// ```
+pub fn msd_radix_sort<T: AsRef<[u8]>>(v: &mut [T]) {
+    let mut groups = vec![(0, v.len(), 0)];
+    while let Some((lo, hi, d)) = groups.pop() {
+        let group = &mut v[lo .. hi];
+        if group.len() <= CUTOFF {
+            insertion_from(group, d);
+            continue;
+        }
+        // A byte shared by all the strings would make a pass with one bucket.
+        let d = d + common_prefix(group, d);
+
+        let mut counts = [0; 257];
+        for s in group.iter() {
+            counts[byte(s, d)] += 1;
+        }
+        let mut starts = [0; 258];
+        for b in 0 .. 257 {
+            starts[b + 1] = starts[b] + counts[b];
+        }
+
+        // Swap every string into its bucket; next[b] is the first place in
+        // bucket b which doesn't hold a string of it yet.
+        let mut next = starts;
+        for b in 0 .. 257 {
+            while next[b] < starts[b + 1] {
+                let c = byte(&group[next[b]], d);
+                if c == b {
+                    next[b] += 1;
+                } else {
+                    group.swap(next[b], next[c]);
+                    next[c] += 1;
+                }
+            }
+        }
+
+        // Strings which ended are equal.
+        for b in 1 .. 257 {
+            if counts[b] > 1 {
+                groups.push((lo + starts[b], lo + starts[b + 1], d + 1));
+            }
+        }
+    }
+}
+
+/// Three-way string quicksort (Bentley and Sedgewick): partitions the strings
+/// into those whose byte at the current depth is less than the pivot's, equal
+/// to it and greater, and sorts the equal ones by the next byte. It's
+/// quicksort on bytes, which only goes deeper into strings sharing a prefix.
+/// Not stable.
+/// Time: O(total length of the distinguishing prefixes + n log n) expected
+/// Memory: O(n) for the stack of groups
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::sort;
+///
+/// let mut v: Vec<String> = ["banana", "band", "ban", "apple", "bandana", "ban"]
+///     .iter().map(|s| s.to_string()).collect();
+/// sort::string_quicksort(&mut v);
+/// assert_eq!(v, vec!["apple", "ban", "ban", "banana", "band", "bandana"]);
+/// ```
+pub fn string_quicksort<T: AsRef<[u8]>>(v: &mut [T]) {
+    let mut groups = vec![(0, v.len(), 0)];
+    while let Some((lo, hi, d)) = groups.pop() {
+        let group = &mut v[lo .. hi];
+        let len = group.len();
+        if len <= CUTOFF {
+            insertion_from(group, d);
+            continue;
+        }
+        let d = d + common_prefix(group, d);
+
+        // The median of three bytes as the pivot.
+        let mut sample = [byte(&group[0], d), byte(&group[len / 2], d), byte(&group[len - 1], d)];
+        sample.sort();
+        let pivot = sample[1];
+
+        // group[.. lt] < pivot, group[lt .. i] == pivot, group[gt ..] > pivot.
+        let (mut lt, mut i, mut gt) = (0, 0, len);
+        while i < gt {
+            let c = byte(&group[i], d);
+            if c < pivot {
+                group.swap(lt, i);
+                lt += 1;
+                i += 1;
+            } else if c > pivot {
+                gt -= 1;
+                group.swap(i, gt);
+            } else {
+                i += 1;
+            }
+        }
+
+        groups.push((lo, lo + lt, d));
+        groups.push((lo + gt, hi, d));
+        // Strings which ended are equal.
+        if pivot > 0 {
+            groups.push((lo + lt, lo + gt, d + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng};
+    use rand::prng::XorShiftRng;
+
+    quickcheck! {
+        fn msd_radix_sort_std(v: Vec<String>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            msd_radix_sort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+
+        fn string_quicksort_std(v: Vec<String>) -> bool {
+            let mut v1 = v.clone();
+            let mut v2 = v;
+            string_quicksort(&mut v1);
+            v2.sort();
+
+            v1 == v2
+        }
+
+        // Short strings over a tiny alphabet, so that there are many shared
+        // prefixes and duplicates.
+        fn shared_prefixes(v: Vec<Vec<bool>>) -> bool {
+            let v: Vec<Vec<u8>> = v.into_iter()
+                .map(|s| s.into_iter().take(6).map(|b| b as u8).collect())
+                .collect();
+            let mut v1 = v.clone();
+            let mut v2 = v.clone();
+            let mut v3 = v;
+            msd_radix_sort(&mut v1);
+            string_quicksort(&mut v2);
+            v3.sort();
+
+            v1 == v3 && v2 == v3
+        }
+    }
+
+    // Many long strings differing only at the end, which would be a deep
+    // recursion.
+    #[test]
+    fn long_common_prefix() {
+        let mut rng = XorShiftRng::from_seed([7; 16]);
+        let prefix = "x".repeat(10_000);
+        let v: Vec<String> = (0 .. 1000).map(|_| format!("{}{}", prefix, rng.gen::<u16>())).collect();
+        let mut sorted = v.clone();
+        sorted.sort();
+
+        let mut v1 = v.clone();
+        msd_radix_sort(&mut v1);
+        assert!(v1 == sorted);
+
+        let mut v2 = v;
+        string_quicksort(&mut v2);
+        assert!(v2 == sorted);
+    }
+}
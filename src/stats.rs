@@ -0,0 +1,60 @@
+//! Counting comparisons, to see the costs of algorithms empirically. Wrap the
+//! elements in `Counted` and run any algorithm of the crate on them:
+//!
+//! ```
+//! extern crate aisd;
+//! use aisd::sort;
+//! use aisd::stats::{self, Counted};
+//!
+//! let mut v: Vec<_> = (0 .. 1000).rev().map(Counted).collect();
+//! let (_, plain) = stats::count(|| sort::heapsort(&mut v.clone()));
+//! let (_, bottom_up) = stats::count(|| sort::bottom_up_heapsort(&mut v));
+//! assert!(bottom_up < plain);
+//! ```
+//!
+//! Only available with the `stats` feature.
+
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+thread_local! {
+    static COMPARISONS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Returns the number of comparisons of `Counted` values made by this thread
+/// since the last `reset`.
+pub fn comparisons() -> u64 {
+    COMPARISONS.with(|c| c.get())
+}
+
+/// Sets the counter of comparisons of this thread to zero.
+pub fn reset() {
+    COMPARISONS.with(|c| c.set(0));
+}
+
+/// Runs `f` and returns its result together with the number of comparisons it
+/// made. Resets the counter.
+pub fn count<R, F: FnOnce() -> R>(f: F) -> (R, u64) {
+    reset();
+    let result = f();
+
+    (result, comparisons())
+}
+
+/// A value whose every ordering comparison (`cmp`, `partial_cmp`, `<` and so on)
+/// is counted. Equality isn't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Counted<T>(pub T);
+
+impl<T: Ord> Ord for Counted<T> {
+    fn cmp(&self, other: &Counted<T>) -> Ordering {
+        COMPARISONS.with(|c| c.set(c.get() + 1));
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<T: Ord> PartialOrd for Counted<T> {
+    fn partial_cmp(&self, other: &Counted<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
@@ -0,0 +1,223 @@
+//! The Aho-Corasick automaton, for finding many patterns at once.
+
+use std::collections::VecDeque;
+
+/// An occurrence of a pattern in a text, as found by `AhoCorasick::find_iter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Match {
+    /// The index of the pattern, in the order they were given.
+    pub pattern: usize,
+
+    /// The position in the text where the pattern starts.
+    pub start: usize,
+
+    /// The position in the text just after the end of the pattern.
+    pub end: usize
+}
+
+/// An automaton which finds all occurrences of a set of patterns in a text in
+/// a single pass over it (Aho and Corasick).
+///
+/// The states are the nodes of the trie of the patterns, that is the prefixes
+/// of the patterns. The failure link of a state leads to its longest proper
+/// suffix which is a state too, and following the links makes the trie a
+/// complete automaton: after reading a text, it's in the state of the longest
+/// suffix of the text which is a prefix of a pattern. The patterns ending
+/// there are that state's own ones and those of the states reachable by
+/// failure links, which dictionary links jump between directly.
+///
+/// The transitions are kept in a table of 256 per state, so a step is a
+/// single lookup.
+/// Memory: 1 KiB per state, of which there is at most one more than the total
+/// length of the patterns
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::strings::AhoCorasick;
+///
+/// let ac = AhoCorasick::new(&["he", "she", "his", "hers"]);
+/// let found: Vec<(usize, usize)> = ac.find_iter("ushers").map(|m| (m.pattern, m.start)).collect();
+/// assert_eq!(found, vec![(1, 1), (0, 2), (3, 2)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    // The transitions, 256 per state; the root is the state 0.
+    delta: Vec<u32>,
+    // The patterns which are the states, and the nearest state reachable by
+    // failure links which is a pattern.
+    output: Vec<Vec<usize>>,
+    dict: Vec<Option<usize>>,
+    lens: Vec<usize>
+}
+
+impl AhoCorasick {
+    /// Builds the automaton of the patterns. They may be empty or repeat.
+    /// Time: O(256 * total length of the patterns)
+    pub fn new<I, P>(patterns: I) -> AhoCorasick
+        where I: IntoIterator<Item = P>, P: AsRef<[u8]>
+    {
+        // The trie, with 0 for a missing child, as the root is nobody's child.
+        let mut delta = vec![0u32; 256];
+        let mut output = vec![vec![]];
+        let mut lens = vec![];
+        for (k, p) in patterns.into_iter().enumerate() {
+            let mut s = 0;
+            for &b in p.as_ref() {
+                let i = 256 * s + b as usize;
+                if delta[i] == 0 {
+                    delta[i] = output.len() as u32;
+                    delta.extend_from_slice(&[0; 256]);
+                    output.push(vec![]);
+                }
+                s = delta[i] as usize;
+            }
+            output[s].push(k);
+            lens.push(p.as_ref().len());
+        }
+
+        // Breadth-first, so that the failure link of every state is done
+        // before it. Missing children become the transitions of the failure
+        // link.
+        let states = output.len();
+        let mut fail = vec![0; states];
+        let mut dict = vec![None; states];
+        let mut queue: VecDeque<usize> = delta[.. 256].iter().filter(|&&t| t != 0).map(|&t| t as usize).collect();
+        for &t in &queue {
+            dict[t] = if output[0].is_empty() {None} else {Some(0)};
+        }
+        while let Some(s) = queue.pop_front() {
+            for b in 0 .. 256 {
+                let t = delta[256 * s + b] as usize;
+                let through_fail = delta[256 * fail[s] + b];
+                if t == 0 {
+                    delta[256 * s + b] = through_fail;
+                } else {
+                    let f = through_fail as usize;
+                    fail[t] = f;
+                    dict[t] = if output[f].is_empty() {dict[f]} else {Some(f)};
+                    queue.push_back(t);
+                }
+            }
+        }
+
+        AhoCorasick {delta, output, dict, lens}
+    }
+
+    /// Returns the number of patterns.
+    pub fn pattern_count(&self) -> usize {
+        self.lens.len()
+    }
+
+    /// Returns the number of states, one more than the number of distinct
+    /// non-empty prefixes of the patterns.
+    pub fn state_count(&self) -> usize {
+        self.output.len()
+    }
+
+    /// Returns an iterator over all occurrences of the patterns in `text`,
+    /// including overlapping ones, found as the text is read. They come in the
+    /// order of their ends, and of those ending at the same place, the longer
+    /// patterns first and equal ones in the order of their indices.
+    /// Time: O(length of the text + number of occurrences)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate aisd;
+    /// use aisd::strings::{AhoCorasick, Match};
+    ///
+    /// let ac = AhoCorasick::new(vec!["aa", "a"]);
+    /// let mut found = ac.find_iter(&b"baaa"[..]);
+    /// assert_eq!(found.next(), Some(Match {pattern: 1, start: 1, end: 2}));
+    /// assert_eq!(found.count(), 4);
+    /// ```
+    pub fn find_iter<'a, T: AsRef<[u8]> + ?Sized>(&'a self, text: &'a T) -> FindIter<'a> {
+        FindIter {ac: self, text: text.as_ref(), pos: 0, state: 0, out: Some(0), index: 0}
+    }
+}
+
+/// The iterator returned by `AhoCorasick::find_iter`.
+#[derive(Debug, Clone)]
+pub struct FindIter<'a> {
+    ac: &'a AhoCorasick,
+    text: &'a [u8],
+    // The number of bytes read and the state after them.
+    pos: usize,
+    state: usize,
+    // The state whose patterns are being reported, and the next one of them.
+    out: Option<usize>,
+    index: usize
+}
+
+impl<'a> Iterator for FindIter<'a> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        let ac = self.ac;
+        loop {
+            while let Some(s) = self.out {
+                if let Some(&pattern) = ac.output[s].get(self.index) {
+                    self.index += 1;
+                    return Some(Match {pattern, start: self.pos - ac.lens[pattern], end: self.pos});
+                }
+                self.out = ac.dict[s];
+                self.index = 0;
+            }
+
+            let &b = self.text.get(self.pos)?;
+            self.state = ac.delta[256 * self.state + b as usize] as usize;
+            self.pos += 1;
+            self.out = Some(self.state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every pattern at every end, in the promised order.
+    fn find_naive(patterns: &[Vec<u8>], text: &[u8]) -> Vec<Match> {
+        let mut found = vec![];
+        for end in 0 ..= text.len() {
+            let mut here: Vec<usize> = (0 .. patterns.len()).filter(|&k| text[.. end].ends_with(&patterns[k])).collect();
+            here.sort_by_key(|&k| (!patterns[k].len(), k));
+            found.extend(here.into_iter().map(|k| Match {pattern: k, start: end - patterns[k].len(), end}));
+        }
+
+        found
+    }
+
+    quickcheck! {
+        // Patterns and text over the alphabet {0, 1, 2}, so that there are
+        // many occurrences.
+        fn find_iter_naive(patterns: Vec<Vec<u8>>, text: Vec<u8>) -> bool {
+            let patterns: Vec<Vec<u8>> = patterns.into_iter().take(10)
+                .map(|p| p.into_iter().take(5).map(|b| b % 3).collect())
+                .collect();
+            let text: Vec<u8> = text.into_iter().map(|b| b % 3).collect();
+            let ac = AhoCorasick::new(&patterns);
+
+            ac.pattern_count() == patterns.len() &&
+            ac.find_iter(&text).collect::<Vec<_>>() == find_naive(&patterns, &text)
+        }
+
+        fn find_iter_bytes(patterns: Vec<Vec<u8>>, text: Vec<u8>) -> bool {
+            let ac = AhoCorasick::new(&patterns);
+            ac.find_iter(&text).collect::<Vec<_>>() == find_naive(&patterns, &text)
+        }
+    }
+
+    #[test]
+    fn empty_and_repeated() {
+        let ac = AhoCorasick::new(["", "ab", "ab"]);
+        assert_eq!(ac.state_count(), 3);
+        let found: Vec<(usize, usize)> = ac.find_iter("ab").map(|m| (m.pattern, m.start)).collect();
+        assert_eq!(found, vec![(0, 0), (0, 1), (1, 0), (2, 0), (0, 2)]);
+
+        let none = AhoCorasick::new(Vec::<&str>::new());
+        assert_eq!(none.find_iter("abc").count(), 0);
+    }
+}
@@ -0,0 +1,126 @@
+//! The Knuth-Morris-Pratt algorithm for finding a pattern in a text.
+
+/// The failure function of a pattern: for every prefix `pattern[..= i]`, the
+/// length of its longest proper prefix which is also its suffix (its longest
+/// border). When a match of the prefix fails at the next byte, the search can
+/// go on from the border, as the text there is known to match it.
+/// Time: O(length of the pattern)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::strings::kmp_failure;
+///
+/// assert_eq!(kmp_failure("abacabab"), vec![0, 0, 1, 0, 1, 2, 3, 2]);
+/// ```
+pub fn kmp_failure<P: AsRef<[u8]>>(pattern: P) -> Vec<usize> {
+    let p = pattern.as_ref();
+    let mut failure = vec![0; p.len()];
+    // The length of the border of the prefix before i.
+    let mut k = 0;
+    for i in 1 .. p.len() {
+        while k > 0 && p[i] != p[k] {
+            k = failure[k - 1];
+        }
+        if p[i] == p[k] {
+            k += 1;
+        }
+        failure[i] = k;
+    }
+
+    failure
+}
+
+/// Finds all the positions in `text` where `pattern` starts, in increasing
+/// order, including overlapping ones, with the Knuth-Morris-Pratt algorithm.
+/// Every byte of the text is read once, and after a mismatch the match goes
+/// on from the longest border of what was matched, so the text is never read
+/// again. An empty pattern is found at every position, up to the end.
+/// Time: O(length of the text + length of the pattern)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::strings::kmp_find_all;
+///
+/// assert_eq!(kmp_find_all("abababcab", "abab"), vec![0, 2]);
+/// assert_eq!(kmp_find_all(&[0u8, 255, 0, 255][..], [255u8, 0]), vec![1]);
+/// ```
+pub fn kmp_find_all<T: AsRef<[u8]>, P: AsRef<[u8]>>(text: T, pattern: P) -> Vec<usize> {
+    let (t, p) = (text.as_ref(), pattern.as_ref());
+    if p.is_empty() {
+        return (0 ..= t.len()).collect();
+    }
+
+    let failure = kmp_failure(p);
+    let mut found = vec![];
+    // The number of bytes of the pattern matched so far.
+    let mut k = 0;
+    for (i, &b) in t.iter().enumerate() {
+        while k > 0 && b != p[k] {
+            k = failure[k - 1];
+        }
+        if b == p[k] {
+            k += 1;
+        }
+        if k == p.len() {
+            found.push(i + 1 - k);
+            k = failure[k - 1];
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Strings over a tiny alphabet, so that there are many matches and long
+    // borders.
+    fn bits(v: Vec<bool>) -> Vec<u8> {
+        v.into_iter().map(|b| b as u8).collect()
+    }
+
+    quickcheck! {
+        fn kmp_failure_naive(p: Vec<bool>) -> bool {
+            let p = bits(p);
+            let naive: Vec<usize> = (0 .. p.len()).map(|i| {
+                let prefix = &p[..= i];
+                (0 ..= i).rev().find(|&k| prefix[.. k] == prefix[i + 1 - k ..]).unwrap()
+            }).collect();
+
+            kmp_failure(&p) == naive
+        }
+
+        fn kmp_find_all_naive(text: Vec<bool>, pattern: Vec<bool>) -> bool {
+            let (text, pattern) = (bits(text), bits(pattern));
+            let pattern = &pattern[.. pattern.len().min(4)];
+            let naive: Vec<usize> = (0 .. (text.len() + 1).saturating_sub(pattern.len()))
+                .filter(|&i| text[i ..].starts_with(pattern))
+                .collect();
+
+            kmp_find_all(&text, pattern) == naive
+        }
+
+        // A prefix of the text is found at least where it starts.
+        fn kmp_find_all_prefix(text: String, len: usize) -> bool {
+            let text = text.as_bytes();
+            let pattern = &text[.. len % (text.len() + 1)];
+            let naive: Vec<usize> = (0 ..= text.len() - pattern.len())
+                .filter(|&i| text[i ..].starts_with(pattern))
+                .collect();
+
+            kmp_find_all(text, pattern) == naive && naive[0] == 0
+        }
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(kmp_find_all("abc", ""), vec![0, 1, 2, 3]);
+        assert_eq!(kmp_find_all("", "a"), Vec::<usize>::new());
+        assert_eq!(kmp_failure(""), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,15 @@
+//! Searching in strings. Everything works on bytes, so on `str`s as well as
+//! on binary data: the functions take anything which is `AsRef<[u8]>`, and
+//! positions are counted in bytes.
+
+pub mod aho_corasick;
+pub mod kmp;
+pub mod rabin_karp;
+pub mod suffix_array;
+pub mod suffix_automaton;
+
+pub use self::aho_corasick::{AhoCorasick, FindIter, Match};
+pub use self::kmp::{kmp_failure, kmp_find_all};
+pub use self::rabin_karp::{rabin_karp, rabin_karp_multi};
+pub use self::suffix_array::{lcp_array, suffix_array, SuffixArray};
+pub use self::suffix_automaton::SuffixAutomaton;
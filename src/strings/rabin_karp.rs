@@ -0,0 +1,192 @@
+//! The Rabin-Karp algorithm: finding patterns by rolling hashes of the windows
+//! of a text.
+
+use std::collections::HashMap;
+
+/// The hashes are taken modulo the Mersenne prime 2^61 - 1.
+const MODULUS: u64 = (1 << 61) - 1;
+
+/// Two bases, which give two independent hashes.
+const BASES: [u64; 2] = [0x1c6a_7a3d_9e5b_3f01, 0x0f2b_8c41_d3a9_6e57];
+
+type Hash = [u64; 2];
+
+fn mul_mod(a: u64, b: u64) -> u64 {
+    let p = a as u128 * b as u128;
+    // 2^61 = 1, so the high bits add to the low ones.
+    let r = (p as u64 & MODULUS) + (p >> 61) as u64;
+    if r >= MODULUS {r - MODULUS} else {r}
+}
+
+fn add_mod(a: u64, b: u64) -> u64 {
+    let r = a + b;
+    if r >= MODULUS {r - MODULUS} else {r}
+}
+
+/// The hashes of `s`: the values of the polynomial with its bytes, plus one,
+/// as coefficients, at both bases.
+fn hash(s: &[u8]) -> Hash {
+    let mut h = [0; 2];
+    for &b in s {
+        for j in 0 .. 2 {
+            h[j] = add_mod(mul_mod(h[j], BASES[j]), b as u64 + 1);
+        }
+    }
+
+    h
+}
+
+/// Calls `visit` with the position and the hashes of every window of `m`
+/// bytes of `t`, from the first one. Every window's hashes come from the
+/// previous one's in constant time, by removing the byte which left and
+/// adding the byte which came.
+fn windows<F: FnMut(usize, Hash)>(t: &[u8], m: usize, mut visit: F) {
+    if m > t.len() {
+        return;
+    }
+    // The weights of the byte leaving the window, base^m.
+    let mut top = [1; 2];
+    for _ in 0 .. m {
+        for j in 0 .. 2 {
+            top[j] = mul_mod(top[j], BASES[j]);
+        }
+    }
+
+    let mut h = hash(&t[.. m]);
+    visit(0, h);
+    for i in m .. t.len() {
+        for j in 0 .. 2 {
+            let added = add_mod(mul_mod(h[j], BASES[j]), t[i] as u64 + 1);
+            let removed = mul_mod(top[j], t[i - m] as u64 + 1);
+            h[j] = add_mod(added, MODULUS - removed);
+        }
+        visit(i + 1 - m, h);
+    }
+}
+
+/// Finds all the positions in `text` where `pattern` starts, in increasing
+/// order, including overlapping ones, with the Rabin-Karp algorithm: the hash
+/// of every window of the text as long as the pattern is compared with the
+/// hash of the pattern.
+///
+/// The bytes of the windows aren't compared, so windows with the hashes of
+/// the pattern are reported whatever they are. But there are two hashes
+/// modulo 2^61 - 1 with different bases, so for inputs which weren't crafted
+/// against the fixed bases, a false match has a chance of about (m / 2^61)^2
+/// per window, for a pattern of length m. `kmp_find_all` gives the same
+/// result with certainty.
+/// Time: O(length of the text + length of the pattern)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::strings::rabin_karp;
+///
+/// assert_eq!(rabin_karp("abracadabra", "abra"), vec![0, 7]);
+/// assert_eq!(rabin_karp("aaaa", "aa"), vec![0, 1, 2]);
+/// ```
+pub fn rabin_karp<T: AsRef<[u8]>, P: AsRef<[u8]>>(text: T, pattern: P) -> Vec<usize> {
+    let (t, p) = (text.as_ref(), pattern.as_ref());
+    let target = hash(p);
+    let mut found = vec![];
+    windows(t, p.len(), |i, h| if h == target {
+        found.push(i);
+    });
+
+    found
+}
+
+/// Like `rabin_karp`, but finds many patterns of the same length at once, in
+/// a single pass over the text: the hashes of the windows are looked up in a
+/// table of the hashes of the patterns. Returns the position of every match
+/// and the index of the pattern found there, in the order of positions and
+/// then of the patterns. Equal patterns are all reported.
+/// Time: O(length of the text + total length of the patterns + number of
+/// matches) expected
+///
+/// # Panics
+///
+/// Panics if the patterns aren't all of the same length.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::strings::rabin_karp_multi;
+///
+/// let text = "the cat sat on the mat";
+/// assert_eq!(rabin_karp_multi(text, &["cat", "mat", "the"]), vec![(0, 2), (4, 0), (15, 2), (19, 1)]);
+/// ```
+pub fn rabin_karp_multi<T: AsRef<[u8]>, P: AsRef<[u8]>>(text: T, patterns: &[P]) -> Vec<(usize, usize)> {
+    let m = match patterns.first() {
+        Some(p) => p.as_ref().len(),
+        None => return vec![]
+    };
+    assert!(patterns.iter().all(|p| p.as_ref().len() == m), "rabin_karp_multi: patterns of different lengths");
+
+    let mut table: HashMap<Hash, Vec<usize>> = HashMap::new();
+    for (k, p) in patterns.iter().enumerate() {
+        table.entry(hash(p.as_ref())).or_default().push(k);
+    }
+
+    let mut found = vec![];
+    windows(text.as_ref(), m, |i, h| if let Some(ks) = table.get(&h) {
+        found.extend(ks.iter().map(|&k| (i, k)));
+    });
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strings::kmp_find_all;
+
+    // Strings over a tiny alphabet, so that there are many matches.
+    fn bits(v: Vec<bool>) -> Vec<u8> {
+        v.into_iter().map(|b| b as u8).collect()
+    }
+
+    quickcheck! {
+        fn rabin_karp_kmp(text: Vec<bool>, pattern: Vec<bool>) -> bool {
+            let (text, pattern) = (bits(text), bits(pattern));
+            let pattern = &pattern[.. pattern.len().min(5)];
+
+            rabin_karp(&text, pattern) == kmp_find_all(&text, pattern)
+        }
+
+        fn rabin_karp_long_patterns(text: Vec<u8>, start: usize, len: usize) -> bool {
+            let start = start % (text.len() + 1);
+            let pattern = &text[start .. start + len % (text.len() - start + 1)];
+
+            rabin_karp(&text, pattern) == kmp_find_all(&text, pattern)
+        }
+
+        fn rabin_karp_multi_kmp(text: Vec<bool>, patterns: Vec<u8>) -> bool {
+            let text = bits(text);
+            // Up to 8 patterns of 3 bits, so some of them are equal.
+            let patterns: Vec<Vec<u8>> = patterns.iter().take(8).map(|&x| vec![x & 1, x >> 1 & 1, x >> 2 & 1]).collect();
+            let mut naive: Vec<(usize, usize)> = patterns.iter().enumerate()
+                .flat_map(|(k, p)| kmp_find_all(&text, p).into_iter().map(move |i| (i, k)))
+                .collect();
+            naive.sort();
+
+            rabin_karp_multi(&text, &patterns) == naive
+        }
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(rabin_karp("ab", ""), vec![0, 1, 2]);
+        assert_eq!(rabin_karp("", "a"), Vec::<usize>::new());
+        assert_eq!(rabin_karp_multi("ab", &[] as &[&str]), vec![]);
+        assert_eq!(rabin_karp_multi("a", &["", ""]), vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn different_lengths() {
+        rabin_karp_multi("abc", &["a", "bc"]);
+    }
+}
@@ -0,0 +1,277 @@
+//! Suffix arrays, by prefix doubling, and their LCP arrays, by Kasai's
+//! algorithm.
+
+use std::ops::Range;
+
+/// Sorts `order` stably by `key`, which is less than `classes`.
+fn counting_sort<F: Fn(usize) -> usize>(order: &[usize], classes: usize, key: F) -> Vec<usize> {
+    let mut starts = vec![0; classes + 1];
+    for &i in order {
+        starts[key(i) + 1] += 1;
+    }
+    for c in 0 .. classes {
+        starts[c + 1] += starts[c];
+    }
+    let mut sorted = vec![0; order.len()];
+    for &i in order {
+        let c = key(i);
+        sorted[starts[c]] = i;
+        starts[c] += 1;
+    }
+
+    sorted
+}
+
+/// Builds the suffix array of a text: the starting positions of its suffixes
+/// in increasing order of the suffixes.
+///
+/// Prefix doubling (Manber and Myers): the suffixes are sorted by their first
+/// byte, and then, knowing the ranks of all the suffixes by their first k
+/// bytes, by their first 2k bytes, which are pairs of ranks. Each round is a
+/// radix sort of the pairs, and it's over when all the ranks differ.
+/// Time: O(n log n) for a text of length n
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::strings::suffix_array;
+///
+/// // a, abra, abracadabra, acadabra, adabra, bra, bracadabra, cadabra, dabra, ra, racadabra
+/// assert_eq!(suffix_array("abracadabra"), vec![10, 7, 0, 3, 5, 8, 1, 4, 6, 9, 2]);
+/// ```
+pub fn suffix_array<T: AsRef<[u8]>>(text: T) -> Vec<usize> {
+    let t = text.as_ref();
+    let n = t.len();
+    let mut sa = counting_sort(&(0 .. n).collect::<Vec<_>>(), 256, |i| t[i] as usize);
+    let mut rank = vec![0; n];
+    for j in 1 .. n {
+        rank[sa[j]] = rank[sa[j - 1]] + (t[sa[j]] != t[sa[j - 1]]) as usize;
+    }
+
+    let mut k = 1;
+    while n > 0 && rank[sa[n - 1]] < n - 1 {
+        // By the second rank: the suffixes shorter than k + 1 have none, so
+        // they go first, and then the others in the order of the suffixes k
+        // bytes later. Then stably by the first rank.
+        let by_second: Vec<usize> = (n - k.min(n) .. n).chain(sa.iter().filter(|&&i| i >= k).map(|&i| i - k)).collect();
+        sa = counting_sort(&by_second, n, |i| rank[i]);
+
+        let key = |i: usize| (rank[i], rank.get(i + k));
+        let mut next = vec![0; n];
+        for j in 1 .. n {
+            next[sa[j]] = next[sa[j - 1]] + (key(sa[j]) != key(sa[j - 1])) as usize;
+        }
+        rank = next;
+        k *= 2;
+    }
+
+    sa
+}
+
+/// Computes the LCP array of a text with Kasai's algorithm: for every suffix
+/// in the suffix array `sa` but the first, the length of the longest common
+/// prefix with the one before it. `lcp[0]` is 0.
+///
+/// The suffixes are taken in the order of the text. When the suffix at `i`
+/// shares h bytes with the one before it in the array, the suffix at `i + 1`
+/// shares at least h - 1 with the one before it, so the comparison starts
+/// there, and h only goes down by one per step.
+/// Time: O(n) for a text of length n
+///
+/// # Panics
+///
+/// Panics if `sa` isn't as long as the text.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::strings::{lcp_array, suffix_array};
+///
+/// let sa = suffix_array("banana");
+/// assert_eq!(sa, vec![5, 3, 1, 0, 4, 2]);
+/// assert_eq!(lcp_array("banana", &sa), vec![0, 1, 3, 0, 0, 2]);
+/// ```
+pub fn lcp_array<T: AsRef<[u8]>>(text: T, sa: &[usize]) -> Vec<usize> {
+    let t = text.as_ref();
+    let n = t.len();
+    assert_eq!(sa.len(), n, "lcp_array: the suffix array isn't as long as the text");
+    let mut rank = vec![0; n];
+    for (j, &i) in sa.iter().enumerate() {
+        rank[i] = j;
+    }
+
+    let mut lcp = vec![0; n];
+    let mut h: usize = 0;
+    for i in 0 .. n {
+        if rank[i] == 0 {
+            h = 0;
+            continue;
+        }
+        let prev = sa[rank[i] - 1];
+        while i + h < n && prev + h < n && t[i + h] == t[prev + h] {
+            h += 1;
+        }
+        lcp[rank[i]] = h;
+        h = h.saturating_sub(1);
+    }
+
+    lcp
+}
+
+/// A text with its suffix array and LCP array, for finding patterns in it.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::strings::SuffixArray;
+///
+/// let sa = SuffixArray::new("mississippi");
+/// assert_eq!(sa.count_occurrences("ss"), 2);
+/// assert_eq!(sa.find_all("issi"), vec![1, 4]);
+/// assert_eq!(sa.longest_repeated(), "issi".as_bytes());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuffixArray {
+    text: Vec<u8>,
+    sa: Vec<usize>,
+    lcp: Vec<usize>
+}
+
+impl SuffixArray {
+    /// Builds the suffix array and the LCP array of a copy of `text`.
+    /// Time: O(n log n) for a text of length n
+    pub fn new<T: AsRef<[u8]>>(text: T) -> SuffixArray {
+        let text = text.as_ref().to_vec();
+        let sa = suffix_array(&text);
+        let lcp = lcp_array(&text, &sa);
+        SuffixArray {text, sa, lcp}
+    }
+
+    /// Returns the text.
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// Returns the suffix array, as computed by `suffix_array`.
+    pub fn suffixes(&self) -> &[usize] {
+        &self.sa
+    }
+
+    /// Returns the LCP array, as computed by `lcp_array`.
+    pub fn lcp(&self) -> &[usize] {
+        &self.lcp
+    }
+
+    /// Returns the range of the suffix array with the suffixes which start
+    /// with `pattern`. They're next to each other, and found by binary search.
+    /// Time: O(m log n) for a pattern of length m and a text of length n
+    pub fn range<P: AsRef<[u8]>>(&self, pattern: P) -> Range<usize> {
+        let p = pattern.as_ref();
+        let prefix = |i: usize| &self.text[i .. (i + p.len()).min(self.text.len())];
+        let start = self.sa.partition_point(|&i| prefix(i) < p);
+        let end = self.sa.partition_point(|&i| prefix(i) <= p);
+
+        start .. end
+    }
+
+    /// Counts the occurrences of `pattern` in the text, including overlapping
+    /// ones. An empty pattern occurs at every position but the end.
+    /// Time: O(m log n) for a pattern of length m and a text of length n
+    pub fn count_occurrences<P: AsRef<[u8]>>(&self, pattern: P) -> usize {
+        self.range(pattern).len()
+    }
+
+    /// Finds all the positions where `pattern` starts, in increasing order.
+    /// Time: O(m log n + k log k) for a pattern of length m, a text of length
+    /// n and k occurrences
+    pub fn find_all<P: AsRef<[u8]>>(&self, pattern: P) -> Vec<usize> {
+        let mut found = self.sa[self.range(pattern)].to_vec();
+        found.sort();
+        found
+    }
+
+    /// Returns the longest substring which occurs at least twice, maybe
+    /// overlapping, or the first one in the order of the suffixes if there
+    /// are many. It's the longest common prefix of two neighbouring suffixes.
+    /// Time: O(n) for a text of length n
+    pub fn longest_repeated(&self) -> &[u8] {
+        match (0 .. self.lcp.len()).rev().max_by_key(|&j| self.lcp[j]) {
+            Some(j) => &self.text[self.sa[j] .. self.sa[j] + self.lcp[j]],
+            None => &[]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strings::kmp_find_all;
+
+    // Texts over the alphabet {0, 1, 2}, with many repeats.
+    fn small(v: Vec<u8>) -> Vec<u8> {
+        v.into_iter().map(|b| b % 3).collect()
+    }
+
+    fn suffix_array_naive(t: &[u8]) -> Vec<usize> {
+        let mut sa: Vec<usize> = (0 .. t.len()).collect();
+        sa.sort_by_key(|&i| &t[i ..]);
+        sa
+    }
+
+    quickcheck! {
+        fn suffix_array_sort(t: Vec<u8>) -> bool {
+            let t = small(t);
+            suffix_array(&t) == suffix_array_naive(&t)
+        }
+
+        fn suffix_array_bytes(t: Vec<u8>) -> bool {
+            suffix_array(&t) == suffix_array_naive(&t)
+        }
+
+        fn lcp_array_naive(t: Vec<u8>) -> bool {
+            let t = small(t);
+            let sa = suffix_array(&t);
+            let lcp = lcp_array(&t, &sa);
+
+            (0 .. t.len()).all(|j| {
+                let common = if j == 0 {0} else {
+                    t[sa[j - 1] ..].iter().zip(&t[sa[j] ..]).take_while(|p| p.0 == p.1).count()
+                };
+                lcp[j] == common
+            })
+        }
+
+        fn find_all_kmp(t: Vec<u8>, p: Vec<u8>) -> bool {
+            let (t, p) = (small(t), small(p));
+            let p = &p[.. p.len().min(4)];
+            let sa = SuffixArray::new(&t);
+            let mut expected = kmp_find_all(&t, p);
+            // The empty suffix isn't in the array.
+            expected.retain(|&i| i < t.len());
+
+            sa.find_all(p) == expected && sa.count_occurrences(p) == expected.len()
+        }
+
+        fn longest_repeated_naive(t: Vec<u8>) -> bool {
+            let t = small(t);
+            let sa = SuffixArray::new(&t);
+            let r = sa.longest_repeated();
+            let longest = (0 .. t.len()).rev()
+                .find(|&len| (0 .. t.len() - len + 1).any(|i| kmp_find_all(&t, &t[i .. i + len]).len() >= 2))
+                .unwrap_or(0);
+
+            r.len() == longest && (r.is_empty() || kmp_find_all(&t, r).len() >= 2)
+        }
+    }
+
+    #[test]
+    fn repeated_byte() {
+        let t = vec![7u8; 1000];
+        assert_eq!(suffix_array(&t), (0 .. 1000).rev().collect::<Vec<_>>());
+        assert_eq!(suffix_array(""), Vec::<usize>::new());
+        assert_eq!(SuffixArray::new("").count_occurrences("a"), 0);
+    }
+}
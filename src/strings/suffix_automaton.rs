@@ -0,0 +1,274 @@
+//! The suffix automaton of a text, built online.
+
+/// A state of the automaton: a class of substrings which end at the same
+/// positions of the text.
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+    // The length of the longest substring of the class.
+    len: usize,
+    // The state of the longest suffix of those substrings which ends at more
+    // positions, or `None` for the initial state.
+    link: Option<usize>,
+    // The transitions, by byte, in increasing order of the bytes.
+    next: Vec<(u8, usize)>
+}
+
+impl State {
+    fn go(&self, b: u8) -> Option<usize> {
+        self.next.binary_search_by_key(&b, |e| e.0).ok().map(|i| self.next[i].1)
+    }
+
+    fn set(&mut self, b: u8, to: usize) {
+        match self.next.binary_search_by_key(&b, |e| e.0) {
+            Ok(i) => self.next[i].1 = to,
+            Err(i) => self.next.insert(i, (b, to))
+        }
+    }
+}
+
+/// The suffix automaton of a text (Blumer et al.): the smallest automaton
+/// which accepts exactly the suffixes of the text, and in which the paths from
+/// the initial state spell exactly its substrings. Every state stands for the
+/// substrings ending at the same set of positions, which are suffixes of each
+/// other with consecutive lengths, and its suffix link leads to the state of
+/// the next shorter ones.
+///
+/// It's built online, a byte at a time, in amortized constant time per byte
+/// for a fixed alphabet, and it has at most 2n - 1 states and 3n - 4
+/// transitions for a text of length n >= 3.
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::strings::SuffixAutomaton;
+///
+/// let mut sam = SuffixAutomaton::new("abca");
+/// assert!(sam.contains("bca") && !sam.contains("ac"));
+/// assert_eq!(sam.distinct_substrings(), 9);
+///
+/// sam.push(b'b');
+/// assert_eq!(sam.distinct_substrings(), 12);
+/// assert_eq!(sam.longest_common_substring("xxcabxx"), b"cab");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuffixAutomaton {
+    states: Vec<State>,
+    // The state of the whole text.
+    last: usize,
+    distinct: usize
+}
+
+impl SuffixAutomaton {
+    /// Builds the automaton of `text`.
+    /// Time: O(n log k) for a text of length n with k distinct bytes
+    pub fn new<T: AsRef<[u8]>>(text: T) -> SuffixAutomaton {
+        let mut sam = SuffixAutomaton {
+            states: vec![State {len: 0, link: None, next: vec![]}],
+            last: 0,
+            distinct: 0
+        };
+        for &b in text.as_ref() {
+            sam.push(b);
+        }
+
+        sam
+    }
+
+    /// Returns the length of the text.
+    pub fn len(&self) -> usize {
+        self.states[self.last].len
+    }
+
+    /// Checks whether the text is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of states.
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Appends `b` to the text. The new suffixes are the old ones followed by
+    /// `b`: the suffixes without a transition by `b` get one to a new state,
+    /// up to the first which has one. If the substrings of its target aren't
+    /// all one byte longer than its own, the target is split by cloning it.
+    /// Time: O(log k) amortized, for k distinct bytes in the text
+    pub fn push(&mut self, b: u8) {
+        let cur = self.states.len();
+        self.states.push(State {len: self.len() + 1, link: Some(0), next: vec![]});
+
+        let mut p = Some(self.last);
+        while let Some(u) = p {
+            if self.states[u].go(b).is_some() {
+                break;
+            }
+            self.states[u].set(b, cur);
+            p = self.states[u].link;
+        }
+
+        if let Some(u) = p {
+            let q = self.states[u].go(b).unwrap();
+            if self.states[u].len + 1 == self.states[q].len {
+                self.states[cur].link = Some(q);
+            } else {
+                let clone = self.states.len();
+                let mut state = self.states[q].clone();
+                state.len = self.states[u].len + 1;
+                self.states.push(state);
+
+                let mut p = Some(u);
+                while let Some(v) = p {
+                    if self.states[v].go(b) != Some(q) {
+                        break;
+                    }
+                    self.states[v].set(b, clone);
+                    p = self.states[v].link;
+                }
+                self.states[q].link = Some(clone);
+                self.states[cur].link = Some(clone);
+            }
+        }
+
+        self.last = cur;
+        // The new substrings are the suffixes longer than those which were
+        // there before.
+        let link = self.states[cur].link.unwrap();
+        self.distinct += self.states[cur].len - self.states[link].len;
+    }
+
+    /// Checks whether `pattern` is a substring of the text.
+    /// Time: O(m log k) for a pattern of length m and k distinct bytes in the text
+    pub fn contains<P: AsRef<[u8]>>(&self, pattern: P) -> bool {
+        let mut s = 0;
+        for &b in pattern.as_ref() {
+            match self.states[s].go(b) {
+                Some(t) => s = t,
+                None => return false
+            }
+        }
+
+        true
+    }
+
+    /// Returns the number of distinct non-empty substrings of the text. Every
+    /// state stands for as many as the lengths of its substrings, from one
+    /// more than the length of its suffix link's up to its own length.
+    /// Time: O(1), as it's kept up to date
+    pub fn distinct_substrings(&self) -> usize {
+        self.distinct
+    }
+
+    /// Finds the longest substring of `other` which is also a substring of the
+    /// text, the first one in `other` if there are many. The automaton reads
+    /// `other`, keeping the longest suffix of what was read which is a
+    /// substring of the text, and shortening it by suffix links when the next
+    /// byte doesn't fit.
+    /// Time: O(m log k) for `other` of length m and k distinct bytes in the text
+    pub fn longest_common_substring<'a, T: AsRef<[u8]> + ?Sized>(&self, other: &'a T) -> &'a [u8] {
+        let other = other.as_ref();
+        let (mut s, mut len) = (0, 0);
+        let (mut best, mut best_end) = (0, 0);
+        for (i, &b) in other.iter().enumerate() {
+            loop {
+                if let Some(t) = self.states[s].go(b) {
+                    s = t;
+                    len += 1;
+                    break;
+                }
+                match self.states[s].link {
+                    Some(l) => {
+                        s = l;
+                        len = self.states[l].len;
+                    },
+                    None => break
+                }
+            }
+            if len > best {
+                best = len;
+                best_end = i + 1;
+            }
+        }
+
+        &other[best_end - best .. best_end]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    // Texts over the alphabet {0, 1, 2}, with many repeats.
+    fn small(v: Vec<u8>) -> Vec<u8> {
+        v.into_iter().map(|b| b % 3).collect()
+    }
+
+    fn is_substring(t: &[u8], p: &[u8]) -> bool {
+        p.is_empty() || t.windows(p.len()).any(|w| w == p)
+    }
+
+    quickcheck! {
+        fn contains_naive(t: Vec<u8>, p: Vec<u8>) -> bool {
+            let (t, p) = (small(t), small(p));
+            let sam = SuffixAutomaton::new(&t);
+            (0 ..= p.len()).all(|k| sam.contains(&p[.. k]) == is_substring(&t, &p[.. k]))
+        }
+
+        fn distinct_substrings_naive(t: Vec<u8>) -> bool {
+            let t = small(t);
+            let sam = SuffixAutomaton::new(&t);
+            let mut substrings = HashSet::new();
+            for i in 0 .. t.len() {
+                for j in i + 1 ..= t.len() {
+                    substrings.insert(&t[i .. j]);
+                }
+            }
+            let n = t.len();
+
+            sam.distinct_substrings() == substrings.len() && sam.len() == n &&
+            sam.state_count() <= (2 * n).saturating_sub(1).max(n + 1)
+        }
+
+        fn longest_common_substring_naive(t: Vec<u8>, other: Vec<u8>) -> bool {
+            let (t, other) = (small(t), small(other));
+            let sam = SuffixAutomaton::new(&t);
+            let common = sam.longest_common_substring(&other);
+            // The longest common substring by dynamic programming over the
+            // longest common suffixes of prefixes.
+            let mut longest = 0;
+            let mut prev = vec![0; other.len() + 1];
+            for &a in &t {
+                let mut row = vec![0; other.len() + 1];
+                for (j, &b) in other.iter().enumerate() {
+                    if a == b {
+                        row[j + 1] = prev[j] + 1;
+                        longest = longest.max(row[j + 1]);
+                    }
+                }
+                prev = row;
+            }
+
+            common.len() == longest && is_substring(&t, common) && is_substring(&other, common)
+        }
+
+        fn online_same(t: Vec<u8>) -> bool {
+            let mut sam = SuffixAutomaton::new("");
+            for &b in &t {
+                sam.push(b);
+            }
+
+            sam == SuffixAutomaton::new(&t)
+        }
+    }
+
+    #[test]
+    fn repeated_byte() {
+        let sam = SuffixAutomaton::new(vec![b'a'; 1000]);
+        assert_eq!(sam.state_count(), 1001);
+        assert_eq!(sam.distinct_substrings(), 1000);
+        assert!(SuffixAutomaton::new("").is_empty());
+    }
+}
@@ -0,0 +1,118 @@
+//! Longest subsequences with a given property.
+
+/// Finds a longest subsequence of `xs` in which every element is followed by
+/// one it's `before`, as indices.
+///
+/// `tails[k]` is the index of the least element which ends such a subsequence
+/// of length `k + 1` among the elements so far. The tails only grow with `k`,
+/// so the place of a new element is found by binary search: it extends the
+/// longest subsequence whose tail is `before` it, replacing the next tail.
+/// `parent[i]` is the element before `i` in the subsequence ending at `i`.
+fn longest<T, F: Fn(&T, &T) -> bool>(xs: &[T], before: F) -> Vec<usize> {
+    let mut tails: Vec<usize> = vec![];
+    let mut parent = vec![None; xs.len()];
+
+    for (i, x) in xs.iter().enumerate() {
+        let k = tails.partition_point(|&t| before(&xs[t], x));
+        parent[i] = if k > 0 {Some(tails[k - 1])} else {None};
+        if k == tails.len() {
+            tails.push(i);
+        } else {
+            tails[k] = i;
+        }
+    }
+
+    let mut v = vec![];
+    let mut current = tails.last().cloned();
+    while let Some(i) = current {
+        v.push(i);
+        current = parent[i];
+    }
+    v.reverse();
+
+    v
+}
+
+/// Finds a longest strictly increasing subsequence of `xs`, returning the
+/// indices of its elements in increasing order.
+/// Time: O(n log n)
+/// Memory: O(n)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::subsequence::lis;
+///
+/// let xs = [3, 1, 4, 1, 5, 9, 2, 6];
+/// let s = lis(&xs);
+/// assert_eq!(s, vec![1, 2, 4, 7]);
+/// assert_eq!(s.iter().map(|&i| xs[i]).collect::<Vec<_>>(), vec![1, 4, 5, 6]);
+///
+/// assert_eq!(lis(&[2, 2, 2]).len(), 1);
+/// ```
+pub fn lis<T: Ord>(xs: &[T]) -> Vec<usize> {
+    longest(xs, |a, b| a < b)
+}
+
+/// Like `lis`, but the subsequence only has to be non-decreasing, so equal
+/// elements can all be in it.
+/// Time: O(n log n)
+/// Memory: O(n)
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::subsequence::lis_non_decreasing;
+///
+/// assert_eq!(lis_non_decreasing(&[2, 2, 1, 2]), vec![0, 1, 3]);
+/// ```
+pub fn lis_non_decreasing<T: Ord>(xs: &[T]) -> Vec<usize> {
+    longest(xs, |a, b| a <= b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lis, lis_non_decreasing};
+
+    // The length of the longest subsequence, by the quadratic dynamic program.
+    fn naive<F: Fn(u8, u8) -> bool>(xs: &[u8], before: F) -> usize {
+        let mut best = vec![1; xs.len()];
+        for i in 0 .. xs.len() {
+            for j in 0 .. i {
+                if before(xs[j], xs[i]) {
+                    best[i] = best[i].max(best[j] + 1);
+                }
+            }
+        }
+
+        best.into_iter().max().unwrap_or(0)
+    }
+
+    quickcheck! {
+        fn lis_naive(xs: Vec<u8>) -> bool {
+            let s = lis(&xs);
+
+            s.len() == naive(&xs, |a, b| a < b) &&
+            s.windows(2).all(|w| w[0] < w[1] && xs[w[0]] < xs[w[1]])
+        }
+
+        fn lis_non_decreasing_naive(xs: Vec<u8>) -> bool {
+            let s = lis_non_decreasing(&xs);
+
+            s.len() == naive(&xs, |a, b| a <= b) &&
+            s.windows(2).all(|w| w[0] < w[1] && xs[w[0]] <= xs[w[1]])
+        }
+    }
+
+    #[test]
+    fn sorted_big() {
+        let n = 100_000;
+        let xs: Vec<usize> = (0 .. n).collect();
+
+        assert_eq!(lis(&xs), xs);
+        assert_eq!(lis(&xs.iter().rev().collect::<Vec<_>>()).len(), 1);
+        assert_eq!(lis_non_decreasing(&vec![0; n]).len(), n);
+    }
+}
@@ -0,0 +1,228 @@
+//! LOUDS (Level-Order Unary Degree Sequence) encoding of static trees.
+
+use std::collections::VecDeque;
+
+use bitvec::BitVec;
+
+/// Computes the breadth-first order of the nodes of a tree rooted at 0, given as
+/// lists of children. The i-th element is the node that `LoudsTree` calls i.
+pub fn bfs_order(children: &[Vec<usize>]) -> Vec<usize> {
+    let mut order = Vec::with_capacity(children.len());
+    let mut queue = VecDeque::new();
+
+    if !children.is_empty() {
+        queue.push_back(0);
+    }
+    while let Some(u) = queue.pop_front() {
+        order.push(u);
+        queue.extend(children[u].iter().cloned());
+    }
+
+    order
+}
+
+/// A static ordered tree encoded in 2n + 1 bits (plus the rank index of the
+/// `BitVec`). Nodes are visited in breadth-first order and each node is written
+/// down as its degree in unary: d ones followed by a zero. An extra "10" in front
+/// stands for a virtual parent of the root.
+///
+/// Nodes are numbered in breadth-first order, with the root being 0 (see `bfs_order`).
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::trees::louds::LoudsTree;
+///
+/// //       0
+/// //      / \
+/// //     1   2
+/// //    / \   \
+/// //   3   4   5
+/// let t = LoudsTree::new(&[vec![1, 2], vec![3, 4], vec![5], vec![], vec![], vec![]]);
+///
+/// assert_eq!(t.len(), 6);
+/// assert_eq!(t.parent(4), Some(1));
+/// assert_eq!(t.parent(0), None);
+/// assert_eq!(t.first_child(2), Some(5));
+/// assert_eq!(t.first_child(3), None);
+/// assert_eq!(t.next_sibling(3), Some(4));
+/// assert_eq!(t.next_sibling(4), None);
+/// assert_eq!(t.degree(1), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoudsTree {
+    bits: BitVec
+}
+
+impl LoudsTree {
+    /// Encodes a tree rooted at 0, given as lists of children. The children
+    /// of every node keep their order.
+    /// Time: O(n)
+    pub fn new(children: &[Vec<usize>]) -> LoudsTree {
+        let mut bits = BitVec::new();
+        if children.is_empty() {
+            return LoudsTree {bits};
+        }
+        bits.push(true).push(false);
+
+        for u in bfs_order(children) {
+            for _ in &children[u] {
+                bits.push(true);
+            }
+            bits.push(false);
+        }
+
+        LoudsTree {bits}
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.bits.len() / 2
+    }
+
+    /// Checks whether the tree has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the encoding.
+    pub fn bits(&self) -> &BitVec {
+        &self.bits
+    }
+
+    /// Returns the parent of node `i`.
+    /// Time: O(log n)
+    pub fn parent(&self, i: usize) -> Option<usize> {
+        if i == 0 || i >= self.len() {
+            None
+        } else {
+            // The one standing for i is in the list of its parent p, which starts
+            // after the p-th zero.
+            self.bits.select1(i).map(|pos| self.bits.rank0(pos) - 1)
+        }
+    }
+
+    /// Returns the first child of node `i`.
+    /// Time: O(log n)
+    pub fn first_child(&self, i: usize) -> Option<usize> {
+        if i >= self.len() {
+            return None;
+        }
+
+        let pos = self.bits.select0(i)? + 1;
+        if self.bits.get(pos) == Some(true) {
+            Some(self.bits.rank1(pos))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the next sibling of node `i`.
+    /// Time: O(log n)
+    pub fn next_sibling(&self, i: usize) -> Option<usize> {
+        if i >= self.len() {
+            return None;
+        }
+
+        let pos = self.bits.select1(i)?;
+        if self.bits.get(pos + 1) == Some(true) {
+            Some(i + 1)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of children of node `i`.
+    /// Time: O(log n)
+    pub fn degree(&self, i: usize) -> usize {
+        match (self.bits.select0(i), self.bits.select0(i + 1)) {
+            (Some(start), Some(end)) if i < self.len() => end - start - 1,
+            _ => 0
+        }
+    }
+
+    /// Returns the children of node `i`, in order.
+    pub fn children(&self, i: usize) -> Vec<usize> {
+        match self.first_child(i) {
+            None => vec![],
+            Some(c) => (c .. c + self.degree(i)).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use trees::louds::*;
+
+    /// Turns `parents` into a tree: node i + 1 is a child of node parents[i] % (i + 1).
+    fn tree(parents: Vec<usize>) -> Vec<Vec<usize>> {
+        let mut children = vec![vec![]];
+        for (i, p) in parents.into_iter().enumerate() {
+            children[p % (i + 1)].push(i + 1);
+            children.push(vec![]);
+        }
+
+        children
+    }
+
+    /// Renumbers the nodes of a tree in breadth-first order.
+    fn renumber(children: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        let order = bfs_order(children);
+        let mut new = vec![0; children.len()];
+        for (i, &u) in order.iter().enumerate() {
+            new[u] = i;
+        }
+
+        order.iter().map(|&u| children[u].iter().map(|&c| new[c]).collect()).collect()
+    }
+
+    #[test]
+    fn empty() {
+        let l = LoudsTree::new(&[]);
+
+        assert!(l.is_empty());
+        assert_eq!(l.parent(0), None);
+        assert_eq!(l.first_child(0), None);
+        assert_eq!(l.degree(0), 0);
+    }
+
+    quickcheck! {
+        fn size(parents: Vec<usize>) -> bool {
+            let t = tree(parents);
+            let l = LoudsTree::new(&t);
+
+            l.len() == t.len() && l.bits().len() == 2 * t.len() + 1
+        }
+
+        fn children_naive(parents: Vec<usize>) -> bool {
+            let t = renumber(&tree(parents));
+            let l = LoudsTree::new(&t);
+
+            (0 .. t.len()).all(|u| l.children(u) == t[u] && l.degree(u) == t[u].len())
+        }
+
+        fn parent_naive(parents: Vec<usize>) -> bool {
+            let t = renumber(&tree(parents));
+            let l = LoudsTree::new(&t);
+
+            l.parent(0) == None &&
+            (0 .. t.len()).all(|u| t[u].iter().all(|&c| l.parent(c) == Some(u)))
+        }
+
+        fn first_child_next_sibling(parents: Vec<usize>) -> bool {
+            let t = renumber(&tree(parents));
+            let l = LoudsTree::new(&t);
+
+            (0 .. t.len()).all(|u| {
+                let mut cs = vec![];
+                let mut c = l.first_child(u);
+                while let Some(x) = c {
+                    cs.push(x);
+                    c = l.next_sibling(x);
+                }
+                cs == t[u]
+            })
+        }
+    }
+}
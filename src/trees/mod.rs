@@ -0,0 +1,3 @@
+//! Trees as data (as opposed to search trees, which live in `map`).
+
+pub mod louds;
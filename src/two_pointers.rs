@@ -0,0 +1,245 @@
+//! The two-pointer technique: a window slides over a slice, its right end moving
+//! one step at a time and its left end catching up when the window stops being
+//! good. Every element enters and leaves the window at most once, so the window is
+//! updated O(n) times in total.
+//!
+//! The drivers below work for any property of windows which is kept by their
+//! subwindows (like "at most k distinct elements" or "sum at most s"). The caller
+//! passes the state of the empty window and two closures which update it:
+//! - `add(state, x)` puts `x` at the right end of the window and tells whether the
+//!   window is still good,
+//! - `remove(state, x)` takes `x` off the left end and tells whether the window
+//!   is good.
+//!
+//! The empty window is always good.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Range;
+
+/// Calls `f(l, r)` for every right end `r`, with `l` the least left end for which
+/// `xs[l .. r]` is good. `r` goes from 1 to `xs.len()`.
+fn slide<'a, T, S, A, R, F>(xs: &'a [T], mut state: S, mut add: A, mut remove: R, mut f: F)
+    where A: FnMut(&mut S, &'a T) -> bool, R: FnMut(&mut S, &'a T) -> bool, F: FnMut(usize, usize)
+{
+    let mut l = 0;
+    for (r, x) in xs.iter().enumerate() {
+        let mut good = add(&mut state, x);
+        while !good && l <= r {
+            good = remove(&mut state, &xs[l]);
+            l += 1;
+        }
+        f(l, r + 1);
+    }
+}
+
+/// Finds the longest good window. Of the longest ones, it picks the leftmost.
+/// Time: O(n) calls to `add` and `remove`
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::two_pointers::longest_window;
+///
+/// // The longest window with sum at most 6.
+/// let xs = [4, 1, 2, 3, 5, 1, 1];
+/// let w = longest_window(&xs, 0,
+///     |sum, &x| {*sum += x; *sum <= 6},
+///     |sum, &x| {*sum -= x; *sum <= 6});
+/// assert_eq!(w, 1 .. 4);
+/// ```
+pub fn longest_window<'a, T, S, A, R>(xs: &'a [T], state: S, add: A, remove: R) -> Range<usize>
+    where A: FnMut(&mut S, &'a T) -> bool, R: FnMut(&mut S, &'a T) -> bool
+{
+    let mut best = 0 .. 0;
+    slide(xs, state, add, remove, |l, r| {
+        if r - l > best.len() {
+            best = l .. r;
+        }
+    });
+
+    best
+}
+
+/// Counts the nonempty good windows. When `xs[l .. r]` is the longest good window
+/// ending at `r`, all of its `r - l` suffixes are good too.
+/// Time: O(n) calls to `add` and `remove`
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::two_pointers::count_subarrays_with;
+///
+/// // [1], [2], [1], [3], [1, 2] and [2, 1] have sums at most 3.
+/// let xs = [1, 2, 1, 3];
+/// let count = count_subarrays_with(&xs, 0,
+///     |sum, &x| {*sum += x; *sum <= 3},
+///     |sum, &x| {*sum -= x; *sum <= 3});
+/// assert_eq!(count, 6);
+/// ```
+pub fn count_subarrays_with<'a, T, S, A, R>(xs: &'a [T], state: S, add: A, remove: R) -> usize
+    where A: FnMut(&mut S, &'a T) -> bool, R: FnMut(&mut S, &'a T) -> bool
+{
+    let mut count = 0;
+    slide(xs, state, add, remove, |l, r| count += r - l);
+
+    count
+}
+
+/// Finds the longest window with at most `k` distinct elements, for example the
+/// longest substring of a string (as a slice of chars) with at most `k` distinct
+/// letters. Of the longest ones, it picks the leftmost.
+/// Time: O(n) expected
+pub fn longest_k_distinct<T: Hash + Eq>(xs: &[T], k: usize) -> Range<usize> {
+    // The number of copies of every element in the window.
+    longest_window(xs, HashMap::new(),
+        |counts: &mut HashMap<&T, usize>, x| {
+            *counts.entry(x).or_insert(0) += 1;
+            counts.len() <= k
+        },
+        |counts, x| {
+            let c = counts.get_mut(x).unwrap();
+            *c -= 1;
+            if *c == 0 {
+                counts.remove(x);
+            }
+            counts.len() <= k
+        })
+}
+
+/// Finds the shortest window which contains every element of `need` (as a
+/// multiset: an element needed twice must be in the window twice). Of the
+/// shortest ones, it picks the leftmost. Returns `None` if there's no such window.
+///
+/// It's the other way round than in the drivers: containing `need` is kept by
+/// superwindows, so the left end moves as long as the window stays good.
+/// Time: O(n + size of `need`) expected
+///
+/// # Example
+///
+/// ```
+/// extern crate aisd;
+/// use aisd::two_pointers::min_window_covering;
+///
+/// let s: Vec<char> = "ADOBECODEBANC".chars().collect();
+/// let need: Vec<char> = "ABC".chars().collect();
+/// let w = min_window_covering(&s, &need).unwrap();
+/// assert_eq!(s[w].iter().collect::<String>(), "BANC");
+/// ```
+pub fn min_window_covering<T: Hash + Eq>(xs: &[T], need: &[T]) -> Option<Range<usize>> {
+    if need.is_empty() {
+        return Some(0 .. 0);
+    }
+
+    // How many more copies of every element the window needs (negative if it
+    // has spares), and how many elements of `need` are still missing.
+    let mut missing: HashMap<&T, isize> = HashMap::new();
+    for x in need {
+        *missing.entry(x).or_insert(0) += 1;
+    }
+    let mut total = need.len();
+
+    let mut best: Option<Range<usize>> = None;
+    let mut l = 0;
+    for (r, x) in xs.iter().enumerate() {
+        if let Some(m) = missing.get_mut(x) {
+            if *m > 0 {
+                total -= 1;
+            }
+            *m -= 1;
+        }
+
+        while total == 0 {
+            if best.as_ref().is_none_or(|b| r + 1 - l < b.len()) {
+                best = Some(l .. r + 1);
+            }
+
+            if let Some(m) = missing.get_mut(&xs[l]) {
+                *m += 1;
+                if *m > 0 {
+                    total += 1;
+                }
+            }
+            l += 1;
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    fn distinct(xs: &[u8]) -> usize {
+        xs.iter().collect::<HashSet<_>>().len()
+    }
+
+    // Checks that `xs` contains `need` as a multiset.
+    fn covers(xs: &[u8], need: &[u8]) -> bool {
+        let mut xs = xs.to_vec();
+        need.iter().all(|x| match xs.iter().position(|y| y == x) {
+            Some(i) => {
+                xs.swap_remove(i);
+                true
+            },
+            None => false
+        })
+    }
+
+    // All windows, shorter ones first and leftmost first among the same length.
+    fn windows(n: usize) -> impl Iterator<Item = Range<usize>> {
+        (0 ..= n).flat_map(move |len| (0 ..= n - len).map(move |l| l .. l + len))
+    }
+
+    quickcheck! {
+        fn longest_window_naive(xs: Vec<u8>, s: u16) -> bool {
+            let s = s as u32;
+            let w = longest_window(&xs, 0,
+                |sum, &x| {*sum += x as u32; *sum <= s},
+                |sum, &x| {*sum -= x as u32; *sum <= s});
+
+            let good = |w: &Range<usize>| xs[w.clone()].iter().map(|&x| x as u32).sum::<u32>() <= s;
+            let longest = windows(xs.len()).filter(&good).map(|w| w.len()).max().unwrap_or(0);
+
+            good(&w) && w.len() == longest
+        }
+
+        fn count_subarrays_naive(xs: Vec<u8>, k: usize) -> bool {
+            let xs: Vec<u8> = xs.iter().map(|x| x % 5).collect();
+            let k = k % 4;
+            let count = count_subarrays_with(&xs, [0; 5],
+                |counts, &x| {counts[x as usize] += 1; counts.iter().filter(|&&c| c > 0).count() <= k},
+                |counts, &x| {counts[x as usize] -= 1; counts.iter().filter(|&&c| c > 0).count() <= k});
+
+            let naive = windows(xs.len()).filter(|w| !w.is_empty() && distinct(&xs[w.clone()]) <= k).count();
+
+            count == naive
+        }
+
+        fn longest_k_distinct_naive(xs: Vec<u8>, k: usize) -> bool {
+            let xs: Vec<u8> = xs.iter().map(|x| x % 6).collect();
+            let k = k % 5;
+            let w = longest_k_distinct(&xs, k);
+
+            // The leftmost of the longest.
+            let expected = windows(xs.len()).filter(|w| distinct(&xs[w.clone()]) <= k)
+                .fold(0 .. 0, |best, w| if w.len() > best.len() {w} else {best});
+
+            w == expected
+        }
+
+        fn min_window_covering_naive(xs: Vec<u8>, need: Vec<u8>) -> bool {
+            let xs: Vec<u8> = xs.iter().map(|x| x % 4).collect();
+            let need: Vec<u8> = need.iter().take(4).map(|x| x % 4).collect();
+
+            let expected = windows(xs.len()).find(|w| covers(&xs[w.clone()], &need));
+
+            min_window_covering(&xs, &need) == expected
+        }
+    }
+}
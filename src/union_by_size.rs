@@ -7,6 +7,8 @@ use quickcheck::Gen;
 use rand;
 use rand::Rng;
 
+use disjoint_set::DisjointSet;
+
 /// The classical data structure for the disjoint-set problem, also known as union-find.
 ///
 /// # Example
@@ -104,6 +106,24 @@ impl UnionFind {
     }
 }
 
+impl DisjointSet for UnionFind {
+    fn new(size: usize) -> UnionFind {
+        UnionFind::new(size)
+    }
+
+    fn size(&self) -> usize {
+        UnionFind::size(self)
+    }
+
+    fn union(&mut self, i: usize, j: usize) {
+        UnionFind::union(self, i, j)
+    }
+
+    fn find(&mut self, i: usize) -> Option<usize> {
+        UnionFind::find(self, i)
+    }
+}
+
 impl Arbitrary for UnionFind {
     fn arbitrary<G: Gen>(g: &mut G) -> UnionFind {
         let size: usize = Arbitrary::arbitrary(g);
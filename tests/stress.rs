@@ -0,0 +1,166 @@
+//! Long-running randomized tests which compare the heaps and sorts of the crate
+//! against the ones from std on millions of operations, way more than quickcheck
+//! tries. Run with
+//!
+//!     cargo test --release --features stress --test stress
+//!
+//! Every run uses fresh seeds, which get printed on failure. To replay a failure,
+//! set `STRESS_SEED` to the printed seed. `STRESS_OPS` sets the number of
+//! operations per seed.
+
+extern crate aisd;
+extern crate rand;
+
+use aisd::depq::{DoubleHeap, DEPQ};
+use aisd::pq::{Heap, PriorityQueue};
+
+use rand::{Rng, SeedableRng};
+use rand::prng::XorShiftRng;
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::env;
+
+/// Number of seeds tried by each test, unless `STRESS_SEED` is set.
+const SEEDS: usize = 10;
+
+fn env_var(name: &str) -> Option<u64> {
+    env::var(name).ok().map(|s| s.parse().unwrap_or_else(|_| panic!("{} must be a number", name)))
+}
+
+fn ops() -> usize {
+    env_var("STRESS_OPS").unwrap_or(1_000_000) as usize
+}
+
+/// Runs `test` for every seed. The test returns a description of the first
+/// difference from the oracle, if there's one.
+fn run<F: Fn(&mut XorShiftRng, usize) -> Result<(), String>>(name: &str, test: F) {
+    let seeds: Vec<u64> = match env_var("STRESS_SEED") {
+        Some(seed) => vec![seed],
+        None => (0 .. SEEDS).map(|_| rand::thread_rng().gen()).collect()
+    };
+
+    for seed in seeds {
+        let mut bytes = [0; 16];
+        bytes[.. 8].copy_from_slice(&seed.to_le_bytes());
+        bytes[8 ..].copy_from_slice(&(!seed).to_le_bytes());
+        let mut rng = XorShiftRng::from_seed(bytes);
+
+        if let Err(e) = test(&mut rng, ops()) {
+            panic!("{}: {} (replay with STRESS_SEED={})", name, e, seed);
+        }
+    }
+}
+
+#[test]
+fn heap_vs_binary_heap() {
+    run("Heap", |rng, ops| {
+        let mut h = Heap::new();
+        let mut oracle = BinaryHeap::new();
+
+        for i in 0 .. ops {
+            // Insert a bit more often than delete, so that the heap grows.
+            if rng.gen_bool(0.55) {
+                let x: u32 = rng.gen_range(0, 1000);
+                h.insert(x);
+                oracle.push(Reverse(x));
+            } else {
+                let got = h.del_min();
+                let expected = oracle.pop().map(|Reverse(x)| x);
+                if got != expected {
+                    return Err(format!("op {}: del_min gave {:?}, expected {:?}", i, got, expected));
+                }
+            }
+
+            if h.size() != oracle.len() {
+                return Err(format!("op {}: size {}, expected {}", i, h.size(), oracle.len()));
+            }
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn double_heap_vs_btreemap() {
+    run("DoubleHeap", |rng, ops| {
+        let mut h = DoubleHeap::new();
+        // A multiset: element -> number of copies.
+        let mut oracle: BTreeMap<u32, usize> = BTreeMap::new();
+
+        for i in 0 .. ops {
+            match rng.gen_range(0, 5) {
+                0 ..= 2 => {
+                    let x: u32 = rng.gen_range(0, 1000);
+                    h.ins(x);
+                    *oracle.entry(x).or_insert(0) += 1;
+                },
+                k => {
+                    let (got, expected) = if k == 3 {
+                        (h.del_min(), oracle.keys().next().cloned())
+                    } else {
+                        (h.del_max(), oracle.keys().next_back().cloned())
+                    };
+                    if let Some(x) = expected {
+                        let n = oracle[&x];
+                        if n == 1 {
+                            oracle.remove(&x);
+                        } else {
+                            oracle.insert(x, n - 1);
+                        }
+                    }
+
+                    if got != expected {
+                        let op = if k == 3 {"del_min"} else {"del_max"};
+                        return Err(format!("op {}: {} gave {:?}, expected {:?}", i, op, got, expected));
+                    }
+                }
+            }
+
+            let size: usize = oracle.values().sum();
+            if h.size() != size {
+                return Err(format!("op {}: size {}, expected {}", i, h.size(), size));
+            }
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn sorts_vs_sort_unstable() {
+    run("sort", |rng, ops| {
+        // Arrays of random lengths, adding up to about `ops` elements.
+        let mut done = 0;
+        while done < ops {
+            let len = rng.gen_range(0, 10_000);
+            let range = rng.gen_range(1, 1 << 20);
+            let v: Vec<u32> = (0 .. len).map(|_| rng.gen_range(0, range)).collect();
+            done += len + 1;
+
+            let mut expected = v.clone();
+            expected.sort_unstable();
+
+            let mut sorted = v.clone();
+            Heap::sort(&mut sorted);
+            if sorted != expected {
+                return Err(format!("Heap::sort is wrong for an array of length {}", len));
+            }
+
+            if Heap::sort2(v.clone()) != expected {
+                return Err(format!("Heap::sort2 is wrong for an array of length {}", len));
+            }
+
+            let mut h = DoubleHeap::make_heap(v);
+            let mut sorted = vec![];
+            while let Some(x) = h.del_min() {
+                sorted.push(x);
+            }
+            if sorted != expected {
+                return Err(format!("DoubleHeap::make_heap is wrong for an array of length {}", len));
+            }
+        }
+
+        Ok(())
+    });
+}